@@ -2,7 +2,66 @@ use clap::Parser;
 // TODO: make non-annotation generate different DeserializeError that is simpler
 //       and works with From<cbor_event:Error> only
 
-#[derive(Debug, Default, Parser)]
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, clap::ValueEnum)]
+pub enum CborCrate {
+    /// Only cbor_event's Serialize/Deserialize (and ours)
+    #[default]
+    CborEvent,
+    /// Also implement minicbor's Encode/Decode
+    Minicbor,
+    /// Also implement serde's Serialize/Deserialize as CBOR for use with ciborium,
+    /// as well as conversions to/from ciborium::value::Value. These go through cbor_event's
+    /// encoding and a ciborium Value so they are slower than to_cbor_bytes()
+    Ciborium,
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, clap::ValueEnum)]
+pub enum RegexEngine {
+    /// The regex crate. No backreferences or lookaround
+    #[default]
+    Regex,
+    /// The fancy-regex crate. Adds backreferences, lookaround, atomic groups etc to regex
+    FancyRegex,
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, clap::ValueEnum)]
+pub enum IntWidth {
+    #[value(name = "8")]
+    W8,
+    #[value(name = "16")]
+    W16,
+    #[value(name = "32")]
+    W32,
+    /// uint is u64 and int is our Int type covering the whole CBOR range
+    #[default]
+    #[value(name = "64")]
+    W64,
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, clap::ValueEnum)]
+pub enum Visibility {
+    #[default]
+    #[value(name = "pub")]
+    Pub,
+    #[value(name = "pub(crate)")]
+    PubCrate,
+    /// No visibility token i.e. only visible in the module the type is generated in
+    #[value(name = "private")]
+    Private,
+}
+
+impl Visibility {
+    /// Token to put before `struct` / `enum` / `fn` etc. None for private as that has no token.
+    pub fn token(self) -> Option<&'static str> {
+        match self {
+            Self::Pub => Some("pub"),
+            Self::PubCrate => Some("pub(crate)"),
+            Self::Private => None,
+        }
+    }
+}
+
+#[derive(Clone, Debug, Default, Parser)]
 #[clap()]
 pub struct Cli {
     /// Input .cddl file to generate from. If this is a directory then it will read all *.cddl files and generate one output for each.
@@ -48,6 +107,112 @@ pub struct Cli {
     #[clap(long, value_parser, action = clap::ArgAction::Set, default_value_t = false)]
     pub canonical_form: bool,
 
+    /// Wraps the CBOR produced by to_cbor_bytes() in the self-described CBOR tag 55799.
+    /// from_cbor_bytes() will then accept CBOR both with and without this tag.
+    #[clap(long, value_parser, action = clap::ArgAction::Set, default_value_t = false)]
+    pub self_describe_tag: bool,
+
+    /// Check .size on tstr as a count of unicode codepoints instead of UTF-8 bytes.
+    /// This changes validation for any text containing multi-byte characters.
+    #[clap(long, value_parser, action = clap::ArgAction::Set, default_value_t = false)]
+    pub tstr_size_codepoints: bool,
+
+    /// Generates bstr .size N (exact sizes only) as [u8; N] instead of a length-checked Vec<u8>.
+    /// Not supported with --wasm as wasm_bindgen can't pass arrays.
+    #[clap(long, value_parser, action = clap::ArgAction::Set, default_value_t = false)]
+    pub fixed_size_byte_arrays: bool,
+
+    /// Generates tstr .size 1 as a char instead of a String checked to be 1 long.
+    /// This is always one character (unicode codepoint) even if .size is otherwise in UTF-8 bytes.
+    #[clap(long, value_parser, action = clap::ArgAction::Set, default_value_t = false)]
+    pub tstr_size_1_as_char: bool,
+
+    /// Generates a constants module with a named const for every map key and fixed value field
+    /// as well as every rule that is a single constant e.g. `version = 3`.
+    #[clap(long, value_parser, action = clap::ArgAction::Set, default_value_t = false)]
+    pub constants_module: bool,
+
+    /// Allows .cbor on tstr (not just bstr as per the CDDL spec) as base64-encoded CBOR.
+    /// Some non-standard specs use this.
+    #[clap(long, value_parser, action = clap::ArgAction::Set, default_value_t = false)]
+    pub allow_cbor_on_text: bool,
+
+    /// Serialize floats as the shortest of half/single/double precision that represents the value exactly
+    /// instead of always using double precision. float16 fields always do this.
+    #[clap(long, value_parser, action = clap::ArgAction::Set, default_value_t = false)]
+    pub shortest_floats: bool,
+
+    /// Generates From<Foo> for ciborium::value::Value and TryFrom<ciborium::value::Value> for Foo for all record types.
+    /// The conversion goes through the CBOR encoding so map keys are the ones from the CDDL.
+    #[clap(long, value_parser, action = clap::ArgAction::Set, default_value_t = false)]
+    pub cbor_value_conversions: bool,
+
+    /// Additional CBOR crate to generate Encode/Decode (or serde for ciborium) impls for.
+    /// cbor_event is always used for the main (de)serialization logic.
+    #[clap(long, value_enum, value_name = "CBOR_CRATE", default_value_t = CborCrate::CborEvent)]
+    pub cbor_crate: CborCrate,
+
+    /// Bit width of the rust type used for a bare uint / int e.g. 32 for u32 / i32.
+    /// Values that don't fit are deserialization errors. Ranges and .size still pick their own type.
+    #[clap(long, value_enum, value_name = "BITS", default_value_t = IntWidth::W64)]
+    pub default_int_width: IntWidth,
+
+    /// Declares the fields of map structs in alphabetical order instead of CDDL order, which also
+    /// orders new()'s arguments. Array structs always keep CDDL order as it's their encoding order.
+    #[clap(long, value_parser, action = clap::ArgAction::Set, default_value_t = false)]
+    pub sort_fields: bool,
+
+    /// Prefix of the field names generated for array elements without a derivable name e.g. index_0
+    #[clap(long, value_parser, value_name = "PREFIX", default_value = "index")]
+    pub index_field_prefix: String,
+
+    /// Prefix of the field names generated for map entries keyed by a literal without a name e.g. key_1
+    #[clap(long, value_parser, value_name = "PREFIX", default_value = "key")]
+    pub key_field_prefix: String,
+
+    /// Errors on fields that would get a positional name (see --index-field-prefix/--key-field-prefix)
+    /// instead of generating one, requiring such fields to be named with @name.
+    #[clap(long, value_parser, action = clap::ArgAction::Set, default_value_t = false)]
+    pub strict_field_names: bool,
+
+    /// Instead of aborting on rules that code can't be generated for, print a warning and
+    /// generate them as an alias to RawCbor which keeps the CBOR item as its raw encoded bytes.
+    #[clap(long, value_parser, action = clap::ArgAction::Set, default_value_t = false)]
+    pub warn_unsupported: bool,
+
+    /// Enums whose largest variant is estimated to be more than this many bytes bigger than their
+    /// smallest one get #[allow(clippy::large_enum_variant)] as boxing it is left up to the user.
+    #[clap(long, value_parser, value_name = "BYTES", default_value_t = 256)]
+    pub large_enum_variant_threshold: usize,
+
+    /// Visibility of the generated rust types and their inherent fns. Can be overridden per-type with @visibility.
+    /// Anything but pub requires --wasm=false as the wasm crate wraps the rust types from outside the crate.
+    #[clap(long, value_enum, value_name = "VISIBILITY", default_value_t = Visibility::Pub)]
+    pub visibility: Visibility,
+
+    /// Maximum number of aliases followed when resolving a chain of type aliases (e.g. a = b, b = c, ...).
+    /// Exceeding this is an error, which is what happens for aliases that refer to each other in a cycle.
+    #[clap(
+        long,
+        value_parser,
+        value_name = "MAX_ALIAS_DEPTH",
+        default_value_t = 64
+    )]
+    pub max_alias_depth: usize,
+
+    /// Regex crate used to check .regexp (XML Schema regex) patterns in the generated code
+    #[clap(long, value_enum, value_name = "REGEX_ENGINE", default_value_t = RegexEngine::Regex)]
+    pub regexp_engine: RegexEngine,
+
+    /// Regex crate used to check .pcre (Perl-compatible regex) patterns in the generated code
+    #[clap(long, value_enum, value_name = "REGEX_ENGINE", default_value_t = RegexEngine::Regex)]
+    pub pcre_engine: RegexEngine,
+
+    /// Generates a #![no_std] rust crate that uses alloc's String/Vec/BTreeMap instead.
+    /// cbor_event requires std so this only generates minicbor (de)serialization and requires --cbor-crate=minicbor
+    #[clap(long, value_parser, action = clap::ArgAction::Set, default_value_t = false)]
+    pub no_std: bool,
+
     /// Generates a wasm_bindgen crate for wasm bindings
     #[clap(long, value_parser, action = clap::ArgAction::Set, default_value_t = true)]
     pub wasm: bool,
@@ -85,6 +250,15 @@ pub struct Cli {
     /// calling qux!(rust::path::A, A); for every struct A with a CBOR/JSON API
     #[clap(long, value_parser)]
     pub wasm_conversions_macro: Option<String>,
+
+    /// Also writes a graphviz DOT diagram of the generated types and how they refer to each other to this file
+    #[clap(long, value_parser, value_name = "DOT_FILE")]
+    pub type_graph_dot: Option<std::path::PathBuf>,
+
+    /// Instead of writing to the output directory, generate to a temporary directory and compare it
+    /// against the existing output. Exits with code 1 and prints a diff if anything differs.
+    #[clap(long, value_parser, action = clap::ArgAction::Set, default_value_t = false)]
+    pub check: bool,
 }
 
 impl Cli {
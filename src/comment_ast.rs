@@ -2,7 +2,9 @@ extern crate nom;
 use nom::{
     branch::alt,
     bytes::complete::{tag, take_while, take_while1},
+    character::complete::char,
     multi::many0,
+    sequence::delimited,
     IResult,
 };
 
@@ -16,6 +18,36 @@ pub struct RuleMetadata {
     pub custom_json: bool,
     pub custom_serialize: Option<String>,
     pub custom_deserialize: Option<String>,
+    /// Map key whose value discriminates between the variants of a map group choice
+    pub tag_field: Option<String>,
+    /// (ok, err) type choices to represent as a rust Result<ok, err>
+    pub result: Option<(String, String)>,
+    /// keep explicit nulls distinct from absent keys for optional T / null map fields (the default)
+    pub explicit_null: bool,
+    /// read an explicit null the same as an absent key for optional T / null map fields
+    pub collapse_null: bool,
+    /// also generate a {Name}Update struct with every field optional for partial updates
+    pub all_fields_optional: bool,
+    /// derive Hash on the struct even if its fields aren't all known to implement Hash
+    pub hash: bool,
+    /// derive PartialEq and Eq on the struct even if its fields aren't all known to implement them
+    pub eq: bool,
+    /// require the type choice to be generated as a string-backed enum e.g. "a" / "b"
+    pub string_enum: bool,
+    /// cargo feature the generated type is gated behind e.g. #[cfg(feature = "foo")]
+    pub feature: Option<String>,
+    /// integer type for #[repr(..)] on enums of integer constants with the constants as discriminants
+    pub repr: Option<String>,
+    /// accept any other uint for a choice of integer constants in a catch-all Unknown variant
+    pub open: bool,
+    /// serialize this byte field as a hex string in JSON with --json-serde-derives
+    pub hex: bool,
+    /// don't generate Display / LowerHex / UpperHex on a bytes wrapper
+    pub no_hex_display: bool,
+    /// user-provided rust type (path) to use for a field instead of the one from the CDDL
+    pub rust_type: Option<String>,
+    /// visibility of the generated rust type overriding --visibility e.g. pub(crate)
+    pub visibility: Option<String>,
     pub comment: Option<String>,
 }
 
@@ -51,6 +83,21 @@ pub fn merge_metadata(r1: &RuleMetadata, r2: &RuleMetadata) -> RuleMetadata {
             r2.custom_deserialize,
             "custom_deserialize"
         ),
+        tag_field: merge_metadata_fields!(r1.tag_field, r2.tag_field, "tag_field"),
+        result: merge_metadata_fields!(r1.result, r2.result, "result"),
+        explicit_null: r1.explicit_null || r2.explicit_null,
+        collapse_null: r1.collapse_null || r2.collapse_null,
+        all_fields_optional: r1.all_fields_optional || r2.all_fields_optional,
+        hash: r1.hash || r2.hash,
+        eq: r1.eq || r2.eq,
+        string_enum: r1.string_enum || r2.string_enum,
+        feature: merge_metadata_fields!(r1.feature, r2.feature, "feature"),
+        repr: merge_metadata_fields!(r1.repr, r2.repr, "repr"),
+        open: r1.open || r2.open,
+        hex: r1.hex || r2.hex,
+        no_hex_display: r1.no_hex_display || r2.no_hex_display,
+        rust_type: merge_metadata_fields!(r1.rust_type, r2.rust_type, "rust_type"),
+        visibility: merge_metadata_fields!(r1.visibility, r2.visibility, "visibility"),
         comment: merge_metadata_fields!(r1.comment, r2.comment, "comment"),
     };
     merged.verify();
@@ -65,6 +112,21 @@ enum ParseResult {
     CustomJson,
     CustomSerialize(String),
     CustomDeserialize(String),
+    TagField(String),
+    Result(String, String),
+    ExplicitNull,
+    CollapseNull,
+    AllFieldsOptional,
+    Hash,
+    Eq,
+    StringEnum,
+    Feature(String),
+    Repr(String),
+    Open,
+    Hex,
+    NoHexDisplay,
+    RustType(String),
+    Visibility(String),
     Comment(String),
 }
 
@@ -111,6 +173,51 @@ impl RuleMetadata {
                     custom_deserialize,
                     "custom_deserialize"
                 ),
+                ParseResult::TagField(tag_field) => {
+                    merge_parse_fields!(base.tag_field, tag_field, "tag_field")
+                }
+                ParseResult::Result(ok, err) => {
+                    merge_parse_fields!(base.result, (ok.clone(), err.clone()), "result")
+                }
+                ParseResult::ExplicitNull => {
+                    base.explicit_null = true;
+                }
+                ParseResult::CollapseNull => {
+                    base.collapse_null = true;
+                }
+                ParseResult::AllFieldsOptional => {
+                    base.all_fields_optional = true;
+                }
+                ParseResult::Hash => {
+                    base.hash = true;
+                }
+                ParseResult::Eq => {
+                    base.eq = true;
+                }
+                ParseResult::StringEnum => {
+                    base.string_enum = true;
+                }
+                ParseResult::Feature(feature) => {
+                    merge_parse_fields!(base.feature, feature, "feature")
+                }
+                ParseResult::Repr(repr) => {
+                    merge_parse_fields!(base.repr, repr, "repr")
+                }
+                ParseResult::Open => {
+                    base.open = true;
+                }
+                ParseResult::Hex => {
+                    base.hex = true;
+                }
+                ParseResult::NoHexDisplay => {
+                    base.no_hex_display = true;
+                }
+                ParseResult::RustType(rust_type) => {
+                    merge_parse_fields!(base.rust_type, rust_type, "rust_type")
+                }
+                ParseResult::Visibility(visibility) => {
+                    merge_parse_fields!(base.visibility, visibility, "visibility")
+                }
                 ParseResult::Comment(comment) => {
                     merge_parse_fields!(base.comment, comment, "comment")
                 }
@@ -125,15 +232,30 @@ impl RuleMetadata {
             // this would make no sense anyway as with newtype we're already not making an alias
             panic!("cannot use both @newtype and @no_alias on the same alias");
         }
+        if let Some(visibility) = &self.visibility {
+            if <crate::cli::Visibility as clap::ValueEnum>::from_str(visibility, false).is_err() {
+                panic!(
+                    "@visibility {} is not one of pub, pub(crate) or private",
+                    visibility
+                );
+            }
+        }
     }
 }
 
 fn tag_name(input: &str) -> IResult<&str, ParseResult> {
     let (input, _) = tag("@name")(input)?;
     let (input, _) = take_while(char::is_whitespace)(input)?;
-    let (input, name) = take_while1(|ch| !char::is_whitespace(ch))(input)?;
+    // quotes allow spaces e.g. @name "foo bar". Line breaks inside are treated as spaces.
+    fn quoted_name(input: &str) -> IResult<&str, &str> {
+        delimited(char('"'), take_while1(|ch| ch != '"'), char('"'))(input)
+    }
+    let (input, name) = alt((quoted_name, take_while1(|ch| !char::is_whitespace(ch))))(input)?;
 
-    Ok((input, ParseResult::Name(name.to_string())))
+    Ok((
+        input,
+        ParseResult::Name(name.split_whitespace().collect::<Vec<_>>().join(" ")),
+    ))
 }
 
 fn tag_newtype(input: &str) -> IResult<&str, ParseResult> {
@@ -190,6 +312,119 @@ fn tag_custom_deserialize(input: &str) -> IResult<&str, ParseResult> {
     ))
 }
 
+fn tag_tag_field(input: &str) -> IResult<&str, ParseResult> {
+    let (input, _) = tag("@tag_field")(input)?;
+    let (input, _) = take_while(char::is_whitespace)(input)?;
+    let (input, tag_field) = take_while1(|ch| !char::is_whitespace(ch))(input)?;
+
+    Ok((input, ParseResult::TagField(tag_field.to_string())))
+}
+
+fn tag_result(input: &str) -> IResult<&str, ParseResult> {
+    let (input, _) = tag("@result")(input)?;
+    let (input, _) = take_while(char::is_whitespace)(input)?;
+    let (input, _) = tag("ok=")(input)?;
+    let (input, ok) = take_while1(|ch| !char::is_whitespace(ch))(input)?;
+    let (input, _) = take_while(char::is_whitespace)(input)?;
+    let (input, _) = tag("err=")(input)?;
+    let (input, err) = take_while1(|ch| !char::is_whitespace(ch))(input)?;
+
+    Ok((input, ParseResult::Result(ok.to_string(), err.to_string())))
+}
+
+fn tag_explicit_null(input: &str) -> IResult<&str, ParseResult> {
+    let (input, _) = tag("@explicit_null")(input)?;
+
+    Ok((input, ParseResult::ExplicitNull))
+}
+
+fn tag_collapse_null(input: &str) -> IResult<&str, ParseResult> {
+    let (input, _) = tag("@collapse_null")(input)?;
+
+    Ok((input, ParseResult::CollapseNull))
+}
+
+fn tag_all_fields_optional(input: &str) -> IResult<&str, ParseResult> {
+    let (input, _) = tag("@all_fields_optional")(input)?;
+
+    Ok((input, ParseResult::AllFieldsOptional))
+}
+
+fn tag_hash(input: &str) -> IResult<&str, ParseResult> {
+    let (input, _) = tag("@hash")(input)?;
+
+    Ok((input, ParseResult::Hash))
+}
+
+fn tag_eq(input: &str) -> IResult<&str, ParseResult> {
+    let (input, _) = tag("@eq")(input)?;
+
+    Ok((input, ParseResult::Eq))
+}
+
+fn tag_string_enum(input: &str) -> IResult<&str, ParseResult> {
+    let (input, _) = tag("@string_enum")(input)?;
+
+    Ok((input, ParseResult::StringEnum))
+}
+
+fn tag_feature(input: &str) -> IResult<&str, ParseResult> {
+    let (input, _) = tag("@feature")(input)?;
+    let (input, _) = take_while(char::is_whitespace)(input)?;
+    fn quoted_feature(input: &str) -> IResult<&str, &str> {
+        delimited(char('"'), take_while1(|ch| ch != '"'), char('"'))(input)
+    }
+    let (input, feature) = alt((
+        quoted_feature,
+        take_while1(|ch| !char::is_whitespace(ch) && ch != '@'),
+    ))(input)?;
+
+    Ok((input, ParseResult::Feature(feature.trim().to_string())))
+}
+
+fn tag_repr(input: &str) -> IResult<&str, ParseResult> {
+    let (input, _) = tag("@repr")(input)?;
+    let (input, _) = take_while(char::is_whitespace)(input)?;
+    let (input, repr) = take_while1(|ch: char| ch.is_ascii_alphanumeric())(input)?;
+
+    Ok((input, ParseResult::Repr(repr.to_string())))
+}
+
+fn tag_open(input: &str) -> IResult<&str, ParseResult> {
+    let (input, _) = tag("@open")(input)?;
+
+    Ok((input, ParseResult::Open))
+}
+
+fn tag_hex(input: &str) -> IResult<&str, ParseResult> {
+    let (input, _) = tag("@hex")(input)?;
+
+    Ok((input, ParseResult::Hex))
+}
+
+fn tag_no_hex_display(input: &str) -> IResult<&str, ParseResult> {
+    let (input, _) = tag("@no_hex_display")(input)?;
+
+    Ok((input, ParseResult::NoHexDisplay))
+}
+
+fn tag_rust_type(input: &str) -> IResult<&str, ParseResult> {
+    let (input, _) = tag("@rust_type")(input)?;
+    let (input, _) = take_while(char::is_whitespace)(input)?;
+    let (input, rust_type) = take_while1(|ch| !char::is_whitespace(ch))(input)?;
+
+    Ok((input, ParseResult::RustType(rust_type.to_string())))
+}
+
+fn tag_visibility(input: &str) -> IResult<&str, ParseResult> {
+    let (input, _) = tag("@visibility")(input)?;
+    let (input, _) = take_while(char::is_whitespace)(input)?;
+    let (input, visibility) =
+        take_while1(|ch: char| ch.is_ascii_alphanumeric() || "_()".contains(ch))(input)?;
+
+    Ok((input, ParseResult::Visibility(visibility.to_string())))
+}
+
 fn tag_comment(input: &str) -> IResult<&str, ParseResult> {
     let (input, _) = tag("@doc")(input)?;
     let (input, comment) = take_while1(|c| c != '@')(input)?;
@@ -207,6 +442,20 @@ fn whitespace_then_tag(input: &str) -> IResult<&str, ParseResult> {
         tag_custom_json,
         tag_custom_serialize,
         tag_custom_deserialize,
+        tag_tag_field,
+        tag_result,
+        // nom's alt() takes at most 21 parsers
+        alt((tag_explicit_null, tag_collapse_null)),
+        tag_all_fields_optional,
+        alt((tag_hash, tag_eq)),
+        tag_string_enum,
+        tag_feature,
+        tag_repr,
+        tag_open,
+        tag_hex,
+        tag_no_hex_display,
+        tag_rust_type,
+        tag_visibility,
         tag_comment,
     ))(input)?;
 
@@ -230,14 +479,33 @@ impl<'a> From<Option<&'a cddl::ast::Comments<'a>>> for RuleMetadata {
 
 pub fn metadata_from_comments(comments: &[&str]) -> RuleMetadata {
     let mut result = RuleMetadata::default();
-    for comment in comments {
-        if let Ok(comment_metadata) = rule_metadata(comment) {
+    // comment lines are parsed separately unless a @name continues onto the next line
+    let mut lines = String::new();
+    for (i, comment) in comments.iter().enumerate() {
+        lines.push_str(comment);
+        if name_continues(&lines) && i + 1 < comments.len() {
+            lines.push('\n');
+            continue;
+        }
+        if let Ok(comment_metadata) = rule_metadata(&lines) {
             result = merge_metadata(&result, &comment_metadata.1);
         }
+        lines.clear();
     }
     result
 }
 
+/// Whether the last @name is missing its value or has an unclosed quote
+fn name_continues(comment: &str) -> bool {
+    match comment.rfind("@name") {
+        Some(i) => {
+            let value = comment[i + "@name".len()..].trim_start();
+            value.is_empty() || (value.starts_with('"') && !value[1..].contains('"'))
+        }
+        None => false,
+    }
+}
+
 #[test]
 fn parse_comment_name() {
     assert_eq!(
@@ -246,13 +514,7 @@ fn parse_comment_name() {
             "",
             RuleMetadata {
                 name: Some("foo".to_string()),
-                newtype: None,
-                no_alias: false,
-                used_as_key: false,
-                custom_json: false,
-                custom_serialize: None,
-                custom_deserialize: None,
-                comment: None,
+                ..RuleMetadata::default()
             }
         ))
     );
@@ -265,14 +527,8 @@ fn parse_comment_newtype() {
         Ok((
             "",
             RuleMetadata {
-                name: None,
                 newtype: Some(None),
-                no_alias: false,
-                used_as_key: false,
-                custom_json: false,
-                custom_serialize: None,
-                custom_deserialize: None,
-                comment: None,
+                ..RuleMetadata::default()
             }
         ))
     );
@@ -285,14 +541,9 @@ fn parse_comment_newtype_getter_before() {
         Ok((
             "",
             RuleMetadata {
-                name: None,
                 newtype: Some(Some("custom_getter".to_owned())),
-                no_alias: false,
                 used_as_key: true,
-                custom_json: false,
-                custom_serialize: None,
-                custom_deserialize: None,
-                comment: None,
+                ..RuleMetadata::default()
             }
         ))
     );
@@ -305,14 +556,9 @@ fn parse_comment_newtype_getter_after() {
         Ok((
             "",
             RuleMetadata {
-                name: None,
                 newtype: Some(Some("custom_getter".to_owned())),
-                no_alias: false,
                 used_as_key: true,
-                custom_json: false,
-                custom_serialize: None,
-                custom_deserialize: None,
-                comment: None,
+                ..RuleMetadata::default()
             }
         ))
     );
@@ -327,12 +573,7 @@ fn parse_comment_newtype_and_name() {
             RuleMetadata {
                 name: Some("foo".to_string()),
                 newtype: Some(None),
-                no_alias: false,
-                used_as_key: false,
-                custom_json: false,
-                custom_serialize: None,
-                custom_deserialize: None,
-                comment: None,
+                ..RuleMetadata::default()
             }
         ))
     );
@@ -347,12 +588,8 @@ fn parse_comment_newtype_and_name_and_used_as_key() {
             RuleMetadata {
                 name: Some("foo".to_string()),
                 newtype: Some(None),
-                no_alias: false,
                 used_as_key: true,
-                custom_json: false,
-                custom_serialize: None,
-                custom_deserialize: None,
-                comment: None,
+                ..RuleMetadata::default()
             }
         ))
     );
@@ -365,14 +602,8 @@ fn parse_comment_used_as_key() {
         Ok((
             "",
             RuleMetadata {
-                name: None,
-                newtype: None,
-                no_alias: false,
                 used_as_key: true,
-                custom_json: false,
-                custom_serialize: None,
-                custom_deserialize: None,
-                comment: None,
+                ..RuleMetadata::default()
             }
         ))
     );
@@ -387,12 +618,7 @@ fn parse_comment_newtype_and_name_inverse() {
             RuleMetadata {
                 name: Some("foo".to_string()),
                 newtype: Some(None),
-                no_alias: false,
-                used_as_key: false,
-                custom_json: false,
-                custom_serialize: None,
-                custom_deserialize: None,
-                comment: None,
+                ..RuleMetadata::default()
             }
         ))
     );
@@ -406,13 +632,8 @@ fn parse_comment_name_noalias() {
             "",
             RuleMetadata {
                 name: Some("foo".to_string()),
-                newtype: None,
                 no_alias: true,
-                used_as_key: false,
-                custom_json: false,
-                custom_serialize: None,
-                custom_deserialize: None,
-                comment: None,
+                ..RuleMetadata::default()
             }
         ))
     );
@@ -425,14 +646,9 @@ fn parse_comment_newtype_and_custom_json() {
         Ok((
             "",
             RuleMetadata {
-                name: None,
                 newtype: Some(None),
-                no_alias: false,
-                used_as_key: false,
                 custom_json: true,
-                custom_serialize: None,
-                custom_deserialize: None,
-                comment: None,
+                ..RuleMetadata::default()
             }
         ))
     );
@@ -451,14 +667,9 @@ fn parse_comment_custom_serialize_deserialize() {
         Ok((
             "",
             RuleMetadata {
-                name: None,
-                newtype: None,
-                no_alias: false,
-                used_as_key: false,
-                custom_json: false,
                 custom_serialize: Some("foo".to_string()),
                 custom_deserialize: Some("bar".to_string()),
-                comment: None,
+                ..RuleMetadata::default()
             }
         ))
     );
@@ -474,12 +685,268 @@ fn parse_comment_all_except_no_alias() {
             RuleMetadata {
                 name: Some("baz".to_string()),
                 newtype: Some(None),
-                no_alias: false,
                 used_as_key: true,
                 custom_json: true,
                 custom_serialize: Some("foo".to_string()),
                 custom_deserialize: Some("bar".to_string()),
                 comment: Some("this is a doc comment".to_string()),
+                ..RuleMetadata::default()
+            }
+        ))
+    );
+}
+
+#[test]
+fn parse_comment_tag_field() {
+    assert_eq!(
+        rule_metadata("@tag_field type @doc tagged union"),
+        Ok((
+            "",
+            RuleMetadata {
+                tag_field: Some("type".to_string()),
+                comment: Some("tagged union".to_string()),
+                ..RuleMetadata::default()
+            }
+        ))
+    );
+}
+
+#[test]
+fn parse_comment_result() {
+    assert_eq!(
+        rule_metadata("@result ok=uint err=error<text> @no_alias"),
+        Ok((
+            "",
+            RuleMetadata {
+                no_alias: true,
+                result: Some(("uint".to_string(), "error<text>".to_string())),
+                ..RuleMetadata::default()
+            }
+        ))
+    );
+}
+
+#[test]
+fn parse_comment_explicit_null() {
+    assert_eq!(
+        rule_metadata("@name maybe_text @explicit_null"),
+        Ok((
+            "",
+            RuleMetadata {
+                name: Some("maybe_text".to_string()),
+                explicit_null: true,
+                ..RuleMetadata::default()
+            }
+        ))
+    );
+}
+
+#[test]
+fn parse_comment_collapse_null() {
+    assert_eq!(
+        rule_metadata("@collapse_null @name maybe_text"),
+        Ok((
+            "",
+            RuleMetadata {
+                name: Some("maybe_text".to_string()),
+                collapse_null: true,
+                ..RuleMetadata::default()
+            }
+        ))
+    );
+}
+
+#[test]
+fn parse_comment_all_fields_optional() {
+    assert_eq!(
+        rule_metadata("@all_fields_optional @doc config"),
+        Ok((
+            "",
+            RuleMetadata {
+                all_fields_optional: true,
+                comment: Some("config".to_string()),
+                ..RuleMetadata::default()
+            }
+        ))
+    );
+}
+
+#[test]
+fn parse_comment_hash() {
+    assert_eq!(
+        rule_metadata("@hash @name foo"),
+        Ok((
+            "",
+            RuleMetadata {
+                name: Some("foo".to_string()),
+                hash: true,
+                ..RuleMetadata::default()
+            }
+        ))
+    );
+}
+
+#[test]
+fn parse_comment_string_enum() {
+    assert_eq!(
+        rule_metadata("@string_enum"),
+        Ok((
+            "",
+            RuleMetadata {
+                string_enum: true,
+                ..RuleMetadata::default()
+            }
+        ))
+    );
+}
+
+#[test]
+fn parse_comment_name_quoted() {
+    assert_eq!(
+        rule_metadata("@name \"foo  bar\" @newtype"),
+        Ok((
+            "",
+            RuleMetadata {
+                name: Some("foo bar".to_string()),
+                newtype: Some(None),
+                ..RuleMetadata::default()
+            }
+        ))
+    );
+}
+
+#[test]
+fn parse_comment_name_multiline() {
+    assert_eq!(
+        metadata_from_comments(&[" @doc docs @name", " foo @newtype"]),
+        RuleMetadata {
+            name: Some("foo".to_string()),
+            newtype: Some(None),
+            comment: Some("docs".to_string()),
+            ..RuleMetadata::default()
+        }
+    );
+    assert_eq!(
+        metadata_from_comments(&[" @name \"foo", " bar\"", " @used_as_key"]),
+        RuleMetadata {
+            name: Some("foo bar".to_string()),
+            used_as_key: true,
+            ..RuleMetadata::default()
+        }
+    );
+}
+
+#[test]
+fn parse_comment_feature() {
+    assert_eq!(
+        rule_metadata("@feature \"experimental\" @name foo"),
+        Ok((
+            "",
+            RuleMetadata {
+                name: Some("foo".to_string()),
+                feature: Some("experimental".to_string()),
+                ..RuleMetadata::default()
+            }
+        ))
+    );
+    assert_eq!(
+        rule_metadata("@feature unstable-v2"),
+        Ok((
+            "",
+            RuleMetadata {
+                feature: Some("unstable-v2".to_string()),
+                ..RuleMetadata::default()
+            }
+        ))
+    );
+}
+
+#[test]
+fn parse_comment_repr_open() {
+    assert_eq!(
+        rule_metadata("@repr u8 @open"),
+        Ok((
+            "",
+            RuleMetadata {
+                repr: Some("u8".to_string()),
+                open: true,
+                ..RuleMetadata::default()
+            }
+        ))
+    );
+}
+
+#[test]
+fn parse_comment_hex() {
+    assert_eq!(
+        rule_metadata("@name key @hex"),
+        Ok((
+            "",
+            RuleMetadata {
+                name: Some("key".to_string()),
+                hex: true,
+                ..RuleMetadata::default()
+            }
+        ))
+    );
+}
+
+#[test]
+fn parse_comment_no_hex_display() {
+    assert_eq!(
+        rule_metadata("@newtype @no_hex_display"),
+        Ok((
+            "",
+            RuleMetadata {
+                newtype: Some(None),
+                no_hex_display: true,
+                ..RuleMetadata::default()
+            }
+        ))
+    );
+}
+
+#[test]
+fn parse_comment_rust_type() {
+    assert_eq!(
+        rule_metadata("@name amount @rust_type crate::domain::Amount"),
+        Ok((
+            "",
+            RuleMetadata {
+                name: Some("amount".to_string()),
+                rust_type: Some("crate::domain::Amount".to_string()),
+                ..RuleMetadata::default()
+            }
+        ))
+    );
+}
+
+#[test]
+fn parse_comment_visibility() {
+    assert_eq!(
+        rule_metadata("@name foo @visibility pub(crate) @hash"),
+        Ok((
+            "",
+            RuleMetadata {
+                name: Some("foo".to_string()),
+                visibility: Some("pub(crate)".to_string()),
+                hash: true,
+                ..RuleMetadata::default()
+            }
+        ))
+    );
+}
+
+#[test]
+fn parse_comment_eq() {
+    assert_eq!(
+        rule_metadata("@eq @name foo"),
+        Ok((
+            "",
+            RuleMetadata {
+                name: Some("foo".to_string()),
+                eq: true,
+                ..RuleMetadata::default()
             }
         ))
     );
@@ -2,11 +2,19 @@ use std::collections::{BTreeMap, BTreeSet};
 
 use cddl::ast::*;
 
+use crate::intermediate::{ConceptualRustType, IntermediateTypes, RustIdent, RustStructKind};
+
 pub fn topological_rule_order<'a>(rules: &'a [&'a Rule<'a>]) -> Vec<&'a Rule<'a>> {
     let mut adj_list = BTreeMap::new();
     for cddl_rule in rules.iter() {
         let (ident, refs) = find_references(cddl_rule);
-        adj_list.insert(ident.ident, (*cddl_rule, refs));
+        // choice alternates (/= and //=) share their name with the original rule
+        // so we keep the original definition and merge in the references
+        adj_list
+            .entry(ident.ident)
+            .or_insert((*cddl_rule, Vec::new()))
+            .1
+            .extend(refs);
     }
     let mut unvisited = adj_list.keys().copied().collect::<BTreeSet<&str>>();
     let mut topo_order = Vec::new();
@@ -34,7 +42,9 @@ fn dfs_visit<'a>(
     let (rule, neighbors) = adj_list.get(u).unwrap();
     for v in neighbors.iter() {
         if processing.contains(v.ident) {
-            eprintln!("Recursive type: '{u}' / '{v}' - code will possibly need to be edited by hand to use Box/etc");
+            eprintln!(
+                "Recursive type: '{u}' / '{v}' - any references not behind a Vec/map will be boxed"
+            );
             continue;
         }
         if unvisited.contains(v.ident) {
@@ -75,7 +85,15 @@ fn find_references<'a>(cddl_rule: &'a Rule<'a>) -> (&'a Identifier, Vec<&'a Iden
 }
 
 fn find_refs_type1<'a>(refs: &mut Vec<&'a Identifier<'a>>, type1: &'a Type1<'a>) {
-    match &type1.type2 {
+    find_refs_type2(refs, &type1.type2);
+    // e.g. constants used in .cat or the type in .cbor
+    if let Some(operator) = &type1.operator {
+        find_refs_type2(refs, &operator.type2);
+    }
+}
+
+fn find_refs_type2<'a>(refs: &mut Vec<&'a Identifier<'a>>, type2: &'a Type2<'a>) {
+    match type2 {
         Type2::Typename {
             ident,
             generic_args,
@@ -152,3 +170,72 @@ fn find_refs_generic_args<'a>(
             .for_each(|arg| find_refs_type1(refs, arg.arg.as_ref()));
     }
 }
+
+/// Renders the parsed types as a graphviz DOT digraph for visualizing large specs.
+/// Each generated type is a node labeled with its kind. Edges go from a type to every type
+/// it directly refers to: solid for fields/elements/keys/values, dashed for enum variants
+/// and dotted from each generic instance to the generic definition it instantiates.
+pub fn type_graph_dot(types: &IntermediateTypes) -> String {
+    let mut nodes = Vec::new();
+    let mut edges = Vec::new();
+    types.visit_rust_structs(&mut |rust_struct| {
+        let ident = rust_struct.ident();
+        nodes.push(format!(
+            "    \"{ident}\" [shape=box, label=\"{ident}\\n({:?})\"];",
+            rust_struct.kind()
+        ));
+        let style = match rust_struct.kind() {
+            RustStructKind::TypeChoice | RustStructKind::GroupChoice => ", style=dashed",
+            _ => "",
+        };
+        for (name, ty) in rust_struct.field_types() {
+            let mut referenced = Vec::new();
+            find_refs_rust_type(&mut referenced, &ty.conceptual_type);
+            for to in referenced {
+                edges.push(format!(
+                    "    \"{ident}\" -> \"{to}\" [label=\"{name}\"{style}];"
+                ));
+            }
+        }
+    });
+    let generic_defs = types
+        .list_generic_instances()
+        .map(|instance| instance.generic_ident())
+        .collect::<BTreeSet<_>>();
+    for def in generic_defs {
+        nodes.push(format!(
+            "    \"{def}\" [shape=box, style=dashed, label=\"{def}\\n(generic)\"];"
+        ));
+    }
+    for instance in types.list_generic_instances() {
+        edges.push(format!(
+            "    \"{}\" -> \"{}\" [label=\"instance of\", style=dotted];",
+            instance.instance_ident(),
+            instance.generic_ident()
+        ));
+    }
+    let mut dot = String::from("digraph types {\n");
+    for line in nodes.iter().chain(edges.iter()) {
+        dot.push_str(line);
+        dot.push('\n');
+    }
+    dot.push_str("}\n");
+    dot
+}
+
+/// Rust types directly referenced by a type e.g. Foo and Bar for Vec<Option<Foo>> or Map<Foo, Bar>,
+/// without going into those types themselves
+fn find_refs_rust_type<'a>(refs: &mut Vec<&'a RustIdent>, ty: &'a ConceptualRustType) {
+    match ty {
+        ConceptualRustType::Rust(ident) => refs.push(ident),
+        ConceptualRustType::Array(inner) | ConceptualRustType::Optional(inner) => {
+            find_refs_rust_type(refs, &inner.conceptual_type)
+        }
+        ConceptualRustType::Map(first, second) | ConceptualRustType::Result(first, second) => {
+            find_refs_rust_type(refs, &first.conceptual_type);
+            find_refs_rust_type(refs, &second.conceptual_type);
+        }
+        ConceptualRustType::Alias(_, inner) => find_refs_rust_type(refs, inner),
+        ConceptualRustType::Fixed(_) | ConceptualRustType::Primitive(_) => (),
+    }
+}
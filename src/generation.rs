@@ -1,4 +1,4 @@
-use crate::cli::Cli;
+use crate::cli::{CborCrate, Cli, RegexEngine, Visibility};
 use codegen::{Block, TypeAlias};
 use std::borrow::Cow;
 use std::collections::{BTreeMap, BTreeSet};
@@ -7,12 +7,13 @@ use std::path::Path;
 use std::process::{Command, Stdio};
 
 use crate::intermediate::{
-    AliasIdent, CBOREncodingOperation, CDDLIdent, ConceptualRustType, EnumVariant, EnumVariantData,
-    FixedValue, IntermediateTypes, ModuleScope, Primitive, Representation, RustField, RustIdent,
-    RustRecord, RustStructCBORLen, RustStructConfig, RustStructType, RustType,
-    RustTypeSerializeConfig, ToWasmBoundaryOperations, VariantIdent, ROOT_SCOPE,
+    enum_variants_ints, enum_variants_text, AliasIdent, CBOREncodingOperation, CDDLIdent,
+    ConceptualRustType, EnumVariant, EnumVariantData, FixedValue, FloatBound, IntermediateTypes,
+    ModuleScope, Primitive, RegexPattern, Representation, RustField, RustIdent, RustRecord,
+    RustStructCBORLen, RustStructConfig, RustStructType, RustType, RustTypeSerializeConfig,
+    ToWasmBoundaryOperations, VariantIdent, ROOT_SCOPE,
 };
-use crate::utils::{cbor_type_code_str, convert_to_snake_case};
+use crate::utils::{append_number_if_duplicate, cbor_type_code_str, convert_to_snake_case};
 
 #[derive(Debug, Clone)]
 struct SerializeConfig<'a> {
@@ -232,6 +233,53 @@ impl<'a> DeserializeConfig<'a> {
     }
 }
 
+/// #[must_use] on generated constructors that check bounds/regexes etc
+const MUST_USE_VALIDATION: &str = "must_use = \"validation may fail\"";
+
+/// Makes the static to_cbor_bytes() / from_cbor_bytes() helpers (de)serialize tag 55799
+/// around the document root. Only these top-level entry points are changed, never the
+/// serialize()/deserialize() impls that also handle nested values.
+fn add_self_describe_tag(static_serialization: String) -> String {
+    const FROM_BYTES: &str = "let mut raw = Deserializer::from(std::io::Cursor::new(data));";
+    const TO_BYTES: &str = "let mut buf = Serializer::new_vec();";
+    assert!(static_serialization.contains(FROM_BYTES) && static_serialization.contains(TO_BYTES));
+    static_serialization
+        .replace(
+            FROM_BYTES,
+            &format!("let data = strip_self_describe_tag(data);\n        {FROM_BYTES}"),
+        )
+        .replace(
+            TO_BYTES,
+            &format!("{TO_BYTES}\n        buf.write_tag(SELF_DESCRIBE_CBOR_TAG).unwrap();"),
+        )
+}
+
+/// The cargo feature `ident` was gated behind via @feature, if any
+fn feature_of(types: &IntermediateTypes, ident: &RustIdent) -> Option<String> {
+    types
+        .rust_struct(ident)
+        .and_then(|rust_struct| rust_struct.config().feature.clone())
+}
+
+/// Puts every top-level item in `code` behind #[cfg(feature = "{feature}")].
+/// codegen always separates top-level items with an empty line so we use that to find them.
+fn gate_items_behind_feature(code: &str, feature: &str) -> String {
+    let mut gated = String::new();
+    let mut item_start = true;
+    for line in code.lines() {
+        // imports aren't separated from each other
+        if (item_start && !line.is_empty() && !line.starts_with([' ', '\t', '}', ')']))
+            || line.starts_with("use ")
+        {
+            gated.push_str(&format!("#[cfg(feature = \"{feature}\")]\n"));
+        }
+        item_start = line.trim().is_empty();
+        gated.push_str(line);
+        gated.push('\n');
+    }
+    gated
+}
+
 fn concat_files<P: AsRef<Path>>(paths: &Vec<P>) -> std::io::Result<String> {
     let mut buf = String::new();
     for path in paths {
@@ -267,7 +315,8 @@ trait EncodingVarIsCopy {
 impl<'a> EncodingVarIsCopy for SerializingRustType<'a> {
     fn encoding_var_is_copy(&self, types: &IntermediateTypes) -> bool {
         match self {
-            Self::EncodingOperation(CBOREncodingOperation::CBORBytes, _) => false,
+            Self::EncodingOperation(CBOREncodingOperation::CBORBytes(_), _) => false,
+            Self::EncodingOperation(CBOREncodingOperation::CBORText, _) => false,
             Self::EncodingOperation(CBOREncodingOperation::Tagged(_), _) => true,
             Self::Root(ty, _cfg) => ty.encoding_var_is_copy(types),
         }
@@ -292,10 +341,14 @@ impl EncodingVarIsCopy for ConceptualRustType {
             Self::Map(_, _) => true,
             Self::Fixed(fv) => fv.encoding_var_is_copy(types),
             Self::Optional(ty) => SerializingRustType::from(&**ty).encoding_var_is_copy(types),
+            Self::Result(_, _) => {
+                unimplemented!("preserve_encodings is not implemented for @result types")
+            }
             Self::Primitive(p) => match p {
                 // bool has no encoding var
                 Primitive::Bool
                 | Primitive::F64
+                | Primitive::F16
                 | Primitive::F32
                 | Primitive::I8
                 | Primitive::I16
@@ -305,8 +358,12 @@ impl EncodingVarIsCopy for ConceptualRustType {
                 | Primitive::U16
                 | Primitive::U32
                 | Primitive::U64
+                | Primitive::U128
+                | Primitive::I128
                 | Primitive::N64 => true,
-                Primitive::Bytes | Primitive::Str => false,
+                Primitive::Bytes | Primitive::ByteArray(_) | Primitive::Str | Primitive::Char => {
+                    false
+                }
             },
             Self::Rust(ident) => match types.rust_struct(ident).unwrap().variant() {
                 RustStructType::CStyleEnum { variants } => {
@@ -473,9 +530,22 @@ pub struct GenerationScope {
     wasm_lib_scope: codegen::Scope,
     wasm_scopes: BTreeMap<ModuleScope, codegen::Scope>,
     cbor_encodings_scopes: BTreeMap<ModuleScope, codegen::Scope>,
+    // constants.rs with --constants-module
+    constants_scope: codegen::Scope,
     json_lines: BlocksOrLines,
     already_generated: BTreeSet<RustIdent>,
     no_deser_reasons: BTreeMap<RustIdent, Vec<String>>,
+    feature_gated: FeatureGatedScopes,
+}
+
+/// Code for types annotated with @feature, kept apart from the regular scopes
+/// until generation is done so every item can be put behind a #[cfg] at once.
+#[derive(Default)]
+struct FeatureGatedScopes {
+    rust: BTreeMap<(ModuleScope, String), codegen::Scope>,
+    serialize: BTreeMap<(ModuleScope, String), codegen::Scope>,
+    wasm: BTreeMap<(ModuleScope, String), codegen::Scope>,
+    cbor_encodings: BTreeMap<(ModuleScope, String), codegen::Scope>,
 }
 
 impl Default for GenerationScope {
@@ -494,9 +564,11 @@ impl GenerationScope {
             wasm_lib_scope: codegen::Scope::new(),
             wasm_scopes: BTreeMap::new(),
             cbor_encodings_scopes: BTreeMap::new(),
+            constants_scope: codegen::Scope::new(),
             json_lines: BlocksOrLines::default(),
             already_generated: BTreeSet::new(),
             no_deser_reasons: BTreeMap::new(),
+            feature_gated: FeatureGatedScopes::default(),
         }
     }
 
@@ -509,14 +581,19 @@ impl GenerationScope {
             if let AliasIdent::Rust(ident) = alias_ident {
                 // also make sure not to generate it if we instead generated a binary wrapper type
                 if alias_info.gen_rust_alias {
-                    self.rust(types, ident).push_type_alias(
-                        TypeAlias::new(
-                            ident,
-                            alias_info.base_type.for_rust_member(types, false, cli),
-                        )
-                        .vis("pub")
-                        .clone(),
+                    let config = RustStructConfig::from(alias_info.rule_metadata.as_ref());
+                    assert!(
+                        config.visibility.unwrap_or_default() == Visibility::Pub || !cli.wasm,
+                        "{ident}: @visibility other than pub requires --wasm=false"
+                    );
+                    let mut alias = TypeAlias::new(
+                        ident,
+                        alias_info.base_type.for_rust_member(types, false, cli),
                     );
+                    if let Some(vis) = config.visibility_token(cli) {
+                        alias.vis(vis);
+                    }
+                    self.rust(types, ident).push_type_alias(alias);
                 }
                 if alias_info.gen_wasm_alias {
                     // WASM crate
@@ -531,6 +608,7 @@ impl GenerationScope {
                             FixedValue::Uint(u) => ("u32", u.to_string()),
                             FixedValue::Float(f) => ("f64", f.to_string()),
                             FixedValue::Text(s) => ("String", format!("\"{s}\".to_owned()")),
+                            FixedValue::SimpleValue(n) => ("u8", n.to_string()),
                         };
                         self.wasm(types, ident)
                             .new_fn(convert_to_snake_case(ident.as_ref()))
@@ -619,6 +697,11 @@ impl GenerationScope {
                         &mut existing_aliases,
                     );
                 }
+                let config = rust_struct.documented_config();
+                assert!(
+                    config.visibility.unwrap_or_default() == Visibility::Pub || !cli.wasm,
+                    "{rust_ident}: @visibility other than pub requires --wasm=false"
+                );
                 match rust_struct.variant() {
                     RustStructType::Record(record) => {
                         codegen_struct(
@@ -627,7 +710,7 @@ impl GenerationScope {
                             rust_ident,
                             rust_struct.tag(),
                             record,
-                            rust_struct.config(),
+                            &config,
                             cli,
                         );
                     }
@@ -671,7 +754,7 @@ impl GenerationScope {
                             rust_ident,
                             variants,
                             rust_struct.tag(),
-                            rust_struct.config(),
+                            &config,
                             cli,
                         );
                     }
@@ -682,17 +765,22 @@ impl GenerationScope {
                         variants,
                         *rep,
                         rust_struct.tag(),
-                        rust_struct.config(),
+                        &config,
                         cli,
                     ),
-                    RustStructType::Wrapper { wrapped, min_max } => match rust_struct.tag() {
+                    RustStructType::Wrapper {
+                        wrapped,
+                        min_max,
+                        regex,
+                    } => match rust_struct.tag() {
                         Some(tag) => generate_wrapper_struct(
                             self,
                             types,
                             rust_ident,
                             &wrapped.clone().tag(tag),
                             *min_max,
-                            rust_struct.config(),
+                            regex.as_ref(),
+                            &config,
                             cli,
                         ),
                         None => generate_wrapper_struct(
@@ -701,7 +789,8 @@ impl GenerationScope {
                             rust_ident,
                             wrapped,
                             *min_max,
-                            rust_struct.config(),
+                            regex.as_ref(),
+                            &config,
                             cli,
                         ),
                     },
@@ -713,6 +802,8 @@ impl GenerationScope {
                                     generate_int(self, types, cli)
                                 }
                             }
+                            // only registered when needed (--warn-unsupported)
+                            "RawCbor" => generate_raw_cbor(self, types, cli),
                             _ => (), /* user-specified external types */
                         }
                     }
@@ -723,7 +814,7 @@ impl GenerationScope {
                             rust_ident,
                             variants,
                             rust_struct.tag(),
-                            rust_struct.config(),
+                            &config,
                             cli,
                         );
                     }
@@ -734,6 +825,100 @@ impl GenerationScope {
             }
         }
 
+        // TryFrom<&[u8]> - this must be done after all structs are generated
+        // so we know which ones ended up having deserialization generated
+        if cli.to_from_bytes_methods {
+            for (rust_ident, rust_struct) in types.rust_structs() {
+                let has_deserialize = match rust_struct.variant() {
+                    RustStructType::Record(_)
+                    | RustStructType::TypeChoice { .. }
+                    | RustStructType::GroupChoice { .. }
+                    | RustStructType::Wrapper { .. }
+                    | RustStructType::CStyleEnum { .. } => true,
+                    RustStructType::Extern => {
+                        (rust_ident.as_ref() == "Int" && types.is_referenced(rust_ident))
+                            || rust_ident.as_ref() == "RawCbor"
+                    }
+                    // type aliases to Vec/BTreeMap or user-defined
+                    RustStructType::Table { .. }
+                    | RustStructType::Array { .. }
+                    | RustStructType::RawBytesType => false,
+                };
+                if has_deserialize
+                    && self.deserialize_generated(rust_ident)
+                    && rust_struct.config().custom_deserialize.is_none()
+                {
+                    self.rust_serialize(types, rust_ident)
+                        .push_impl(make_try_from_bytes_impl(rust_ident.as_ref()));
+                }
+            }
+        }
+
+        // minicbor::Encode/Decode for anything that couldn't derive them in codegen_struct.
+        // Like TryFrom this needs to know which types ended up with deserialization.
+        if cli.cbor_crate == CborCrate::Minicbor {
+            for (rust_ident, rust_struct) in types.rust_structs() {
+                match minicbor_impls(types, rust_ident, rust_struct) {
+                    MinicborImpls::Manual(record) => {
+                        let (encode, decode) = make_minicbor_record_impls(
+                            types,
+                            rust_ident,
+                            rust_struct.tag(),
+                            record,
+                            cli,
+                        );
+                        self.rust(types, rust_ident)
+                            .push_impl(encode)
+                            .push_impl(decode);
+                    }
+                    MinicborImpls::Delegated => {
+                        // --no-std rejects these in check_no_std_support() as cbor_event needs std
+                        debug_assert!(!cli.no_std);
+                        if self.deserialize_generated(rust_ident) {
+                            let (encode, decode) = make_minicbor_impls(rust_ident.as_ref());
+                            self.rust_serialize(types, rust_ident)
+                                .push_impl(encode)
+                                .push_impl(decode);
+                        }
+                    }
+                    MinicborImpls::Derived | MinicborImpls::Custom | MinicborImpls::None => (),
+                }
+            }
+        }
+
+        // ciborium::value::Value conversions. Like TryFrom this needs to know which types ended up with deserialization.
+        // With --cbor-crate=ciborium every type with (de)serialization gets them as well as serde impls using them.
+        if cli.cbor_value_conversions || cli.cbor_crate == CborCrate::Ciborium {
+            for (rust_ident, rust_struct) in types.rust_structs() {
+                let has_serialization = match rust_struct.variant() {
+                    RustStructType::Record(_) => true,
+                    RustStructType::TypeChoice { .. }
+                    | RustStructType::GroupChoice { .. }
+                    | RustStructType::Wrapper { .. }
+                    | RustStructType::CStyleEnum { .. } => cli.cbor_crate == CborCrate::Ciborium,
+                    RustStructType::Extern
+                    | RustStructType::Table { .. }
+                    | RustStructType::Array { .. }
+                    | RustStructType::RawBytesType => false,
+                };
+                if has_serialization && !types.is_plain_group(rust_ident) {
+                    let (from, try_from) = make_cbor_value_impls(rust_ident.as_ref());
+                    self.rust_serialize(types, rust_ident).push_impl(from);
+                    if cli.cbor_crate == CborCrate::Ciborium {
+                        self.rust_serialize(types, rust_ident)
+                            .push_impl(make_ciborium_serialize_impl(rust_ident.as_ref()));
+                    }
+                    if self.deserialize_generated(rust_ident) {
+                        self.rust_serialize(types, rust_ident).push_impl(try_from);
+                        if cli.cbor_crate == CborCrate::Ciborium {
+                            self.rust_serialize(types, rust_ident)
+                                .push_impl(make_ciborium_deserialize_impl(rust_ident.as_ref()));
+                        }
+                    }
+                }
+            }
+        }
+
         // JSON export crate
         if cli.json_schema_export {
             self.json_lines
@@ -750,7 +935,10 @@ impl GenerationScope {
                 // The is_referenced check is for things like Int which are included by default
                 // in order for the CDDL to parse but might not be used.
                 // However, we need to export other root types from the user's spec
-                if !is_typedef && (rust_ident.as_ref() != "Int" || types.is_referenced(rust_ident))
+                // json-gen doesn't forward @feature features so gated types are left out
+                if !is_typedef
+                    && rust_struct.config().feature.is_none()
+                    && (rust_ident.as_ref() != "Int" || types.is_referenced(rust_ident))
                 {
                     main_lines_by_file
                         .entry(types.scope(rust_ident).clone())
@@ -778,6 +966,9 @@ impl GenerationScope {
         // rust
         self.rust_lib()
             .raw("#![allow(clippy::too_many_arguments)]\n");
+        if cli.no_std {
+            self.rust_lib().raw("#![no_std]\nextern crate alloc;\n");
+        }
         let codegen_comment = "// This file was code-generated using an experimental CDDL to rust tool:\n// https://github.com/dcSpark/cddl-codegen\n";
         for content in self.rust_scopes.values_mut() {
             content.raw(codegen_comment);
@@ -785,6 +976,10 @@ impl GenerationScope {
         for content in self.cbor_encodings_scopes.values_mut() {
             content.raw(codegen_comment);
         }
+        if cli.constants_module {
+            self.constants_scope.raw(codegen_comment);
+            self.generate_constants(types);
+        }
         for content in self.serialize_scopes.values_mut() {
             content.raw(codegen_comment);
         }
@@ -793,15 +988,22 @@ impl GenerationScope {
         }
 
         // declare modules (root lib specific)
-        if cli.export_static_files() {
+        // error.rs / serialization.rs are all cbor_event-based so they aren't used with no_std
+        if cli.export_static_files() && !cli.no_std {
             self.rust_lib().raw("pub mod error;");
             if cli.preserve_encodings {
                 self.rust_lib().raw("pub mod ordered_hash_map;");
             }
+            if types.uses_non_empty_vec() {
+                self.rust_lib().raw("pub mod non_empty_vec;");
+            }
         }
         if cli.preserve_encodings {
             self.rust_lib().raw("extern crate derivative;");
         }
+        if cli.constants_module {
+            self.rust_lib().raw("pub mod constants;");
+        }
         let scope_names = self
             .rust_scopes
             .keys()
@@ -824,7 +1026,9 @@ impl GenerationScope {
 
         // declare common modules in each module (struct files)
         for content in self.rust_scopes.values_mut() {
-            content.raw("pub mod serialization;");
+            if !cli.no_std {
+                content.raw("pub mod serialization;");
+            }
             if cli.preserve_encodings {
                 content.raw("pub mod cbor_encodings;");
             }
@@ -832,6 +1036,14 @@ impl GenerationScope {
 
         // general common imports (struct files)
         for content in self.rust_scopes.values_mut() {
+            if cli.no_std {
+                content
+                    .push_import("alloc::boxed", "Box", None)
+                    .push_import("alloc::string", "String", None)
+                    .push_import("alloc::vec", "Vec", None)
+                    .push_import("core::convert", "TryFrom", None);
+                continue;
+            }
             // needed if there's any params that can fail
             content
                 .push_import("std::convert", "TryFrom", None)
@@ -938,7 +1150,11 @@ impl GenerationScope {
             // TODO: we blindly add these two map imports. Ideally we would only do it when needed
             // but the code to figure that out would be potentially complex.
             // Issue (general - not just here): https://github.com/dcSpark/cddl-codegen/issues/139
-            content.push_import("std::collections", "BTreeMap", None);
+            if cli.no_std {
+                content.push_import("alloc::collections", "BTreeMap", None);
+            } else {
+                content.push_import("std::collections", "BTreeMap", None);
+            }
             if cli.preserve_encodings {
                 content.push_import(
                     format!("{}::ordered_hash_map", cli.common_import_rust()),
@@ -946,6 +1162,13 @@ impl GenerationScope {
                     None,
                 );
             }
+            if types.uses_non_empty_vec() {
+                content.push_import(
+                    format!("{}::non_empty_vec", cli.common_import_rust()),
+                    "NonEmptyVec",
+                    None,
+                );
+            }
         }
 
         // serialization
@@ -1046,6 +1269,8 @@ impl GenerationScope {
             // for these modules when they only exist to support modules nested deeper
             declare_modules(&mut self.wasm_scopes, &wasm_scope_names);
         }
+
+        self.merge_feature_gated_scopes();
     }
 
     /// Exports all already-generated state to the provided directory.
@@ -1126,7 +1351,7 @@ impl GenerationScope {
 
         // serialiation.rs / {module}/serialization.rs files (if input is a directory)
         let mut merged_rust_serialize_scope = codegen::Scope::new();
-        if cli.export_static_files() {
+        if cli.export_static_files() && !cli.no_std {
             let mut serialize_paths = vec![cli.static_dir.join("serialization.rs")];
             if cli.preserve_encodings {
                 serialize_paths.push(cli.static_dir.join("serialization_preserve.rs"));
@@ -1151,16 +1376,25 @@ impl GenerationScope {
             if export_raw_bytes_encoding_trait {
                 serialize_paths.push(cli.static_dir.join("raw_bytes_encoding.rs"));
             }
-            merged_rust_serialize_scope.raw(concat_files(&serialize_paths)?);
+            if cli.self_describe_tag {
+                serialize_paths.push(cli.static_dir.join("serialization_self_describe.rs"));
+            }
+            let mut static_serialization = concat_files(&serialize_paths)?;
+            if cli.self_describe_tag {
+                static_serialization = add_self_describe_tag(static_serialization);
+            }
+            merged_rust_serialize_scope.raw(static_serialization);
         }
         merged_rust_serialize_scope.append(&self.rust_serialize_lib_scope);
-        merge_scopes_and_export(
-            rust_dir.join("rust/src"),
-            merged_rust_serialize_scope,
-            &self.serialize_scopes,
-            "serialization.rs",
-            "serialization.rs",
-        )?;
+        if !cli.no_std {
+            merge_scopes_and_export(
+                rust_dir.join("rust/src"),
+                merged_rust_serialize_scope,
+                &self.serialize_scopes,
+                "serialization.rs",
+                "serialization.rs",
+            )?;
+        }
 
         // cbor_encodings.rs / {module}/cbor_encodings.rs (if input is a directory)
         if cli.preserve_encodings {
@@ -1182,6 +1416,14 @@ impl GenerationScope {
             }
         }
 
+        // constants.rs
+        if cli.constants_module {
+            std::fs::write(
+                rust_dir.join("rust/src/constants.rs"),
+                rustfmt_generated_string(&self.constants_scope.to_string())?.as_ref(),
+            )?;
+        }
+
         // Cargo.toml
         let mut rust_cargo_toml = std::fs::read_to_string(cli.static_dir.join("Cargo_rust.toml"))?;
         if cli.preserve_encodings {
@@ -1195,7 +1437,30 @@ impl GenerationScope {
         if cli.json_schema_export {
             rust_cargo_toml.push_str("schemars = \"0.8.8\"\n");
         }
+        if cli.no_std {
+            // cdylib would need a panic handler
+            rust_cargo_toml = rust_cargo_toml
+                .replace(
+                    "crate-type = [\"cdylib\", \"rlib\"]",
+                    "crate-type = [\"rlib\"]",
+                )
+                .replace("cbor_event = \"2.4.0\"\n", "");
+            rust_cargo_toml.push_str(
+                "minicbor = { version = \"0.20\", default-features = false, features = [\"alloc\", \"derive\"] }\n",
+            );
+        } else if cli.cbor_crate == CborCrate::Minicbor {
+            rust_cargo_toml
+                .push_str("minicbor = { version = \"0.20\", features = [\"std\", \"derive\"] }\n");
+        }
+        // @hex fields use hex's serde support directly
+        let hex_serde_fields = cli.json_serde_derives
+            && types.rust_structs().values().any(|rust_struct| {
+                !rust_struct.config().custom_json
+                    && matches!(rust_struct.variant(), RustStructType::Record(record)
+                        if record.fields.iter().any(|field| field.rule_metadata.hex))
+            });
         if export_raw_bytes_encoding_trait
+            || hex_serde_fields
             || types
                 .rust_structs()
                 .iter()
@@ -1204,13 +1469,44 @@ impl GenerationScope {
                         !rust_struct.config().custom_json
                             && matches!(
                                 wrapped.resolve_alias_shallow(),
-                                ConceptualRustType::Primitive(Primitive::Bytes)
+                                ConceptualRustType::Primitive(
+                                    Primitive::Bytes | Primitive::ByteArray(_)
+                                )
                             )
                     }
                     _ => false,
                 })
         {
-            rust_cargo_toml.push_str("hex = \"0.4.3\"\n");
+            rust_cargo_toml.push_str(if hex_serde_fields {
+                "hex = { version = \"0.4.3\", features = [\"serde\"] }\n"
+            } else {
+                "hex = \"0.4.3\"\n"
+            });
+        }
+        let regex_engines = types
+            .rust_structs()
+            .values()
+            .filter_map(|rust_struct| match rust_struct.variant() {
+                RustStructType::Wrapper {
+                    regex: Some(regex), ..
+                } => Some(regex.engine),
+                _ => None,
+            })
+            .collect::<Vec<_>>();
+        if cli.allow_cbor_on_text {
+            rust_cargo_toml.push_str("base64 = \"0.21\"\n");
+        }
+        if cli.cbor_value_conversions || cli.cbor_crate == CborCrate::Ciborium {
+            rust_cargo_toml.push_str("ciborium = \"0.2\"\n");
+        }
+        if cli.cbor_crate == CborCrate::Ciborium {
+            rust_cargo_toml.push_str("serde = \"1.0\"\n");
+        }
+        if regex_engines.contains(&RegexEngine::Regex) {
+            rust_cargo_toml.push_str("regex = \"1.10\"\n");
+        }
+        if regex_engines.contains(&RegexEngine::FancyRegex) {
+            rust_cargo_toml.push_str("fancy-regex = \"0.13\"\n");
         }
         if cli.wasm
             && types
@@ -1223,12 +1519,24 @@ impl GenerationScope {
             rust_cargo_toml
                 .push_str("wasm-bindgen = { version = \"0.2\", features=[\"serde-serialize\"] }\n");
         }
+        // types gated via @feature
+        let features = types
+            .rust_structs()
+            .values()
+            .filter_map(|rust_struct| rust_struct.config().feature.as_ref())
+            .collect::<BTreeSet<_>>();
+        if !features.is_empty() {
+            rust_cargo_toml.push_str("\n[features]\n");
+            for feature in features.iter() {
+                rust_cargo_toml.push_str(&format!("{feature} = []\n"));
+            }
+        }
         std::fs::write(
             rust_dir.join("rust/Cargo.toml"),
             rust_cargo_toml.replace("cddl-lib", &cli.lib_name),
         )?;
 
-        if cli.export_static_files() {
+        if cli.export_static_files() && !cli.no_std {
             // error.rs
             std::fs::copy(
                 cli.static_dir.join("error.rs"),
@@ -1254,6 +1562,26 @@ impl GenerationScope {
                     rustfmt_generated_string(&ordered_hash_map_rs)?.as_ref(),
                 )?;
             }
+
+            // non_empty_vec.rs
+            if types.uses_non_empty_vec() {
+                let mut non_empty_vec_rs =
+                    std::fs::read_to_string(cli.static_dir.join("non_empty_vec.rs"))?;
+                if cli.json_serde_derives {
+                    non_empty_vec_rs.push_str(&std::fs::read_to_string(
+                        cli.static_dir.join("non_empty_vec_json.rs"),
+                    )?);
+                }
+                if cli.json_schema_export {
+                    non_empty_vec_rs.push_str(&std::fs::read_to_string(
+                        cli.static_dir.join("non_empty_vec_schemars.rs"),
+                    )?);
+                }
+                std::fs::write(
+                    rust_dir.join("rust/src/non_empty_vec.rs"),
+                    rustfmt_generated_string(&non_empty_vec_rs)?.as_ref(),
+                )?;
+            }
         }
 
         // wasm crate
@@ -1272,6 +1600,12 @@ impl GenerationScope {
                 wasm_toml.push_str("serde_json = \"1.0.57\"\n");
                 wasm_toml.push_str("serde-wasm-bindgen = \"0.4.5\"\n");
             }
+            if !features.is_empty() {
+                wasm_toml.push_str("\n[features]\n");
+                for feature in features.iter() {
+                    wasm_toml.push_str(&format!("{feature} = [\"cddl-lib/{feature}\"]\n"));
+                }
+            }
             std::fs::write(
                 rust_dir.join("wasm/Cargo.toml"),
                 wasm_toml.replace("cddl-lib", &cli.lib_name),
@@ -1334,14 +1668,77 @@ impl GenerationScope {
     /// Used for all the generated structs and associated traits (besides serialization ones)
     pub fn rust(&mut self, types: &IntermediateTypes, ident: &RustIdent) -> &mut codegen::Scope {
         let scope_name = types.scope(ident).to_owned();
-        self.rust_scopes
-            .entry(scope_name)
-            .or_insert(codegen::Scope::new())
+        match feature_of(types, ident) {
+            Some(feature) => {
+                // so module declarations/imports still happen if all its types are gated
+                self.rust_scopes
+                    .entry(scope_name.clone())
+                    .or_insert(codegen::Scope::new());
+                self.feature_gated
+                    .rust
+                    .entry((scope_name, feature))
+                    .or_insert(codegen::Scope::new())
+            }
+            None => self
+                .rust_scopes
+                .entry(scope_name)
+                .or_insert(codegen::Scope::new()),
+        }
     }
 
     /// Scope header above the rest of the "lib" rust scope.
     /// This is useful for when there is no explicit scope
     /// e.g. implicit types like arrays/tables (for WASM)
+    /// Populates constants.rs with every map key / fixed value field and constant rule.
+    /// Names are the SCREAMING_SNAKE_CASE rule name followed by the field name for fields.
+    fn generate_constants(&mut self, types: &IntermediateTypes) {
+        let mut used_names = BTreeMap::new();
+        let mut push_const =
+            |scope: &mut codegen::Scope, name: String, doc: String, value: &FixedValue| {
+                if let Some((ty, val)) = value.to_const_type_and_value() {
+                    let name = append_number_if_duplicate(&mut used_names, name.to_uppercase());
+                    scope.raw(format!("/// {doc}\npub const {name}: {ty} = {val};"));
+                }
+            };
+        for (alias_ident, alias_info) in types.type_aliases() {
+            if let (AliasIdent::Rust(ident), ConceptualRustType::Fixed(value)) =
+                (alias_ident, &alias_info.base_type.conceptual_type)
+            {
+                push_const(
+                    &mut self.constants_scope,
+                    convert_to_snake_case(ident.as_ref()),
+                    ident.to_string(),
+                    value,
+                );
+            }
+        }
+        for (rust_ident, rust_struct) in types.rust_structs() {
+            if let RustStructType::Record(record) = rust_struct.variant() {
+                let struct_name = convert_to_snake_case(rust_ident.as_ref());
+                for field in record.fields.iter() {
+                    if let Some(key) = &field.key {
+                        push_const(
+                            &mut self.constants_scope,
+                            format!("{struct_name}_{}_key", field.name),
+                            format!("map key of {rust_ident}::{}", field.name),
+                            key,
+                        );
+                    }
+                    if let ConceptualRustType::Fixed(value) =
+                        field.rust_type.conceptual_type.resolve_alias_shallow()
+                    {
+                        push_const(
+                            &mut self.constants_scope,
+                            format!("{struct_name}_{}", field.name),
+                            format!("fixed value of {rust_ident}::{}", field.name),
+                            value,
+                        );
+                    }
+                }
+            }
+        }
+    }
+
     pub fn rust_lib(&mut self) -> &mut codegen::Scope {
         &mut self.rust_lib_scope
     }
@@ -1353,9 +1750,21 @@ impl GenerationScope {
         ident: &RustIdent,
     ) -> &mut codegen::Scope {
         let scope_name = types.scope(ident).to_owned();
-        self.serialize_scopes
-            .entry(scope_name)
-            .or_insert(codegen::Scope::new())
+        match feature_of(types, ident) {
+            Some(feature) => {
+                self.serialize_scopes
+                    .entry(scope_name.clone())
+                    .or_insert(codegen::Scope::new());
+                self.feature_gated
+                    .serialize
+                    .entry((scope_name, feature))
+                    .or_insert(codegen::Scope::new())
+            }
+            None => self
+                .serialize_scopes
+                .entry(scope_name)
+                .or_insert(codegen::Scope::new()),
+        }
     }
 
     /// Serialization scope for lib.cddl
@@ -1368,9 +1777,21 @@ impl GenerationScope {
     /// Used for all the generated WASM wrapper structs and associated traits
     pub fn wasm(&mut self, types: &IntermediateTypes, ident: &RustIdent) -> &mut codegen::Scope {
         let scope_name = types.scope(ident).to_owned();
-        self.wasm_scopes
-            .entry(scope_name)
-            .or_insert(codegen::Scope::new())
+        match feature_of(types, ident) {
+            Some(feature) => {
+                self.wasm_scopes
+                    .entry(scope_name.clone())
+                    .or_insert(codegen::Scope::new());
+                self.feature_gated
+                    .wasm
+                    .entry((scope_name, feature))
+                    .or_insert(codegen::Scope::new())
+            }
+            None => self
+                .wasm_scopes
+                .entry(scope_name)
+                .or_insert(codegen::Scope::new()),
+        }
     }
 
     /// Scope header above the rest of the "lib" WASM scope.
@@ -1387,9 +1808,42 @@ impl GenerationScope {
         ident: &RustIdent,
     ) -> &mut codegen::Scope {
         let scope = types.scope(ident).clone();
-        self.cbor_encodings_scopes
-            .entry(scope)
-            .or_insert(codegen::Scope::new())
+        match feature_of(types, ident) {
+            Some(feature) => {
+                self.cbor_encodings_scopes
+                    .entry(scope.clone())
+                    .or_insert(codegen::Scope::new());
+                self.feature_gated
+                    .cbor_encodings
+                    .entry((scope, feature))
+                    .or_insert(codegen::Scope::new())
+            }
+            None => self
+                .cbor_encodings_scopes
+                .entry(scope)
+                .or_insert(codegen::Scope::new()),
+        }
+    }
+
+    /// Moves all code generated for @feature types into the regular scopes,
+    /// with each item behind #[cfg(feature = "...")]
+    fn merge_feature_gated_scopes(&mut self) {
+        fn merge(
+            gated: BTreeMap<(ModuleScope, String), codegen::Scope>,
+            scopes: &mut BTreeMap<ModuleScope, codegen::Scope>,
+        ) {
+            for ((scope, feature), content) in gated {
+                scopes
+                    .entry(scope)
+                    .or_insert(codegen::Scope::new())
+                    .raw(gate_items_behind_feature(&content.to_string(), &feature));
+            }
+        }
+        let gated = std::mem::take(&mut self.feature_gated);
+        merge(gated.rust, &mut self.rust_scopes);
+        merge(gated.serialize, &mut self.serialize_scopes);
+        merge(gated.wasm, &mut self.wasm_scopes);
+        merge(gated.cbor_encodings, &mut self.cbor_encodings_scopes);
     }
 
     /// Write code for serializing {serializing_rust_type} directly into {body}
@@ -1487,7 +1941,10 @@ impl GenerationScope {
                     );
                     self.generate_serialize(types, *child, body, config, cli);
                 }
-                SerializingRustType::EncodingOperation(CBOREncodingOperation::CBORBytes, child) => {
+                SerializingRustType::EncodingOperation(
+                    CBOREncodingOperation::CBORBytes(_),
+                    child,
+                ) => {
                     let inner_se = format!("{}_inner_se", config.var_name);
                     body.line(&format!("let mut {inner_se} = Serializer::new_vec();"));
                     let inner_config = config
@@ -1510,6 +1967,29 @@ impl GenerationScope {
                         cli,
                     );
                 }
+                SerializingRustType::EncodingOperation(CBOREncodingOperation::CBORText, child) => {
+                    let inner_se = format!("{}_inner_se", config.var_name);
+                    body.line(&format!("let mut {inner_se} = Serializer::new_vec();"));
+                    let inner_config = config
+                        .clone()
+                        .is_end(false)
+                        .serializer_name_overload((&inner_se, true));
+                    self.generate_serialize(types, *child, body, inner_config, cli);
+                    body.line(&format!(
+                        "let {}_text = base64::Engine::encode(&base64::engine::general_purpose::STANDARD, {}.finalize());",
+                        config.var_name, inner_se
+                    ));
+                    write_string_sz(
+                        body,
+                        "write_text",
+                        serializer_use,
+                        &format!("{}_text", config.var_name),
+                        true,
+                        line_ender,
+                        &config.encoding_var(Some("text"), encoding_var_is_copy),
+                        cli,
+                    );
+                }
                 SerializingRustType::Root(ConceptualRustType::Fixed(value), _cfg) => match value {
                     FixedValue::Null => {
                         body.line(&format!(
@@ -1521,6 +2001,11 @@ impl GenerationScope {
                             "{serializer_use}.write_special(cbor_event::Special::Bool({b})){line_ender}"
                         ));
                     }
+                    FixedValue::SimpleValue(n) => {
+                        body.line(&format!(
+                            "{serializer_use}.write_special(cbor_event::Special::Unassigned({n})){line_ender}"
+                        ));
+                    }
                     FixedValue::Uint(u) => {
                         let expr = format!("{u}u64");
                         write_using_sz(
@@ -1572,9 +2057,15 @@ impl GenerationScope {
                         }
                     }
                     FixedValue::Float(f) => {
-                        body.line(&format!(
-                            "{serializer_use}.write_special(cbor_event::Special::Float({f})){line_ender}"
-                        ));
+                        if cli.shortest_floats {
+                            body.line(&format!(
+                                "{serializer_use}.write_float_shortest({f:?}){line_ender}"
+                            ));
+                        } else {
+                            body.line(&format!(
+                                "{serializer_use}.write_special(cbor_event::Special::Float({f})){line_ender}"
+                            ));
+                        }
                     }
                     FixedValue::Text(s) => {
                         write_string_sz(
@@ -1596,17 +2087,32 @@ impl GenerationScope {
                                 "{serializer_use}.write_special(cbor_event::Special::Bool({expr_deref})){line_ender}"
                             ));
                         }
+                        Primitive::F16 => {
+                            body.line(&format!(
+                                "{serializer_use}.write_float_shortest({expr_deref} as f64){line_ender}"
+                            ));
+                        }
+                        Primitive::F32 if cli.shortest_floats => {
+                            body.line(&format!(
+                                "{serializer_use}.write_float_shortest({expr_deref} as f64){line_ender}"
+                            ));
+                        }
                         Primitive::F32 => {
                             body.line(&format!(
                                 "{serializer_use}.write_special(cbor_event::Special::Float({expr_deref} as f64)){line_ender}"
                             ));
                         }
+                        Primitive::F64 if cli.shortest_floats => {
+                            body.line(&format!(
+                                "{serializer_use}.write_float_shortest({expr_deref}){line_ender}"
+                            ));
+                        }
                         Primitive::F64 => {
                             body.line(&format!(
                                 "{serializer_use}.write_special(cbor_event::Special::Float({expr_deref})){line_ender}"
                             ));
                         }
-                        Primitive::Bytes => {
+                        Primitive::Bytes | Primitive::ByteArray(_) => {
                             write_string_sz(
                                 body,
                                 "write_bytes",
@@ -1630,6 +2136,18 @@ impl GenerationScope {
                                 cli,
                             );
                         }
+                        Primitive::Char => {
+                            write_string_sz(
+                                body,
+                                "write_text",
+                                serializer_use,
+                                &format!("{}.to_string()", config.expr),
+                                true,
+                                line_ender,
+                                &encoding_var,
+                                cli,
+                            );
+                        }
                         Primitive::I8 | Primitive::I16 | Primitive::I32 | Primitive::I64 => {
                             let mut pos = Block::new(format!("if {expr_deref} >= 0"));
                             let expr_pos = format!("{expr_deref} as u64");
@@ -1714,6 +2232,17 @@ impl GenerationScope {
                                 body.line(&format!("{serializer_use}.write_negative_integer_sz(-({expr_deref} as i128 + 1), cbor_event::Sz::canonical({expr_deref})){line_ender}"));
                             }
                         }
+                        // fixed 16-byte bignums so there are no encoding details to preserve
+                        Primitive::U128 => {
+                            body.line(&format!(
+                                "{serializer_use}.write_u128_bignum({expr_deref}){line_ender}"
+                            ));
+                        }
+                        Primitive::I128 => {
+                            body.line(&format!(
+                                "{serializer_use}.write_i128_bignum({expr_deref}){line_ender}"
+                            ));
+                        }
                     }
                 }
                 SerializingRustType::Root(ConceptualRustType::Rust(t), type_cfg) => {
@@ -2022,6 +2551,34 @@ impl GenerationScope {
                     }
                     body.push_block(opt_block);
                 }
+                SerializingRustType::Root(ConceptualRustType::Result(ok, err), _cfg) => {
+                    let mut res_block = Block::new(format!("match {expr_ref}"));
+                    let res_config = config.clone().expr("x").expr_is_ref(true).is_end(true);
+                    let mut ok_block = Block::new("Ok(x) =>");
+                    self.generate_serialize(
+                        types,
+                        (&**ok).into(),
+                        &mut ok_block,
+                        res_config.clone(),
+                        cli,
+                    );
+                    ok_block.after(",");
+                    res_block.push_block(ok_block);
+                    let mut err_block = Block::new("Err(x) =>");
+                    self.generate_serialize(
+                        types,
+                        (&**err).into(),
+                        &mut err_block,
+                        res_config,
+                        cli,
+                    );
+                    err_block.after(",");
+                    res_block.push_block(err_block);
+                    if !config.is_end {
+                        res_block.after("?;");
+                    }
+                    body.push_block(res_block);
+                }
                 SerializingRustType::Root(ConceptualRustType::Alias(ident, ty), _cfg) => {
                     let config_for_alias = if let Some(custom_serialize) = types
                         .type_aliases()
@@ -2165,6 +2722,22 @@ impl GenerationScope {
                                 ));
                             }
                         }
+                        FixedValue::SimpleValue(n) => {
+                            let mut special_block =
+                                Block::new(format!("match {deserializer_name}.special()?"));
+                            special_block
+                                .line(format!("cbor_event::Special::Unassigned({n}) => (),"))
+                                .line(format!("found => return Err(DeserializeFailure::SimpleValueMismatch{{ found, expected: {n} }}.into()),"));
+                            deser_code.content.push_block(special_block);
+                            if cli.preserve_encodings {
+                                deser_code.content.line(&format!(
+                                    "{}{}{}",
+                                    before_after.before_str(false),
+                                    final_expr(config.final_exprs, None),
+                                    before_after.after_str(false)
+                                ));
+                            }
+                        }
                         FixedValue::Uint(x) => {
                             if cli.preserve_encodings {
                                 deser_code.content.line(&format!(
@@ -2256,8 +2829,14 @@ impl GenerationScope {
                         }
                         FixedValue::Float(x) => {
                             deser_code.content.line(&format!(
-                                "let {}_value = {}.float()?;",
-                                config.var_name, deserializer_name
+                                "let {}_value = {}.{}()?;",
+                                config.var_name,
+                                deserializer_name,
+                                if cli.shortest_floats {
+                                    "float_any_width"
+                                } else {
+                                    "float"
+                                }
                             ));
                             let mut compare_block =
                                 Block::new(format!("if {}_value != {}", config.var_name, x));
@@ -2281,6 +2860,27 @@ impl GenerationScope {
                         deser_code.read_len_used = true;
                         deser_code.throws = true;
                     }
+                    // integers narrower than the u64/i64 read from CBOR must be checked to fit
+                    // instead of being truncated by the `as` cast
+                    let mut type_cfg = type_cfg.clone();
+                    if type_cfg.bounds.is_none() {
+                        let implicit_bounds = match p {
+                            Primitive::U8 => Some((None, Some(u8::MAX as i128))),
+                            Primitive::U16 => Some((None, Some(u16::MAX as i128))),
+                            Primitive::U32 => Some((None, Some(u32::MAX as i128))),
+                            Primitive::I8 => Some((Some(i8::MIN as i128), Some(i8::MAX as i128))),
+                            Primitive::I16 => {
+                                Some((Some(i16::MIN as i128), Some(i16::MAX as i128)))
+                            }
+                            Primitive::I32 => {
+                                Some((Some(i32::MIN as i128), Some(i32::MAX as i128)))
+                            }
+                            _ => None,
+                        };
+                        if implicit_bounds.is_some() {
+                            type_cfg.to_mut().bounds = implicit_bounds;
+                        }
+                    }
                     let error_convert = if before_after.expects_result {
                         convert_err_to_ours
                     } else {
@@ -2293,7 +2893,11 @@ impl GenerationScope {
                                 "{}.and_then(|{}| {} else {{ Ok({}) }})",
                                 convert_err_to_ours,
                                 x,
-                                bounds_check_if_block(bounds, &bounds_check_expr(*p, x), false),
+                                bounds_check_if_block(
+                                    bounds,
+                                    &bounds_check_expr(*p, x, cli),
+                                    false,
+                                ),
                                 x,
                             )),
                             None => Cow::Borrowed(""),
@@ -2314,7 +2918,7 @@ impl GenerationScope {
                                         x,
                                         bounds_check_if_block(
                                             bounds,
-                                            &bounds_check_expr(*p, x),
+                                            &bounds_check_expr(*p, x, cli),
                                             false
                                         ),
                                         final_expr(final_exprs, Some(x_expr.to_owned())),
@@ -2351,27 +2955,83 @@ impl GenerationScope {
                         Primitive::Bytes => {
                             deser_primitive(config.final_exprs, "bytes", "bytes", "bytes")
                         }
-                        Primitive::U8 | Primitive::U16 | Primitive::U32 => deser_primitive(
-                            config.final_exprs,
-                            "unsigned_integer",
-                            "x",
-                            &format!("x as {}", p),
-                        ),
-                        Primitive::U64 => {
-                            deser_primitive(config.final_exprs, "unsigned_integer", "x", "x")
-                        }
-                        Primitive::I8 | Primitive::I16 | Primitive::I32 | Primitive::I64 => {
-                            // we need to only look at poisitve or negative bounds to avoid comparing e.g. a u64 with a negative
-                            let positive_bounds = type_cfg.bounds.map(|(lower, upper)| {
-                                (lower.filter(|l| *l > 0), upper.filter(|u| *u > 0))
-                            });
-                            let negative_bounds = type_cfg.bounds.map(|(lower, upper)| {
-                                (lower.filter(|l| *l < 0), upper.filter(|u| *u < 0))
-                            });
-                            let mut type_check = Block::new(format!(
-                                "{}match {}.cbor_type()?",
-                                before_after.before_str(false),
-                                deserializer_name
+                        Primitive::ByteArray(n) => {
+                            // the length check is the conversion from Vec<u8> itself
+                            let to_array = format!(
+                                "<[u8; {n}]>::try_from(bytes).map_err(|bytes| DeserializeFailure::RangeCheck{{ found: bytes.len() as isize, min: Some({n}), max: Some({n}) }}.into())"
+                            );
+                            if cli.preserve_encodings {
+                                let mut final_exprs = config.final_exprs;
+                                final_exprs.push("StringEncoding::from(enc)".to_owned());
+                                deser_code.content.line(&format!(
+                                    "{}{}.bytes_sz(){}.and_then(|(bytes, enc)| {}.map(|bytes| {})){}",
+                                    before_after.before_str(true),
+                                    deserializer_name,
+                                    convert_err_to_ours,
+                                    to_array,
+                                    final_expr(final_exprs, Some("bytes".to_owned())),
+                                    before_after.after_str(true)
+                                ));
+                            } else {
+                                deser_code.content.line(&format!(
+                                    "{}{}.bytes(){}.and_then(|bytes| {})?{}",
+                                    before_after.before_str(false),
+                                    deserializer_name,
+                                    convert_err_to_ours,
+                                    to_array,
+                                    before_after.after_str(false)
+                                ));
+                                deser_code.throws = true;
+                            }
+                        }
+                        Primitive::Char => {
+                            // exactly one character however many bytes it is in UTF-8
+                            let to_char = "{ let mut chars = s.chars(); match (chars.next(), chars.next()) { (Some(c), None) => Ok(c), _ => Err(DeserializeFailure::RangeCheck{ found: s.chars().count() as isize, min: Some(1), max: Some(1) }.into()) } }";
+                            if cli.preserve_encodings {
+                                let mut final_exprs = config.final_exprs;
+                                final_exprs.push("StringEncoding::from(enc)".to_owned());
+                                deser_code.content.line(&format!(
+                                    "{}{}.text_sz(){}.and_then(|(s, enc)| {}.map(|c| {})){}",
+                                    before_after.before_str(true),
+                                    deserializer_name,
+                                    convert_err_to_ours,
+                                    to_char,
+                                    final_expr(final_exprs, Some("c".to_owned())),
+                                    before_after.after_str(true)
+                                ));
+                            } else {
+                                deser_code.content.line(&format!(
+                                    "{}{}.text(){}.and_then(|s| {})?{}",
+                                    before_after.before_str(false),
+                                    deserializer_name,
+                                    convert_err_to_ours,
+                                    to_char,
+                                    before_after.after_str(false)
+                                ));
+                                deser_code.throws = true;
+                            }
+                        }
+                        Primitive::U8 | Primitive::U16 | Primitive::U32 => deser_primitive(
+                            config.final_exprs,
+                            "unsigned_integer",
+                            "x",
+                            &format!("x as {}", p),
+                        ),
+                        Primitive::U64 => {
+                            deser_primitive(config.final_exprs, "unsigned_integer", "x", "x")
+                        }
+                        Primitive::I8 | Primitive::I16 | Primitive::I32 | Primitive::I64 => {
+                            // we need to only look at poisitve or negative bounds to avoid comparing e.g. a u64 with a negative
+                            let positive_bounds = type_cfg.bounds.map(|(lower, upper)| {
+                                (lower.filter(|l| *l > 0), upper.filter(|u| *u > 0))
+                            });
+                            let negative_bounds = type_cfg.bounds.map(|(lower, upper)| {
+                                (lower.filter(|l| *l < 0), upper.filter(|u| *u < 0))
+                            });
+                            let mut type_check = Block::new(format!(
+                                "{}match {}.cbor_type()?",
+                                before_after.before_str(false),
+                                deserializer_name
                             ));
                             if cli.preserve_encodings {
                                 let bounds_fn =
@@ -2382,7 +3042,7 @@ impl GenerationScope {
                                             convert_err_to_ours,
                                             bounds_check_if_block(
                                                 bounds,
-                                                &bounds_check_expr(*p, "x"),
+                                                &bounds_check_expr(*p, "x", cli),
                                                 false
                                             ),
                                         )),
@@ -2423,7 +3083,7 @@ impl GenerationScope {
                                             convert_err_to_ours,
                                             bounds_check_if_block(
                                                 bounds,
-                                                &bounds_check_expr(*p, "x"),
+                                                &bounds_check_expr(*p, "x", cli),
                                                 false
                                             ),
                                         )),
@@ -2462,7 +3122,7 @@ impl GenerationScope {
                                     ".and_then(|(x, _enc)| {} else {{ Ok((x + 1).abs() as u64) }})",
                                     bounds_check_if_block(
                                         bounds,
-                                        &bounds_check_expr(*p, "x"),
+                                        &bounds_check_expr(*p, "x", cli),
                                         false
                                     ),
                                 )),
@@ -2478,6 +3138,13 @@ impl GenerationScope {
                                 ));
                             }
                         }
+                        Primitive::U128 | Primitive::I128 => {
+                            deser_code.content.line(&final_result_expr_complete(
+                                &mut deser_code.throws,
+                                config.final_exprs,
+                                &format!("{deserializer_name}.{p}_bignum()"),
+                            ));
+                        }
                         Primitive::Str => deser_primitive(config.final_exprs, "text", "s", "s"),
                         Primitive::Bool => {
                             // no encoding differences for bool
@@ -2487,11 +3154,27 @@ impl GenerationScope {
                                 "raw.bool().map_err(Into::into)",
                             ));
                         }
-                        Primitive::F32 => {
+                        Primitive::F16 | Primitive::F32 => {
+                            let deser_expr: Cow<str> =
+                                if *p == Primitive::F16 || cli.shortest_floats {
+                                    "raw.float_any_width().map(|f| f as f32)".into()
+                                } else {
+                                    "f32::deserialize(raw)".into()
+                                };
+                            let deser_expr = match &type_cfg.float_bounds {
+                                // always convert error to have consistent E for the and_then
+                                Some(bounds) => Cow::Owned(format!(
+                                    "{}{}.and_then(|f| {} else {{ Ok(f) }})",
+                                    deser_expr,
+                                    convert_err_to_ours,
+                                    float_bounds_check_if_block(bounds, "f", false),
+                                )),
+                                None => deser_expr,
+                            };
                             deser_code.content.line(&final_result_expr_complete(
                                 &mut deser_code.throws,
                                 config.final_exprs,
-                                "f32::deserialize(raw)",
+                                &deser_expr,
                             ));
                             if cli.preserve_encodings {
                                 unimplemented!("preserve_encodings is not implemented for float")
@@ -2501,10 +3184,25 @@ impl GenerationScope {
                             }
                         }
                         Primitive::F64 => {
+                            let deser_expr: Cow<str> = if cli.shortest_floats {
+                                "raw.float_any_width()".into()
+                            } else {
+                                "f64::deserialize(raw)".into()
+                            };
+                            let deser_expr = match &type_cfg.float_bounds {
+                                // always convert error to have consistent E for the and_then
+                                Some(bounds) => Cow::Owned(format!(
+                                    "{}{}.and_then(|f| {} else {{ Ok(f) }})",
+                                    deser_expr,
+                                    convert_err_to_ours,
+                                    float_bounds_check_if_block(bounds, "f", false),
+                                )),
+                                None => deser_expr,
+                            };
                             deser_code.content.line(&final_result_expr_complete(
                                 &mut deser_code.throws,
                                 config.final_exprs,
-                                "f64::deserialize(raw)",
+                                &deser_expr,
                             ));
                             if cli.preserve_encodings {
                                 unimplemented!("preserve_encodings is not implemented for float")
@@ -2625,10 +3323,11 @@ impl GenerationScope {
                                     assert!(!config.optional_field);
                                     deser_code.read_len_used = true;
                                     let final_expr_value = format!(
-                                        "{}::deserialize_as_embedded_group({}, {}, len)",
+                                        "{}::deserialize_as_embedded_group({}, {}, len){}",
                                         ident,
                                         deserializer_name,
-                                        config.pass_read_len()
+                                        config.pass_read_len(),
+                                        box_if(type_cfg.boxed)
                                     );
 
                                     deser_code.content.line(&final_result_expr_complete(
@@ -2642,8 +3341,10 @@ impl GenerationScope {
                                         deser_code.read_len_used = true;
                                         deser_code.throws = true;
                                     }
-                                    let final_expr_value =
-                                        format!("{ident}::deserialize({deserializer_name})");
+                                    let final_expr_value = format!(
+                                        "{ident}::deserialize({deserializer_name}){}",
+                                        box_if(type_cfg.boxed)
+                                    );
                                     deser_code.content.line(&final_result_expr_complete(
                                         &mut deser_code.throws,
                                         config.final_exprs,
@@ -2788,6 +3489,53 @@ impl GenerationScope {
                     deser_code.content.push_block(deser_block);
                     deser_code.throws = true;
                 }
+                SerializingRustType::Root(ConceptualRustType::Result(ok, err), _cfg) => {
+                    if config.optional_field {
+                        deser_code.content.line("read_len.read_elems(1)?;");
+                        deser_code.read_len_used = true;
+                    }
+                    // the Ok type is attempted first and if that fails we rewind and try the Err type
+                    let initial_position_var = format!("{}_initial_position", config.var_name);
+                    let ok_var = format!("{}_ok", config.var_name);
+                    deser_code.content.line(&format!(
+                        "let {initial_position_var} = {deserializer_name}.as_mut_ref().stream_position().unwrap();"
+                    ));
+                    let mut ok_deser =
+                        Block::new(format!("let {ok_var} = (|| -> Result<_, DeserializeError>"));
+                    self.generate_deserialize(
+                        types,
+                        (&**ok).into(),
+                        DeserializeBeforeAfter::new("", "", true),
+                        config.clone().optional_field(false),
+                        cli,
+                    )
+                    .add_to(&mut ok_deser);
+                    ok_deser.after(")();");
+                    deser_code.content.push_block(ok_deser);
+                    let mut deser_block = Block::new(format!(
+                        "{}match {}",
+                        before_after.before_str(false),
+                        ok_var
+                    ));
+                    deser_block.line("Ok(x) => Ok(x),");
+                    let mut err_block = Block::new("Err(_) =>");
+                    err_block.line(format!(
+                        "{deserializer_name}.as_mut_ref().seek(SeekFrom::Start({initial_position_var})).unwrap();"
+                    ));
+                    self.generate_deserialize(
+                        types,
+                        (&**err).into(),
+                        DeserializeBeforeAfter::new("Err(", ")", false),
+                        config.optional_field(false),
+                        cli,
+                    )
+                    .add_to(&mut err_block);
+                    err_block.after(",");
+                    deser_block.push_block(err_block);
+                    deser_block.after(before_after.after_str(false));
+                    deser_code.content.push_block(deser_block);
+                    deser_code.throws = true;
+                }
                 SerializingRustType::Root(ConceptualRustType::Array(ty), type_cfg) => {
                     if config.optional_field {
                         deser_code.content.line("read_len.read_elems(1)?;");
@@ -3150,7 +3898,7 @@ impl GenerationScope {
                     }
                     deser_code.throws = true;
                 }
-                SerializingRustType::Root(ConceptualRustType::Alias(ident, ty), _cfg) => {
+                SerializingRustType::Root(ConceptualRustType::Alias(ident, ty), cfg) => {
                     let config_for_alias = if let Some(custom_deserialize) = types
                         .type_aliases()
                         .get(ident)
@@ -3163,16 +3911,31 @@ impl GenerationScope {
                     } else {
                         config
                     };
+                    // boxing is done on the reference to the alias so it must be passed through
+                    let aliased_type = if cfg.boxed {
+                        SerializingRustType::Root(
+                            ty,
+                            Cow::Owned(RustTypeSerializeConfig {
+                                boxed: true,
+                                ..RustTypeSerializeConfig::default()
+                            }),
+                        )
+                    } else {
+                        (&**ty).into()
+                    };
                     self.generate_deserialize(
                         types,
-                        (&**ty).into(),
+                        aliased_type,
                         before_after,
                         config_for_alias,
                         cli,
                     )
                     .add_to_code(&mut deser_code);
                 }
-                SerializingRustType::EncodingOperation(CBOREncodingOperation::CBORBytes, child) => {
+                SerializingRustType::EncodingOperation(
+                    CBOREncodingOperation::CBORBytes(encoded_bounds),
+                    child,
+                ) => {
                     if cli.preserve_encodings {
                         config.final_exprs.push(format!(
                             "StringEncoding::from({}_bytes_encoding)",
@@ -3187,6 +3950,47 @@ impl GenerationScope {
                             .content
                             .line(&format!("let {}_bytes = raw.bytes()?;", config.var_name));
                     };
+                    if let Some(bounds) = encoded_bounds {
+                        deser_code.content.line(&bounds_check_if_block(
+                            bounds,
+                            &format!("{}_bytes.len()", config.var_name),
+                            true,
+                        ));
+                    }
+                    let name_overload = "inner_de";
+                    deser_code.content.line(&format!(
+                        "let {} = &mut Deserializer::from(std::io::Cursor::new({}_bytes));",
+                        name_overload, config.var_name
+                    ));
+                    self.generate_deserialize(
+                        types,
+                        *child,
+                        before_after,
+                        config.overload_deserializer(name_overload),
+                        cli,
+                    )
+                    .add_to_code(&mut deser_code);
+                    deser_code.throws = true;
+                }
+                SerializingRustType::EncodingOperation(CBOREncodingOperation::CBORText, child) => {
+                    if cli.preserve_encodings {
+                        config.final_exprs.push(format!(
+                            "StringEncoding::from({}_text_encoding)",
+                            config.var_name
+                        ));
+                        deser_code.content.line(&format!(
+                            "let ({}_text, {}_text_encoding) = raw.text_sz()?;",
+                            config.var_name, config.var_name
+                        ));
+                    } else {
+                        deser_code
+                            .content
+                            .line(&format!("let {}_text = raw.text()?;", config.var_name));
+                    };
+                    deser_code.content.line(&format!(
+                        "let {}_bytes = base64::Engine::decode(&base64::engine::general_purpose::STANDARD, &{}_text).map_err(|e| DeserializeFailure::InvalidStructure(Box::new(e)))?;",
+                        config.var_name, config.var_name
+                    ));
                     let name_overload = "inner_de";
                     deser_code.content.line(&format!(
                         "let {} = &mut Deserializer::from(std::io::Cursor::new({}_bytes));",
@@ -3298,6 +4102,10 @@ impl GenerationScope {
             ConceptualRustType::Optional(ty) => {
                 self.deserialize_generated_for_type(types, &ty.conceptual_type)
             }
+            ConceptualRustType::Result(ok, err) => {
+                self.deserialize_generated_for_type(types, &ok.conceptual_type)
+                    && self.deserialize_generated_for_type(types, &err.conceptual_type)
+            }
             ConceptualRustType::Alias(_ident, ty) => self.deserialize_generated_for_type(types, ty),
         }
     }
@@ -3325,7 +4133,7 @@ impl GenerationScope {
         types: &IntermediateTypes,
         name: &RustIdent,
         variants: &[EnumVariant],
-        tag: Option<usize>,
+        tag: Option<u64>,
         config: &RustStructConfig,
         cli: &Cli,
     ) {
@@ -3375,6 +4183,7 @@ impl GenerationScope {
                 };
                 if can_fail {
                     new_func
+                        .attr(MUST_USE_VALIDATION)
                         .ret(format!("Result<{name}, JsError>"))
                         .line(format!("{ctor}.map(Into::into).map_err(Into::into)"));
                 } else {
@@ -3411,6 +4220,13 @@ impl GenerationScope {
                 .ret("usize")
                 .arg_ref_self()
                 .line("self.0.len()");
+            wrapper
+                .s_impl
+                .new_fn("is_empty")
+                .vis("pub")
+                .ret("bool")
+                .arg_ref_self()
+                .line("self.0.is_empty()");
             wrapper
                 .s_impl
                 .new_fn("get")
@@ -3576,9 +4392,10 @@ fn write_string_sz(
     }
 }
 
-fn bounds_check_expr(p: Primitive, e: &str) -> String {
+fn bounds_check_expr(p: Primitive, e: &str, cli: &Cli) -> String {
     match p {
         Primitive::Bool
+        | Primitive::F16
         | Primitive::F32
         | Primitive::F64
         | Primitive::I8
@@ -3589,20 +4406,36 @@ fn bounds_check_expr(p: Primitive, e: &str) -> String {
         | Primitive::U16
         | Primitive::U32
         | Primitive::U64
+        | Primitive::U128
+        | Primitive::I128
         | Primitive::N64 => e.to_owned(),
-        Primitive::Str | Primitive::Bytes => format!("{e}.len()"),
+        Primitive::Str => str_size_expr(e, cli),
+        Primitive::Bytes | Primitive::ByteArray(_) => format!("{e}.len()"),
+        // always exactly one character so it's never bounded
+        Primitive::Char => "1".to_owned(),
+    }
+}
+
+/// .size on tstr is either in UTF-8 bytes (default) or unicode codepoints (--tstr-size-codepoints)
+fn str_size_expr(e: &str, cli: &Cli) -> String {
+    if cli.tstr_size_codepoints {
+        format!("{e}.chars().count()")
+    } else {
+        format!("{e}.len()")
     }
 }
 
-fn bounds_check_expr_rust_type(ty: &RustType, e: &str) -> Option<String> {
+fn bounds_check_expr_rust_type(ty: &RustType, e: &str, cli: &Cli) -> Option<String> {
     match ty.resolve_alias_shallow() {
-        ConceptualRustType::Primitive(p) => Some(bounds_check_expr(*p, e)),
+        ConceptualRustType::Primitive(p) => Some(bounds_check_expr(*p, e, cli)),
         ConceptualRustType::Array(_) |
         ConceptualRustType::Map(_, _) => Some(format!("{e}.len()")),
         // Alias should never be hit due to above alias resolving
         ConceptualRustType::Alias(_, _) => unreachable!(),
         // RustType is covered by passed in ctor
         ConceptualRustType::Rust(_) |
+        // @result types can't have bounds on them as a whole
+        ConceptualRustType::Result(_, _) |
         // Optional is not passed into ctor, but instead set later
         ConceptualRustType::Optional(_) |
         // FixedValue has no field associated with it
@@ -3647,6 +4480,49 @@ fn bounds_check_if_block(
     )
 }
 
+/// Like bounds_check_if_block() but float bounds are compared as-is so exclusive ones
+/// stay exclusive. It's written as a negation so that NaN is always out of bounds.
+fn float_bounds_check_if_block(
+    bounds: &(Option<FloatBound>, Option<FloatBound>),
+    e: &str,
+    return_err: bool,
+) -> String {
+    let possible_return = if return_err { "return " } else { "" };
+    // debug formatting keeps the .0 on whole floats
+    let in_bounds = [
+        bounds.0.map(|min| {
+            format!(
+                "{e} {} {:?}",
+                if min.inclusive { ">=" } else { ">" },
+                min.value
+            )
+        }),
+        bounds.1.map(|max| {
+            format!(
+                "{e} {} {:?}",
+                if max.inclusive { "<=" } else { "<" },
+                max.value
+            )
+        }),
+    ]
+    .into_iter()
+    .flatten()
+    .collect::<Vec<String>>()
+    .join(" && ");
+    let bound_expr = |bound: Option<FloatBound>| match bound {
+        Some(bound) => format!("Some(({:?}, {}))", bound.value, bound.inclusive),
+        None => "None".to_owned(),
+    };
+    format!(
+        "if !({}) {{ {}Err(DeserializeFailure::FloatRangeCheck{{ found: {} as f64, min: {}, max: {} }}.into()) }}",
+        in_bounds,
+        possible_return,
+        e,
+        bound_expr(bounds.0),
+        bound_expr(bounds.1),
+    )
+}
+
 fn declare_modules(
     gen_scopes: &mut BTreeMap<ModuleScope, codegen::Scope>,
     module_scopes: &[ModuleScope],
@@ -3766,9 +4642,16 @@ fn create_base_rust_struct(
 ) -> (codegen::Struct, codegen::Impl) {
     let name = &ident.to_string();
     let mut s = codegen::Struct::new(name);
+    // records with floats implement them manually instead (see codegen_struct())
+    let float_record = matches!(
+        types.rust_struct(ident).map(|rust_struct| rust_struct.variant()),
+        Some(RustStructType::Record(record)) if record.has_float_fields()
+    );
     add_struct_derives(
         &mut s,
         types.used_as_key(ident),
+        types.is_hashable(ident, cli),
+        types.is_eq(ident, cli) && !float_record,
         false,
         manual_json_impl,
         cli,
@@ -3815,6 +4698,8 @@ struct WasmWrapper<'a> {
     from_native: Option<codegen::Impl>,
     // AsRef
     as_ref: Option<codegen::Impl>,
+    // rust Box<T> <-> wasm for recursive types
+    box_conversions: Vec<codegen::Impl>,
     // (macro name, macro params)
     macros: Vec<(String, Vec<String>)>,
 }
@@ -3842,6 +4727,29 @@ impl<'a> WasmWrapper<'a> {
         if let Some(as_ref) = self.as_ref {
             gen_scope.wasm(types, self.ident).push_impl(as_ref);
         }
+        for box_conversion in self.box_conversions {
+            gen_scope.wasm(types, self.ident).push_impl(box_conversion);
+        }
+    }
+
+    /// Recursive types are stored as Box<T> in some places on the rust side.
+    /// These are always generated as they're not covered by wasm_conversions_macro.
+    fn add_box_conversion_methods(&mut self, native_name: &str) {
+        let mut from_boxed = codegen::Impl::new(self.ident.to_string());
+        from_boxed
+            .impl_trait(format!("From<Box<{native_name}>>"))
+            .new_fn("from")
+            .arg("native", format!("Box<{native_name}>"))
+            .ret("Self")
+            .line("Self(*native)");
+        let mut to_boxed = codegen::Impl::new(format!("Box<{native_name}>"));
+        to_boxed
+            .impl_trait(format!("From<{}>", self.ident))
+            .new_fn("from")
+            .arg("wasm", self.ident.to_string())
+            .ret("Self")
+            .line("Box::new(wasm.0)");
+        self.box_conversions = vec![from_boxed, to_boxed];
     }
 
     /// native_name is &str since we need to possibly prepend namespacing
@@ -3910,7 +4818,11 @@ fn create_base_wasm_struct<'a>(
             None => {
                 if cli.to_from_bytes_methods {
                     let mut to_bytes = codegen::Function::new("to_cbor_bytes");
-                    to_bytes.ret("Vec<u8>").arg_ref_self().vis("pub");
+                    to_bytes
+                        .attr("must_use")
+                        .ret("Vec<u8>")
+                        .arg_ref_self()
+                        .vis("pub");
                     if cli.preserve_encodings && cli.canonical_form {
                         to_bytes.line(format!(
                             "{}::serialization::Serialize::to_cbor_bytes(&self.0)",
@@ -3973,6 +4885,7 @@ fn create_base_wasm_struct<'a>(
         from_wasm: None,
         from_native: None,
         as_ref: None,
+        box_conversions: Vec::new(),
         macros,
     }
 }
@@ -3992,6 +4905,9 @@ fn create_base_wasm_wrapper<'a>(
         let native_name = rust_crate_struct_from_wasm(types, ident, cli);
         base.s.tuple_field(None, &native_name);
         base.add_conversion_methods(&native_name, cli);
+        if types.is_boxed(ident) {
+            base.add_box_conversion_methods(&native_name);
+        }
     }
     base
 }
@@ -4003,7 +4919,7 @@ fn create_base_wasm_wrapper<'a>(
 fn create_serialize_impl(
     ident: &RustIdent,
     rep: Option<Representation>,
-    tag: Option<usize>,
+    tag: Option<u64>,
     definite_len: &str,
     use_this_encoding: Option<&str>,
     cli: &Cli,
@@ -4027,7 +4943,7 @@ fn create_serialize_impl(
 fn create_serialize_impls(
     ident: &RustIdent,
     rep: Option<Representation>,
-    tag: Option<usize>,
+    tag: Option<u64>,
     definite_len: &str,
     use_this_encoding: Option<&str>,
     generate_serialize_embedded: bool,
@@ -4194,7 +5110,7 @@ fn add_deserialize_final_len_check(
 fn create_deserialize_impls(
     ident: &RustIdent,
     rep: Option<Representation>,
-    tag: Option<usize>,
+    tag: Option<u64>,
     len_info: Option<RustStructCBORLen>,
     generate_deserialize_embedded: bool,
     store_encoding: Option<&str>,
@@ -4325,7 +5241,7 @@ fn codegen_table_type(
     name: &RustIdent,
     key_type: RustType,
     value_type: RustType,
-    tag: Option<usize>,
+    tag: Option<u64>,
     exists_in_rust: bool,
     cli: &Cli,
 ) {
@@ -4361,6 +5277,14 @@ fn codegen_table_type(
         .ret("usize")
         .arg_ref_self()
         .line("self.0.len()");
+    // is_empty
+    wrapper
+        .s_impl
+        .new_fn("is_empty")
+        .vis("pub")
+        .ret("bool")
+        .arg_ref_self()
+        .line("self.0.is_empty()");
     // insert
     let mut insert_func = codegen::Function::new("insert");
     insert_func
@@ -4642,15 +5566,17 @@ fn encoding_fields_impl(
             encs
         }
         SerializingRustType::Root(ConceptualRustType::Primitive(p), _cfg) => match p {
-            Primitive::Bytes | Primitive::Str => vec![EncodingField {
-                field_name: format!("{name}_encoding"),
-                type_name: "StringEncoding".to_owned(),
-                default_expr: "StringEncoding::default()",
-                enc_conversion_before: "StringEncoding::from(",
-                enc_conversion_after: ")",
-                is_copy: false,
-                inner: Vec::new(),
-            }],
+            Primitive::Bytes | Primitive::ByteArray(_) | Primitive::Str | Primitive::Char => {
+                vec![EncodingField {
+                    field_name: format!("{name}_encoding"),
+                    type_name: "StringEncoding".to_owned(),
+                    default_expr: "StringEncoding::default()",
+                    enc_conversion_before: "StringEncoding::from(",
+                    enc_conversion_after: ")",
+                    is_copy: false,
+                    inner: Vec::new(),
+                }]
+            }
             Primitive::I8
             | Primitive::I16
             | Primitive::I32
@@ -4660,6 +5586,7 @@ fn encoding_fields_impl(
             | Primitive::U16
             | Primitive::U32
             | Primitive::U64
+            | Primitive::F16
             | Primitive::F32
             | Primitive::F64 => vec![EncodingField {
                 field_name: format!("{name}_encoding"),
@@ -4675,9 +5602,11 @@ fn encoding_fields_impl(
             {
                 vec![]
             }
+            // always a 16-byte bignum
+            Primitive::U128 | Primitive::I128 => vec![],
         },
         SerializingRustType::Root(ConceptualRustType::Fixed(f), _cfg) => match f {
-            FixedValue::Bool(_) | FixedValue::Null => vec![],
+            FixedValue::Bool(_) | FixedValue::Null | FixedValue::SimpleValue(_) => vec![],
             FixedValue::Nint(_) => encoding_fields_impl(
                 types,
                 name,
@@ -4709,6 +5638,9 @@ fn encoding_fields_impl(
         SerializingRustType::Root(ConceptualRustType::Optional(ty), _cfg) => {
             encoding_fields(types, name, ty, false, cli)
         }
+        SerializingRustType::Root(ConceptualRustType::Result(_, _), _cfg) => {
+            unimplemented!("preserve_encodings is not implemented for @result types")
+        }
         SerializingRustType::Root(ConceptualRustType::Rust(rust_ident), _cfg) => {
             match &types.rust_struct(rust_ident).unwrap().variant() {
                 // for c-style enums we push those up to where they are used instead of self-containing
@@ -4738,7 +5670,7 @@ fn encoding_fields_impl(
             encs.append(&mut encoding_fields_impl(types, name, *child, cli));
             encs
         }
-        SerializingRustType::EncodingOperation(CBOREncodingOperation::CBORBytes, child) => {
+        SerializingRustType::EncodingOperation(CBOREncodingOperation::CBORBytes(_), child) => {
             let mut encs = encoding_fields_impl(
                 types,
                 &format!("{name}_bytes"),
@@ -4748,6 +5680,42 @@ fn encoding_fields_impl(
             encs.append(&mut encoding_fields_impl(types, name, *child, cli));
             encs
         }
+        SerializingRustType::EncodingOperation(CBOREncodingOperation::CBORText, child) => {
+            let mut encs = encoding_fields_impl(
+                types,
+                &format!("{name}_text"),
+                (&ConceptualRustType::Primitive(Primitive::Str)).into(),
+                cli,
+            );
+            encs.append(&mut encoding_fields_impl(types, name, *child, cli));
+            encs
+        }
+    }
+}
+
+/// `let {name} = {name}.unwrap_or(default);` for an optional field with a .default value
+fn unwrap_or_default_value(field_name: &str, default_value: &FixedValue) -> String {
+    match default_value {
+        FixedValue::Text(_) => {
+            // to avoid clippy::or_fun_call
+            format!(
+                "let {} = {}.unwrap_or_else(|| {});",
+                field_name,
+                field_name,
+                default_value.to_primitive_str_assign()
+            )
+        }
+        FixedValue::Bool(_)
+        | FixedValue::Nint(_)
+        | FixedValue::Null
+        | FixedValue::SimpleValue(_)
+        | FixedValue::Float(_)
+        | FixedValue::Uint(_) => format!(
+            "let {} = {}.unwrap_or({});",
+            field_name,
+            field_name,
+            default_value.to_primitive_str_assign()
+        ),
     }
 }
 
@@ -4796,7 +5764,21 @@ fn generate_array_struct_serialization(
     let opt_self = if vars_in_self { "self." } else { "" };
     for field in record.fields.iter() {
         let field_expr = format!("{}{}", opt_self, field.name);
-        if field.optional {
+        if field.tail {
+            let elem_type = match &field.rust_type.conceptual_type {
+                ConceptualRustType::Array(elem_type) => elem_type,
+                _ => unreachable!("array tail {} must be a Vec", field.name),
+            };
+            let mut tail_ser_loop = Block::new(format!("for element in {field_expr}.iter()"));
+            gen_scope.generate_serialize(
+                types,
+                (&**elem_type).into(),
+                &mut tail_ser_loop,
+                SerializeConfig::new("element", format!("{}_elem", field.name)).expr_is_ref(true),
+                cli,
+            );
+            ser_func.push_block(tail_ser_loop);
+        } else if field.optional {
             if field.rust_type.is_fixed_value() && !cli.preserve_encodings {
                 // we just want to skip this entirely if we aren't remembering enecodings
                 continue;
@@ -4807,7 +5789,7 @@ fn generate_array_struct_serialization(
                 (
                     if cli.preserve_encodings {
                         if vars_in_self {
-                            format!("if {} != {} || self.encodings.map(|encs| encs.{}_default_present).unwrap_or(false)", field_expr, default_value.to_primitive_str_compare(), field.name)
+                            format!("if {} != {} || self.encodings.as_ref().map(|encs| encs.{}_default_present).unwrap_or(false)", field_expr, default_value.to_primitive_str_compare(), field.name)
                         } else {
                             format!(
                                 "if {} != {} || {}_default_present",
@@ -4892,7 +5874,7 @@ fn generate_array_struct_deserialization(
     types: &IntermediateTypes,
     name: &RustIdent,
     record: &RustRecord,
-    tag: Option<usize>,
+    tag: Option<u64>,
     in_embedded: bool,
     vars_in_self: bool,
     cli: &Cli,
@@ -4924,7 +5906,65 @@ fn generate_array_struct_deserialization(
         } else {
             (Cow::from(format!("let {} = ", field.name)), Cow::from(";"))
         };
-        if field.optional {
+        if field.tail {
+            let elem_type = match &field.rust_type.conceptual_type {
+                ConceptualRustType::Array(elem_type) => elem_type,
+                _ => unreachable!("array tail {} must be a Vec", field.name),
+            };
+            if in_embedded {
+                gen_scope.dont_generate_deserialize(
+                    name,
+                    format!("Plain group with repeated tail {}", field.name),
+                );
+            }
+            deser_code
+                .content
+                .line(&format!("let mut {} = Vec::new();", field.name));
+            let mut tail_deser_loop = make_deser_loop("len", "read_len.read()", cli);
+            // the break itself is read by the final len check
+            let mut break_check = Block::new(
+                "if raw.as_mut_ref().fill_buf().ok().and_then(|buf| buf.first().copied()) == Some(0xff)",
+            );
+            break_check.line("break;");
+            tail_deser_loop.push_block(break_check);
+            tail_deser_loop.line("read_len.read_elems(1)?;");
+            let elem_config = DeserializeConfig::new(&format!("{}_elem", field.name));
+            if cli.annotate_fields {
+                gen_scope
+                    .generate_deserialize(
+                        types,
+                        (&**elem_type).into(),
+                        DeserializeBeforeAfter::new("", "", true),
+                        elem_config,
+                        cli,
+                    )
+                    .annotate(&field.name, &format!("{}.push(", field.name), "?);")
+                    .add_to(&mut tail_deser_loop);
+            } else {
+                gen_scope
+                    .generate_deserialize(
+                        types,
+                        (&**elem_type).into(),
+                        DeserializeBeforeAfter::new(&format!("{}.push(", field.name), ");", false),
+                        elem_config,
+                        cli,
+                    )
+                    .add_to(&mut tail_deser_loop);
+            }
+            deser_code.content.push_block(tail_deser_loop);
+            if field.rust_type.config.non_empty {
+                deser_code
+                    .content
+                    .line(&format!("let {0} = NonEmptyVec::new({0})?;", field.name));
+            } else if let Some(bounds) = &field.rust_type.config.bounds {
+                deser_code.content.line(&bounds_check_if_block(
+                    bounds,
+                    &format!("{}.len()", field.name),
+                    true,
+                ));
+            }
+            deser_code.read_len_used = true;
+        } else if field.optional {
             // we can support optional fields, but only when they're immediately non-ambiguous
             // i.e. when the next type (possibly skipping subsequent optional fields)
             // is different from the current type.
@@ -4936,9 +5976,13 @@ fn generate_array_struct_deserialization(
             let field_cbor_types = field.rust_type.cbor_types(types);
             let mut possibly_last_field = true;
             for i in (field_index + 1)..record.fields.len() {
-                if record.fields[i]
-                    .rust_type
-                    .cbor_types(types)
+                let next_cbor_types = match &record.fields[i].rust_type.conceptual_type {
+                    ConceptualRustType::Array(elem_type) if record.fields[i].tail => {
+                        elem_type.cbor_types(types)
+                    }
+                    _ => record.fields[i].rust_type.cbor_types(types),
+                };
+                if next_cbor_types
                     .iter()
                     .any(|ct| field_cbor_types.contains(ct))
                 {
@@ -5071,6 +6115,20 @@ fn generate_array_struct_deserialization(
             }
             type_check_else.after(after);
             deser_code.content.push_block(type_check_else);
+            // a missing element deserializes to its .default value
+            if let Some(default_value) = &field.rust_type.config.default {
+                if cli.preserve_encodings {
+                    deser_code.content.line(&format!(
+                        "let {}_default_present = {} == Some({});",
+                        field.name,
+                        field.name,
+                        default_value.to_primitive_str_assign()
+                    ));
+                }
+                deser_code
+                    .content
+                    .line(&unwrap_or_default_value(&field.name, default_value));
+            }
         } else {
             // mandatory fields
             if cli.annotate_fields {
@@ -5145,7 +6203,7 @@ fn codegen_struct(
     gen_scope: &mut GenerationScope,
     types: &IntermediateTypes,
     name: &RustIdent,
-    tag: Option<usize>,
+    tag: Option<u64>,
     record: &RustRecord,
     config: &RustStructConfig,
     cli: &Cli,
@@ -5153,20 +6211,25 @@ fn codegen_struct(
     let new_can_fail = record
         .fields
         .iter()
-        .any(|f| !f.optional && f.rust_type.config.bounds.is_some());
+        .any(|f| !f.optional && f.rust_type.config.has_bounds());
+    // the encodings field is still needed to preserve encodings
+    let unit_struct = !cli.preserve_encodings
+        && matches!(types.rust_struct(name), Some(rust_struct) if rust_struct.is_unit_struct());
     // wasm wrapper
     if cli.wasm {
         let mut wrapper = create_base_wasm_wrapper(gen_scope, types, name, true, cli);
         let mut wasm_new = codegen::Function::new("new");
         if new_can_fail {
-            wasm_new.ret(format!("Result<{name}, JsError>"));
+            wasm_new
+                .attr(MUST_USE_VALIDATION)
+                .ret(format!("Result<{name}, JsError>"));
         } else {
             wasm_new.ret("Self");
         }
         wasm_new.vis("pub");
         let mut wasm_new_args = Vec::new();
         let mut wasm_new_comments = Vec::new();
-        for field in &record.fields {
+        for (_, field) in record.declaration_order(cli) {
             // Fixed values don't need constructors or getters or fields in the rust code
             if !field.rust_type.is_fixed_value() {
                 if field.optional {
@@ -5177,10 +6240,15 @@ fn codegen_struct(
                         .arg(&field.name, field.rust_type.for_wasm_param(types))
                         .vis("pub");
                     // don't call needs_bounds_check_if_inlined() since if it's a RustType it's checked during that ctor
-                    if let Some(bounds) = field.rust_type.config.bounds.as_ref() {
+                    if field.rust_type.config.has_bounds() {
                         setter.ret("Result<(), JsError>");
+                    }
+                    if let Some(float_bounds) = &field.rust_type.config.float_bounds {
+                        setter.line(float_bounds_check_if_block(float_bounds, &field.name, true));
+                    }
+                    if let Some(bounds) = field.rust_type.config.bounds.as_ref() {
                         if let Some(check_expr) =
-                            bounds_check_expr_rust_type(&field.rust_type, &field.name)
+                            bounds_check_expr_rust_type(&field.rust_type, &field.name, cli)
                         {
                             if let ConceptualRustType::Primitive(Primitive::N64) =
                                 field.rust_type.resolve_alias_shallow()
@@ -5231,6 +6299,17 @@ fn codegen_struct(
                                 false,
                             ),
                         );
+                    } else if let ConceptualRustType::Optional(_) =
+                        field.rust_type.resolve_alias_shallow()
+                    {
+                        getter
+                            .ret(field.rust_type.for_wasm_return(types))
+                            .doc(format!("Returns None if {} is not present OR it is but it's set to null\nThis is to get around wasm_bindgen not supporting Option<Option<T>>", field.name))
+                            .line(field.rust_type.to_wasm_boundary(
+                                types,
+                                &format!("self.0.{}.clone().flatten()", field.name),
+                                false,
+                            ));
                     } else {
                         getter
                             .ret(format!(
@@ -5299,7 +6378,10 @@ fn codegen_struct(
 
     // Struct (fields) + constructor
     let (mut native_struct, mut native_impl) = create_base_rust_struct(types, name, false, cli);
-    native_struct.vis("pub");
+    let vis = config.visibility_token(cli);
+    if let Some(vis) = vis {
+        native_struct.vis(vis);
+    }
     if let Some(doc) = config.doc.as_ref() {
         native_struct.doc(doc);
     }
@@ -5309,24 +6391,78 @@ fn codegen_struct(
     } else {
         ("Self", "Self")
     };
-    native_new.ret(ctor_ret).vis("pub");
+    native_new.ret(ctor_ret);
+    if let Some(vis) = vis {
+        native_new.vis(vis);
+    }
+    if new_can_fail {
+        native_new.attr(MUST_USE_VALIDATION);
+    }
     let mut native_new_block = Block::new(ctor_before);
     if new_can_fail {
         native_new_block.after(")");
     }
     let mut native_new_comments = Vec::new();
-    // for clippy we generate a Default impl if new has no args
-    let mut new_arg_count = 0;
-    for field in &record.fields {
-        if !gen_scope.deserialize_generated_for_type(types, &field.rust_type.conceptual_type) {
-            gen_scope.dont_generate_deserialize(
-                name,
-                format!(
-                    "field {}: {} couldn't generate deserialize",
-                    field.name,
-                    field.rust_type.for_rust_member(types, false, cli)
-                ),
-            );
+    let minicbor_derive = cli.cbor_crate == CborCrate::Minicbor
+        && config.custom_serialize.is_none()
+        && config.custom_deserialize.is_none()
+        && minicbor_derivable(types, tag, record);
+    if minicbor_derive {
+        native_struct
+            .derive("minicbor::Encode")
+            .derive("minicbor::Decode");
+        if record.rep == Representation::Map {
+            native_struct.attr("cbor(map)");
+        }
+    }
+    for (index, field) in record.declaration_order(cli) {
+        let minicbor_attr = if minicbor_derive {
+            let n = match &field.key {
+                Some(FixedValue::Uint(k)) => *k,
+                _ => index,
+            };
+            if matches!(
+                field.rust_type.conceptual_type.resolve_alias_shallow(),
+                ConceptualRustType::Primitive(Primitive::Bytes | Primitive::ByteArray(_))
+            ) {
+                format!("#[cbor(n({n}), with = \"minicbor::bytes\")] ")
+            } else {
+                format!("#[n({n})] ")
+            }
+        } else {
+            String::new()
+        };
+        // @hex byte fields are hex strings in JSON instead of arrays of numbers
+        let field_attrs =
+            if field.rule_metadata.hex && cli.json_serde_derives && !config.custom_json {
+                let schemars_attr = if cli.json_schema_export {
+                    "#[schemars(with = \"String\")] "
+                } else {
+                    ""
+                };
+                format!("{minicbor_attr}#[serde(with = \"hex\")] {schemars_attr}")
+            } else if cli.json_serde_derives
+                && !config.custom_json
+                && matches!(
+                    field.rust_type.conceptual_type.resolve_alias_shallow(),
+                    ConceptualRustType::Rust(ident) if types.is_plain_group(ident)
+                )
+            {
+                // embedded plain groups are their own struct in rust but their fields are
+                // part of ours in CBOR so the JSON is kept flat the same way
+                format!("{minicbor_attr}#[serde(flatten)] ")
+            } else {
+                minicbor_attr
+            };
+        if !gen_scope.deserialize_generated_for_type(types, &field.rust_type.conceptual_type) {
+            gen_scope.dont_generate_deserialize(
+                name,
+                format!(
+                    "field {}: {} couldn't generate deserialize",
+                    field.name,
+                    field.rust_type.for_rust_member(types, false, cli)
+                ),
+            );
         }
         // Fixed values only exist in (de)serialization code (outside of preserve-encodings=true)
         if !field.rust_type.is_fixed_value() {
@@ -5339,7 +6475,7 @@ fn codegen_struct(
                 ));
                 // field
                 codegen::Field::new(
-                    &format!("pub {}", field.name),
+                    &format!("{}pub {}", field_attrs, field.name),
                     field.rust_type.for_rust_member(types, false, cli),
                 )
             } else if field.optional {
@@ -5347,7 +6483,7 @@ fn codegen_struct(
                 native_new_block.line(format!("{}: None,", field.name));
                 // field
                 codegen::Field::new(
-                    &format!("pub {}", field.name),
+                    &format!("{}pub {}", field_attrs, field.name),
                     format!(
                         "Option<{}>",
                         field.rust_type.for_rust_member(types, false, cli)
@@ -5359,11 +6495,24 @@ fn codegen_struct(
                 if let Some(comment) = &field.rule_metadata.comment {
                     native_new_comments.push(format!("* `{}` - {}", field.name, comment));
                 }
-                new_arg_count += 1;
-                native_new_block.line(format!("{},", field.name));
-                if let Some(bounds) = field.rust_type.config.bounds.as_ref() {
+                if field.rust_type.config.non_empty {
+                    // NonEmptyVec::new() does the length check
+                    native_new_block.line(format!("{0}: NonEmptyVec::new({0})?,", field.name));
+                } else {
+                    native_new_block.line(format!("{},", field.name));
+                }
+                if let Some(float_bounds) = &field.rust_type.config.float_bounds {
+                    native_new.line(float_bounds_check_if_block(float_bounds, &field.name, true));
+                }
+                if let Some(bounds) = field
+                    .rust_type
+                    .config
+                    .bounds
+                    .as_ref()
+                    .filter(|_| !field.rust_type.config.non_empty)
+                {
                     if let Some(check_expr) =
-                        bounds_check_expr_rust_type(&field.rust_type, &field.name)
+                        bounds_check_expr_rust_type(&field.rust_type, &field.name, cli)
                     {
                         if let ConceptualRustType::Primitive(Primitive::N64) =
                             field.rust_type.resolve_alias_shallow()
@@ -5380,7 +6529,7 @@ fn codegen_struct(
                 }
                 // field
                 codegen::Field::new(
-                    &format!("pub {}", field.name),
+                    &format!("{}pub {}", field_attrs, field.name),
                     field.rust_type.for_rust_member(types, false, cli),
                 )
             };
@@ -5440,7 +6589,11 @@ fn codegen_struct(
     } else {
         None
     };
-    native_new.push_block(native_new_block);
+    if unit_struct {
+        native_new.line("Self");
+    } else {
+        native_new.push_block(native_new_block);
+    }
     native_impl.push_fn(native_new);
 
     // Serialization (via rust traits) - includes Deserialization too
@@ -5614,8 +6767,12 @@ fn codegen_struct(
                         deser_block_code.content.push_block(dup_check);
 
                         let temp_var_prefix = format!("tmp_{}", field.name);
-                        let var_names_str =
-                            encoding_var_names_str(types, &temp_var_prefix, &field.rust_type, cli);
+                        let var_names_str = encoding_var_names_str(
+                            types,
+                            &temp_var_prefix,
+                            &field.deserialize_rust_type(),
+                            cli,
+                        );
                         if cli.annotate_fields {
                             let (before, after) = if var_names_str.is_empty() {
                                 ("".to_owned(), "?")
@@ -5634,7 +6791,7 @@ fn codegen_struct(
                             gen_scope
                                 .generate_deserialize(
                                     types,
-                                    (&field.rust_type).into(),
+                                    (&*field.deserialize_rust_type()).into(),
                                     DeserializeBeforeAfter::new("", "", true),
                                     deser_config,
                                     cli,
@@ -5659,7 +6816,7 @@ fn codegen_struct(
                             gen_scope
                                 .generate_deserialize(
                                     types,
-                                    (&field.rust_type).into(),
+                                    (&*field.deserialize_rust_type()).into(),
                                     DeserializeBeforeAfter::new(&before, after, false),
                                     deser_config,
                                     cli,
@@ -5764,7 +6921,7 @@ fn codegen_struct(
                             gen_scope
                                 .generate_deserialize(
                                     types,
-                                    (&field.rust_type).into(),
+                                    (&*field.deserialize_rust_type()).into(),
                                     DeserializeBeforeAfter::new("", "", true),
                                     deser_config,
                                     cli,
@@ -5784,7 +6941,7 @@ fn codegen_struct(
                             gen_scope
                                 .generate_deserialize(
                                     types,
-                                    (&field.rust_type).into(),
+                                    (&*field.deserialize_rust_type()).into(),
                                     DeserializeBeforeAfter::new(
                                         &format!("{} = Some(", field.name),
                                         ");",
@@ -6024,6 +7181,11 @@ fn codegen_struct(
                             mandatory_field_check.after(";");
                             deser_code.content.push_block(mandatory_field_check);
                         }
+                    } else if field.collapse_null {
+                        // an explicit null was deserialized as Some(None) so treat it as absent
+                        deser_code
+                            .content
+                            .line(&format!("let {} = {}.flatten();", field.name, field.name));
                     } else if let Some(default_value) = &field.rust_type.config.default {
                         if cli.preserve_encodings {
                             let mut default_present_check = Block::new(format!(
@@ -6035,29 +7197,9 @@ fn codegen_struct(
                                 .line(format!("{}_default_present = true;", field.name));
                             deser_code.content.push_block(default_present_check);
                         }
-                        match default_value {
-                            FixedValue::Text(_) => {
-                                // to avoid clippy::or_fun_call
-                                deser_code.content.line(&format!(
-                                    "let {} = {}.unwrap_or_else(|| {});",
-                                    field.name,
-                                    field.name,
-                                    default_value.to_primitive_str_assign()
-                                ));
-                            }
-                            FixedValue::Bool(_)
-                            | FixedValue::Nint(_)
-                            | FixedValue::Null
-                            | FixedValue::Float(_)
-                            | FixedValue::Uint(_) => {
-                                deser_code.content.line(&format!(
-                                    "let {} = {}.unwrap_or({});",
-                                    field.name,
-                                    field.name,
-                                    default_value.to_primitive_str_assign()
-                                ));
-                            }
-                        }
+                        deser_code
+                            .content
+                            .line(&unwrap_or_default_value(&field.name, default_value));
                     }
                     if !field.rust_type.is_fixed_value() {
                         ctor_block.line(format!("{},", field.name));
@@ -6124,7 +7266,11 @@ fn codegen_struct(
                 cli,
             );
         }
-        deser_code.content.push_block(ctor_block);
+        if unit_struct {
+            deser_code.content.line(&format!("Ok({name})"));
+        } else {
+            deser_code.content.push_block(ctor_block);
+        }
 
         if cli.annotate_fields {
             deser_code = deser_code.annotate(name.as_ref(), "", "");
@@ -6183,9 +7329,45 @@ fn codegen_struct(
         .push_struct(native_struct)
         .push_impl(native_impl);
 
-    // for clippy we generate a Default when new takes no args.
+    if record.has_float_fields() && types.is_eq(name, cli) && !types.used_as_key(name) {
+        let mut partial_eq_impl = codegen::Impl::new(name.to_string());
+        let mut comparisons = record
+            .fields
+            .iter()
+            .filter(|field| !field.rust_type.is_fixed_value())
+            .map(|field| {
+                let self_field = format!("self.{}", field.name);
+                let other_field = format!("other.{}", field.name);
+                if field.optional {
+                    let optional = ConceptualRustType::Optional(Box::new(field.rust_type.clone()));
+                    eq_expr(&optional, &self_field, &other_field)
+                } else {
+                    eq_expr(&field.rust_type.conceptual_type, &self_field, &other_field)
+                }
+            })
+            .collect::<Vec<_>>();
+        // a match at the start of the body would be parsed as a statement instead of an operand
+        if comparisons.len() > 1 && comparisons[0].starts_with("match") {
+            comparisons[0] = format!("({})", comparisons[0]);
+        }
+        partial_eq_impl
+            .impl_trait("PartialEq")
+            .new_fn("eq")
+            .arg_ref_self()
+            .arg("other", "&Self")
+            .ret("bool")
+            .line(comparisons.join(" && "));
+        let mut eq_impl = codegen::Impl::new(name.to_string());
+        eq_impl.impl_trait("Eq");
+        gen_scope
+            .rust(types, name)
+            .push_impl(partial_eq_impl)
+            .push_impl(eq_impl);
+    }
+
+    // new() takes no args here so Default is the same. This is also needed for clippy.
     // We keep new() for consistency with other types.
-    if new_arg_count == 0 {
+    if record.is_defaultable() {
         let mut default_impl = codegen::Impl::new(name.to_string());
         default_impl
             .impl_trait("Default")
@@ -6203,7 +7385,7 @@ fn codegen_group_choices(
     name: &RustIdent,
     variants: &[EnumVariant],
     rep: Representation,
-    tag: Option<usize>,
+    tag: Option<u64>,
     config: &RustStructConfig,
     cli: &Cli,
 ) {
@@ -6257,9 +7439,7 @@ fn codegen_group_choices(
                         .iter()
                         .filter(|f| (!f.optional || inlined) && !f.rust_type.is_fixed_value())
                         .collect();
-                    let can_fail = ctor_fields
-                        .iter()
-                        .any(|f| f.rust_type.config.bounds.is_some());
+                    let can_fail = ctor_fields.iter().any(|f| f.rust_type.config.has_bounds());
                     match ctor_fields.len() {
                         0 => {
                             new_func
@@ -6335,7 +7515,7 @@ fn codegen_group_choices(
                             )
                         );
                         new_func.arg(&field_name, variant.rust_type().for_wasm_param(types));
-                        if variant.rust_type().config.bounds.is_some() {
+                        if variant.rust_type().config.has_bounds() {
                             new_func
                                 .ret(format!("Result<{name}, JsError>"))
                                 .line(format!("{ctor}.map(Into::into).map_err(Into::into)"));
@@ -6679,7 +7859,7 @@ fn generate_c_style_enum(
     types: &IntermediateTypes,
     name: &RustIdent,
     variants: &[EnumVariant],
-    tag: Option<usize>,
+    tag: Option<u64>,
     config: &RustStructConfig,
     cli: &Cli,
 ) -> bool {
@@ -6699,7 +7879,13 @@ fn generate_c_style_enum(
     }
     // rust enum containing the data
     let mut e = codegen::Enum::new(name.to_string());
-    e.vis("pub");
+    let vis = config.visibility_token(cli);
+    if let Some(vis) = vis {
+        e.vis(vis);
+    }
+    if let Some(doc) = config.doc.as_ref() {
+        e.doc(doc);
+    }
     e.derive("Copy")
         .derive("Eq")
         .derive("PartialEq")
@@ -6719,17 +7905,125 @@ fn generate_c_style_enum(
     add_struct_derives(
         &mut e,
         types.used_as_key(name),
+        types.is_hashable(name, cli),
+        false,
         true,
         config.custom_json,
         cli,
     );
-    for variant in variants.iter() {
-        e.new_variant(variant.name.to_string());
+    match (&config.repr, enum_variants_ints(variants)) {
+        // the CDDL constants as discriminants so e.g. `Command::Stop as u8` gives its value.
+        // codegen has no support for discriminants so it goes in the variant name.
+        (Some(repr), Some(ints)) => {
+            e.attr(&format!("repr({repr})"));
+            for (variant, value) in variants.iter().zip(ints) {
+                e.new_variant(format!("{} = {value}", variant.name));
+            }
+        }
+        _ => {
+            for variant in variants.iter() {
+                e.new_variant(variant.name.to_string());
+            }
+        }
     }
     gen_scope.rust(types, name).push_enum(e);
+    generate_uint_variant_consts(gen_scope, types, name, variants, vis);
+    if let Some(texts) = enum_variants_text(variants) {
+        generate_string_enum_impls(gen_scope, types, name, variants, &texts, vis, cli);
+    }
     true
 }
 
+/// associated consts with the value of each uint constant variant e.g. `Command::UINT24: u64 = 24`
+/// as the variant names only spell out small values (Zero, One, ...)
+fn generate_uint_variant_consts(
+    gen_scope: &mut GenerationScope,
+    types: &IntermediateTypes,
+    name: &RustIdent,
+    variants: &[EnumVariant],
+    vis: Option<&str>,
+) {
+    let vis = vis.map(|vis| format!("{vis} ")).unwrap_or_default();
+    let consts = variants
+        .iter()
+        .filter_map(
+            |variant| match variant.rust_type().conceptual_type.resolve_alias_shallow() {
+                ConceptualRustType::Fixed(FixedValue::Uint(u)) => Some(format!(
+                    "    {vis}const {}: u64 = {u};",
+                    convert_to_snake_case(&variant.name.to_string()).to_uppercase()
+                )),
+                _ => None,
+            },
+        )
+        .collect::<Vec<_>>();
+    if !consts.is_empty() {
+        gen_scope
+            .rust(types, name)
+            .raw(format!("impl {name} {{\n{}\n}}", consts.join("\n")));
+    }
+}
+
+/// as_str() / Display / TryFrom<&str> for C-style enums of text e.g. "mainnet" / "testnet"
+fn generate_string_enum_impls(
+    gen_scope: &mut GenerationScope,
+    types: &IntermediateTypes,
+    name: &RustIdent,
+    variants: &[EnumVariant],
+    texts: &[String],
+    vis: Option<&str>,
+    cli: &Cli,
+) {
+    let fmt_mod = if cli.no_std { "core::fmt" } else { "std::fmt" };
+    let mut as_str_match = Block::new("match self");
+    let mut try_from_match = Block::new("match s");
+    for (variant, text) in variants.iter().zip(texts) {
+        as_str_match.line(format!("Self::{} => {:?},", variant.name, text));
+        try_from_match.line(format!("{:?} => Ok(Self::{}),", text, variant.name));
+    }
+    // there's no error.rs with no_std
+    let error_type = if cli.no_std {
+        try_from_match.line("_ => Err(()),");
+        "()"
+    } else {
+        try_from_match.line(format!(
+            "_ => Err(DeserializeError::new(\"{name}\", DeserializeFailure::NoVariantMatched)),"
+        ));
+        "DeserializeError"
+    };
+    let mut as_str = codegen::Function::new("as_str");
+    as_str
+        .doc("The CDDL text of this variant")
+        .arg_ref_self()
+        .ret("&'static str")
+        .push_block(as_str_match);
+    if let Some(vis) = vis {
+        as_str.vis(vis);
+    }
+    let mut as_str_impl = codegen::Impl::new(name);
+    as_str_impl.push_fn(as_str);
+    let mut display = codegen::Impl::new(name);
+    display
+        .impl_trait(format!("{fmt_mod}::Display"))
+        .new_fn("fmt")
+        .arg_ref_self()
+        .arg("f", format!("&mut {fmt_mod}::Formatter<'_>"))
+        .ret(format!("{fmt_mod}::Result"))
+        .line("f.write_str(self.as_str())");
+    let mut try_from = codegen::Impl::new(name);
+    try_from
+        .impl_trait("TryFrom<&str>")
+        .associate_type("Error", error_type)
+        .new_fn("try_from")
+        .arg("s", "&str")
+        .ret("Result<Self, Self::Error>")
+        .push_block(try_from_match);
+    gen_scope
+        .rust(types, name)
+        .push_impl(as_str_impl)
+        .push_impl(display)
+        .push_impl(try_from);
+}
+
 fn make_enum_variant_return_if_deserialized(
     gen_scope: &mut GenerationScope,
     types: &IntermediateTypes,
@@ -6809,7 +8103,7 @@ fn make_inline_deser_code(
     gen_scope: &mut GenerationScope,
     types: &IntermediateTypes,
     name: &RustIdent,
-    tag: Option<usize>,
+    tag: Option<u64>,
     record: &RustRecord,
     enum_gen_info: &EnumVariantInRust,
     cli: &Cli,
@@ -6861,7 +8155,7 @@ fn generate_enum(
     variants: &[EnumVariant],
     rep: Option<Representation>,
     generate_deserialize_directly: bool,
-    tag: Option<usize>,
+    tag: Option<u64>,
     config: &RustStructConfig,
     cli: &Cli,
 ) {
@@ -6878,10 +8172,24 @@ fn generate_enum(
 
     // rust enum containing the data
     let mut e = codegen::Enum::new(name.to_string());
-    e.vis("pub");
+    let vis = config.visibility_token(cli);
+    if let Some(vis) = vis {
+        e.vis(vis);
+    }
     if let Some(doc) = config.doc.as_ref() {
         e.doc(doc);
     }
+    let variant_sizes = variants
+        .iter()
+        .map(|variant| variant.estimated_size(types))
+        .collect::<Vec<usize>>();
+    if let (Some(smallest), Some(largest)) =
+        (variant_sizes.iter().min(), variant_sizes.iter().max())
+    {
+        if largest - smallest > cli.large_enum_variant_threshold {
+            e.attr("allow(clippy::large_enum_variant)");
+        }
+    }
     let mut e_impl = codegen::Impl::new(name.to_string());
     // instead of using create_serialize_impl() and having the length encoded there, we want to make it easier
     // to offer definite length encoding even if we're mixing plain group members and non-plain group members (or mixed length plain ones)
@@ -6889,6 +8197,8 @@ fn generate_enum(
     add_struct_derives(
         &mut e,
         types.used_as_key(name),
+        types.is_hashable(name, cli),
+        types.is_eq(name, cli),
         true,
         config.custom_json,
         cli,
@@ -6938,9 +8248,13 @@ fn generate_enum(
         );
         deser_impl
     };
+    let tag_field = config.tag_field.as_deref();
     // We avoid checking ALL variants if we can figure it out by instead checking the type.
     // This only works when the variants don't have first types in common.
-    let mut non_overlapping_types_match = {
+    // With @tag_field we instead dispatch on the value of that key.
+    let mut non_overlapping_types_match = if tag_field.is_some() {
+        None
+    } else {
         let mut all_first_types = BTreeSet::new();
         let mut duplicates_or_unknown = false;
         for variant in variants.iter() {
@@ -6968,12 +8282,26 @@ fn generate_enum(
             Some((Block::new("match raw.cbor_type()?"), deser_covers_all_types))
         }
     };
+    // Failing that, array choices that each start with a different fixed value
+    // e.g. [0, foo // 1, bar] can still be dispatched on that first value.
+    let leading_value_patterns = match (tag_field, &non_overlapping_types_match, rep) {
+        (None, None, Some(Representation::Array)) => leading_fixed_value_patterns(types, variants),
+        _ => None,
+    };
     if non_overlapping_types_match.is_none() {
         deser_body
             .line("let initial_position = raw.as_mut_ref().stream_position().unwrap();")
             .line("let mut errs = Vec::new();");
     }
-    for variant in variants.iter() {
+    if let Some(tag_field) = tag_field {
+        deser_body.push_block(make_tag_field_scan_block(tag_field, cli));
+    }
+    if leading_value_patterns.is_some() {
+        deser_body
+            .line("let leading_value = <cbor_event::Value as cbor_event::de::Deserialize>::deserialize(raw).ok();")
+            .line("raw.as_mut_ref().seek(SeekFrom::Start(initial_position)).unwrap();");
+    }
+    for (variant_index, variant) in variants.iter().enumerate() {
         let enum_gen_info = EnumVariantInRust::new(types, variant, rep, cli);
         let variant_var_name = variant.name_as_var();
         let mut v = codegen::Variant::new(variant.name.to_string());
@@ -6999,7 +8327,9 @@ fn generate_enum(
         e.push_variant(v);
         // new (particularly useful if we have encoding variables)
         let mut new_func = codegen::Function::new(&format!("new_{variant_var_name}"));
-        new_func.vis("pub");
+        if let Some(vis) = vis {
+            new_func.vis(vis);
+        }
         if let Some(doc) = &variant.doc {
             new_func.doc(doc);
         }
@@ -7033,7 +8363,7 @@ fn generate_enum(
                             .collect();
                         let can_fail = ctor_fields
                             .iter()
-                            .any(|field| field.rust_type.config.bounds.is_some());
+                            .any(|field| field.rust_type.config.has_bounds());
                         // bounds checking should be handled by the called constructor here
                         let mut ctor = format!("{}::new(", ty.conceptual_type.for_variant());
                         for field in ctor_fields {
@@ -7059,9 +8389,16 @@ fn generate_enum(
                             let field_name = variant.name_as_var();
                             new_func
                                 .arg(&field_name, variant.rust_type().for_rust_move(types, cli));
+                            if let Some(float_bounds) = &ty.config.float_bounds {
+                                new_func.line(float_bounds_check_if_block(
+                                    float_bounds,
+                                    &field_name,
+                                    true,
+                                ));
+                            }
                             if let Some(bounds) = &ty.config.bounds {
                                 if let Some(check_expr) =
-                                    bounds_check_expr_rust_type(ty, &field_name)
+                                    bounds_check_expr_rust_type(ty, &field_name, cli)
                                 {
                                     if let ConceptualRustType::Primitive(Primitive::N64) =
                                         ty.resolve_alias_shallow()
@@ -7080,7 +8417,7 @@ fn generate_enum(
                                     }
                                 }
                             }
-                            (vec![field_name], ty.config.bounds.is_some())
+                            (vec![field_name], ty.config.has_bounds())
                         }
                     }
                 }
@@ -7095,14 +8432,26 @@ fn generate_enum(
                             &field.name,
                             field.to_embedded_rust_type().for_rust_move(types, cli),
                         );
-                        field.name.clone()
+                        if field.rust_type.config.non_empty {
+                            format!("NonEmptyVec::new({})?", field.name)
+                        } else {
+                            field.name.clone()
+                        }
                     })
                     .collect();
-                let can_fail = record.fields.iter().any(|field| {
+                // fold instead of any() so every field gets its checks, not just the first
+                let can_fail = record.fields.iter().fold(false, |any_can_fail, field| {
                     let can_fail = field.rust_type.needs_bounds_check_if_inlined(types);
-                    if can_fail {
+                    if let Some(float_bounds) = &field.rust_type.config.float_bounds {
+                        new_func.line(float_bounds_check_if_block(float_bounds, &field.name, true));
+                    }
+                    // NonEmptyVec::new() in the initializer does the length check
+                    if can_fail
+                        && field.rust_type.config.bounds.is_some()
+                        && !field.rust_type.config.non_empty
+                    {
                         if let Some(check_expr) =
-                            bounds_check_expr_rust_type(&field.rust_type, &field.name)
+                            bounds_check_expr_rust_type(&field.rust_type, &field.name, cli)
                         {
                             if let ConceptualRustType::Primitive(Primitive::N64) =
                                 field.rust_type.resolve_alias_shallow()
@@ -7121,7 +8470,7 @@ fn generate_enum(
                             }
                         }
                     }
-                    can_fail
+                    any_can_fail || can_fail
                 });
                 (init_fields, can_fail)
             }
@@ -7130,6 +8479,7 @@ fn generate_enum(
             init_fields.push(enc_field.default_expr.to_owned());
         }
         let (ret_type, ctor_before, ctor_after) = if can_fail {
+            new_func.attr(MUST_USE_VALIDATION);
             ("Result<Self, DeserializeError>", "Ok(", ")")
         } else {
             ("Self", "", "")
@@ -7371,6 +8721,23 @@ fn generate_enum(
                 }
             }
             None => {
+                // with @tag_field (or a leading fixed value) only the variant whose
+                // discriminator matched is attempted
+                let mut discriminant_check = match (tag_field, &leading_value_patterns) {
+                    (Some(tag_field), _) => {
+                        Some(tag_field_value_check(types, name, variant, tag_field))
+                    }
+                    (None, Some(patterns)) => Some(format!(
+                        "matches!(&leading_value, {})",
+                        patterns[variant_index]
+                    )),
+                    (None, None) => None,
+                }
+                .map(|condition| Block::new(format!("if {condition}")));
+                let variant_body: &mut dyn CodeBlock = match discriminant_check.as_mut() {
+                    Some(check) => check,
+                    None => &mut *deser_body,
+                };
                 let mut return_if_deserialized = match &variant.data {
                     EnumVariantData::RustType(ty) => {
                         let mut return_if_deserialized = make_enum_variant_return_if_deserialized(
@@ -7389,7 +8756,7 @@ fn generate_enum(
                                 };
                                 (len_info, r)
                             }),
-                            deser_body,
+                            variant_body,
                             cli,
                         );
                         let names_without_outer = enum_gen_info.names_without_outer();
@@ -7428,7 +8795,7 @@ fn generate_enum(
                         );
                         variant_deser.after(")(raw);");
                         variant_deser.push_all(variant_deser_code.content);
-                        deser_body.push_block(variant_deser);
+                        variant_body.push_block(variant_deser);
                         // can't chain blocks so we just put them one after the other
                         let mut return_if_deserialized = Block::new("match variant_deser");
                         return_if_deserialized.line("Ok(variant) => return Ok(variant),");
@@ -7441,7 +8808,10 @@ fn generate_enum(
                     .line("raw.as_mut_ref().seek(SeekFrom::Start(initial_position)).unwrap();");
                 return_if_deserialized.push_block(variant_deser_failed_block);
                 return_if_deserialized.after(";");
-                deser_body.push_block(return_if_deserialized);
+                variant_body.push_block(return_if_deserialized);
+                if let Some(discriminant_check) = discriminant_check {
+                    deser_body.push_block(discriminant_check);
+                }
             }
         }
     }
@@ -7466,15 +8836,178 @@ fn generate_enum(
         deser_func.push_block(error_annotator);
     }
     deser_impl.push_fn(deser_func);
+    if rep == Some(Representation::Array) {
+        if let Some(discriminant) = make_discriminant_function(types, variants, vis) {
+            e_impl.push_fn(discriminant);
+        }
+    }
     // TODO: should we stick this in another scope somewhere or not? it's not exposed to wasm
     // however, clients expanding upon the generated lib might find it of use to change.
     gen_scope.rust(types, name).push_enum(e).push_impl(e_impl);
+    generate_uint_variant_consts(gen_scope, types, name, variants, vis);
     gen_scope
         .rust_serialize(types, name)
         .push_impl(ser_impl)
         .push_impl(deser_impl);
 }
 
+// Reads ahead in the map to find the @tag_field key's value, storing it in tag_field_value
+// then rewinds back to the start of the map's contents.
+fn make_tag_field_scan_block(tag_field: &str, cli: &Cli) -> Block {
+    let mut scan = Block::new("let tag_field_value =");
+    scan.line("let mut tag_field_value = None;")
+        .line("let mut read = 0;");
+    let mut scan_loop = make_deser_loop("len", "read", cli);
+    scan_loop.push_block(make_deser_loop_break_check());
+    scan_loop
+        .line("let key = <cbor_event::Value as cbor_event::de::Deserialize>::deserialize(raw)?;")
+        .line("let value = <cbor_event::Value as cbor_event::de::Deserialize>::deserialize(raw)?;");
+    let mut found = Block::new(format!(
+        "if matches!(&key, cbor_event::Value::Text(k) if k == \"{tag_field}\")"
+    ));
+    found.line("tag_field_value = Some(value);").line("break;");
+    scan_loop.push_block(found);
+    scan_loop.line("read += 1;");
+    scan.push_block(scan_loop);
+    scan.line("raw.as_mut_ref().seek(SeekFrom::Start(initial_position)).unwrap();")
+        .line("tag_field_value");
+    scan.after(";");
+    scan
+}
+
+// Condition for a @tag_field enum's variant to be the one the discriminator value selects
+fn tag_field_value_check(
+    types: &IntermediateTypes,
+    name: &RustIdent,
+    variant: &EnumVariant,
+    tag_field: &str,
+) -> String {
+    // both checked in parse_group()
+    let value = variant
+        .tag_field_value(types, tag_field)
+        .unwrap_or_else(|e| panic!("{name}::{}: {e}", variant.name));
+    let pattern = fixed_value_pattern(value).unwrap_or_else(|| {
+        panic!(
+            "{name}::{}: float discriminators not supported for @tag_field",
+            variant.name
+        )
+    });
+    format!("matches!(&tag_field_value, {pattern})")
+}
+
+// Pattern for an Option<cbor_event::Value> that was read in to be the given fixed value.
+// There is none for floats as cbor_event::Value has no float variant.
+fn fixed_value_pattern(value: &FixedValue) -> Option<String> {
+    match value {
+        FixedValue::Null => {
+            Some("Some(cbor_event::Value::Special(cbor_event::Special::Null))".to_owned())
+        }
+        FixedValue::Bool(b) => Some(format!(
+            "Some(cbor_event::Value::Special(cbor_event::Special::Bool({b})))"
+        )),
+        FixedValue::Uint(u) => Some(format!("Some(cbor_event::Value::U64({u}))")),
+        FixedValue::Nint(i) => Some(format!("Some(cbor_event::Value::I64({i}))")),
+        FixedValue::Text(s) => Some(format!("Some(cbor_event::Value::Text(v)) if v == {s:?}")),
+        FixedValue::SimpleValue(n) => Some(format!(
+            "Some(cbor_event::Value::Special(cbor_event::Special::Unassigned({n})))"
+        )),
+        FixedValue::Float(_) => None,
+    }
+}
+
+// The fixed value an array group choice variant must start with, if any
+fn leading_fixed_value<'a>(
+    types: &'a IntermediateTypes,
+    variant: &'a EnumVariant,
+) -> Option<&'a FixedValue> {
+    let fields = match &variant.data {
+        EnumVariantData::Inlined(record) => &record.fields,
+        EnumVariantData::RustType(ty) if ty.encodings.is_empty() => {
+            match ty.conceptual_type.resolve_alias_shallow() {
+                ConceptualRustType::Fixed(value) => return Some(value),
+                ConceptualRustType::Rust(ident) if types.is_plain_group(ident) => {
+                    match types.rust_struct(ident)?.variant() {
+                        RustStructType::Record(record) => &record.fields,
+                        _ => return None,
+                    }
+                }
+                _ => return None,
+            }
+        }
+        EnumVariantData::RustType(_) => return None,
+    };
+    let first = fields.first()?;
+    if first.optional || !first.rust_type.encodings.is_empty() {
+        return None;
+    }
+    match first.rust_type.conceptual_type.resolve_alias_shallow() {
+        ConceptualRustType::Fixed(value) => Some(value),
+        _ => None,
+    }
+}
+
+// Patterns (see fixed_value_pattern()) for each variant's leading fixed value, in variant order.
+// None unless every variant starts with one and they are all different.
+fn leading_fixed_value_patterns(
+    types: &IntermediateTypes,
+    variants: &[EnumVariant],
+) -> Option<Vec<String>> {
+    let mut seen = BTreeSet::new();
+    let mut patterns = Vec::new();
+    for variant in variants {
+        let pattern = fixed_value_pattern(leading_fixed_value(types, variant)?)?;
+        if !seen.insert(pattern.clone()) {
+            return None;
+        }
+        patterns.push(pattern);
+    }
+    Some(patterns)
+}
+
+// discriminant() accessor for array group choices that each start with a different
+// fixed integer e.g. [0, uint // 1, text] returns 0 or 1 depending on the variant.
+fn make_discriminant_function(
+    types: &IntermediateTypes,
+    variants: &[EnumVariant],
+    vis: Option<&str>,
+) -> Option<codegen::Function> {
+    let mut seen = BTreeSet::new();
+    let mut discriminants = Vec::new();
+    for variant in variants {
+        let discriminant = match leading_fixed_value(types, variant)? {
+            FixedValue::Uint(u) => *u as i128,
+            FixedValue::Nint(i) => *i as i128,
+            _ => return None,
+        };
+        if !seen.insert(discriminant) {
+            return None;
+        }
+        discriminants.push(discriminant);
+    }
+    let ret = if discriminants.iter().all(|d| *d >= 0) {
+        "u64"
+    } else {
+        "i64"
+    };
+    let mut discriminant_func = codegen::Function::new("discriminant");
+    discriminant_func
+        .arg_ref_self()
+        .ret(ret)
+        .doc("The leading integer of the encoded array identifying the variant");
+    if let Some(vis) = vis {
+        discriminant_func.vis(vis);
+    }
+    let mut discriminant_match = Block::new("match self");
+    for (variant, discriminant) in variants.iter().zip(discriminants) {
+        discriminant_match.line(format!(
+            "Self::{} {{ .. }} => {discriminant},",
+            variant.name
+        ));
+    }
+    discriminant_func.push_block(discriminant_match);
+    Some(discriminant_func)
+}
+
 fn make_serialization_function(name: &str, cli: &Cli) -> codegen::Function {
     let mut f = codegen::Function::new(name);
     f.generic("'se, W: Write")
@@ -7497,6 +9030,15 @@ fn make_serialization_impl(name: &str, cli: &Cli) -> codegen::Impl {
     ser_impl
 }
 
+// for deserializing into a boxed reference e.g. `.map(Box::new)` on Result<T, _>
+fn box_if(boxed: bool) -> &'static str {
+    if boxed {
+        ".map(Box::new)"
+    } else {
+        ""
+    }
+}
+
 fn make_deserialization_function(name: &str) -> codegen::Function {
     let mut f = codegen::Function::new(name);
     f.generic("R: BufRead + Seek")
@@ -7505,6 +9047,863 @@ fn make_deserialization_function(name: &str) -> codegen::Function {
     f
 }
 
+// Deserializes directly from the borrowed slice so callers don't need to clone into a Vec<u8>
+fn make_try_from_bytes_impl(name: &str) -> codegen::Impl {
+    let mut try_from = codegen::Impl::new(name);
+    try_from
+        .impl_trait("TryFrom<&[u8]>")
+        .associate_type("Error", "DeserializeError")
+        .new_fn("try_from")
+        .arg("bytes", "&[u8]")
+        .ret("Result<Self, Self::Error>")
+        .line("Self::from_cbor_bytes(bytes)");
+    try_from
+}
+
+// Conversions to/from ciborium's dynamically typed CBOR values by way of our CBOR encoding.
+fn make_cbor_value_impls(name: &str) -> (codegen::Impl, codegen::Impl) {
+    let mut from = codegen::Impl::new("ciborium::value::Value");
+    from.impl_trait(format!("From<{name}>"))
+        .new_fn("from")
+        .arg("value", name)
+        .ret("Self")
+        .line("ciborium::de::from_reader(value.to_cbor_bytes().as_slice())")
+        .line("    .expect(\"generated serialization is always valid CBOR\")");
+    let mut try_from = codegen::Impl::new(name);
+    try_from
+        .impl_trait("TryFrom<ciborium::value::Value>")
+        .associate_type("Error", "DeserializeError")
+        .new_fn("try_from")
+        .arg("value", "ciborium::value::Value")
+        .ret("Result<Self, Self::Error>")
+        .line("let mut bytes = Vec::new();")
+        .line("ciborium::ser::into_writer(&value, &mut bytes)")
+        .line("    .map_err(|e| DeserializeFailure::InvalidStructure(Box::new(e)))?;")
+        .line("Self::from_cbor_bytes(&bytes)");
+    (from, try_from)
+}
+
+// serde impls for use with ciborium e.g. ciborium::ser::into_writer(&foo, w).
+// These go through ciborium::value::Value so tags are Value::Tag and map keys are the ones from the CDDL.
+// serde impls for --cbor-crate=ciborium. These aren't a separate backend: the value is encoded with
+// the cbor_event code and parsed back into a ciborium Value to serialize, which is a lot of extra work.
+fn make_ciborium_serialize_impl(name: &str) -> codegen::Impl {
+    let mut serialize = codegen::Impl::new(name);
+    serialize
+        .impl_trait("serde::Serialize")
+        .new_fn("serialize")
+        .generic("S: serde::Serializer")
+        .arg_ref_self()
+        .arg("serializer", "S")
+        .ret("Result<S::Ok, S::Error>")
+        .line("let value: ciborium::value::Value = ciborium::de::from_reader(self.to_cbor_bytes().as_slice())")
+        .line("    .map_err(serde::ser::Error::custom)?;")
+        .line("serde::Serialize::serialize(&value, serializer)");
+    serialize
+}
+
+fn make_ciborium_deserialize_impl(name: &str) -> codegen::Impl {
+    let mut deserialize = codegen::Impl::new(name);
+    deserialize
+        .generic("'de")
+        .impl_trait("serde::Deserialize<'de>")
+        .new_fn("deserialize")
+        .generic("D: serde::Deserializer<'de>")
+        .arg("deserializer", "D")
+        .ret("Result<Self, D::Error>")
+        .line("let value = <ciborium::value::Value as serde::Deserialize>::deserialize(deserializer)?;")
+        .line("Self::try_from(value).map_err(serde::de::Error::custom)");
+    deserialize
+}
+
+/// Where a type's minicbor::Encode/Decode impls come from
+enum MinicborImpls<'a> {
+    /// #[derive(minicbor::Encode, minicbor::Decode)] in codegen_struct()
+    Derived,
+    /// make_minicbor_record_impls()
+    Manual(&'a RustRecord),
+    /// make_minicbor_impls() which needs cbor_event
+    Delegated,
+    /// @custom_serialize / @custom_deserialize so they must be written by the user
+    Custom,
+    /// plain groups, externs and Vec/BTreeMap aliases have no impls of their own
+    None,
+}
+
+fn minicbor_impls<'a>(
+    types: &IntermediateTypes,
+    rust_ident: &RustIdent,
+    rust_struct: &'a crate::intermediate::RustStruct,
+) -> MinicborImpls<'a> {
+    let custom = rust_struct.config().custom_serialize.is_some()
+        || rust_struct.config().custom_deserialize.is_some();
+    match rust_struct.variant() {
+        _ if types.is_plain_group(rust_ident) => MinicborImpls::None,
+        RustStructType::Extern
+        | RustStructType::Table { .. }
+        | RustStructType::Array { .. }
+        | RustStructType::RawBytesType => MinicborImpls::None,
+        _ if custom => MinicborImpls::Custom,
+        RustStructType::Record(record) if minicbor_derivable(types, rust_struct.tag(), record) => {
+            MinicborImpls::Derived
+        }
+        RustStructType::Record(record) if minicbor_manual_record_supported(types, record) => {
+            MinicborImpls::Manual(record)
+        }
+        RustStructType::Record(_)
+        | RustStructType::TypeChoice { .. }
+        | RustStructType::GroupChoice { .. }
+        | RustStructType::Wrapper { .. }
+        | RustStructType::CStyleEnum { .. } => MinicborImpls::Delegated,
+    }
+}
+
+/// --no-std has no cbor_event nor error.rs so every type's minicbor impls must be derived
+/// or written by make_minicbor_record_impls(), and nothing can use NonEmptyVec.
+pub fn check_no_std_support(types: &IntermediateTypes) -> Result<(), String> {
+    for (rust_ident, rust_struct) in types.rust_structs() {
+        match minicbor_impls(types, rust_ident, rust_struct) {
+            MinicborImpls::Delegated => return Err(format!(
+                "{rust_ident}: --no-std can't generate minicbor impls for this type as they would need cbor_event (e.g. type/group choices, wrappers, .size/.default/.cbor fields or non-trailing optional array fields)"
+            )),
+            MinicborImpls::Custom => return Err(format!(
+                "{rust_ident}: @custom_serialize / @custom_deserialize are not supported with --no-std"
+            )),
+            MinicborImpls::Derived | MinicborImpls::Manual(_) | MinicborImpls::None => (),
+        }
+        if let RustStructType::Record(record) = rust_struct.variant() {
+            if let Some(field) = record
+                .fields
+                .iter()
+                .find(|field| field.rust_type.config.non_empty)
+            {
+                return Err(format!(
+                    "{rust_ident}.{}: + arrays are not supported with --no-std as NonEmptyVec uses error.rs",
+                    field.name
+                ));
+            }
+        }
+    }
+    Ok(())
+}
+
+// minicbor::Encode/Decode that delegate to our cbor_event-based (de)serialization.
+// Used for types that neither minicbor's derive attributes nor make_minicbor_record_impls()
+// can express e.g. type/group choices or bounded types.
+fn make_minicbor_impls(name: &str) -> (codegen::Impl, codegen::Impl) {
+    let mut encode = codegen::Impl::new(name);
+    encode
+        .generic("C")
+        .impl_trait("minicbor::Encode<C>")
+        .new_fn("encode")
+        .generic("W: minicbor::encode::Write")
+        .arg_ref_self()
+        .arg("e", "&mut minicbor::Encoder<W>")
+        .arg("_ctx", "&mut C")
+        .ret("Result<(), minicbor::encode::Error<W::Error>>")
+        .line("e.writer_mut()")
+        .line("    .write_all(&self.to_cbor_bytes())")
+        .line("    .map_err(minicbor::encode::Error::write)");
+    let mut decode = codegen::Impl::new(name);
+    decode
+        .generic("'b")
+        .generic("C")
+        .impl_trait("minicbor::Decode<'b, C>")
+        .new_fn("decode")
+        .arg("d", "&mut minicbor::Decoder<'b>")
+        .arg("_ctx", "&mut C")
+        .ret("Result<Self, minicbor::decode::Error>")
+        .line("let start = d.position();")
+        .line("let mut raw = Deserializer::from(std::io::Cursor::new(&d.input()[start..]));")
+        .line("let ret = Self::deserialize(&mut raw)")
+        .line("    .map_err(|e| minicbor::decode::Error::message(e.to_string()))?;")
+        .line("d.set_position(start + raw.as_mut_ref().position() as usize);")
+        .line("Ok(ret)");
+    (encode, decode)
+}
+
+/// Whether minicbor_encode_value()/minicbor_decode_value() can write this type with minicbor's
+/// Encoder/Decoder directly. Bounds, defaults and .cbor encodings would need our own checks so those
+/// types keep delegating to cbor_event.
+fn minicbor_manual_type_supported(types: &IntermediateTypes, ty: &RustType) -> bool {
+    !ty.config.has_bounds()
+        && ty.config.default.is_none()
+        && ty
+            .encodings
+            .iter()
+            .all(|enc| matches!(enc, CBOREncodingOperation::Tagged(_)))
+        && minicbor_manual_conceptual_type_supported(types, &ty.conceptual_type)
+}
+
+fn minicbor_manual_conceptual_type_supported(
+    types: &IntermediateTypes,
+    ty: &ConceptualRustType,
+) -> bool {
+    match ty {
+        ConceptualRustType::Fixed(fixed) => matches!(
+            fixed,
+            FixedValue::Null
+                | FixedValue::Bool(_)
+                | FixedValue::Uint(_)
+                | FixedValue::Nint(_)
+                | FixedValue::Text(_)
+        ),
+        // our bignums and nint-only type have no minicbor equivalent
+        ConceptualRustType::Primitive(p) => {
+            !matches!(p, Primitive::N64 | Primitive::U128 | Primitive::I128)
+        }
+        // these are encoded with e.encode_with() so they need minicbor impls themselves
+        ConceptualRustType::Rust(ident) => {
+            !types.is_plain_group(ident)
+                && matches!(
+                    types
+                        .rust_struct(ident)
+                        .map(|rust_struct| rust_struct.variant()),
+                    Some(
+                        RustStructType::Record(_)
+                            | RustStructType::TypeChoice { .. }
+                            | RustStructType::GroupChoice { .. }
+                            | RustStructType::Wrapper { .. }
+                            | RustStructType::CStyleEnum { .. }
+                    )
+                )
+        }
+        ConceptualRustType::Array(inner) | ConceptualRustType::Optional(inner) => {
+            minicbor_manual_type_supported(types, inner)
+        }
+        ConceptualRustType::Map(key, value) => {
+            minicbor_manual_type_supported(types, key)
+                && minicbor_manual_type_supported(types, value)
+        }
+        ConceptualRustType::Alias(_, inner) => {
+            minicbor_manual_conceptual_type_supported(types, inner)
+        }
+        ConceptualRustType::Result(_, _) => false,
+    }
+}
+
+/// Whether a record can get minicbor impls written with minicbor_encode_value()/minicbor_decode_value().
+/// Array fields are positional so an optional one is only allowed as the last field, where
+/// its presence can be told from the array length.
+fn minicbor_manual_record_supported(types: &IntermediateTypes, record: &RustRecord) -> bool {
+    let last = record.fields.len().saturating_sub(1);
+    record.fields.iter().enumerate().all(|(i, field)| {
+        let position_ok = match record.rep {
+            Representation::Map => matches!(
+                field.key,
+                Some(FixedValue::Uint(_) | FixedValue::Nint(_) | FixedValue::Text(_))
+            ),
+            Representation::Array => {
+                !field.optional || (i == last && !field.rust_type.is_fixed_value())
+            }
+        };
+        let value_type = match &field.rust_type.conceptual_type {
+            // length is checked via NonEmptyVec::new() instead
+            ConceptualRustType::Array(elem) if field.tail => elem,
+            _ => &field.rust_type,
+        };
+        // n*m tails would need their length checked too
+        let tail_ok =
+            !field.tail || field.rust_type.config.non_empty || !field.rust_type.config.has_bounds();
+        position_ok
+            && tail_ok
+            && field.rule_metadata.custom_serialize.is_none()
+            && field.rule_metadata.custom_deserialize.is_none()
+            && minicbor_manual_type_supported(types, value_type)
+    })
+}
+
+fn minicbor_primitive_method(p: &Primitive) -> Option<&'static str> {
+    match p {
+        Primitive::Bool => Some("bool"),
+        Primitive::F64 => Some("f64"),
+        Primitive::F32 => Some("f32"),
+        Primitive::F16 => Some("f16"),
+        Primitive::U8 => Some("u8"),
+        Primitive::I8 => Some("i8"),
+        Primitive::U16 => Some("u16"),
+        Primitive::I16 => Some("i16"),
+        Primitive::U32 => Some("u32"),
+        Primitive::I32 => Some("i32"),
+        Primitive::U64 => Some("u64"),
+        Primitive::I64 => Some("i64"),
+        _ => None,
+    }
+}
+
+/// Writes a fixed value or map key with minicbor's Encoder e
+fn minicbor_encode_fixed_value(body: &mut dyn CodeBlock, fixed: &FixedValue) {
+    body.line(&match fixed {
+        FixedValue::Null => String::from("e.null()?;"),
+        FixedValue::Bool(b) => format!("e.bool({b})?;"),
+        FixedValue::Uint(u) => format!("e.u64({u})?;"),
+        FixedValue::Nint(i) => format!("e.i64({i})?;"),
+        FixedValue::Text(s) => format!("e.str({s:?})?;"),
+        FixedValue::Float(_) | FixedValue::SimpleValue(_) => {
+            unreachable!("checked by minicbor_manual_conceptual_type_supported()")
+        }
+    });
+}
+
+/// Writes expr (a reference to a value of type ty) with minicbor's Encoder e.
+/// depth keeps the variables of nested arrays/maps apart.
+fn minicbor_encode_value(body: &mut dyn CodeBlock, ty: &RustType, expr: &str, depth: usize) {
+    // the last encoding operation is the outermost one
+    for enc in ty.encodings.iter().rev() {
+        if let CBOREncodingOperation::Tagged(tag) = enc {
+            body.line(&format!("e.tag(minicbor::data::Tag::new({tag}))?;"));
+        }
+    }
+    minicbor_encode_conceptual_value(body, &ty.conceptual_type, expr, depth);
+}
+
+fn minicbor_encode_conceptual_value(
+    body: &mut dyn CodeBlock,
+    ty: &ConceptualRustType,
+    expr: &str,
+    depth: usize,
+) {
+    // method calls auto-ref so e.g. &self.foo can be called as self.foo.len()
+    let receiver = expr.strip_prefix('&').unwrap_or(expr);
+    match ty {
+        ConceptualRustType::Fixed(fixed) => minicbor_encode_fixed_value(body, fixed),
+        ConceptualRustType::Primitive(p) => match minicbor_primitive_method(p) {
+            Some(method) => {
+                body.line(&format!("e.{method}(*{expr})?;"));
+            }
+            None => match p {
+                Primitive::Str => {
+                    body.line(&format!("e.str({expr})?;"));
+                }
+                Primitive::Bytes | Primitive::ByteArray(_) => {
+                    body.line(&format!("e.bytes({expr})?;"));
+                }
+                // a 1-char text string instead of minicbor's integer encoding of char
+                Primitive::Char => {
+                    body.line(&format!("e.str({receiver}.encode_utf8(&mut [0; 4]))?;"));
+                }
+                _ => unreachable!("checked by minicbor_manual_conceptual_type_supported()"),
+            },
+        },
+        ConceptualRustType::Rust(_) => {
+            body.line(&format!("e.encode_with({expr}, ctx)?;"));
+        }
+        ConceptualRustType::Array(elem) => {
+            let elem_var = format!("elem{depth}");
+            body.line(&format!("e.array({receiver}.len() as u64)?;"));
+            let mut elem_loop = Block::new(format!("for {elem_var} in {receiver}.iter()"));
+            minicbor_encode_value(&mut elem_loop, elem, &elem_var, depth + 1);
+            body.push_block(elem_loop);
+        }
+        ConceptualRustType::Map(key, value) => {
+            let (key_var, value_var) = (format!("key{depth}"), format!("value{depth}"));
+            body.line(&format!("e.map({receiver}.len() as u64)?;"));
+            let mut entry_loop =
+                Block::new(format!("for ({key_var}, {value_var}) in {receiver}.iter()"));
+            minicbor_encode_value(&mut entry_loop, key, &key_var, depth + 1);
+            minicbor_encode_value(&mut entry_loop, value, &value_var, depth + 1);
+            body.push_block(entry_loop);
+        }
+        ConceptualRustType::Optional(inner) => {
+            let inner_var = format!("inner{depth}");
+            let mut some_block = Block::new(format!("Some({inner_var}) =>"));
+            minicbor_encode_value(&mut some_block, inner, &inner_var, depth + 1);
+            let mut match_block = Block::new(format!("match {expr}"));
+            match_block.push_block(some_block);
+            match_block.line("None => { e.null()?; }");
+            body.push_block(match_block);
+        }
+        ConceptualRustType::Alias(_, inner) => {
+            minicbor_encode_conceptual_value(body, inner, expr, depth)
+        }
+        ConceptualRustType::Result(_, _) => {
+            unreachable!("checked by minicbor_manual_conceptual_type_supported()")
+        }
+    }
+}
+
+fn minicbor_decode_error(msg: &str) -> String {
+    format!("return Err(minicbor::decode::Error::message({msg:?}))")
+}
+
+/// Checks a fixed value or map key read with minicbor's Decoder d
+fn minicbor_decode_fixed_value(fixed: &FixedValue, name: &str) -> String {
+    let mismatch = minicbor_decode_error(&format!(
+        "{name}: expected {}",
+        fixed.to_primitive_str_compare()
+    ));
+    match fixed {
+        FixedValue::Null => {
+            format!("if d.datatype()? != minicbor::data::Type::Null {{ {mismatch} }} d.skip()?;")
+        }
+        FixedValue::Bool(b) => format!("if d.bool()? != {b} {{ {mismatch} }}"),
+        FixedValue::Uint(u) => format!("if d.u64()? != {u} {{ {mismatch} }}"),
+        FixedValue::Nint(i) => format!("if d.i64()? != {i} {{ {mismatch} }}"),
+        FixedValue::Text(s) => format!("if d.str()? != {s:?} {{ {mismatch} }}"),
+        FixedValue::Float(_) | FixedValue::SimpleValue(_) => {
+            unreachable!("checked by minicbor_manual_conceptual_type_supported()")
+        }
+    }
+}
+
+/// Expression reading a value of type ty with minicbor's Decoder d. Fixed values are only checked.
+/// name is used in error messages and depth keeps the variables of nested arrays/maps apart.
+fn minicbor_decode_value(
+    types: &IntermediateTypes,
+    ty: &RustType,
+    name: &str,
+    depth: usize,
+    cli: &Cli,
+) -> String {
+    let mut tag_checks = String::new();
+    for enc in ty.encodings.iter().rev() {
+        if let CBOREncodingOperation::Tagged(tag) = enc {
+            tag_checks.push_str(&format!(
+                "if d.tag()? != minicbor::data::Tag::new({tag}) {{ {} }} ",
+                minicbor_decode_error(&format!("{name}: expected tag {tag}"))
+            ));
+        }
+    }
+    let value = match &ty.conceptual_type {
+        // Box<T> is decoded through T
+        ConceptualRustType::Rust(_) => format!(
+            "d.decode_with::<C, {}>(ctx)?",
+            ty.for_rust_member(types, false, cli)
+        ),
+        conceptual_type => {
+            minicbor_decode_conceptual_value(types, conceptual_type, name, depth, cli)
+        }
+    };
+    if tag_checks.is_empty() {
+        value
+    } else {
+        format!("{{ {tag_checks}{value} }}")
+    }
+}
+
+fn minicbor_decode_conceptual_value(
+    types: &IntermediateTypes,
+    ty: &ConceptualRustType,
+    name: &str,
+    depth: usize,
+    cli: &Cli,
+) -> String {
+    // reads until the definite length or the break of an indefinite-length array/map
+    let more_elems = |len: &str, read: &str| {
+        format!("match {len} {{ Some({len}) => {read} < {len}, None => d.datatype()? != minicbor::data::Type::Break }}")
+    };
+    match ty {
+        ConceptualRustType::Fixed(fixed) => {
+            format!("{{ {} }}", minicbor_decode_fixed_value(fixed, name))
+        }
+        ConceptualRustType::Primitive(p) => match minicbor_primitive_method(p) {
+            Some(method) => format!("d.{method}()?"),
+            None => match p {
+                Primitive::Str => String::from("String::from(d.str()?)"),
+                Primitive::Bytes => String::from("d.bytes()?.to_vec()"),
+                Primitive::ByteArray(n) => format!(
+                    "<[u8; {n}]>::try_from(d.bytes()?).map_err(|_| minicbor::decode::Error::message({:?}))?",
+                    format!("{name}: expected {n} bytes")
+                ),
+                Primitive::Char => format!(
+                    "{{ let mut chars = d.str()?.chars(); match (chars.next(), chars.next()) {{ (Some(c), None) => c, _ => {} }} }}",
+                    minicbor_decode_error(&format!("{name}: expected a single character"))
+                ),
+                _ => unreachable!("checked by minicbor_manual_conceptual_type_supported()"),
+            },
+        },
+        ConceptualRustType::Rust(ident) => format!("d.decode_with::<C, {ident}>(ctx)?"),
+        ConceptualRustType::Array(elem) => {
+            let (len, elems) = (format!("len{depth}"), format!("elems{depth}"));
+            format!(
+                "{{ let {len} = d.array()?; let mut {elems} = Vec::new(); while {} {{ {elems}.push({}); }} if {len}.is_none() {{ d.skip()?; }} {elems} }}",
+                more_elems(&len, &format!("({elems}.len() as u64)")),
+                minicbor_decode_value(types, elem, name, depth + 1, cli)
+            )
+        }
+        ConceptualRustType::Map(key, value) => {
+            let (len, table) = (format!("len{depth}"), format!("table{depth}"));
+            format!(
+                "{{ let {len} = d.map()?; let mut {table} = BTreeMap::new(); while {} {{ let key = {}; let value = {}; if {table}.insert(key, value).is_some() {{ {} }} }} if {len}.is_none() {{ d.skip()?; }} {table} }}",
+                more_elems(&len, &format!("({table}.len() as u64)")),
+                minicbor_decode_value(types, key, name, depth + 1, cli),
+                minicbor_decode_value(types, value, name, depth + 1, cli),
+                minicbor_decode_error(&format!("{name}: duplicate key"))
+            )
+        }
+        ConceptualRustType::Optional(inner) => format!(
+            "if d.datatype()? == minicbor::data::Type::Null {{ d.skip()?; None }} else {{ Some({}) }}",
+            minicbor_decode_value(types, inner, name, depth + 1, cli)
+        ),
+        ConceptualRustType::Alias(_, inner) => {
+            minicbor_decode_conceptual_value(types, inner, name, depth, cli)
+        }
+        ConceptualRustType::Result(_, _) => {
+            unreachable!("checked by minicbor_manual_conceptual_type_supported()")
+        }
+    }
+}
+
+/// minicbor::Encode/Decode for a record written with minicbor's Encoder/Decoder directly
+/// e.g. for text keys or tags that the derive attributes can't express.
+/// The encoding is the same as our cbor_event-based (de)serialization.
+fn make_minicbor_record_impls(
+    types: &IntermediateTypes,
+    name: &RustIdent,
+    tag: Option<u64>,
+    record: &RustRecord,
+    cli: &Cli,
+) -> (codegen::Impl, codegen::Impl) {
+    // only nested types are (de)serialized with the context
+    let uses_ctx = record.fields.iter().any(|field| {
+        let mut nested = false;
+        field
+            .rust_type
+            .conceptual_type
+            .visit_types(types, &mut |ty| {
+                nested |= matches!(ty, ConceptualRustType::Rust(_));
+            });
+        nested
+    });
+    let ctx_arg = if uses_ctx { "ctx" } else { "_ctx" };
+    let mut encode = codegen::Impl::new(name);
+    let encode_fn = encode
+        .generic("C")
+        .impl_trait("minicbor::Encode<C>")
+        .new_fn("encode")
+        .generic("W: minicbor::encode::Write")
+        .arg_ref_self()
+        .arg("e", "&mut minicbor::Encoder<W>")
+        .arg(ctx_arg, "&mut C")
+        .ret("Result<(), minicbor::encode::Error<W::Error>>");
+    if let Some(tag) = tag {
+        encode_fn.line(format!("e.tag(minicbor::data::Tag::new({tag}))?;"));
+    }
+    let len = record.definite_info("self", false, types, cli);
+    match record.rep {
+        Representation::Array => encode_fn.line(format!("e.array({len})?;")),
+        Representation::Map => encode_fn.line(format!("e.map({len})?;")),
+    };
+    for field in record.fields.iter() {
+        let field_expr = format!("&self.{}", field.name);
+        if field.rust_type.is_fixed_value() {
+            // optional fixed values aren't stored so like cbor_event we never write them
+            if !field.optional {
+                if let Some(key) = &field.key {
+                    minicbor_encode_fixed_value(encode_fn, key);
+                }
+                minicbor_encode_value(encode_fn, &field.rust_type, &field_expr, 0);
+            }
+        } else if field.tail {
+            let elem_type = match &field.rust_type.conceptual_type {
+                ConceptualRustType::Array(elem_type) => elem_type,
+                _ => unreachable!("array tail {} must be a Vec", field.name),
+            };
+            let mut tail_loop = Block::new(format!("for elem in self.{}.iter()", field.name));
+            minicbor_encode_value(&mut tail_loop, elem_type, "elem", 1);
+            encode_fn.push_block(tail_loop);
+        } else if field.optional {
+            let mut present = Block::new(format!("if let Some({0}) = &self.{0}", field.name));
+            if let Some(key) = &field.key {
+                minicbor_encode_fixed_value(&mut present, key);
+            }
+            minicbor_encode_value(&mut present, &field.rust_type, &field.name, 0);
+            encode_fn.push_block(present);
+        } else {
+            if let Some(key) = &field.key {
+                minicbor_encode_fixed_value(encode_fn, key);
+            }
+            minicbor_encode_value(encode_fn, &field.rust_type, &field_expr, 0);
+        }
+    }
+    encode_fn.line("Ok(())");
+
+    let mut decode = codegen::Impl::new(name);
+    let decode_fn = decode
+        .generic("'b")
+        .generic("C")
+        .impl_trait("minicbor::Decode<'b, C>")
+        .new_fn("decode")
+        .arg("d", "&mut minicbor::Decoder<'b>")
+        .arg(ctx_arg, "&mut C")
+        .ret("Result<Self, minicbor::decode::Error>");
+    if let Some(tag) = tag {
+        let mut tag_check = Block::new(format!("if d.tag()? != minicbor::data::Tag::new({tag})"));
+        tag_check.line(minicbor_decode_error(&format!(
+            "{name}: expected tag {tag}"
+        )));
+        decode_fn.push_block(tag_check);
+    }
+    let stored_fields = record
+        .fields
+        .iter()
+        .filter(|field| !field.rust_type.is_fixed_value())
+        .collect::<Vec<_>>();
+    match record.rep {
+        Representation::Array => {
+            decode_fn.line("let len = d.array()?;");
+            let mandatory = record
+                .fields
+                .iter()
+                .filter(|field| !field.optional && !field.tail)
+                .count();
+            let mut read = vec![mandatory.to_string()];
+            for field in record.fields.iter() {
+                let field_name = format!("{name}.{}", field.name);
+                if field.rust_type.is_fixed_value() {
+                    decode_fn.line(minicbor_decode_value(
+                        types,
+                        &field.rust_type,
+                        &field_name,
+                        0,
+                        cli,
+                    ));
+                } else if field.tail {
+                    let elem_type = match &field.rust_type.conceptual_type {
+                        ConceptualRustType::Array(elem_type) => elem_type,
+                        _ => unreachable!("array tail {} must be a Vec", field.name),
+                    };
+                    decode_fn.line(format!("let mut {} = Vec::new();", field.name));
+                    let mut tail_loop = Block::new(format!(
+                        "while match len {{ Some(len) => {mandatory} + ({}.len() as u64) < len, None => d.datatype()? != minicbor::data::Type::Break }}",
+                        field.name
+                    ));
+                    tail_loop.line(format!(
+                        "{}.push({});",
+                        field.name,
+                        minicbor_decode_value(types, elem_type, &field_name, 1, cli)
+                    ));
+                    decode_fn.push_block(tail_loop);
+                    if field.rust_type.config.non_empty {
+                        decode_fn.line(format!(
+                            "let {0} = NonEmptyVec::new({0}).map_err(|_| minicbor::decode::Error::message({1:?}))?;",
+                            field.name,
+                            format!("{field_name}: expected at least one element")
+                        ));
+                    }
+                    read.push(format!("{}.len() as u64", field.name));
+                } else if field.optional {
+                    // only allowed as the last field so it's there if anything is left
+                    decode_fn.line(format!(
+                        "let {} = if match len {{ Some(len) => len > {mandatory}, None => d.datatype()? != minicbor::data::Type::Break }} {{ Some({}) }} else {{ None }};",
+                        field.name,
+                        minicbor_decode_value(types, &field.rust_type, &field_name, 0, cli)
+                    ));
+                    read.push(format!("{}.is_some() as u64", field.name));
+                } else {
+                    decode_fn.line(format!(
+                        "let {} = {};",
+                        field.name,
+                        minicbor_decode_value(types, &field.rust_type, &field_name, 0, cli)
+                    ));
+                }
+            }
+            let mut len_check = Block::new("match len");
+            len_check.line(format!(
+                "Some(len) => if len != {} {{ {} }},",
+                read.join(" + "),
+                minicbor_decode_error(&format!("{name}: array length mismatch"))
+            ));
+            len_check.line(format!(
+                "None => {{ if d.datatype()? != minicbor::data::Type::Break {{ {} }} d.skip()?; }}",
+                minicbor_decode_error(&format!("{name}: expected the end of the array"))
+            ));
+            decode_fn.push_block(len_check);
+        }
+        Representation::Map => {
+            decode_fn.line("let len = d.map()?;");
+            for field in record.fields.iter() {
+                if field.rust_type.is_fixed_value() {
+                    decode_fn.line(format!("let mut {}_present = false;", field.name));
+                } else {
+                    decode_fn.line(format!("let mut {} = None;", field.name));
+                }
+            }
+            decode_fn.line("let mut read = 0;");
+            let mut key_loop = Block::new("while match len { Some(len) => read < len, None => d.datatype()? != minicbor::data::Type::Break }");
+            // one match per key type as each is read differently
+            let key_types: [(&str, &str, fn(&FixedValue) -> Option<String>); 3] = [
+                (
+                    "minicbor::data::Type::U8 | minicbor::data::Type::U16 | minicbor::data::Type::U32 | minicbor::data::Type::U64",
+                    "d.u64()?",
+                    |key| match key {
+                        FixedValue::Uint(u) => Some(u.to_string()),
+                        _ => None,
+                    },
+                ),
+                (
+                    "minicbor::data::Type::I8 | minicbor::data::Type::I16 | minicbor::data::Type::I32 | minicbor::data::Type::I64",
+                    "d.i64()?",
+                    |key| match key {
+                        FixedValue::Nint(i) => Some(i.to_string()),
+                        _ => None,
+                    },
+                ),
+                (
+                    "minicbor::data::Type::String",
+                    "d.str()?",
+                    |key| match key {
+                        FixedValue::Text(s) => Some(format!("{s:?}")),
+                        _ => None,
+                    },
+                ),
+            ];
+            let mut key_type_match = Block::new("match d.datatype()?");
+            for (datatype, read_key, key_pattern) in key_types {
+                let keyed_fields = record
+                    .fields
+                    .iter()
+                    .filter_map(|field| Some((field, field.key.as_ref().and_then(key_pattern)?)))
+                    .collect::<Vec<_>>();
+                if keyed_fields.is_empty() {
+                    continue;
+                }
+                let mut key_match = Block::new(format!("{datatype} => match {read_key}"));
+                for (field, pattern) in keyed_fields {
+                    let field_name = format!("{name}.{}", field.name);
+                    let mut key_arm = Block::new(format!("{pattern} =>"));
+                    let duplicate = minicbor_decode_error(&format!("{field_name}: duplicate key"));
+                    if field.rust_type.is_fixed_value() {
+                        key_arm.line(format!("if {}_present {{ {duplicate} }}", field.name));
+                        key_arm.line(minicbor_decode_value(
+                            types,
+                            &field.rust_type,
+                            &field_name,
+                            0,
+                            cli,
+                        ));
+                        key_arm.line(format!("{}_present = true;", field.name));
+                    } else {
+                        key_arm.line(format!("if {}.is_some() {{ {duplicate} }}", field.name));
+                        let value =
+                            minicbor_decode_value(types, &field.rust_type, &field_name, 0, cli);
+                        if field.collapse_null {
+                            // an explicit null is read as if the key were absent
+                            key_arm.line(format!(
+                                "if d.datatype()? == minicbor::data::Type::Null {{ d.skip()?; }} else {{ {} = Some({value}); }}",
+                                field.name
+                            ));
+                        } else {
+                            key_arm.line(format!("{} = Some({value});", field.name));
+                        }
+                    }
+                    key_match.push_block(key_arm);
+                }
+                key_match.line(format!(
+                    "_ => {},",
+                    minicbor_decode_error(&format!("{name}: unknown key"))
+                ));
+                key_match.after(",");
+                key_type_match.push_block(key_match);
+            }
+            key_type_match.line(format!(
+                "_ => {},",
+                minicbor_decode_error(&format!("{name}: unexpected key type"))
+            ));
+            key_loop.push_block(key_type_match);
+            key_loop.line("read += 1;");
+            decode_fn.push_block(key_loop);
+            let mut end_break = Block::new("if len.is_none()");
+            end_break.line("d.skip()?;");
+            decode_fn.push_block(end_break);
+            for field in record.fields.iter().filter(|field| !field.optional) {
+                let missing = format!(
+                    "minicbor::decode::Error::message({:?})",
+                    format!("{name}.{}: missing key", field.name)
+                );
+                if field.rust_type.is_fixed_value() {
+                    decode_fn.line(format!(
+                        "if !{}_present {{ return Err({missing}); }}",
+                        field.name
+                    ));
+                } else {
+                    decode_fn.line(format!(
+                        "let {0} = {0}.ok_or_else(|| {missing})?;",
+                        field.name
+                    ));
+                }
+            }
+        }
+    }
+    let mut ctor = Block::new("Ok(Self");
+    for field in stored_fields {
+        ctor.line(format!("{},", field.name));
+    }
+    ctor.after(")");
+    decode_fn.push_block(ctor);
+    (encode, decode)
+}
+
+fn minicbor_type_derivable(types: &IntermediateTypes, ty: &RustType) -> bool {
+    ty.encodings.is_empty()
+        && !ty.config.has_bounds()
+        && ty.config.default.is_none()
+        && minicbor_conceptual_type_derivable(types, &ty.conceptual_type)
+}
+
+fn minicbor_conceptual_type_derivable(types: &IntermediateTypes, ty: &ConceptualRustType) -> bool {
+    match ty {
+        // minicbor has no nint-only type nor our fixed 16-byte bignums. It also encodes char as
+        // an integer instead of a 1-char text string and the f32 holding a float16 as a float32
+        ConceptualRustType::Primitive(p) => !matches!(
+            p,
+            Primitive::N64 | Primitive::U128 | Primitive::I128 | Primitive::Char | Primitive::F16
+        ),
+        ConceptualRustType::Rust(ident) => {
+            !types.is_plain_group(ident)
+                && matches!(
+                    types
+                        .rust_struct(ident)
+                        .map(|rust_struct| rust_struct.variant()),
+                    Some(
+                        RustStructType::Record(_)
+                            | RustStructType::TypeChoice { .. }
+                            | RustStructType::GroupChoice { .. }
+                            | RustStructType::Wrapper { .. }
+                            | RustStructType::CStyleEnum { .. }
+                    )
+                )
+        }
+        // Vec<Vec<u8>> would be encoded by minicbor as an array of arrays
+        ConceptualRustType::Array(inner) => {
+            !matches!(
+                inner.conceptual_type.resolve_alias_shallow(),
+                ConceptualRustType::Primitive(Primitive::Bytes | Primitive::ByteArray(_))
+            ) && minicbor_type_derivable(types, inner)
+        }
+        ConceptualRustType::Alias(_, inner) => minicbor_conceptual_type_derivable(types, inner),
+        ConceptualRustType::Fixed(_)
+        | ConceptualRustType::Optional(_)
+        | ConceptualRustType::Result(_, _)
+        | ConceptualRustType::Map(_, _) => false,
+    }
+}
+
+/// Whether a record's encoding can be expressed purely with minicbor's derive attributes.
+/// Map keys must be uints (minicbor uses u32 indices) and array fields are positional so
+/// optional ones are only allowed at the end where minicbor omits them when None.
+fn minicbor_derivable(types: &IntermediateTypes, tag: Option<u64>, record: &RustRecord) -> bool {
+    if tag.is_some() {
+        return false;
+    }
+    let mut seen_optional = false;
+    record.fields.iter().all(|field| {
+        let position_ok = match record.rep {
+            Representation::Map => {
+                matches!(field.key, Some(FixedValue::Uint(k)) if k <= u32::MAX as usize)
+            }
+            Representation::Array => !seen_optional || field.optional,
+        };
+        seen_optional |= field.optional;
+        position_ok
+            && !field.collapse_null
+            && !field.tail
+            && field.rule_metadata.custom_serialize.is_none()
+            && field.rule_metadata.custom_deserialize.is_none()
+            && minicbor_type_derivable(types, &field.rust_type)
+    })
+}
+
 fn make_encoding_struct(encoding_name: &str) -> codegen::Struct {
     let mut encoding_struct = codegen::Struct::new(encoding_name.to_string());
     encoding_struct
@@ -7515,7 +9914,7 @@ fn make_encoding_struct(encoding_name: &str) -> codegen::Struct {
     encoding_struct
 }
 
-fn generate_tag_check(deser_func: &mut dyn CodeBlock, ident: &RustIdent, tag: Option<usize>) {
+fn generate_tag_check(deser_func: &mut dyn CodeBlock, ident: &RustIdent, tag: Option<u64>) {
     if let Some(tag) = tag {
         deser_func.line(&format!(
             "let tag = raw.tag().map_err(|e| DeserializeError::from(e).annotate(\"{ident}\"))?;"
@@ -7533,10 +9932,13 @@ fn generate_wrapper_struct(
     type_name: &RustIdent,
     field_type: &RustType,
     min_max: Option<(Option<i128>, Option<i128>)>,
+    regex: Option<&RegexPattern>,
     struct_config: &RustStructConfig,
     cli: &Cli,
 ) {
-    if min_max.is_some() {
+    let float_bounds = field_type.config.float_bounds.as_ref();
+    let new_can_fail = min_max.is_some() || float_bounds.is_some() || regex.is_some();
+    if new_can_fail {
         assert!(types.can_new_fail(type_name));
     }
     if cli.wasm {
@@ -7549,6 +9951,7 @@ fn generate_wrapper_struct(
         if types.can_new_fail(type_name) {
             // you can't use Self in a parameter in wasm_bindgen for some reason
             wasm_new
+                .attr(MUST_USE_VALIDATION)
                 .ret("Result<{}, JsError>")
                 // TODO: test
                 .line("inner.try_into().map(Into::into).map_err(Into::into)");
@@ -7586,7 +9989,7 @@ fn generate_wrapper_struct(
     let mut json_schema_impl = codegen::Impl::new(type_name);
     let json_hex_bytes = matches!(
         field_type.resolve_alias_shallow(),
-        ConceptualRustType::Primitive(Primitive::Bytes)
+        ConceptualRustType::Primitive(Primitive::Bytes | Primitive::ByteArray(_))
     );
     let json_schema_type = if json_hex_bytes {
         Cow::Borrowed("String")
@@ -7618,7 +10021,16 @@ fn generate_wrapper_struct(
                 serde_deser_fn
                     .line("let s = <String as serde::de::Deserialize>::deserialize(deserializer)?;")
                     .line("hex::decode(&s)");
-                if types.can_new_fail(type_name) {
+                if let ConceptualRustType::Primitive(Primitive::ByteArray(n)) =
+                    field_type.resolve_alias_shallow()
+                {
+                    serde_deser_fn
+                        .line(format!(
+                            ".ok().and_then(|bytes| <[u8; {n}]>::try_from(bytes).ok())"
+                        ))
+                        .line(format!(".map({type_name}::new)"))
+                        .line(format!(".ok_or_else(|| {err_body})"));
+                } else if types.can_new_fail(type_name) {
                     serde_deser_fn
                         .line(format!(
                             ".ok().and_then(|bytes| {type_name}::new(bytes).ok())"
@@ -7642,15 +10054,17 @@ fn generate_wrapper_struct(
                             FixedValue::Float(_) => "Float(inner)",
                             FixedValue::Nint(_) => "Signed(inner as i64)",
                             FixedValue::Null => "Option",
+                            FixedValue::SimpleValue(_) => "Other(\"simple value\")",
                             FixedValue::Text(_) => "Str(&inner)",
                             FixedValue::Uint(_) => "Unsigned(inner)",
                         },
                         ConceptualRustType::Map(_, _) => "Map",
                         ConceptualRustType::Optional(_) => "Option",
+                        ConceptualRustType::Result(_, _) => "Enum",
                         ConceptualRustType::Primitive(p) => match p {
                             Primitive::Bool => "Bool(inner)",
-                            Primitive::Bytes => "Bytes(&inner)",
-                            Primitive::F32 => "Float(inner as f64)",
+                            Primitive::Bytes | Primitive::ByteArray(_) => "Bytes(&inner)",
+                            Primitive::F16 | Primitive::F32 => "Float(inner as f64)",
                             Primitive::F64 => "Float(inner)",
                             Primitive::I8
                             | Primitive::I16
@@ -7658,10 +10072,12 @@ fn generate_wrapper_struct(
                             | Primitive::I64
                             | Primitive::N64 => "Signed(inner as i64)",
                             Primitive::Str => "Str(&inner)",
+                            Primitive::Char => "Char(inner)",
                             Primitive::U8 | Primitive::U16 | Primitive::U32 => {
                                 "Unsigned(inner as u64)"
                             }
                             Primitive::U64 => "Unsigned(inner)",
+                            Primitive::U128 | Primitive::I128 => "Other(\"128-bit integer\")",
                         },
                         ConceptualRustType::Rust(_) => "StructVariant",
                     };
@@ -7703,7 +10119,10 @@ fn generate_wrapper_struct(
                 .push_fn(is_referenceable);
         }
     }
-    s.vis("pub");
+    let vis = struct_config.visibility_token(cli);
+    if let Some(vis) = vis {
+        s.vis(vis);
+    }
     let encoding_name = RustIdent::new(CDDLIdent::new(format!("{type_name}Encoding")));
     let enc_fields = if cli.preserve_encodings {
         s.field("pub inner", field_type.for_rust_member(types, false, cli));
@@ -7745,7 +10164,10 @@ fn generate_wrapper_struct(
     }
     if let Some(Some(getter)) = struct_config.newtype_getter.as_ref() {
         let mut get = codegen::Function::new(getter);
-        get.vis("pub").arg_ref_self();
+        get.arg_ref_self();
+        if let Some(vis) = vis {
+            get.vis(vis);
+        }
         if field_type.is_copy(types) {
             get.ret(field_type.for_rust_member(types, false, cli))
                 .line(field_type.clone_if_not_copy(types, self_var));
@@ -7779,15 +10201,16 @@ fn generate_wrapper_struct(
         }
     }
     let mut new_func = codegen::Function::new("new");
-    new_func
-        .arg("inner", field_type.for_rust_move(types, cli))
-        .vis("pub");
+    new_func.arg("inner", field_type.for_rust_move(types, cli));
+    if let Some(vis) = vis {
+        new_func.vis(vis);
+    }
     let var_names_str = if cli.preserve_encodings {
         encoding_var_names_str(types, "inner", field_type, cli)
     } else {
         "inner".to_owned()
     };
-    let from_impl = if let Some((min, max)) = min_max {
+    if new_can_fail {
         let (before, after) = if var_names_str.is_empty() {
             ("".to_owned(), "")
         } else {
@@ -7802,17 +10225,29 @@ fn generate_wrapper_struct(
                 cli,
             )
             .add_to(&mut deser_func);
-
+        new_func
+            .attr(MUST_USE_VALIDATION)
+            .ret("Result<Self, DeserializeError>");
+    }
+    if let Some(float_bounds) = float_bounds {
+        // deserialization already checks these as part of reading the float
+        new_func.line(float_bounds_check_if_block(float_bounds, "inner", true));
+    }
+    if let Some((min, max)) = min_max {
         let against = if field_type
             .encodings
-            .contains(&CBOREncodingOperation::CBORBytes)
+            .iter()
+            .any(|enc| matches!(enc, CBOREncodingOperation::CBORBytes(_)))
         {
-            "inner.len()"
+            "inner.len()".to_owned()
         } else {
             match &field_type.conceptual_type {
                 ConceptualRustType::Primitive(p) => match p {
-                    Primitive::Bytes | Primitive::Str => "inner.len()",
+                    Primitive::Bytes | Primitive::ByteArray(_) => "inner.len()".to_owned(),
+                    Primitive::Str => str_size_expr("inner", cli),
+                    Primitive::Char => "1".to_owned(),
                     Primitive::Bool
+                    | Primitive::F16
                     | Primitive::F32
                     | Primitive::F64
                     | Primitive::U8
@@ -7823,7 +10258,9 @@ fn generate_wrapper_struct(
                     | Primitive::I16
                     | Primitive::I32
                     | Primitive::I64
-                    | Primitive::N64 => "inner",
+                    | Primitive::U128
+                    | Primitive::I128
+                    | Primitive::N64 => "inner".to_owned(),
                 },
                 _ => unimplemented!(),
             }
@@ -7835,17 +10272,22 @@ fn generate_wrapper_struct(
                 let non_negative = field_type.encodings.is_empty() && match &field_type.conceptual_type {
                     ConceptualRustType::Primitive(p) => match p {
                         Primitive::Bytes |
-                        Primitive::Str => true,
+                        Primitive::ByteArray(_) |
+                        Primitive::Str |
+                        Primitive::Char => true,
                         Primitive::Bool |
                         Primitive::U8 |
                         Primitive::U16 |
                         Primitive::U32 |
-                        Primitive::U64 => true,
+                        Primitive::U64 |
+                        Primitive::U128 => true,
                         Primitive::I8 |
                         Primitive::I16 |
                         Primitive::I32 |
                         Primitive::I64 |
+                        Primitive::I128 |
                         Primitive::N64 |
+                        Primitive::F16 |
                         Primitive::F32 |
                         Primitive::F64 => false,
                     },
@@ -7874,9 +10316,37 @@ fn generate_wrapper_struct(
                 None => String::from("None")
             }));
         deser_func.push_block(check.clone());
-        new_func
-            .ret("Result<Self, DeserializeError>")
-            .push_block(check);
+        new_func.push_block(check);
+    }
+    if let Some(regex) = regex {
+        let engine_crate = match regex.engine {
+            RegexEngine::Regex => "regex",
+            RegexEngine::FancyRegex => "fancy_regex",
+        };
+        let regex_static = format!(
+            "static REGEX: std::sync::OnceLock<{engine_crate}::Regex> = std::sync::OnceLock::new();"
+        );
+        let is_match = format!(
+            "REGEX.get_or_init(|| {engine_crate}::Regex::new({:?}).unwrap()).is_match(&inner)",
+            regex.engine_pattern()
+        );
+        let mut check = match regex.engine {
+            RegexEngine::Regex => Block::new(format!("if !{is_match}")),
+            // only errors when backtracking hits its limit
+            RegexEngine::FancyRegex => Block::new(format!("if !{is_match}.unwrap_or(false)")),
+        };
+        check.line(format!(
+            "return Err(DeserializeError::new(\"{}\", DeserializeFailure::RegexMismatch{{ found: inner, pattern: String::from({:?}) }}));",
+            type_name, regex.pattern
+        ));
+        deser_func.line(&regex_static).push_block(check.clone());
+        new_func.line(regex_static).push_block(check);
+        s.doc(&format!(
+            "Must match the {} pattern `{}`",
+            regex.flavor, regex.pattern
+        ));
+    }
+    let from_impl = if new_can_fail {
         if let Some(enc_fields) = &enc_fields {
             let mut deser_ctor = Block::new("Ok(Self");
             deser_ctor.line("inner,");
@@ -8006,12 +10476,96 @@ fn generate_wrapper_struct(
         .arg("wrapper", type_name.to_string())
         .ret("Self")
         .line(format!("wrapper.{inner_var}"));
+    let ops_mod = if cli.no_std { "core::ops" } else { "std::ops" };
+    let mut deref_impl = codegen::Impl::new(type_name);
+    deref_impl
+        .impl_trait(format!("{ops_mod}::Deref"))
+        .associate_type("Target", field_type.for_rust_member(types, false, cli))
+        .new_fn("deref")
+        .arg_ref_self()
+        .ret("&Self::Target")
+        .line(format!("&{self_var}"));
     gen_scope
         .rust(types, type_name)
         .push_struct(s)
         .push_impl(s_impl)
         .push_impl(from_impl)
-        .push_impl(from_inner_impl);
+        .push_impl(from_inner_impl)
+        .push_impl(deref_impl);
+    // mutable access would bypass the checks in new()
+    if !new_can_fail {
+        let mut deref_mut_impl = codegen::Impl::new(type_name);
+        deref_mut_impl
+            .impl_trait(format!("{ops_mod}::DerefMut"))
+            .new_fn("deref_mut")
+            .arg_mut_self()
+            .ret("&mut Self::Target")
+            .line(format!("&mut {self_var}"));
+        gen_scope.rust(types, type_name).push_impl(deref_mut_impl);
+    }
+    // bytes are usually shown as hex in logs/UIs
+    if json_hex_bytes && !struct_config.no_hex_display {
+        let fmt_mod = if cli.no_std { "core::fmt" } else { "std::fmt" };
+        for (fmt_trait, hex_format) in [("LowerHex", "{:02x}"), ("UpperHex", "{:02X}")] {
+            let mut hex_impl = codegen::Impl::new(type_name);
+            let mut write_bytes = Block::new(format!("for byte in {self_var}.iter()"));
+            write_bytes.line(format!("write!(f, \"{hex_format}\", byte)?;"));
+            hex_impl
+                .impl_trait(format!("{fmt_mod}::{fmt_trait}"))
+                .new_fn("fmt")
+                .arg_ref_self()
+                .arg("f", format!("&mut {fmt_mod}::Formatter<'_>"))
+                .ret(format!("{fmt_mod}::Result"))
+                .push_block(write_bytes)
+                .line("Ok(())");
+            gen_scope.rust(types, type_name).push_impl(hex_impl);
+        }
+        let mut display_impl = codegen::Impl::new(type_name);
+        display_impl
+            .impl_trait(format!("{fmt_mod}::Display"))
+            .new_fn("fmt")
+            .arg_ref_self()
+            .arg("f", format!("&mut {fmt_mod}::Formatter<'_>"))
+            .ret(format!("{fmt_mod}::Result"))
+            .line(format!("{fmt_mod}::LowerHex::fmt(self, f)"));
+        gen_scope.rust(types, type_name).push_impl(display_impl);
+    }
+    // iter()/keys()/values() come from Deref but for loops need IntoIterator
+    if matches!(
+        field_type.resolve_alias_shallow(),
+        ConceptualRustType::Map(_, _) | ConceptualRustType::Array(_)
+    ) {
+        let inner_type = field_type.for_rust_member(types, false, cli);
+        let mut into_iter_impl = codegen::Impl::new(type_name);
+        into_iter_impl
+            .impl_trait("IntoIterator")
+            .associate_type("Item", format!("<{inner_type} as IntoIterator>::Item"))
+            .associate_type(
+                "IntoIter",
+                format!("<{inner_type} as IntoIterator>::IntoIter"),
+            )
+            .new_fn("into_iter")
+            .arg_self()
+            .ret("Self::IntoIter")
+            .line(format!("{self_var}.into_iter()"));
+        let mut into_iter_ref_impl = codegen::Impl::new(format!("&'a {type_name}"));
+        into_iter_ref_impl
+            .generic("'a")
+            .impl_trait("IntoIterator")
+            .associate_type("Item", format!("<&'a {inner_type} as IntoIterator>::Item"))
+            .associate_type(
+                "IntoIter",
+                format!("<&'a {inner_type} as IntoIterator>::IntoIter"),
+            )
+            .new_fn("into_iter")
+            .arg_self()
+            .ret("Self::IntoIter")
+            .line(format!("{self_var}.iter()"));
+        gen_scope
+            .rust(types, type_name)
+            .push_impl(into_iter_impl)
+            .push_impl(into_iter_ref_impl);
+    }
     if !struct_config.custom_json {
         if cli.json_serde_derives {
             gen_scope
@@ -8048,6 +10602,8 @@ fn key_derives(for_ignore: bool, cli: &Cli) -> &'static [&'static str] {
 fn add_struct_derives<T: DataType>(
     data_type: &mut T,
     used_in_key: bool,
+    hashable: bool,
+    eq: bool,
     is_enum: bool,
     custom_json: bool,
     cli: &Cli,
@@ -8086,6 +10642,45 @@ fn add_struct_derives<T: DataType>(
             }
         }
     }
+    // the key derives above already include these
+    if eq && !used_in_key {
+        data_type.derive("PartialEq").derive("Eq");
+    }
+    // with preserve-encodings the key derives above already include Hash
+    if hashable && !(used_in_key && cli.preserve_encodings) {
+        data_type.derive("Hash");
+    }
+}
+
+/// Expression comparing a and b for the manual PartialEq of records with float fields.
+/// Floats are compared by their bits so that e.g. NaN == NaN which also lets them implement Eq.
+fn eq_expr(ty: &ConceptualRustType, a: &str, b: &str) -> String {
+    if !ty.contains_float() {
+        return format!("{a} == {b}");
+    }
+    match ty {
+        ConceptualRustType::Primitive(_) => format!("{a}.to_bits() == {b}.to_bits()"),
+        ConceptualRustType::Optional(inner) => format!(
+            "match (&{a}, &{b}) {{ (Some(a), Some(b)) => {}, (None, None) => true, _ => false }}",
+            eq_expr(&inner.conceptual_type, "a", "b")
+        ),
+        ConceptualRustType::Result(ok, err) => format!(
+            "match (&{a}, &{b}) {{ (Ok(a), Ok(b)) => {}, (Err(a), Err(b)) => {}, _ => false }}",
+            eq_expr(&ok.conceptual_type, "a", "b"),
+            eq_expr(&err.conceptual_type, "a", "b")
+        ),
+        ConceptualRustType::Array(inner) => format!(
+            "{a}.len() == {b}.len() && {a}.iter().zip({b}.iter()).all(|(a, b)| {})",
+            eq_expr(&inner.conceptual_type, "a", "b")
+        ),
+        // maps are compared in iteration order same as their derived PartialEq
+        ConceptualRustType::Map(_k, v) => format!(
+            "{a}.len() == {b}.len() && {a}.iter().zip({b}.iter()).all(|((a_key, a), (b_key, b))| a_key == b_key && {})",
+            eq_expr(&v.conceptual_type, "a", "b")
+        ),
+        ConceptualRustType::Alias(_ident, inner) => eq_expr(inner, a, b),
+        ConceptualRustType::Fixed(_) | ConceptualRustType::Rust(_) => unreachable!(),
+    }
 }
 
 fn generate_int(gen_scope: &mut GenerationScope, types: &IntermediateTypes, cli: &Cli) {
@@ -8162,6 +10757,8 @@ fn generate_int(gen_scope: &mut GenerationScope, types: &IntermediateTypes, cli:
     add_struct_derives(
         &mut native_struct,
         types.used_as_key(&ident),
+        false,
+        types.is_eq(&ident, cli),
         true,
         true,
         cli,
@@ -8304,6 +10901,60 @@ fn generate_int(gen_scope: &mut GenerationScope, types: &IntermediateTypes, cli:
         .push_impl(deser_impl);
 }
 
+/// Placeholder for rules skipped by --warn-unsupported.
+/// Deserializes any CBOR item and keeps its encoded bytes, which are written back as-is.
+fn generate_raw_cbor(gen_scope: &mut GenerationScope, types: &IntermediateTypes, cli: &Cli) {
+    let ident = RustIdent::new(CDDLIdent::new("raw_cbor"));
+    if cli.wasm {
+        let wrapper = create_base_wasm_wrapper(gen_scope, types, &ident, true, cli);
+        wrapper.push(gen_scope, types);
+    }
+
+    let mut native_struct = codegen::Struct::new("RawCbor");
+    native_struct
+        .vis("pub")
+        .doc("Any CBOR item, kept as its encoded bytes")
+        .tuple_field("pub Vec<u8>");
+    add_struct_derives(
+        &mut native_struct,
+        types.used_as_key(&ident),
+        false,
+        true,
+        false,
+        false,
+        cli,
+    );
+
+    // serialization
+    let mut ser_impl = make_serialization_impl("RawCbor", cli);
+    let mut ser_func = make_serialization_function("serialize", cli);
+    ser_func.line("serializer.write_raw_bytes(&self.0)");
+    ser_impl.push_fn(ser_func);
+
+    // deserialization
+    let mut deser_impl = codegen::Impl::new("RawCbor");
+    deser_impl.impl_trait("Deserialize");
+    let mut deser_func = make_deserialization_function("deserialize");
+    let mut annotate = make_err_annotate_block("RawCbor", "", "");
+    annotate
+        .line("let initial_position = raw.as_mut_ref().stream_position().unwrap();")
+        .line("// read the whole item to find where it ends")
+        .line("<cbor_event::Value as cbor_event::de::Deserialize>::deserialize(raw)?;")
+        .line("let end_position = raw.as_mut_ref().stream_position().unwrap();")
+        .line("raw.as_mut_ref().seek(SeekFrom::Start(initial_position)).unwrap();")
+        .line("let mut bytes = vec![0; (end_position - initial_position) as usize];")
+        .line("std::io::Read::read_exact(raw.as_mut_ref(), &mut bytes).unwrap();")
+        .line("Ok(Self(bytes))");
+    deser_func.push_block(annotate);
+    deser_impl.push_fn(deser_func);
+
+    gen_scope.rust_lib().push_struct(native_struct);
+    gen_scope
+        .rust_serialize_lib()
+        .push_impl(ser_impl)
+        .push_impl(deser_impl);
+}
+
 /// Gets the rustfmt path to rustfmt the generated bindings.
 fn rustfmt_path<'a>() -> std::io::Result<Cow<'a, std::path::PathBuf>> {
     if let Ok(rustfmt) = std::env::var("RUSTFMT") {
@@ -90,9 +90,79 @@ impl AliasInfo {
     }
 }
 
+/// An alias chain (e.g. `a = b`, `b = c`, ...) was longer than --max-alias-depth
+/// which is almost always caused by the aliases referring to each other in a cycle.
+#[derive(Debug)]
+pub struct AliasDepthExceeded {
+    /// Every alias followed, starting from the one whose resolution failed
+    pub chain: Vec<AliasIdent>,
+}
+
+impl std::fmt::Display for AliasDepthExceeded {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "alias chain exceeded max depth of {}: ",
+            self.chain.len() - 1
+        )?;
+        for (i, alias) in self.chain.iter().enumerate() {
+            if i > 0 {
+                write!(f, " -> ")?;
+            }
+            write!(f, "{alias}")?;
+        }
+        Ok(())
+    }
+}
+
+impl std::error::Error for AliasDepthExceeded {}
+
+/// A generic definition instantiated with the wrong number of arguments
+/// e.g. `bar = foo<uint, text>` for `foo<T> = [T]`
+#[derive(Debug)]
+pub struct ArityMismatchError {
+    /// Number of generic parameters of the definition
+    pub expected: usize,
+    /// Number of generic arguments given to it
+    pub actual: usize,
+    pub def_name: RustIdent,
+}
+
+impl std::fmt::Display for ArityMismatchError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{} takes {} generic argument(s) but {} were given",
+            self.def_name, self.expected, self.actual
+        )
+    }
+}
+
+impl std::error::Error for ArityMismatchError {}
+
+/// A reference to a type that was never registered e.g. the field type of a struct
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct UnresolvedRef {
+    /// The struct or alias containing the reference
+    pub referencing_type: RustIdent,
+    pub unresolved_ident: RustIdent,
+}
+
+impl std::fmt::Display for UnresolvedRef {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{} references undefined type {}",
+            self.referencing_type, self.unresolved_ident
+        )
+    }
+}
+
 #[derive(Debug, Clone)]
 pub struct PlainGroupInfo<'a> {
     group: Option<cddl::ast::Group<'a>>,
+    // groups from `//=` rules whose fields get appended once the base group is parsed
+    extensions: Vec<cddl::ast::Group<'a>>,
     rule_metadata: RuleMetadata,
 }
 
@@ -100,11 +170,107 @@ impl<'a> PlainGroupInfo<'a> {
     pub fn new(group: Option<cddl::ast::Group<'a>>, rule_metadata: RuleMetadata) -> Self {
         Self {
             group,
+            extensions: Vec::new(),
             rule_metadata,
         }
     }
 }
 
+/// A `$foo` type socket. Every `$foo = ...` / `$foo /= ...` rule adds its choices here
+/// and the socket is then parsed as if it were one rule with all of those choices.
+#[derive(Debug, Clone, Default)]
+pub struct TypeSocket<'a> {
+    choices: Vec<cddl::ast::TypeChoice<'a>>,
+    // whether any `/=` rule added choices
+    extended: bool,
+}
+
+impl<'a> TypeSocket<'a> {
+    pub fn choices(&self) -> &[cddl::ast::TypeChoice<'a>] {
+        &self.choices
+    }
+
+    pub fn is_extended(&self) -> bool {
+        self.extended
+    }
+}
+
+/// type -> types it directly (i.e. not via Vec/map) contains
+type TypeGraph = BTreeMap<RustIdent, BTreeSet<RustIdent>>;
+
+/// Edges whose removal makes the graph acyclic. The DFS back edges are enough to break every
+/// cycle, then any that turn out to be redundant (cycle already broken by another) are dropped.
+fn feedback_edges(graph: &TypeGraph) -> BTreeSet<(RustIdent, RustIdent)> {
+    fn visit<'a>(
+        graph: &'a TypeGraph,
+        u: &'a RustIdent,
+        visited: &mut BTreeSet<&'a RustIdent>,
+        on_stack: &mut BTreeSet<&'a RustIdent>,
+        back_edges: &mut Vec<(RustIdent, RustIdent)>,
+    ) {
+        visited.insert(u);
+        on_stack.insert(u);
+        for v in graph.get(u).into_iter().flatten() {
+            if on_stack.contains(v) {
+                back_edges.push((u.clone(), v.clone()));
+            } else if !visited.contains(v) {
+                visit(graph, v, visited, on_stack, back_edges);
+            }
+        }
+        on_stack.remove(u);
+    }
+    let mut visited = BTreeSet::new();
+    let mut on_stack = BTreeSet::new();
+    let mut back_edges = Vec::new();
+    for u in graph.keys() {
+        if !visited.contains(u) {
+            visit(graph, u, &mut visited, &mut on_stack, &mut back_edges);
+        }
+    }
+    let mut feedback = back_edges.iter().cloned().collect::<BTreeSet<_>>();
+    for edge in back_edges {
+        feedback.remove(&edge);
+        if !is_acyclic(graph, &feedback) {
+            feedback.insert(edge);
+        }
+    }
+    feedback
+}
+
+fn is_acyclic(graph: &TypeGraph, removed: &BTreeSet<(RustIdent, RustIdent)>) -> bool {
+    let is_removed = |u: &RustIdent, v: &RustIdent| removed.contains(&(u.clone(), v.clone()));
+    // Kahn's algorithm: everything gets visited iff there are no cycles
+    let mut in_degree = graph
+        .keys()
+        .map(|u| (u, 0usize))
+        .collect::<BTreeMap<&RustIdent, usize>>();
+    for (u, vs) in graph {
+        for v in vs.iter().filter(|v| !is_removed(u, *v)) {
+            *in_degree.entry(v).or_default() += 1;
+        }
+    }
+    let mut ready = in_degree
+        .iter()
+        .filter(|(_, degree)| **degree == 0)
+        .map(|(u, _)| *u)
+        .collect::<Vec<_>>();
+    let mut visited_count = 0;
+    while let Some(u) = ready.pop() {
+        visited_count += 1;
+        for v in graph.get(u).into_iter().flatten() {
+            if is_removed(u, v) {
+                continue;
+            }
+            let degree = in_degree.get_mut(v).unwrap();
+            *degree -= 1;
+            if *degree == 0 {
+                ready.push(v);
+            }
+        }
+    }
+    visited_count == in_degree.len()
+}
+
 #[derive(Debug)]
 pub struct IntermediateTypes<'a> {
     // Storing the cddl::Group is the easiest way to go here even after the parse/codegen split.
@@ -115,6 +281,8 @@ pub struct IntermediateTypes<'a> {
     // Some(group) = directly defined in .cddl (must call set_plain_group_representatio() later)
     // None = indirectly generated due to a group choice (no reason to call set_rep_if_plain_group() later but it won't crash)
     plain_groups: BTreeMap<RustIdent, PlainGroupInfo<'a>>,
+    // `$foo` sockets, collected up front since the extensions can come after any use of them
+    type_sockets: BTreeMap<RustIdent, TypeSocket<'a>>,
     type_aliases: BTreeMap<AliasIdent, AliasInfo>,
     rust_structs: BTreeMap<RustIdent, RustStruct>,
     prelude_to_emit: BTreeSet<String>,
@@ -122,6 +290,8 @@ pub struct IntermediateTypes<'a> {
     generic_instances: BTreeMap<RustIdent, GenericInstance>,
     news_can_fail: BTreeSet<RustIdent>,
     used_as_key: BTreeSet<RustIdent>,
+    // types referenced via Box<T> somewhere to break up recursion
+    boxed: BTreeSet<RustIdent>,
     // which scope an ident is declared in
     scopes: BTreeMap<RustIdent, ModuleScope>,
     // for scope() to work we keep this here.
@@ -144,6 +314,7 @@ impl<'a> IntermediateTypes<'a> {
         );
         Self {
             plain_groups: BTreeMap::new(),
+            type_sockets: BTreeMap::new(),
             type_aliases: Self::aliases(),
             rust_structs,
             prelude_to_emit: BTreeSet::new(),
@@ -151,6 +322,7 @@ impl<'a> IntermediateTypes<'a> {
             generic_instances: BTreeMap::new(),
             news_can_fail: BTreeSet::new(),
             used_as_key: BTreeSet::new(),
+            boxed: BTreeSet::new(),
             scopes: BTreeMap::new(),
             root_scope: ROOT_SCOPE.clone(),
         }
@@ -206,7 +378,8 @@ impl<'a> IntermediateTypes<'a> {
             rust_ident: &RustIdent,
         ) {
             let ref_scope = types.scope(rust_ident);
-            if current_scope != ref_scope {
+            // @rust_type paths are written out in full so they need no import
+            if current_scope != ref_scope && !rust_ident.as_ref().contains("::") {
                 refs.entry(current_scope.clone())
                     .or_default()
                     .entry(ref_scope.clone())
@@ -272,6 +445,10 @@ impl<'a> IntermediateTypes<'a> {
                 ConceptualRustType::Optional(inner_ty) => {
                     mark_refs(refs, types, wasm, current_scope, inner_ty)
                 }
+                ConceptualRustType::Result(ok, err) => {
+                    mark_refs(refs, types, wasm, current_scope, ok);
+                    mark_refs(refs, types, wasm, current_scope, err);
+                }
             }
         }
         for rust_struct in self.rust_structs().values() {
@@ -323,10 +500,6 @@ impl<'a> IntermediateTypes<'a> {
         };
         insert_alias("uint", ConceptualRustType::Primitive(Primitive::U64).into());
         insert_alias("nint", ConceptualRustType::Primitive(Primitive::N64).into());
-        insert_alias(
-            "bool",
-            ConceptualRustType::Primitive(Primitive::Bool).into(),
-        );
         // TODO: define enum or something as otherwise it can overflow i64
         // and also we can't define the serialization traits for types
         // that are defined outside of this crate (includes primitives)
@@ -345,14 +518,6 @@ impl<'a> IntermediateTypes<'a> {
         let null_type: RustType = ConceptualRustType::Fixed(FixedValue::Null).into();
         insert_alias("null", null_type.clone());
         insert_alias("nil", null_type);
-        insert_alias(
-            "true",
-            ConceptualRustType::Fixed(FixedValue::Bool(true)).into(),
-        );
-        insert_alias(
-            "false",
-            ConceptualRustType::Fixed(FixedValue::Bool(false)).into(),
-        );
         // Note: defaulting to float64 for "float" (so without precision).
         insert_alias(
             "float",
@@ -366,6 +531,10 @@ impl<'a> IntermediateTypes<'a> {
             "float32",
             ConceptualRustType::Primitive(Primitive::F32).into(),
         );
+        insert_alias(
+            "float16",
+            ConceptualRustType::Primitive(Primitive::F16).into(),
+        );
         // What about bingint/other stuff in the standard prelude?
         aliases
     }
@@ -374,6 +543,17 @@ impl<'a> IntermediateTypes<'a> {
     // are in the CDDL prelude so we don't generate code for all of them, potentially
     // bloating generated code a bit
     pub fn new_type(&mut self, raw: &CDDLIdent, cli: &Cli) -> RustType {
+        if let Some(narrowed) = narrowed_default_int(raw, cli.default_int_width) {
+            return ConceptualRustType::Primitive(narrowed).into();
+        }
+        // these can't be redefined so there's no need to look them up as aliases
+        match raw.to_string().as_str() {
+            "bool" => return ConceptualRustType::Primitive(Primitive::Bool).into(),
+            "true" => return ConceptualRustType::Fixed(FixedValue::Bool(true)).into(),
+            "false" => return ConceptualRustType::Fixed(FixedValue::Bool(false)).into(),
+            "any" => return self.raw_cbor_type(),
+            _ => (),
+        }
         let alias_ident = AliasIdent::new(raw.clone());
         let resolved = match self.apply_type_aliases(&alias_ident, cli) {
             Some((ty, true)) => ty,
@@ -452,6 +632,40 @@ impl<'a> IntermediateTypes<'a> {
         self.type_aliases.insert(alias.into(), info);
     }
 
+    /// Stand-in for a rule that code couldn't be generated for (see --warn-unsupported).
+    /// The rule becomes an alias to RawCbor which keeps the encoded CBOR item as-is.
+    pub fn register_raw_cbor_placeholder(&mut self, alias: RustIdent) {
+        let raw_cbor = self.raw_cbor_type();
+        self.register_type_alias(alias, AliasInfo::new_manual(raw_cbor, true, true));
+    }
+
+    /// The type of a field forced with @rust_type. Like extern types the user provides it
+    /// (and its serialization) so there's nothing to generate for it.
+    pub fn rust_type_override(&mut self, path: &str) -> RustType {
+        let ident = RustIdent::new_rust_path(path);
+        self.rust_structs
+            .entry(ident.clone())
+            .or_insert_with(|| RustStruct::new_extern(ident.clone()));
+        ConceptualRustType::Rust(ident).into()
+    }
+
+    /// RawCbor, which is only generated if something uses it. It keeps the encoded bytes
+    /// so that everything in it, including tags we know nothing about, is re-encoded exactly.
+    fn raw_cbor_type(&mut self) -> RustType {
+        let raw_cbor = RustIdent::new(CDDLIdent::new("raw_cbor"));
+        self.rust_structs
+            .entry(raw_cbor.clone())
+            .or_insert_with(|| RustStruct::new_extern(raw_cbor.clone()));
+        ConceptualRustType::Rust(raw_cbor).into()
+    }
+
+    /// Documents the type generated for a rule, if it is one that generates a RustStruct
+    pub fn set_doc_comment(&mut self, ident: &RustIdent, doc_comment: String) {
+        if let Some(rust_struct) = self.rust_structs.get_mut(ident) {
+            rust_struct.set_doc_comment(doc_comment);
+        }
+    }
+
     pub fn rust_struct(&self, ident: &RustIdent) -> Option<&RustStruct> {
         self.rust_structs.get(ident)
     }
@@ -473,7 +687,7 @@ impl<'a> IntermediateTypes<'a> {
         parent_visitor: &ParentVisitor,
         rust_struct: RustStruct,
         cli: &Cli,
-    ) {
+    ) -> Result<(), crate::parsing::CddlError> {
         match &rust_struct.variant {
             RustStructType::Table { domain, range } => {
                 // we must provide the keys type to return
@@ -482,7 +696,7 @@ impl<'a> IntermediateTypes<'a> {
                     domain.clone(),
                     &domain.conceptual_type.name_as_wasm_array(self),
                     cli,
-                );
+                )?;
                 let mut map_type: RustType =
                     ConceptualRustType::Map(Box::new(domain.clone()), Box::new(range.clone()))
                         .into();
@@ -507,13 +721,18 @@ impl<'a> IntermediateTypes<'a> {
             }
             RustStructType::Wrapper {
                 min_max: Some(_), ..
-            } => {
+            }
+            | RustStructType::Wrapper { regex: Some(_), .. } => {
+                self.mark_new_can_fail(rust_struct.ident.clone());
+            }
+            RustStructType::Wrapper { wrapped, .. } if wrapped.config.float_bounds.is_some() => {
                 self.mark_new_can_fail(rust_struct.ident.clone());
             }
             _ => (),
         }
         self.rust_structs
             .insert(rust_struct.ident().clone(), rust_struct);
+        Ok(())
     }
 
     // creates a RustType for the array type - and if needed, registers a type to generate
@@ -525,11 +744,11 @@ impl<'a> IntermediateTypes<'a> {
         element_type: RustType,
         array_type_name: &str,
         cli: &Cli,
-    ) -> RustType {
+    ) -> Result<RustType, crate::parsing::CddlError> {
         let raw_arr_type = ConceptualRustType::Array(Box::new(element_type.clone()));
         // only generate an array wrapper if we can't wasm-expose it raw
         if raw_arr_type.directly_wasm_exposable(self) {
-            return raw_arr_type.into();
+            return Ok(raw_arr_type.into());
         }
         let array_type_ident = RustIdent::new(CDDLIdent::new(array_type_name));
         // If we are the only thing referring to our element and it's a plain group
@@ -540,7 +759,7 @@ impl<'a> IntermediateTypes<'a> {
                 &array_type_ident,
                 Representation::Array,
                 cli,
-            );
+            )?;
         }
         if cli.wasm {
             // we don't pass in tags here. If a tag-wrapped array is done I think it generates
@@ -549,9 +768,27 @@ impl<'a> IntermediateTypes<'a> {
                 parent_visitor,
                 RustStruct::new_array(array_type_ident, None, None, element_type.clone()),
                 cli,
-            );
+            )?;
         }
-        ConceptualRustType::Array(Box::new(element_type)).into()
+        Ok(ConceptualRustType::Array(Box::new(element_type)).into())
+    }
+
+    /// All generic definitions e.g. foo<T> = [T], ordered by their identifier
+    pub fn list_generic_defs(&self) -> impl Iterator<Item = &GenericDef> {
+        self.generic_defs.values()
+    }
+
+    pub fn get_generic_def(&self, name: &RustIdent) -> Option<&GenericDef> {
+        self.generic_defs.get(name)
+    }
+
+    /// All instantiations of generic definitions e.g. bar = foo<uint>, ordered by the instance identifier
+    pub fn list_generic_instances(&self) -> impl Iterator<Item = &GenericInstance> {
+        self.generic_instances.values()
+    }
+
+    pub fn get_generic_instance(&self, ident: &RustIdent) -> Option<&GenericInstance> {
+        self.generic_instances.get(ident)
     }
 
     pub fn register_generic_def(&mut self, def: GenericDef) {
@@ -559,23 +796,152 @@ impl<'a> IntermediateTypes<'a> {
         self.generic_defs.insert(ident, def);
     }
 
-    pub fn register_generic_instance(&mut self, instance: GenericInstance) {
+    /// Identical instantiations e.g. foo = bar<uint> and a bar<uint> member elsewhere share
+    /// one generated type. Returns the identifier of that type, which is the first one registered.
+    /// Instances of extern (or not yet registered) definitions can't have their arity checked.
+    pub fn register_generic_instance(
+        &mut self,
+        instance: GenericInstance,
+    ) -> Result<RustIdent, ArityMismatchError> {
+        if let Some(def) = self.generic_defs.get(&instance.generic_ident) {
+            if instance.generic_args.len() != def.arity() {
+                return Err(ArityMismatchError {
+                    expected: def.arity(),
+                    actual: instance.generic_args.len(),
+                    def_name: def.ident().clone(),
+                });
+            }
+        }
+        if let Some(existing) = self.generic_instances.values().find(|existing| {
+            existing.generic_ident == instance.generic_ident
+                && existing.generic_args == instance.generic_args
+        }) {
+            return Ok(existing.instance_ident.clone());
+        }
         let ident = instance.instance_ident.clone();
-        self.generic_instances.insert(ident, instance);
+        self.generic_instances.insert(ident.clone(), instance);
+        Ok(ident)
+    }
+
+    /// Follows every alias chain to make sure it terminates within max_depth steps.
+    /// Aliases that (indirectly) refer to themselves would otherwise never resolve.
+    pub fn check_alias_depth(&self, max_depth: usize) -> Result<(), AliasDepthExceeded> {
+        let next_alias = |ty: &RustType| -> Option<AliasIdent> {
+            match &ty.conceptual_type {
+                ConceptualRustType::Alias(ident, _) => Some(ident.clone()),
+                ConceptualRustType::Rust(ident) => {
+                    let alias_ident = AliasIdent::Rust(ident.clone());
+                    if self.type_aliases.contains_key(&alias_ident) {
+                        Some(alias_ident)
+                    } else {
+                        None
+                    }
+                }
+                _ => None,
+            }
+        };
+        for start in self.type_aliases.keys() {
+            let mut chain = vec![start.clone()];
+            while let Some(next) = self
+                .type_aliases
+                .get(chain.last().unwrap())
+                .and_then(|alias| next_alias(&alias.base_type))
+            {
+                chain.push(next);
+                if chain.len() > max_depth {
+                    return Err(AliasDepthExceeded { chain });
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Every Rust / Alias reference that doesn't resolve to a registered type.
+    /// Call this after finalize() as that's when generic instances are registered.
+    pub fn validate_completeness(&self) -> Vec<UnresolvedRef> {
+        // only the references directly in the type. the referenced types are checked separately
+        fn unresolved_refs(
+            ty: &ConceptualRustType,
+            types: &IntermediateTypes<'_>,
+            found: &mut BTreeSet<RustIdent>,
+        ) {
+            match ty {
+                ConceptualRustType::Rust(ident) => {
+                    if !types.rust_structs.contains_key(ident)
+                        && !types
+                            .type_aliases
+                            .contains_key(&AliasIdent::Rust(ident.clone()))
+                    {
+                        found.insert(ident.clone());
+                    }
+                }
+                ConceptualRustType::Alias(alias_ident, ty) => {
+                    if let AliasIdent::Rust(ident) = alias_ident {
+                        if !types.type_aliases.contains_key(alias_ident) {
+                            found.insert(ident.clone());
+                        }
+                    }
+                    unresolved_refs(ty, types, found);
+                }
+                ConceptualRustType::Array(ty) | ConceptualRustType::Optional(ty) => {
+                    unresolved_refs(&ty.conceptual_type, types, found)
+                }
+                ConceptualRustType::Result(a, b) | ConceptualRustType::Map(a, b) => {
+                    unresolved_refs(&a.conceptual_type, types, found);
+                    unresolved_refs(&b.conceptual_type, types, found);
+                }
+                ConceptualRustType::Fixed(_) | ConceptualRustType::Primitive(_) => (),
+            }
+        }
+        let mut unresolved = Vec::new();
+        let mut check = |referencing_type: &RustIdent, tys: Vec<&RustType>| {
+            let mut found = BTreeSet::new();
+            for ty in tys {
+                unresolved_refs(&ty.conceptual_type, self, &mut found);
+            }
+            unresolved.extend(found.into_iter().map(|unresolved_ident| UnresolvedRef {
+                referencing_type: referencing_type.clone(),
+                unresolved_ident,
+            }));
+        };
+        for (ident, rust_struct) in self.rust_structs.iter() {
+            let mut tys = rust_struct.variant.inline_rust_types();
+            match &rust_struct.variant {
+                RustStructType::Array { element_type } => tys.push(element_type),
+                RustStructType::Table { domain, range } => tys.extend([domain, range]),
+                _ => (),
+            }
+            check(ident, tys);
+        }
+        for (alias_ident, alias) in self.type_aliases.iter() {
+            if let AliasIdent::Rust(ident) = alias_ident {
+                check(ident, vec![&alias.base_type]);
+            }
+        }
+        unresolved
     }
 
     // call this after all types have been registered
-    pub fn finalize(&mut self, parent_visitor: &ParentVisitor, cli: &Cli) {
+    pub fn finalize(
+        &mut self,
+        parent_visitor: &ParentVisitor,
+        cli: &Cli,
+    ) -> Result<(), crate::parsing::CddlError> {
+        self.check_alias_depth(cli.max_alias_depth)?;
         // resolve generics
         // resolve then register in 2 phases to get around borrow checker
-        let resolved_generics = self
-            .generic_instances
-            .values()
+        // nested instances e.g. bar<uint> in foo<bar<uint>> are registered before the ones using them
+        let mut instances = self.generic_instances.values().collect::<Vec<_>>();
+        instances.sort_by_key(|instance| instance.nesting_depth(self));
+        let resolved_generics = instances
+            .into_iter()
             .map(|instance| instance.resolve(self, cli))
-            .collect::<Vec<_>>();
+            .collect::<Result<Vec<_>, _>>()?;
         for resolved_instance in resolved_generics {
             match resolved_instance {
-                GenericResolved::Resolved(rs) => self.register_rust_struct(parent_visitor, rs, cli),
+                GenericResolved::Resolved(rs) => {
+                    self.register_rust_struct(parent_visitor, rs, cli)?
+                }
                 GenericResolved::Extern {
                     instance_ident,
                     real_ident,
@@ -585,7 +951,7 @@ impl<'a> IntermediateTypes<'a> {
                         parent_visitor,
                         RustStruct::new_extern(instance_ident.clone()),
                         cli,
-                    );
+                    )?;
                     // we do direct rust alias replacing (gen_rust_alias=false) since no problems with generics in rust
                     // but wasm_bindgen can't work with it directly we assume the user will supply the correct mappings
                     self.register_type_alias(
@@ -599,6 +965,7 @@ impl<'a> IntermediateTypes<'a> {
                 }
             }
         }
+        self.box_recursive_references();
         // recursively check all types used as keys or contained within a type used as a key
         // this is so we only derive comparison or hash traits for those types
         let mut used_as_key = BTreeSet::new();
@@ -636,6 +1003,73 @@ impl<'a> IntermediateTypes<'a> {
         for ident in used_as_key {
             self.mark_used_as_key(ident);
         }
+        Ok(())
+    }
+
+    // Boxes references to break up any cycles of inline (i.e. not behind a Vec/map) references
+    // as otherwise the rust struct/enum would be infinitely sized. Only the references along
+    // a minimal set of edges in the type graph are boxed so e.g. for A -> B -> A only one is.
+    fn box_recursive_references(&mut self) {
+        let inline_refs = self
+            .rust_structs
+            .iter()
+            .map(|(ident, rust_struct)| {
+                let mut refs = BTreeSet::new();
+                for ty in rust_struct.variant.inline_rust_types() {
+                    ty.conceptual_type.inline_rust_references(&mut refs);
+                }
+                (ident.clone(), refs)
+            })
+            .collect::<TypeGraph>();
+        let feedback = feedback_edges(&inline_refs);
+        fn box_feedback_edges(
+            ty: &mut RustType,
+            parent: &RustIdent,
+            feedback: &BTreeSet<(RustIdent, RustIdent)>,
+            boxed: &mut BTreeSet<RustIdent>,
+        ) {
+            match &mut ty.conceptual_type {
+                ConceptualRustType::Optional(inner) => {
+                    box_feedback_edges(inner, parent, feedback, boxed)
+                }
+                ConceptualRustType::Result(ok, err) => {
+                    box_feedback_edges(ok, parent, feedback, boxed);
+                    box_feedback_edges(err, parent, feedback, boxed);
+                }
+                conceptual_type => {
+                    if let ConceptualRustType::Rust(child) = conceptual_type.resolve_alias_shallow()
+                    {
+                        if feedback.contains(&(parent.clone(), child.clone())) {
+                            ty.config.boxed = true;
+                            boxed.insert(child.clone());
+                        }
+                    }
+                }
+            }
+        }
+        let mut boxed = BTreeSet::new();
+        for (ident, rust_struct) in self.rust_structs.iter_mut() {
+            for ty in rust_struct.variant.inline_rust_types_mut() {
+                box_feedback_edges(ty, ident, &feedback, &mut boxed);
+            }
+        }
+        self.boxed = boxed;
+    }
+
+    /// Whether this type is referenced via Box<T> anywhere due to being part of a recursive type
+    pub fn is_boxed(&self, ident: &RustIdent) -> bool {
+        self.boxed.contains(ident)
+    }
+
+    /// Whether any + T array tail is stored as a NonEmptyVec<T>
+    pub fn uses_non_empty_vec(&self) -> bool {
+        self.rust_structs.values().any(|rust_struct| {
+            rust_struct
+                .variant
+                .inline_rust_types()
+                .iter()
+                .any(|ty| ty.config.non_empty)
+        })
     }
 
     pub fn visit_types<F: FnMut(&ConceptualRustType)>(&self, f: &mut F) {
@@ -644,6 +1078,15 @@ impl<'a> IntermediateTypes<'a> {
         }
     }
 
+    /// Calls f for every RustStruct in identifier order. This doesn't change anything and is
+    /// meant for tooling that analyzes a parsed spec (docs, diagrams, other generators etc).
+    /// Each one's RustStruct::kind() and RustStruct::field_types() give an overview of it.
+    pub fn visit_rust_structs<F: FnMut(&RustStruct)>(&self, f: &mut F) {
+        for rust_struct in self.rust_structs().values() {
+            f(rust_struct);
+        }
+    }
+
     pub fn is_referenced(&self, ident: &RustIdent) -> bool {
         let mut found = false;
         self.visit_types(&mut |ty| {
@@ -661,6 +1104,81 @@ impl<'a> IntermediateTypes<'a> {
         self.plain_groups.insert(ident, group_info);
     }
 
+    // see self.plain_groups comments
+    // Extensions can't be parsed yet for the same reason as the base group (unknown representation)
+    // so they are stored alongside it and applied via extend_group() in set_rep_if_plain_group()
+    pub fn mark_plain_group_extension(
+        &mut self,
+        ident: &RustIdent,
+        group: cddl::ast::Group<'a>,
+    ) -> Result<(), crate::parsing::CddlError> {
+        match self.plain_groups.get_mut(ident) {
+            Some(PlainGroupInfo {
+                group: Some(_),
+                extensions,
+                ..
+            }) => {
+                extensions.push(group);
+                Ok(())
+            }
+            _ => Err(crate::parsing::CddlError::Unsupported(
+                crate::parsing::ErrorDetails::new(format!(
+                    "{}: group extension (//=) without a plain group definition",
+                    ident
+                )),
+            )),
+        }
+    }
+
+    /// Adds the choices of a `$foo = ...` or `$foo /= ...` rule to the socket `$foo`
+    pub fn mark_type_socket(
+        &mut self,
+        ident: RustIdent,
+        choices: &[cddl::ast::TypeChoice<'a>],
+        is_extension: bool,
+    ) {
+        let socket = self.type_sockets.entry(ident).or_default();
+        socket.choices.extend(choices.iter().cloned());
+        socket.extended |= is_extension;
+    }
+
+    pub fn type_socket(&self, ident: &RustIdent) -> Option<&TypeSocket<'a>> {
+        self.type_sockets.get(ident)
+    }
+
+    /// Appends fields to an already-registered record e.g. from a `//=` group extension
+    pub fn extend_group(
+        &mut self,
+        name: &RustIdent,
+        new_fields: Vec<RustField>,
+        span: cddl::ast::Span,
+    ) -> Result<(), crate::parsing::CddlError> {
+        match self.rust_structs.get_mut(name).map(|rs| &mut rs.variant) {
+            Some(RustStructType::Record(record)) => {
+                for field in new_fields {
+                    if record.fields.iter().any(|f| f.name == field.name) {
+                        return Err(crate::parsing::CddlError::Unsupported(
+                            crate::parsing::ErrorDetails::new(format!(
+                                "{}: field '{}' added via //= is already defined. Use @name to rename it",
+                                name, field.name
+                            ))
+                            .at(span),
+                        ));
+                    }
+                    record.fields.push(field);
+                }
+                Ok(())
+            }
+            _ => Err(crate::parsing::CddlError::Unsupported(
+                crate::parsing::ErrorDetails::new(format!(
+                    "{}: only groups with non-homogeneous fields can be extended via //=",
+                    name
+                ))
+                .at(span),
+            )),
+        }
+    }
+
     // see self.plain_groups comments
     pub fn set_rep_if_plain_group(
         &mut self,
@@ -668,7 +1186,7 @@ impl<'a> IntermediateTypes<'a> {
         ident: &RustIdent,
         rep: Representation,
         cli: &Cli,
-    ) {
+    ) -> Result<(), crate::parsing::CddlError> {
         if let Some(plain_group) = self.plain_groups.get(ident) {
             // the clone is to get around the borrow checker
             let plain_group = plain_group.clone();
@@ -682,7 +1200,14 @@ impl<'a> IntermediateTypes<'a> {
                         RustStructType::GroupChoice { rep, .. } => Some(*rep),
                         _ => None,
                     };
-                    assert_eq!(found_rep, Some(rep));
+                    if found_rep != Some(rep) {
+                        return Err(crate::parsing::CddlError::Unsupported(
+                            crate::parsing::ErrorDetails::new(format!(
+                                "{}: plain group used as both an array and a map",
+                                ident
+                            )),
+                        ));
+                    }
                 } else {
                     // you can't tag plain groups hence the None
                     // we also don't support generics in plain groups hence the other None
@@ -696,7 +1221,26 @@ impl<'a> IntermediateTypes<'a> {
                         None,
                         &plain_group.rule_metadata,
                         cli,
-                    );
+                    )?;
+                    for extension in plain_group.extensions.iter() {
+                        if extension.group_choices.len() != 1 {
+                            return Err(crate::parsing::CddlError::Unsupported(
+                                crate::parsing::ErrorDetails::new(format!(
+                                    "{}: group choices in //= extensions",
+                                    ident
+                                ))
+                                .at(extension.span),
+                            ));
+                        }
+                        let record = crate::parsing::parse_record_from_group_choice(
+                            self,
+                            rep,
+                            parent_visitor,
+                            extension.group_choices.first().unwrap(),
+                            cli,
+                        )?;
+                        self.extend_group(ident, record.fields, extension.span)?;
+                    }
                 }
             } else {
                 // If plain_group is None, then this wasn't defined in .cddl but instead
@@ -706,6 +1250,19 @@ impl<'a> IntermediateTypes<'a> {
                 assert!(self.rust_structs.contains_key(ident));
             }
         }
+        Ok(())
+    }
+
+    /// The group followed by any //= extensions of a plain group defined in the .cddl
+    pub fn plain_group_definition(&self, name: &RustIdent) -> Option<Vec<cddl::ast::Group<'a>>> {
+        self.plain_groups.get(name).and_then(|plain_group| {
+            plain_group.group.as_ref().map(|group| {
+                std::iter::once(group)
+                    .chain(plain_group.extensions.iter())
+                    .cloned()
+                    .collect()
+            })
+        })
     }
 
     pub fn is_plain_group(&self, name: &RustIdent) -> bool {
@@ -735,6 +1292,12 @@ impl<'a> IntermediateTypes<'a> {
         self.scopes.get(ident).unwrap_or(&self.root_scope)
     }
 
+    /// Whether a rule has this name. All rules are marked with their scope before any are
+    /// parsed, so unlike rust_struct() this is also true for ones that haven't been parsed yet.
+    pub fn is_rule(&self, ident: &RustIdent) -> bool {
+        self.scopes.contains_key(ident)
+    }
+
     // we need to do this for some generated intermediate structures as the parsing code
     // doesn't allow to just generate a rust struct but instead inserts everything needed
     pub fn remove_rust_struct(&mut self, ident: &RustIdent) -> Option<RustStruct> {
@@ -751,6 +1314,105 @@ impl<'a> IntermediateTypes<'a> {
         self.used_as_key.insert(name);
     }
 
+    /// Whether the generated rust type derives Hash. This is done for C-style enums
+    /// and for records whose fields all implement Hash (or that are tagged with @hash).
+    pub fn is_hashable(&self, name: &RustIdent, cli: &Cli) -> bool {
+        self.is_hashable_impl(name, cli, &mut BTreeSet::new())
+    }
+
+    fn is_hashable_impl(
+        &self,
+        name: &RustIdent,
+        cli: &Cli,
+        visited: &mut BTreeSet<RustIdent>,
+    ) -> bool {
+        if !visited.insert(name.clone()) {
+            // recursive references don't decide it, the rest of the fields do
+            return true;
+        }
+        match self.rust_struct(name) {
+            Some(rust_struct) => match rust_struct.variant() {
+                RustStructType::CStyleEnum { .. } => true,
+                // the encoding details structs don't implement Hash
+                RustStructType::Record(_) if cli.preserve_encodings => false,
+                RustStructType::Record(record) => {
+                    rust_struct.config().hash
+                        || record.fields.iter().all(|field| {
+                            field
+                                .rust_type
+                                .conceptual_type
+                                .is_hashable(self, cli, visited)
+                        })
+                }
+                // same as records, StringEncoding etc don't implement Hash
+                RustStructType::Wrapper { .. } if cli.preserve_encodings => false,
+                RustStructType::Wrapper { wrapped, .. } => {
+                    rust_struct.config().hash
+                        || wrapped.conceptual_type.is_hashable(self, cli, visited)
+                }
+                _ => false,
+            },
+            None => false,
+        }
+    }
+
+    /// Whether the generated rust type implements PartialEq and Eq. They are derived when
+    /// everything inside the type implements them too (or it's tagged with @eq), except for
+    /// records with float fields which get a manual impl comparing those floats by their bits.
+    pub fn is_eq(&self, name: &RustIdent, cli: &Cli) -> bool {
+        self.is_eq_impl(name, cli, &mut BTreeSet::new())
+    }
+
+    fn is_eq_impl(&self, name: &RustIdent, cli: &Cli, visited: &mut BTreeSet<RustIdent>) -> bool {
+        if !visited.insert(name.clone()) {
+            // recursive references don't decide it, the rest of the fields do
+            return true;
+        }
+        match self.rust_struct(name) {
+            Some(rust_struct) => match rust_struct.variant() {
+                // always derived along with Ord
+                RustStructType::CStyleEnum { .. } => true,
+                RustStructType::Extern => match name.as_ref() {
+                    "Int" => !cli.preserve_encodings,
+                    "RawCbor" => true,
+                    // user-provided so we can't know
+                    _ => false,
+                },
+                // the encoding details structs don't implement PartialEq
+                _ if cli.preserve_encodings => false,
+                RustStructType::Record(record) => {
+                    rust_struct.config().eq
+                        || record.fields.iter().all(|field| {
+                            field
+                                .rust_type
+                                .conceptual_type
+                                .is_eq(self, cli, visited, true)
+                        })
+                }
+                RustStructType::Wrapper { .. }
+                | RustStructType::TypeChoice { .. }
+                | RustStructType::GroupChoice { .. } => {
+                    rust_struct.config().eq
+                        || rust_struct
+                            .variant()
+                            .inline_rust_types()
+                            .iter()
+                            .all(|ty| ty.conceptual_type.is_eq(self, cli, visited, false))
+                }
+                // aliases to Vec / maps in rust
+                RustStructType::Array { element_type } => element_type
+                    .conceptual_type
+                    .is_eq(self, cli, visited, false),
+                RustStructType::Table { domain, range } => {
+                    domain.conceptual_type.is_eq(self, cli, visited, false)
+                        && range.conceptual_type.is_eq(self, cli, visited, false)
+                }
+                RustStructType::RawBytesType => false,
+            },
+            None => false,
+        }
+    }
+
     pub fn print_info(&self) {
         if !self.plain_groups.is_empty() {
             println!("\n\nPlain groups:");
@@ -803,7 +1465,8 @@ impl<'a> IntermediateTypes<'a> {
             let cddl = cddl::parser::cddl_from_str(&def, true).unwrap();
             assert_eq!(cddl.rules.len(), 1);
             let pv = ParentVisitor::new(&cddl).unwrap();
-            crate::parsing::parse_rule(self, &pv, cddl.rules.first().unwrap(), cli);
+            crate::parsing::parse_rule(self, &pv, cddl.rules.first().unwrap(), cli)
+                .expect("prelude rules should always be supported");
         }
     }
 }
@@ -814,6 +1477,19 @@ pub enum Representation {
     Map,
 }
 
+/// The primitive for a bare uint / int with --default-int-width other than 64
+fn narrowed_default_int(raw: &CDDLIdent, width: IntWidth) -> Option<Primitive> {
+    match (raw.to_string().as_str(), width) {
+        ("uint", IntWidth::W8) => Some(Primitive::U8),
+        ("uint", IntWidth::W16) => Some(Primitive::U16),
+        ("uint", IntWidth::W32) => Some(Primitive::U32),
+        ("int", IntWidth::W8) => Some(Primitive::I8),
+        ("int", IntWidth::W16) => Some(Primitive::I16),
+        ("int", IntWidth::W32) => Some(Primitive::I32),
+        _ => None,
+    }
+}
+
 #[derive(Clone, Debug, PartialEq)]
 pub enum FixedValue {
     Null,
@@ -822,9 +1498,16 @@ pub enum FixedValue {
     Uint(usize),
     Float(f64),
     Text(String),
+    // #7.N simple values other than false/true/null
+    SimpleValue(u8),
     // UTF byte types not supported
 }
 
+/// variant names for small uint constants, larger ones are UintN (e.g. Uint24)
+const SMALL_UINT_VARIANT_NAMES: [&str; 11] = [
+    "Zero", "One", "Two", "Three", "Four", "Five", "Six", "Seven", "Eight", "Nine", "Ten",
+];
+
 fn convert_to_alphanumeric(input: &str) -> String {
     input
         .chars()
@@ -840,12 +1523,18 @@ impl FixedValue {
                 true => "True",
                 false => "False",
             }),
-            FixedValue::Nint(i) => VariantIdent::new_custom(format!("U{i}")),
-            FixedValue::Uint(u) => VariantIdent::new_custom(format!("I{u}")),
+            FixedValue::Nint(i) => VariantIdent::new_custom(format!("Neg{}", i.unsigned_abs())),
+            FixedValue::Uint(u) => {
+                VariantIdent::new_custom(match SMALL_UINT_VARIANT_NAMES.get(*u) {
+                    Some(small) => small.to_string(),
+                    None => format!("Uint{u}"),
+                })
+            }
             FixedValue::Float(f) => VariantIdent::new_custom(format!("F{f}")),
             FixedValue::Text(s) => {
                 VariantIdent::new_custom(convert_to_alphanumeric(&convert_to_camel_case(s)))
             }
+            FixedValue::SimpleValue(n) => VariantIdent::new_custom(format!("Simple{n}")),
         }
     }
 
@@ -858,6 +1547,7 @@ impl FixedValue {
             FixedValue::Uint(u) => buf.write_unsigned_integer(*u as u64),
             FixedValue::Float(f) => buf.write_special(Special::Float(*f)),
             FixedValue::Text(s) => buf.write_text(s),
+            FixedValue::SimpleValue(n) => buf.write_special(Special::Unassigned(*n)),
         }
         .expect("Unable to serialize key for canonical ordering");
         buf.finalize()
@@ -873,6 +1563,22 @@ impl FixedValue {
             FixedValue::Uint(u) => u.to_string(),
             FixedValue::Float(f) => f.to_string(),
             FixedValue::Text(s) => format!("\"{s}\".to_owned()"),
+            FixedValue::SimpleValue(n) => n.to_string(),
+        }
+    }
+
+    /// The type and value of a rust `const` holding this literal e.g. ("&str", "\"foo\"")
+    /// or None for null which has no const representation
+    pub fn to_const_type_and_value(&self) -> Option<(&'static str, String)> {
+        match self {
+            FixedValue::Null => None,
+            FixedValue::Bool(b) => Some(("bool", b.to_string())),
+            FixedValue::Nint(i) => Some(("i64", i.to_string())),
+            FixedValue::Uint(u) => Some(("u64", u.to_string())),
+            // debug formatting keeps the .0 on whole floats
+            FixedValue::Float(f) => Some(("f64", format!("{f:?}"))),
+            FixedValue::Text(s) => Some(("&str", format!("{s:?}"))),
+            FixedValue::SimpleValue(n) => Some(("u8", n.to_string())),
         }
     }
 
@@ -891,6 +1597,8 @@ pub enum Primitive {
     Bool,
     F64,
     F32,
+    // float16 in cddl. f32 in rust but always serialized as the shortest exact float
+    F16,
     // u8 in our cddl
     U8,
     // i8 in our cddl
@@ -907,10 +1615,18 @@ pub enum Primitive {
     U64,
     // i64 in our cddl
     I64,
+    // uint .size 16. always encoded as a #6.2 bignum with exactly 16 bytes
+    U128,
+    // int .size 16. always encoded as a #6.2/#6.3 bignum with exactly 16 bytes
+    I128,
     // nint
     N64,
     Str,
     Bytes,
+    // bstr .size N with --fixed-size-byte-arrays
+    ByteArray(usize),
+    // tstr .size 1 with --tstr-size-1-as-char
+    Char,
 }
 
 impl std::fmt::Display for Primitive {
@@ -920,7 +1636,7 @@ impl std::fmt::Display for Primitive {
             "{}",
             match self {
                 Primitive::Bool => "bool",
-                Primitive::F32 => "f32",
+                Primitive::F16 | Primitive::F32 => "f32",
                 Primitive::F64 => "f64",
                 Primitive::U8 => "u8",
                 Primitive::I8 => "i8",
@@ -930,9 +1646,13 @@ impl std::fmt::Display for Primitive {
                 Primitive::I32 => "i32",
                 Primitive::U64 => "u64",
                 Primitive::I64 => "i64",
+                Primitive::U128 => "u128",
+                Primitive::I128 => "i128",
                 Primitive::N64 => "u64",
                 Primitive::Str => "String",
                 Primitive::Bytes => "Vec<u8>",
+                Primitive::ByteArray(n) => return write!(f, "[u8; {n}]"),
+                Primitive::Char => "char",
             }
         )
     }
@@ -942,6 +1662,7 @@ impl Primitive {
     pub fn to_variant(self) -> VariantIdent {
         VariantIdent::new_custom(match self {
             Primitive::Bool => "Bool",
+            Primitive::F16 => "F16",
             Primitive::F32 => "F32",
             Primitive::F64 => "F64",
             Primitive::U8 => "U8",
@@ -952,9 +1673,13 @@ impl Primitive {
             Primitive::I32 => "I32",
             Primitive::U64 => "U64",
             Primitive::I64 => "I64",
+            Primitive::U128 => "U128",
+            Primitive::I128 => "I128",
             Primitive::N64 => "N64",
             Primitive::Str => "Text",
             Primitive::Bytes => "Bytes",
+            Primitive::ByteArray(n) => return VariantIdent::new_custom(format!("Bytes{n}")),
+            Primitive::Char => "Char",
         })
     }
 
@@ -962,6 +1687,7 @@ impl Primitive {
     pub fn cbor_types(&self) -> Vec<CBORType> {
         match self {
             Primitive::Bool => vec![CBORType::Special],
+            Primitive::F16 => vec![CBORType::Special],
             Primitive::F32 => vec![CBORType::Special],
             Primitive::F64 => vec![CBORType::Special],
             Primitive::U8 => vec![CBORType::UnsignedInteger],
@@ -973,8 +1699,9 @@ impl Primitive {
             Primitive::U64 => vec![CBORType::UnsignedInteger],
             Primitive::I64 => vec![CBORType::UnsignedInteger, CBORType::NegativeInteger],
             Primitive::N64 => vec![CBORType::NegativeInteger],
-            Primitive::Str => vec![CBORType::Text],
-            Primitive::Bytes => vec![CBORType::Bytes],
+            Primitive::U128 | Primitive::I128 => vec![CBORType::Tag],
+            Primitive::Str | Primitive::Char => vec![CBORType::Text],
+            Primitive::Bytes | Primitive::ByteArray(_) => vec![CBORType::Bytes],
         }
     }
 }
@@ -982,7 +1709,7 @@ impl Primitive {
 mod idents {
     use crate::{cli::Cli, rust_reserved::STD_TYPES, utils::is_identifier_reserved};
 
-    use super::{IntermediateTypes, RustType};
+    use super::{IntermediateTypes, Primitive, RustType};
 
     // to resolve ambiguities between raw (from CDDL) and already-formatted
     // for things like type aliases, etc, we use these wrapper structs
@@ -995,6 +1722,30 @@ mod idents {
         pub fn new<T: Into<String>>(raw: T) -> Self {
             Self(raw.into())
         }
+
+        /// Whether this is a CDDL builtin that maps directly to a rust primitive or to a constant
+        /// e.g. uint, tstr, null. This excludes the rest of the standard prelude e.g. biguint.
+        pub fn is_builtin(&self) -> bool {
+            self.to_rust_primitive().is_some()
+                || matches!(self.0.as_str(), "null" | "nil" | "true" | "false")
+        }
+
+        /// The rust primitive a CDDL builtin type is represented as
+        pub fn to_rust_primitive(&self) -> Option<Primitive> {
+            match self.0.as_str() {
+                "tstr" | "text" => Some(Primitive::Str),
+                "bstr" | "bytes" => Some(Primitive::Bytes),
+                "int" => Some(Primitive::I64),
+                "uint" => Some(Primitive::U64),
+                "nint" => Some(Primitive::N64),
+                "bool" => Some(Primitive::Bool),
+                // float without a precision defaults to double precision
+                "float" | "float64" => Some(Primitive::F64),
+                "float32" => Some(Primitive::F32),
+                "float16" => Some(Primitive::F16),
+                _ => None,
+            }
+        }
     }
 
     // impl<'a> From<&'a CDDLIdent> for &'a str {
@@ -1040,6 +1791,11 @@ mod idents {
             Self(super::convert_to_camel_case(&cddl_ident.0))
         }
 
+        /// A user-provided type from @rust_type, kept exactly as written e.g. domain::Amount
+        pub fn new_rust_path(path: &str) -> Self {
+            Self(path.to_owned())
+        }
+
         pub fn new_generic(
             generic_ident: &RustIdent,
             generic_args: &[RustType],
@@ -1132,16 +1888,43 @@ mod idents {
         }
     }
 }
-use crate::cli::Cli;
+use crate::cli::{Cli, IntWidth, RegexEngine, Visibility};
 pub use idents::*;
 
 /// Details on how to encode a rust type in CBOR. Order is important
 #[derive(Clone, Debug, Eq, PartialEq)]
 pub enum CBOREncodingOperation {
     /// CBOR tagged type
-    Tagged(usize),
-    /// bytes .cbor T in cddl, outside of serialization is semantically like T
-    CBORBytes,
+    Tagged(u64),
+    /// bytes .cbor T in cddl, outside of serialization is semantically like T.
+    /// Bounds are on the length of the encoded bytes e.g. (bytes .cbor T) .size (0..64)
+    CBORBytes(Option<(Option<i128>, Option<i128>)>),
+    /// text .cbor T in cddl (only with --allow-cbor-on-text). Like CBORBytes but base64 encoded in a text string
+    CBORText,
+}
+
+/// Bound on a float. Unlike integers an exclusive bound can't be made inclusive by
+/// adding or subtracting 1 so e.g. float .lt 1.5 is kept as (1.5, not inclusive)
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct FloatBound {
+    pub value: f64,
+    pub inclusive: bool,
+}
+
+impl FloatBound {
+    pub fn inclusive(value: f64) -> Self {
+        Self {
+            value,
+            inclusive: true,
+        }
+    }
+
+    pub fn exclusive(value: f64) -> Self {
+        Self {
+            value,
+            inclusive: false,
+        }
+    }
 }
 
 #[derive(Clone, Debug, Default, PartialEq)]
@@ -1150,8 +1933,21 @@ pub struct RustTypeSerializeConfig {
     pub default: Option<FixedValue>,
     /// Bounds to check. Relevant to primitives + arrays + maps
     pub bounds: Option<(Option<i128>, Option<i128>)>,
+    /// Bounds to check on float primitives, kept separate as they needn't be integers
+    pub float_bounds: Option<(Option<FloatBound>, Option<FloatBound>)>,
     /// Basic group encoding override. If true basic encoding will not be used in (de)serialization
     pub basic_override: bool,
+    /// Stored as Box<T> to break up an otherwise infinitely-sized recursive type
+    pub boxed: bool,
+    /// + T array tail stored as NonEmptyVec<T> instead of Vec<T>
+    pub non_empty: bool,
+}
+
+impl RustTypeSerializeConfig {
+    /// Whether there are any bounds (integer/length or float) to check
+    pub fn has_bounds(&self) -> bool {
+        self.bounds.is_some() || self.float_bounds.is_some()
+    }
 }
 
 /// A complete rust type, including serialization options that don't impact other areas
@@ -1189,12 +1985,12 @@ impl RustType {
         self
     }
 
-    pub fn tag(mut self, tag: usize) -> Self {
+    pub fn tag(mut self, tag: u64) -> Self {
         self.encodings.push(CBOREncodingOperation::Tagged(tag));
         self
     }
 
-    pub fn tag_if(self, tag: Option<usize>) -> Self {
+    pub fn tag_if(self, tag: Option<u64>) -> Self {
         if let Some(t) = tag {
             self.tag(t)
         } else {
@@ -1211,8 +2007,10 @@ impl RustType {
                 FixedValue::Bool(_) => *p == Primitive::Bool,
                 FixedValue::Nint(_) => p.cbor_types().contains(&CBORType::NegativeInteger),
                 FixedValue::Uint(_) => p.cbor_types().contains(&CBORType::UnsignedInteger),
-                FixedValue::Float(_) => *p == Primitive::F64 || *p == Primitive::F32,
-                FixedValue::Null => false,
+                FixedValue::Float(_) => {
+                    matches!(p, Primitive::F16 | Primitive::F32 | Primitive::F64)
+                }
+                FixedValue::Null | FixedValue::SimpleValue(_) => false,
                 FixedValue::Text(_) => *p == Primitive::Str,
             }
         } else {
@@ -1230,7 +2028,13 @@ impl RustType {
 
     #[allow(clippy::wrong_self_convention)]
     pub fn as_bytes(mut self) -> Self {
-        self.encodings.push(CBOREncodingOperation::CBORBytes);
+        self.encodings.push(CBOREncodingOperation::CBORBytes(None));
+        self
+    }
+
+    #[allow(clippy::wrong_self_convention)]
+    pub fn as_base64_text(mut self) -> Self {
+        self.encodings.push(CBOREncodingOperation::CBORText);
         self
     }
 
@@ -1243,7 +2047,18 @@ impl RustType {
         }
     }
 
-    pub fn with_bounds(self, mut bounds: (Option<i128>, Option<i128>)) -> Self {
+    pub fn with_bounds(mut self, mut bounds: (Option<i128>, Option<i128>)) -> Self {
+        // (bytes .cbor T) .size (0..64) bounds the encoded bytes, not T itself
+        if let Some(CBOREncodingOperation::CBORBytes(encoded_bounds)) = self.encodings.last_mut() {
+            assert!(encoded_bounds.is_none());
+            if bounds.0 == Some(0) {
+                bounds.0 = None;
+            }
+            if bounds.0.is_some() || bounds.1.is_some() {
+                *encoded_bounds = Some(bounds);
+            }
+            return self;
+        }
         assert!(self.config.bounds.is_none());
         // remove redundant 0 for unsigned types
         if bounds.0 == Some(0)
@@ -1269,11 +2084,22 @@ impl RustType {
                 } else {
                     None
                 },
+                float_bounds: self.config.float_bounds,
                 basic_override: self.config.basic_override,
+                boxed: self.config.boxed,
+                non_empty: self.config.non_empty,
             },
         }
     }
 
+    pub fn with_float_bounds(mut self, bounds: (Option<FloatBound>, Option<FloatBound>)) -> Self {
+        assert!(self.config.float_bounds.is_none());
+        if bounds.0.is_some() || bounds.1.is_some() {
+            self.config.float_bounds = Some(bounds);
+        }
+        self
+    }
+
     pub fn not_basic(self) -> Self {
         Self {
             conceptual_type: self.conceptual_type,
@@ -1281,11 +2107,61 @@ impl RustType {
             config: RustTypeSerializeConfig {
                 default: self.config.default,
                 bounds: self.config.bounds,
+                float_bounds: self.config.float_bounds,
                 basic_override: true,
+                boxed: self.config.boxed,
+                non_empty: self.config.non_empty,
             },
         }
     }
 
+    /// Same as ConceptualRustType::for_rust_member() but respects boxing of this reference
+    /// and stores + T array tails as NonEmptyVec<T>
+    pub fn for_rust_member(&self, types: &IntermediateTypes, from_wasm: bool, cli: &Cli) -> String {
+        let member = match &self.conceptual_type {
+            ConceptualRustType::Array(elem) if self.config.non_empty => {
+                let elem = elem.for_rust_member(types, from_wasm, cli);
+                if from_wasm {
+                    format!(
+                        "{}::non_empty_vec::NonEmptyVec<{elem}>",
+                        cli.common_import_wasm()
+                    )
+                } else {
+                    format!("NonEmptyVec<{elem}>")
+                }
+            }
+            conceptual_type => conceptual_type.for_rust_member(types, from_wasm, cli),
+        };
+        if self.config.boxed {
+            format!("Box<{member}>")
+        } else {
+            member
+        }
+    }
+
+    /// Same as ConceptualRustType::for_rust_move() but respects boxing of this reference.
+    /// NonEmptyVec<T> is taken as a Vec<T> whose length the constructor checks.
+    pub fn for_rust_move(&self, types: &IntermediateTypes, cli: &Cli) -> String {
+        if self.config.non_empty {
+            self.conceptual_type.for_rust_member(types, false, cli)
+        } else {
+            self.for_rust_member(types, false, cli)
+        }
+    }
+
+    /// Same as ConceptualRustType::to_wasm_boundary() but converts a NonEmptyVec<T> back into a Vec<T>
+    pub fn to_wasm_boundary(&self, types: &IntermediateTypes, expr: &str, is_ref: bool) -> String {
+        if !self.config.non_empty {
+            return self.conceptual_type.to_wasm_boundary(types, expr, is_ref);
+        }
+        // to_vec() already clones
+        if self.conceptual_type.directly_wasm_exposable(types) {
+            format!("{expr}.to_vec()")
+        } else {
+            format!("{expr}.to_vec().into()")
+        }
+    }
+
     /// Checks whether FROM THIS CONTEXT the type is a basic group.
     /// Only relevant to rust structs.
     pub fn is_basic(&self, types: &IntermediateTypes) -> bool {
@@ -1393,7 +2269,8 @@ impl RustType {
     pub fn cbor_types(&self, types: &IntermediateTypes) -> Vec<CBORType> {
         match self.encodings.last() {
             Some(CBOREncodingOperation::Tagged(_)) => vec![CBORType::Tag],
-            Some(CBOREncodingOperation::CBORBytes) => vec![CBORType::Bytes],
+            Some(CBOREncodingOperation::CBORBytes(_)) => vec![CBORType::Bytes],
+            Some(CBOREncodingOperation::CBORText) => vec![CBORType::Text],
             None => match &self.conceptual_type {
                 ConceptualRustType::Fixed(f) => vec![match f {
                     FixedValue::Uint(_) => CBORType::UnsignedInteger,
@@ -1402,6 +2279,7 @@ impl RustType {
                     FixedValue::Text(_) => CBORType::Text,
                     FixedValue::Null => CBORType::Special,
                     FixedValue::Bool(_) => CBORType::Special,
+                    FixedValue::SimpleValue(_) => CBORType::Special,
                 }],
                 ConceptualRustType::Primitive(p) => p.cbor_types(),
                 ConceptualRustType::Rust(ident) => {
@@ -1444,6 +2322,15 @@ impl RustType {
                     }
                     inner_types
                 }
+                ConceptualRustType::Result(ok, err) => {
+                    let mut inner_types = ok.cbor_types(types);
+                    for err_type in err.cbor_types(types) {
+                        if !inner_types.contains(&err_type) {
+                            inner_types.push(err_type);
+                        }
+                    }
+                    inner_types
+                }
                 ConceptualRustType::Alias(_ident, ty) => {
                     Self::new((**ty).clone()).cbor_types(types)
                 }
@@ -1452,7 +2339,7 @@ impl RustType {
     }
 
     pub fn needs_bounds_check_if_inlined(&self, types: &IntermediateTypes) -> bool {
-        self.config.bounds.is_some()
+        self.config.has_bounds()
             || match self.resolve_alias_shallow() {
                 ConceptualRustType::Rust(ident) => types.can_new_fail(ident),
                 _ => false,
@@ -1471,6 +2358,7 @@ impl RustType {
                 ConceptualRustType::Rust(ident) => types.is_enum(ident),
                 ConceptualRustType::Array(_) => true,
                 ConceptualRustType::Optional(_) => false,
+                ConceptualRustType::Result(_, _) => true,
                 ConceptualRustType::Map(_, _) => false,
                 ConceptualRustType::Alias(_ident, ty) => {
                     Self::new((**ty).clone())._is_serialize_multiline(types)
@@ -1500,6 +2388,8 @@ pub enum ConceptualRustType {
     Array(Box<RustType>),
     // T / null in CDDL - auto-converts to Option<T> in rust for ease of use.
     Optional(Box<RustType>),
+    // T / E in CDDL annotated with @result - converts to Result<T, E> in rust
+    Result(Box<RustType>, Box<RustType>),
     // TODO: table type to support inlined defined table-type groups as fields
     Map(Box<RustType>, Box<RustType>),
     // Alias for another type
@@ -1511,7 +2401,95 @@ pub enum ConceptualRustType {
     // another approach would be necessary.
 }
 
-impl ConceptualRustType {
+impl ConceptualRustType {
+    /// Rough size in bytes of this type in rust, ignoring padding and anything on the heap.
+    /// Only meant for comparing enum variants e.g. for clippy::large_enum_variant
+    pub fn estimated_size(&self, types: &IntermediateTypes) -> usize {
+        // past this we must be in a (boxed/Vec'd) recursive type which couldn't be inline anyway
+        const MAX_DEPTH: usize = 16;
+        fn size(ty: &ConceptualRustType, types: &IntermediateTypes, depth: usize) -> usize {
+            if depth > MAX_DEPTH {
+                return 0;
+            }
+            match ty {
+                ConceptualRustType::Fixed(_) => 0,
+                ConceptualRustType::Primitive(p) => match p {
+                    Primitive::Bool | Primitive::U8 | Primitive::I8 => 1,
+                    Primitive::U16 | Primitive::I16 => 2,
+                    Primitive::U32
+                    | Primitive::I32
+                    | Primitive::F32
+                    | Primitive::F16
+                    | Primitive::Char => 4,
+                    Primitive::U64 | Primitive::I64 | Primitive::N64 | Primitive::F64 => 8,
+                    Primitive::U128 | Primitive::I128 => 16,
+                    Primitive::Str | Primitive::Bytes => 24,
+                    Primitive::ByteArray(n) => *n,
+                },
+                ConceptualRustType::Array(_) | ConceptualRustType::Map(_, _) => 24,
+                ConceptualRustType::Optional(inner) => {
+                    8 + size(&inner.conceptual_type, types, depth + 1)
+                }
+                ConceptualRustType::Result(ok, err) => {
+                    8 + std::cmp::max(
+                        size(&ok.conceptual_type, types, depth + 1),
+                        size(&err.conceptual_type, types, depth + 1),
+                    )
+                }
+                ConceptualRustType::Alias(_, ty) => size(ty, types, depth + 1),
+                ConceptualRustType::Rust(ident) => {
+                    match types.rust_struct(ident).map(RustStruct::variant) {
+                        Some(RustStructType::Record(record)) => record
+                            .fields
+                            .iter()
+                            .filter(|field| !field.rust_type.is_fixed_value())
+                            .map(|field| {
+                                let field_size =
+                                    size(&field.rust_type.conceptual_type, types, depth + 1);
+                                if field.optional && field.rust_type.config.default.is_none() {
+                                    8 + field_size
+                                } else {
+                                    field_size
+                                }
+                            })
+                            .sum(),
+                        Some(
+                            RustStructType::TypeChoice { variants }
+                            | RustStructType::GroupChoice { variants, .. },
+                        ) => {
+                            8 + variants
+                                .iter()
+                                .map(|variant| match &variant.data {
+                                    EnumVariantData::RustType(ty) => {
+                                        size(&ty.conceptual_type, types, depth + 1)
+                                    }
+                                    EnumVariantData::Inlined(record) => record
+                                        .fields
+                                        .iter()
+                                        .filter(|field| !field.rust_type.is_fixed_value())
+                                        .map(|field| {
+                                            size(&field.rust_type.conceptual_type, types, depth + 1)
+                                        })
+                                        .sum(),
+                                })
+                                .max()
+                                .unwrap_or(0)
+                        }
+                        Some(RustStructType::CStyleEnum { .. }) => 1,
+                        Some(RustStructType::Wrapper { wrapped, .. }) => {
+                            size(&wrapped.conceptual_type, types, depth + 1)
+                        }
+                        Some(RustStructType::Table { .. } | RustStructType::Array { .. }) => 24,
+                        // RawBytesType are Vec<u8> but we can't know what extern types are
+                        Some(RustStructType::RawBytesType) => 24,
+                        Some(RustStructType::Extern) | None => 0,
+                    }
+                }
+            }
+        }
+        size(self, types, 0)
+    }
+
     // deep resolve aliases - does it inside of options, maps, arrays, etc
     pub fn resolve_aliases(self) -> Self {
         match self {
@@ -1522,6 +2500,10 @@ impl ConceptualRustType {
                 Box::new(value.resolve_aliases()),
             ),
             Self::Optional(ty) => Self::Optional(Box::new(ty.resolve_aliases())),
+            Self::Result(ok, err) => Self::Result(
+                Box::new(ok.resolve_aliases()),
+                Box::new(err.resolve_aliases()),
+            ),
             _ => self,
         }
     }
@@ -1553,6 +2535,7 @@ impl ConceptualRustType {
                     Self::Primitive(p) => match p {
                         // converts to js number which is supported as Vec<T>
                         Primitive::Bool
+                        | Primitive::F16
                         | Primitive::F32
                         | Primitive::F64
                         | Primitive::I8
@@ -1564,16 +2547,20 @@ impl ConceptualRustType {
                         | Primitive::I64
                         | Primitive::N64
                         | Primitive::U64 => true,
+                        // js BigInt arrays aren't supported as Vec<T> by wasm-bindgen
+                        Primitive::U128 | Primitive::I128 => false,
                         // Bytes is already implemented as Vec<u8> so we can't nest it
-                        Primitive::Bytes => false,
+                        Primitive::Bytes | Primitive::ByteArray(_) => false,
                         // Vec<String> is not supported by wasm-bindgen
                         Primitive::Str => false,
+                        Primitive::Char => false,
                     },
                     Self::Array(_) => false,
                     _ => ty.conceptual_type.directly_wasm_exposable(types),
                 }
             }
             Self::Optional(ty) => ty.conceptual_type.directly_wasm_exposable(types),
+            Self::Result(_, _) => false,
             Self::Map(_, _) => false,
             Self::Alias(_ident, ty) => ty.directly_wasm_exposable(types),
         }
@@ -1631,7 +2618,9 @@ impl ConceptualRustType {
             Self::Optional(ty) => {
                 format!("Option<{}>", ty.conceptual_type._for_rust_read(types, cli))
             }
-            Self::Map(_k, _v) => format!("&{}", self.for_rust_member(types, false, cli)),
+            Self::Result(_, _) | Self::Map(_, _) => {
+                format!("&{}", self.for_rust_member(types, false, cli))
+            }
             Self::Alias(ident, ty) => match &**ty {
                 // TODO: ???
                 Self::Rust(_) => format!("&{ident}"),
@@ -1677,6 +2666,7 @@ impl ConceptualRustType {
                     ty.conceptual_type.for_wasm_param_impl(types, true)
                 )
             }
+            Self::Result(_, _) => panic!("Result types can't be exposed to wasm: {:?}", self),
             Self::Map(_k, _v) => format!("{}{}", opt_ref, self.for_wasm_member(types)),
             // it might not be worth generating this as aliases are ignored by wasm-pack build, but
             // that could change in the future so as long as it doens't cause issues we'll leave it
@@ -1731,6 +2721,7 @@ impl ConceptualRustType {
             Self::Rust(ident) => ident.to_string(),
             Self::Array(ty) => ty.conceptual_type.name_as_wasm_array(types),
             Self::Optional(ty) => format!("Option<{}>", ty.conceptual_type.for_wasm_member(types)),
+            Self::Result(_, _) => panic!("Result types can't be exposed to wasm: {:?}", self),
             Self::Map(k, v) => Self::name_for_wasm_map(k, v).to_string(),
             Self::Alias(ident, ty) => match ident {
                 // we don't generate type aliases for reserved types, just transform
@@ -1759,9 +2750,13 @@ impl ConceptualRustType {
             }
             Self::Array(ty) => ty.conceptual_type.name_as_rust_array(types, from_wasm, cli),
             Self::Optional(ty) => {
+                format!("Option<{}>", ty.for_rust_member(types, from_wasm, cli))
+            }
+            Self::Result(ok, err) => {
                 format!(
-                    "Option<{}>",
-                    ty.conceptual_type.for_rust_member(types, from_wasm, cli)
+                    "Result<{}, {}>",
+                    ok.for_rust_member(types, from_wasm, cli),
+                    err.for_rust_member(types, from_wasm, cli)
                 )
             }
             Self::Map(k, v) => Self::name_for_rust_map(types, k, v, from_wasm, cli),
@@ -1794,6 +2789,11 @@ impl ConceptualRustType {
             Self::Optional(ty) => {
                 VariantIdent::new_custom(format!("Opt{}", ty.conceptual_type.for_variant()))
             }
+            Self::Result(ok, err) => VariantIdent::new_custom(format!(
+                "Result{}Or{}",
+                ok.conceptual_type.for_variant(),
+                err.conceptual_type.for_variant()
+            )),
             Self::Map(k, v) => VariantIdent::new_custom(Self::name_for_wasm_map(k, v).to_string()),
             Self::Alias(ident, _ty) => match ident {
                 AliasIdent::Rust(rust_ident) => VariantIdent::new_rust(rust_ident.clone()),
@@ -1933,6 +2933,7 @@ impl ConceptualRustType {
             Self::Optional(ty) => ty
                 .conceptual_type
                 .to_wasm_boundary_optional(types, expr, is_ref),
+            Self::Result(_, _) => panic!("Result types can't be exposed to wasm: {:?}", self),
             Self::Alias(_ident, ty) => ty.to_wasm_boundary(types, expr, is_ref),
         }
     }
@@ -1958,6 +2959,7 @@ impl ConceptualRustType {
             Self::Fixed(_f) => unreachable!(),
             Self::Primitive(p) => match p {
                 Primitive::Bool
+                | Primitive::F16
                 | Primitive::F32
                 | Primitive::F64
                 | Primitive::I8
@@ -1968,17 +2970,88 @@ impl ConceptualRustType {
                 | Primitive::U8
                 | Primitive::U16
                 | Primitive::U32
-                | Primitive::U64 => true,
+                | Primitive::U64
+                | Primitive::U128
+                | Primitive::I128
+                | Primitive::ByteArray(_)
+                | Primitive::Char => true,
                 Primitive::Str | Primitive::Bytes => false,
             },
             Self::Rust(ident) => types.is_enum(ident),
             Self::Array(_) => false,
             Self::Map(_k, _v) => false,
             Self::Optional(ty) => ty.conceptual_type.is_copy(types),
+            Self::Result(ok, err) => {
+                ok.conceptual_type.is_copy(types) && err.conceptual_type.is_copy(types)
+            }
             Self::Alias(_ident, ty) => ty.is_copy(types),
         }
     }
 
+    /// Whether this implements PartialEq and Eq. floats_ok is for records, which compare
+    /// floats stored directly in them (see contains_float()) by their bits.
+    fn is_eq(
+        &self,
+        types: &IntermediateTypes,
+        cli: &Cli,
+        visited: &mut BTreeSet<RustIdent>,
+        floats_ok: bool,
+    ) -> bool {
+        match self {
+            // fixed values aren't stored in the struct
+            Self::Fixed(_) => true,
+            Self::Primitive(p) => {
+                floats_ok || !matches!(p, Primitive::F16 | Primitive::F32 | Primitive::F64)
+            }
+            Self::Rust(ident) => types.is_eq_impl(ident, cli, visited),
+            Self::Array(ty) | Self::Optional(ty) => {
+                ty.conceptual_type.is_eq(types, cli, visited, floats_ok)
+            }
+            Self::Result(first, second) | Self::Map(first, second) => {
+                first.conceptual_type.is_eq(types, cli, visited, floats_ok)
+                    && second.conceptual_type.is_eq(types, cli, visited, floats_ok)
+            }
+            Self::Alias(_ident, ty) => ty.is_eq(types, cli, visited, floats_ok),
+        }
+    }
+
+    /// Whether there are floats in this type without going into other rust structs
+    /// e.g. Vec<f64> or Option<BTreeMap<String, f32>>
+    pub fn contains_float(&self) -> bool {
+        match self {
+            Self::Fixed(_) | Self::Rust(_) => false,
+            Self::Primitive(p) => matches!(p, Primitive::F16 | Primitive::F32 | Primitive::F64),
+            Self::Array(ty) | Self::Optional(ty) => ty.conceptual_type.contains_float(),
+            Self::Result(first, second) | Self::Map(first, second) => {
+                first.conceptual_type.contains_float() || second.conceptual_type.contains_float()
+            }
+            Self::Alias(_ident, ty) => ty.contains_float(),
+        }
+    }
+
+    fn is_hashable(
+        &self,
+        types: &IntermediateTypes,
+        cli: &Cli,
+        visited: &mut BTreeSet<RustIdent>,
+    ) -> bool {
+        match self {
+            // fixed values aren't stored in the struct
+            Self::Fixed(_) => true,
+            Self::Primitive(p) => !matches!(p, Primitive::F16 | Primitive::F32 | Primitive::F64),
+            Self::Rust(ident) => types.is_hashable_impl(ident, cli, visited),
+            Self::Array(ty) | Self::Optional(ty) => {
+                ty.conceptual_type.is_hashable(types, cli, visited)
+            }
+            Self::Result(ok, err) => {
+                ok.conceptual_type.is_hashable(types, cli, visited)
+                    && err.conceptual_type.is_hashable(types, cli, visited)
+            }
+            Self::Map(_k, _v) => false,
+            Self::Alias(_ident, ty) => ty.is_hashable(types, cli, visited),
+        }
+    }
+
     pub fn clone_if_not_copy(&self, types: &IntermediateTypes, expr: &str) -> String {
         if self.is_copy(types) {
             expr.to_owned()
@@ -1987,6 +3060,23 @@ impl ConceptualRustType {
         }
     }
 
+    /// Rust types that would be stored directly within this type i.e. not behind
+    /// a Vec/map which would break up any recursion.
+    pub fn inline_rust_references(&self, refs: &mut BTreeSet<RustIdent>) {
+        match self {
+            Self::Rust(ident) => {
+                refs.insert(ident.clone());
+            }
+            Self::Alias(_ident, ty) => ty.inline_rust_references(refs),
+            Self::Optional(ty) => ty.conceptual_type.inline_rust_references(refs),
+            Self::Result(ok, err) => {
+                ok.conceptual_type.inline_rust_references(refs);
+                err.conceptual_type.inline_rust_references(refs);
+            }
+            Self::Fixed(_) | Self::Primitive(_) | Self::Array(_) | Self::Map(_, _) => (),
+        }
+    }
+
     pub fn visit_types<F: FnMut(&Self)>(&self, types: &IntermediateTypes, f: &mut F) {
         self.visit_types_excluding(types, f, &mut BTreeSet::new())
     }
@@ -2023,6 +3113,12 @@ impl ConceptualRustType {
                 ty.conceptual_type
                     .visit_types_excluding(types, f, already_visited)
             }
+            Self::Result(ok, err) => {
+                ok.conceptual_type
+                    .visit_types_excluding(types, f, already_visited);
+                err.conceptual_type
+                    .visit_types_excluding(types, f, already_visited);
+            }
             Self::Primitive(_) => (),
             Self::Rust(ident) => {
                 if already_visited.insert(ident.clone()) {
@@ -2125,6 +3221,62 @@ pub struct EnumVariant {
 }
 
 impl EnumVariant {
+    /// See ConceptualRustType::estimated_size()
+    pub fn estimated_size(&self, types: &IntermediateTypes) -> usize {
+        match &self.data {
+            EnumVariantData::RustType(ty) => ty.conceptual_type.estimated_size(types),
+            EnumVariantData::Inlined(record) => record
+                .fields
+                .iter()
+                .filter(|field| !field.rust_type.is_fixed_value())
+                .map(|field| field.rust_type.conceptual_type.estimated_size(types))
+                .sum(),
+        }
+    }
+
+    /// The constant value of the @tag_field discriminator field in this variant.
+    /// Err describes why the variant can't be discriminated by tag_field.
+    pub fn tag_field_value<'a>(
+        &'a self,
+        types: &'a IntermediateTypes,
+        tag_field: &str,
+    ) -> Result<&'a FixedValue, String> {
+        let record = match &self.data {
+            EnumVariantData::Inlined(record) => Some(record),
+            EnumVariantData::RustType(ty) => match ty.conceptual_type.resolve_alias_shallow() {
+                ConceptualRustType::Rust(ident) => {
+                    match types.rust_struct(ident).map(|rs| rs.variant()) {
+                        Some(RustStructType::Record(record)) => Some(record),
+                        _ => None,
+                    }
+                }
+                _ => None,
+            },
+        };
+        let record = record
+            .filter(|record| record.rep == Representation::Map)
+            .ok_or_else(|| "@tag_field variants must be maps".to_owned())?;
+        let tag_key = FixedValue::Text(tag_field.to_owned());
+        record
+            .fields
+            .iter()
+            .find_map(
+                |field| match field.rust_type.conceptual_type.resolve_alias_shallow() {
+                    ConceptualRustType::Fixed(value)
+                        if !field.optional && field.key.as_ref() == Some(&tag_key) =>
+                    {
+                        Some(value)
+                    }
+                    _ => None,
+                },
+            )
+            .ok_or_else(|| {
+                format!(
+                    "@tag_field {tag_field} must be a mandatory fixed value field in every variant"
+                )
+            })
+    }
+
     pub fn new(
         name: VariantIdent,
         rust_type: RustType,
@@ -2253,31 +3405,98 @@ impl EnumVariant {
     }
 }
 
+/// How many times a group entry can occur, taken from its CDDL occurrence indicator
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum Occurrence {
+    /// no occurrence indicator
+    Required,
+    /// ? T
+    Optional,
+    /// * T
+    ZeroOrMore,
+    /// + T
+    OneOrMore,
+    /// n*m T. A missing lower bound is 0 and a missing upper bound is usize::MAX
+    Bounded(usize, usize),
+}
+
+impl Occurrence {
+    /// Whether a field with this occurrence can be absent. Map keys are unique so e.g. * 1: T
+    /// can be there at most once i.e. it is just optional and + 1: T is required.
+    /// Repeated array entries are the Vec tail which is always present.
+    pub fn is_optional_in(&self, rep: Representation) -> bool {
+        match (rep, self.repeated_bounds()) {
+            (Representation::Map, Some((lower, _))) => lower.is_none(),
+            _ => matches!(self, Self::Optional),
+        }
+    }
+
+    /// Length bounds for the Vec<T> of a repeated entry e.g. (Some(1), None) for + T
+    /// or (Some(2), Some(4)) for 2*4 T. None if it isn't repeated.
+    pub fn repeated_bounds(&self) -> Option<(Option<i128>, Option<i128>)> {
+        match self {
+            Self::Required | Self::Optional => None,
+            Self::ZeroOrMore => Some((None, None)),
+            Self::OneOrMore => Some((Some(1), None)),
+            Self::Bounded(lower, upper) => Some((
+                Some(*lower as i128).filter(|l| *l != 0),
+                Some(*upper).filter(|u| *u != usize::MAX).map(|u| u as i128),
+            )),
+        }
+    }
+}
+
 #[derive(Clone, Debug)]
 pub struct RustField {
     pub name: String,
     pub rust_type: RustType,
     pub optional: bool,
+    // full occurrence indicator of the group entry this field came from
+    pub occurrence: Occurrence,
     // None for array fields, Some for map fields. FixedValue for (de)serialization for map keys
     pub key: Option<FixedValue>,
     // comment DSL metadata applied to this field
     pub rule_metadata: RuleMetadata,
+    // optional map field originally T / null where an explicit null is read as if the key were absent
+    pub collapse_null: bool,
+    // trailing * T / + T in an array. rust_type is Vec<T> but the elements are (de)serialized inline after the other fields
+    pub tail: bool,
 }
 
 impl RustField {
     pub fn new(
         name: String,
         rust_type: RustType,
-        optional: bool,
+        occurrence: Occurrence,
         key: Option<FixedValue>,
         rule_metadata: RuleMetadata,
     ) -> Self {
+        // only map fields have keys
+        let rep = match key {
+            Some(_) => Representation::Map,
+            None => Representation::Array,
+        };
         Self {
             name,
             rust_type,
-            optional,
+            optional: occurrence.is_optional_in(rep),
+            occurrence,
             key,
             rule_metadata,
+            collapse_null: false,
+            tail: false,
+        }
+    }
+
+    /// Type used when deserializing this field's value, which for collapsed fields
+    /// must still accept an explicit null.
+    pub fn deserialize_rust_type(&self) -> Cow<RustType> {
+        if self.collapse_null {
+            Cow::Owned(RustType::new(ConceptualRustType::Optional(Box::new(
+                self.rust_type.clone(),
+            ))))
+        } else {
+            Cow::Borrowed(&self.rust_type)
         }
     }
 
@@ -2309,6 +3528,21 @@ pub struct RustStructConfig {
     pub custom_deserialize: Option<String>,
     pub doc: Option<String>,
     pub newtype_getter: Option<Option<String>>,
+    pub tag_field: Option<String>,
+    pub hash: bool,
+    pub eq: bool,
+    pub feature: Option<String>,
+    pub repr: Option<String>,
+    pub no_hex_display: bool,
+    /// @visibility override. None = use --visibility
+    pub visibility: Option<Visibility>,
+}
+
+impl RustStructConfig {
+    /// Visibility token for the rust type and its inherent fns, or None if private
+    pub fn visibility_token(&self, cli: &Cli) -> Option<&'static str> {
+        self.visibility.unwrap_or(cli.visibility).token()
+    }
 }
 
 impl From<Option<&RuleMetadata>> for RustStructConfig {
@@ -2320,6 +3554,16 @@ impl From<Option<&RuleMetadata>> for RustStructConfig {
                 custom_deserialize: rule_metadata.custom_deserialize.clone(),
                 doc: rule_metadata.comment.clone(),
                 newtype_getter: rule_metadata.newtype.clone(),
+                tag_field: rule_metadata.tag_field.clone(),
+                hash: rule_metadata.hash,
+                eq: rule_metadata.eq,
+                feature: rule_metadata.feature.clone(),
+                repr: rule_metadata.repr.clone(),
+                no_hex_display: rule_metadata.no_hex_display,
+                visibility: rule_metadata.visibility.as_deref().map(|visibility| {
+                    // already checked to be valid when parsing the comment
+                    <Visibility as clap::ValueEnum>::from_str(visibility, false).unwrap()
+                }),
             },
             None => Self::default(),
         }
@@ -2334,11 +3578,211 @@ impl From<Option<&RuleMetadata>> for RustStructConfig {
 #[derive(Clone, Debug)]
 pub struct RustStruct {
     ident: RustIdent,
-    tag: Option<usize>,
+    tag: Option<u64>,
     config: RustStructConfig,
+    // regular comments right before the rule e.g. `; a foo\nfoo = [uint]`
+    doc_comment: Option<String>,
     pub(crate) variant: RustStructType,
 }
 
+/// Which syntax a regex control operator's pattern is written in
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum RegexFlavor {
+    /// `.regexp` - XML Schema regexes. These always have to match the entire string.
+    Xsd,
+    /// `.pcre` - Perl-compatible regexes. These match anywhere in the string unless anchored.
+    Pcre,
+}
+
+impl std::fmt::Display for RegexFlavor {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Xsd => write!(f, ".regexp (XML Schema)"),
+            Self::Pcre => write!(f, ".pcre (PCRE)"),
+        }
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct RegexPattern {
+    /// as written in the CDDL
+    pub pattern: String,
+    pub flavor: RegexFlavor,
+    pub engine: RegexEngine,
+}
+
+impl RegexPattern {
+    /// Errors with a description of the first construct that the engine can't express
+    /// (or that isn't part of the flavor's syntax at all)
+    pub fn new(pattern: String, flavor: RegexFlavor, engine: RegexEngine) -> Result<Self, String> {
+        let chars = pattern.chars().collect::<Vec<char>>();
+        let engine_name = match engine {
+            RegexEngine::Regex => "the regex crate",
+            RegexEngine::FancyRegex => "the fancy-regex crate",
+        };
+        let needs_fancy = |feature: &str| -> Result<(), String> {
+            match (flavor, engine) {
+                (RegexFlavor::Pcre, RegexEngine::FancyRegex) => Ok(()),
+                (RegexFlavor::Pcre, RegexEngine::Regex) => Err(format!(
+                    "{feature} in pattern \"{pattern}\" can't be expressed using {engine_name}. Try --pcre-engine=fancy-regex"
+                )),
+                (RegexFlavor::Xsd, _) => Err(format!(
+                    "{feature} in pattern \"{pattern}\" is not part of XML Schema regexes. Use .pcre instead"
+                )),
+            }
+        };
+        let unsupported = |feature: &str| -> Result<(), String> {
+            Err(format!(
+                "{feature} in {flavor} pattern \"{pattern}\" can't be expressed using {engine_name}"
+            ))
+        };
+        let mut in_class = false;
+        let mut after_quantifier = false;
+        let mut i = 0;
+        while i < chars.len() {
+            let quantifier = !in_class && matches!(chars[i], '*' | '+' | '?' | '}');
+            match chars[i] {
+                '\\' => {
+                    match chars.get(i + 1) {
+                        Some('1'..='9') if !in_class => needs_fancy("backreference")?,
+                        Some('k') if !in_class => needs_fancy("named backreference")?,
+                        Some('i' | 'I' | 'c' | 'C') if flavor == RegexFlavor::Xsd => {
+                            unsupported("XML name character escape")?
+                        }
+                        Some('p' | 'P')
+                            if flavor == RegexFlavor::Xsd
+                                && chars[i + 2..].starts_with(&['{', 'I', 's']) =>
+                        {
+                            unsupported("Unicode block escape")?
+                        }
+                        _ => (),
+                    }
+                    // skip the escaped char
+                    i += 1;
+                }
+                '[' if in_class && flavor == RegexFlavor::Xsd && chars[i - 1] == '-' => {
+                    unsupported("character class subtraction")?
+                }
+                '[' => in_class = true,
+                ']' if in_class => in_class = false,
+                '(' if !in_class && chars.get(i + 1) == Some(&'?') => {
+                    let rest = chars[i + 2..].iter().collect::<String>();
+                    if flavor == RegexFlavor::Xsd {
+                        needs_fancy("(? group syntax")?;
+                    } else if rest.starts_with('=')
+                        || rest.starts_with('!')
+                        || rest.starts_with("<=")
+                        || rest.starts_with("<!")
+                    {
+                        needs_fancy("lookaround")?;
+                    } else if rest.starts_with('>') {
+                        needs_fancy("atomic group")?;
+                    } else if rest.starts_with('(') {
+                        needs_fancy("conditional")?;
+                    } else if rest.starts_with('R')
+                        || rest.starts_with('&')
+                        || rest.starts_with("P>")
+                        // (?1) (?+1) (?-1) but not flags e.g. (?-i)
+                        || rest
+                            .trim_start_matches(&['+', '-'][..])
+                            .starts_with(|c: char| c.is_ascii_digit())
+                    {
+                        unsupported("recursion")?;
+                    }
+                }
+                '+' if after_quantifier => needs_fancy("possessive quantifier")?,
+                _ => (),
+            }
+            after_quantifier = quantifier && !after_quantifier;
+            i += 1;
+        }
+        Ok(Self {
+            pattern,
+            flavor,
+            engine,
+        })
+    }
+
+    /// The pattern as the engine expects it. XML Schema regexes have no anchors
+    /// (^ and $ are literals) and instead always match the whole string.
+    /// The regex crates treat [ inside a class as a nested class, which is a literal in PCRE.
+    pub fn engine_pattern(&self) -> String {
+        let mut escaped = String::new();
+        let mut chars = self.pattern.chars().peekable();
+        let mut in_class = false;
+        while let Some(c) = chars.next() {
+            match c {
+                '\\' => {
+                    escaped.push(c);
+                    escaped.extend(chars.next());
+                }
+                // POSIX classes e.g. [[:alpha:]] are supported by both
+                '[' if in_class && chars.peek() == Some(&':') => {
+                    escaped.push(c);
+                    while let Some(c) = chars.next() {
+                        escaped.push(c);
+                        if c == ':' && chars.peek() == Some(&']') {
+                            escaped.extend(chars.next());
+                            break;
+                        }
+                    }
+                }
+                '[' if in_class && self.flavor == RegexFlavor::Pcre => {
+                    escaped.push_str("\\[");
+                }
+                '[' if !in_class => {
+                    in_class = true;
+                    escaped.push(c);
+                    // a leading ] (after an optional ^) is a literal
+                    if chars.peek() == Some(&'^') {
+                        escaped.extend(chars.next());
+                    }
+                    if chars.peek() == Some(&']') {
+                        chars.next();
+                        escaped.push_str("\\]");
+                    }
+                }
+                ']' if in_class => {
+                    in_class = false;
+                    escaped.push(c);
+                }
+                '^' | '$' if !in_class && self.flavor == RegexFlavor::Xsd => {
+                    escaped.push('\\');
+                    escaped.push(c);
+                }
+                c => escaped.push(c),
+            }
+        }
+        match self.flavor {
+            RegexFlavor::Xsd => format!("^(?:{escaped})$"),
+            RegexFlavor::Pcre => escaped,
+        }
+    }
+}
+
+/// Which RustStructType something is, without any of the data
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum RustStructKind {
+    /// e.g. foo = [a: uint, b: text] or foo = { a: uint }
+    Record,
+    /// e.g. foo = { * text => uint }
+    Table,
+    /// e.g. foo = [* uint]
+    Array,
+    /// e.g. foo = uint / text
+    TypeChoice,
+    /// e.g. foo = [0, uint // 1, text]
+    GroupChoice,
+    /// e.g. foo = bytes .size 32 or foo = uint ; @newtype
+    Wrapper,
+    /// provided outside of the generated code e.g. _CDDL_CODEGEN_EXTERN_TYPE_
+    Extern,
+    /// e.g. foo = 0 / 1 / 2
+    CStyleEnum,
+    /// _CDDL_CODEGEN_RAW_BYTES_TYPE_
+    RawBytes,
+}
+
 #[derive(Clone, Debug)]
 pub enum RustStructType {
     Record(RustRecord),
@@ -2359,6 +3803,7 @@ pub enum RustStructType {
     Wrapper {
         wrapped: RustType,
         min_max: Option<(Option<i128>, Option<i128>)>,
+        regex: Option<RegexPattern>,
     },
     /// This is a no-op in generation but to prevent lookups of things in the prelude
     /// e.g. `int` from not being resolved while still being able to detect it when
@@ -2370,10 +3815,87 @@ pub enum RustStructType {
     RawBytesType,
 }
 
+impl RustStructType {
+    pub fn kind(&self) -> RustStructKind {
+        match self {
+            Self::Record(_) => RustStructKind::Record,
+            Self::Table { .. } => RustStructKind::Table,
+            Self::Array { .. } => RustStructKind::Array,
+            Self::TypeChoice { .. } => RustStructKind::TypeChoice,
+            Self::GroupChoice { .. } => RustStructKind::GroupChoice,
+            Self::Wrapper { .. } => RustStructKind::Wrapper,
+            Self::Extern => RustStructKind::Extern,
+            Self::CStyleEnum { .. } => RustStructKind::CStyleEnum,
+            Self::RawBytesType => RustStructKind::RawBytes,
+        }
+    }
+
+    /// Types stored directly within the struct/enum (e.g. fields/variants)
+    pub fn inline_rust_types(&self) -> Vec<&RustType> {
+        match self {
+            Self::Record(record) => record.fields.iter().map(|field| &field.rust_type).collect(),
+            Self::TypeChoice { variants } | Self::GroupChoice { variants, .. } => variants
+                .iter()
+                .flat_map(|variant| match &variant.data {
+                    EnumVariantData::RustType(ty) => vec![ty],
+                    EnumVariantData::Inlined(record) => {
+                        record.fields.iter().map(|field| &field.rust_type).collect()
+                    }
+                })
+                .collect(),
+            Self::Wrapper { wrapped, .. } => vec![wrapped],
+            Self::Table { .. }
+            | Self::Array { .. }
+            | Self::Extern
+            | Self::CStyleEnum { .. }
+            | Self::RawBytesType => vec![],
+        }
+    }
+
+    pub fn inline_rust_types_mut(&mut self) -> Vec<&mut RustType> {
+        match self {
+            Self::Record(record) => record
+                .fields
+                .iter_mut()
+                .map(|field| &mut field.rust_type)
+                .collect(),
+            Self::TypeChoice { variants } | Self::GroupChoice { variants, .. } => variants
+                .iter_mut()
+                .flat_map(|variant| match &mut variant.data {
+                    EnumVariantData::RustType(ty) => vec![ty],
+                    EnumVariantData::Inlined(record) => record
+                        .fields
+                        .iter_mut()
+                        .map(|field| &mut field.rust_type)
+                        .collect(),
+                })
+                .collect(),
+            Self::Wrapper { wrapped, .. } => vec![wrapped],
+            Self::Table { .. }
+            | Self::Array { .. }
+            | Self::Extern
+            | Self::CStyleEnum { .. }
+            | Self::RawBytesType => vec![],
+        }
+    }
+}
+
 impl RustStruct {
+    /// Records with nothing to store e.g. foo = [] or foo = [0, "bar"] as fixed values
+    /// are only (de)serialized. These are generated as unit structs e.g. pub struct Foo;
+    pub fn is_unit_struct(&self) -> bool {
+        match &self.variant {
+            RustStructType::Record(record) => record
+                .fields
+                .iter()
+                .all(|field| field.rust_type.is_fixed_value()),
+            _ => false,
+        }
+    }
+
     pub fn new_record(
         ident: RustIdent,
-        tag: Option<usize>,
+        tag: Option<u64>,
         rule_metadata: Option<&RuleMetadata>,
         record: RustRecord,
     ) -> Self {
@@ -2381,13 +3903,14 @@ impl RustStruct {
             ident,
             tag,
             config: RustStructConfig::from(rule_metadata),
+            doc_comment: None,
             variant: RustStructType::Record(record),
         }
     }
 
     pub fn new_table(
         ident: RustIdent,
-        tag: Option<usize>,
+        tag: Option<u64>,
         rule_metadata: Option<&RuleMetadata>,
         domain: RustType,
         range: RustType,
@@ -2396,13 +3919,14 @@ impl RustStruct {
             ident,
             tag,
             config: RustStructConfig::from(rule_metadata),
+            doc_comment: None,
             variant: RustStructType::Table { domain, range },
         }
     }
 
     pub fn new_array(
         ident: RustIdent,
-        tag: Option<usize>,
+        tag: Option<u64>,
         rule_metadata: Option<&RuleMetadata>,
         element_type: RustType,
     ) -> Self {
@@ -2410,6 +3934,7 @@ impl RustStruct {
             ident,
             tag,
             config: RustStructConfig::from(rule_metadata),
+            doc_comment: None,
             variant: RustStructType::Array { element_type },
         }
     }
@@ -2417,7 +3942,7 @@ impl RustStruct {
     /// This will automatically check if it's a c-stlye enum and use that instead if possible
     pub fn new_type_choice(
         ident: RustIdent,
-        tag: Option<usize>,
+        tag: Option<u64>,
         rule_metadata: Option<&RuleMetadata>,
         variants: Vec<EnumVariant>,
         cli: &Cli,
@@ -2442,6 +3967,7 @@ impl RustStruct {
                 ident,
                 tag,
                 config: RustStructConfig::from(rule_metadata),
+                doc_comment: None,
                 variant: RustStructType::TypeChoice { variants },
             }
         } else {
@@ -2449,6 +3975,7 @@ impl RustStruct {
                 ident,
                 tag,
                 config: RustStructConfig::from(rule_metadata),
+                doc_comment: None,
                 variant: RustStructType::CStyleEnum { variants },
             }
         }
@@ -2456,7 +3983,7 @@ impl RustStruct {
 
     pub fn new_group_choice(
         ident: RustIdent,
-        tag: Option<usize>,
+        tag: Option<u64>,
         rule_metadata: Option<&RuleMetadata>,
         variants: Vec<EnumVariant>,
         rep: Representation,
@@ -2465,13 +3992,14 @@ impl RustStruct {
             ident,
             tag,
             config: RustStructConfig::from(rule_metadata),
+            doc_comment: None,
             variant: RustStructType::GroupChoice { variants, rep },
         }
     }
 
     pub fn new_wrapper(
         ident: RustIdent,
-        tag: Option<usize>,
+        tag: Option<u64>,
         rule_metadata: Option<&RuleMetadata>,
         wrapped_type: RustType,
         min_max: Option<(Option<i128>, Option<i128>)>,
@@ -2480,9 +4008,32 @@ impl RustStruct {
             ident,
             tag,
             config: RustStructConfig::from(rule_metadata),
+            doc_comment: None,
             variant: RustStructType::Wrapper {
                 wrapped: wrapped_type,
                 min_max,
+                regex: None,
+            },
+        }
+    }
+
+    /// A text wrapper whose contents must match a .regexp / .pcre pattern
+    pub fn new_regex_wrapper(
+        ident: RustIdent,
+        tag: Option<u64>,
+        rule_metadata: Option<&RuleMetadata>,
+        wrapped_type: RustType,
+        regex: RegexPattern,
+    ) -> Self {
+        Self {
+            ident,
+            tag,
+            config: RustStructConfig::from(rule_metadata),
+            doc_comment: None,
+            variant: RustStructType::Wrapper {
+                wrapped: wrapped_type,
+                min_max: None,
+                regex: Some(regex),
             },
         }
     }
@@ -2492,6 +4043,7 @@ impl RustStruct {
             ident,
             tag: None,
             config: RustStructConfig::default(),
+            doc_comment: None,
             variant: RustStructType::Extern,
         }
     }
@@ -2501,6 +4053,7 @@ impl RustStruct {
             ident,
             tag: None,
             config: RustStructConfig::default(),
+            doc_comment: None,
             variant: RustStructType::RawBytesType,
         }
     }
@@ -2509,14 +4062,73 @@ impl RustStruct {
         &self.ident
     }
 
-    pub fn tag(&self) -> Option<usize> {
+    pub fn tag(&self) -> Option<u64> {
         self.tag
     }
 
+    pub fn kind(&self) -> RustStructKind {
+        self.variant.kind()
+    }
+
+    /// Types stored directly in this along with a name for each: record fields by field name,
+    /// each variant's type (or Variant.field if the fields were inlined), a wrapper's inner type,
+    /// an array's element type and a table's key and value types.
+    pub fn field_types(&self) -> Vec<(String, &RustType)> {
+        match &self.variant {
+            RustStructType::Table { domain, range } => {
+                vec![("key".to_owned(), domain), ("value".to_owned(), range)]
+            }
+            RustStructType::Array { element_type } => vec![("element".to_owned(), element_type)],
+            RustStructType::Record(record) => record
+                .fields
+                .iter()
+                .map(|field| (field.name.clone(), &field.rust_type))
+                .collect(),
+            RustStructType::TypeChoice { variants }
+            | RustStructType::GroupChoice { variants, .. } => variants
+                .iter()
+                .flat_map(|variant| match &variant.data {
+                    EnumVariantData::RustType(ty) => vec![(variant.name.to_string(), ty)],
+                    EnumVariantData::Inlined(record) => record
+                        .fields
+                        .iter()
+                        .map(|field| (format!("{}.{}", variant.name, field.name), &field.rust_type))
+                        .collect(),
+                })
+                .collect(),
+            RustStructType::Wrapper { wrapped, .. } => vec![("inner".to_owned(), wrapped)],
+            RustStructType::Extern
+            | RustStructType::CStyleEnum { .. }
+            | RustStructType::RawBytesType => {
+                vec![]
+            }
+        }
+    }
+
     pub fn config(&self) -> &RustStructConfig {
         &self.config
     }
 
+    pub fn doc_comment(&self) -> Option<&str> {
+        self.doc_comment.as_deref()
+    }
+
+    pub fn set_doc_comment(&mut self, doc_comment: String) {
+        self.doc_comment = Some(doc_comment);
+    }
+
+    /// config() but with the doc comment before the rule in front of any comment after it
+    pub fn documented_config(&self) -> RustStructConfig {
+        let doc = match (&self.doc_comment, &self.config.doc) {
+            (Some(before), Some(after)) => Some(format!("{before}\n{after}")),
+            (before, after) => before.clone().or_else(|| after.clone()),
+        };
+        RustStructConfig {
+            doc,
+            ..self.config.clone()
+        }
+    }
+
     pub fn variant(&self) -> &RustStructType {
         &self.variant
     }
@@ -2686,10 +4298,39 @@ pub struct RustRecord {
 }
 
 impl RustRecord {
+    /// Whether any stored field has floats in it. These can't derive Eq so instead they get
+    /// a manual PartialEq that compares the floats by their bits, making it an equivalence.
+    pub fn has_float_fields(&self) -> bool {
+        self.fields.iter().any(|field| {
+            !field.rust_type.is_fixed_value() && field.rust_type.conceptual_type.contains_float()
+        })
+    }
+
+    /// Whether new() can be called without arguments i.e. every field is optional,
+    /// has a .default or is a fixed value (which are not stored)
+    pub fn is_defaultable(&self) -> bool {
+        self.fields.iter().all(|field| {
+            field.optional
+                || field.rust_type.config.default.is_some()
+                || field.rust_type.is_fixed_value()
+        })
+    }
+
+    /// Fields (with their index in self.fields) in the order they're declared in the struct and new().
+    /// This is the CDDL order, unless --sort-fields is set in which case maps are sorted by name.
+    /// (De)serialization is unaffected by this.
+    pub fn declaration_order(&self, cli: &Cli) -> Vec<(usize, &RustField)> {
+        let mut fields = self.fields.iter().enumerate().collect::<Vec<_>>();
+        if cli.sort_fields && self.rep == Representation::Map {
+            fields.sort_by(|(_, lhs), (_, rhs)| lhs.name.cmp(&rhs.name));
+        }
+        fields
+    }
+
     pub fn fixed_field_count(&self, types: &IntermediateTypes) -> Option<usize> {
         let mut count = 0;
         for field in &self.fields {
-            if field.optional {
+            if field.optional || field.tail {
                 return None;
             }
             count += match self.rep {
@@ -2776,6 +4417,17 @@ impl RustRecord {
                                 field_contribution
                             ));
                         }
+                    } else if field.tail {
+                        if !conditional_field_expr.is_empty() {
+                            conditional_field_expr.push_str(" + ");
+                        }
+                        if self_expr.is_empty() {
+                            conditional_field_expr
+                                .push_str(&format!("{}.len() as u64", field.name));
+                        } else {
+                            conditional_field_expr
+                                .push_str(&format!("{}.{}.len() as u64", self_expr, field.name));
+                        }
                     } else {
                         match self.rep {
                             Representation::Array => {
@@ -2815,7 +4467,7 @@ impl RustRecord {
     pub fn expanded_mandatory_field_count(&self, types: &IntermediateTypes) -> usize {
         self.fields
             .iter()
-            .filter(|field| !field.optional)
+            .filter(|field| !field.optional && !field.tail)
             .map(|field| field.rust_type.expanded_mandatory_field_count(types))
             .sum()
     }
@@ -2857,6 +4509,24 @@ impl GenericDef {
             orig,
         }
     }
+
+    pub fn ident(&self) -> &RustIdent {
+        self.orig.ident()
+    }
+
+    pub fn generic_params(&self) -> &[RustIdent] {
+        &self.generic_params
+    }
+
+    /// Number of generic parameters e.g. 2 for foo<T, U>
+    pub fn arity(&self) -> usize {
+        self.generic_params.len()
+    }
+
+    /// The definition before any generic params are substituted
+    pub fn orig(&self) -> &RustStruct {
+        &self.orig
+    }
 }
 
 // invocation of a generic definition e.g. foo = bar<text>
@@ -2893,11 +4563,41 @@ impl GenericInstance {
         }
     }
 
+    pub fn instance_ident(&self) -> &RustIdent {
+        &self.instance_ident
+    }
+
+    /// Identifier of the GenericDef this instantiates
+    pub fn generic_ident(&self) -> &RustIdent {
+        &self.generic_ident
+    }
+
+    pub fn generic_args(&self) -> &[RustType] {
+        &self.generic_args
+    }
+
+    /// How many generic instances are nested in the arguments e.g. 1 for foo<bar<uint>>
+    pub fn nesting_depth(&self, types: &IntermediateTypes) -> usize {
+        self.generic_args
+            .iter()
+            .filter_map(|arg| match &arg.conceptual_type {
+                ConceptualRustType::Rust(ident) => types.get_generic_instance(ident),
+                _ => None,
+            })
+            .map(|instance| instance.nesting_depth(types) + 1)
+            .max()
+            .unwrap_or(0)
+    }
+
     // TODO: should we rename fields / variant names after-the-fact?
     // (for the cases where the name came from the original generic param)
-    // returns None when it can't be resolved i.e. extern defs
+    // returns Extern when it can't be resolved i.e. extern defs
     // this will be left to the user instead to handle.
-    pub fn resolve(&self, types: &IntermediateTypes, cli: &Cli) -> GenericResolved {
+    pub fn resolve(
+        &self,
+        types: &IntermediateTypes,
+        cli: &Cli,
+    ) -> Result<GenericResolved, ArityMismatchError> {
         let def = match types.generic_defs.get(&self.generic_ident) {
             Some(def) => def,
             None => {
@@ -2906,7 +4606,7 @@ impl GenericInstance {
                     .map(|rs| matches!(rs.variant(), RustStructType::Extern))
                     .unwrap_or(false)
                 {
-                    return GenericResolved::Extern {
+                    return Ok(GenericResolved::Extern {
                         instance_ident: self.instance_ident.clone(),
                         real_ident: RustIdent::new_generic(
                             &self.generic_ident,
@@ -2914,7 +4614,7 @@ impl GenericInstance {
                             types,
                             cli,
                         ),
-                    };
+                    });
                 }
                 panic!(
                     "Generic instance used on {} without definition | {:?}",
@@ -2922,7 +4622,14 @@ impl GenericInstance {
                 );
             }
         };
-        assert_eq!(def.generic_params.len(), self.generic_args.len());
+        // register_generic_instance() can't check instances registered before their definition
+        if def.arity() != self.generic_args.len() {
+            return Err(ArityMismatchError {
+                expected: def.arity(),
+                actual: self.generic_args.len(),
+                def_name: def.ident().clone(),
+            });
+        }
         let resolved_args = def
             .generic_params
             .iter()
@@ -2960,8 +4667,8 @@ impl GenericInstance {
                 // }
                 todo!("we might need to recursively resolve on these");
             }
-            RustStructType::Wrapper { .. } => {
-                todo!("should we look this up in types to resolve?");
+            RustStructType::Wrapper { wrapped, .. } => {
+                *wrapped = Self::resolve_type(&resolved_args, wrapped);
             }
             RustStructType::Extern => {
                 panic!("generics should not be used on types in the prelude (e.g. int)")
@@ -2970,16 +4677,36 @@ impl GenericInstance {
                 panic!("generics not supported on raw bytes types")
             }
         };
-        GenericResolved::Resolved(instance)
+        Ok(GenericResolved::Resolved(instance))
     }
 
+    // Params can be nested anywhere in the type e.g. #6.24(T) or [* T] and any encodings
+    // applied to them there (tags, .cbor) go around whatever they're resolved to.
     fn resolve_type(args: &BTreeMap<&RustIdent, &RustType>, orig: &RustType) -> RustType {
-        if let ConceptualRustType::Rust(ident) = &orig.conceptual_type {
-            if let Some(resolved_type) = args.get(ident) {
-                return (*resolved_type).clone();
+        let resolve_boxed = |ty: &RustType| Box::new(Self::resolve_type(args, ty));
+        let conceptual_type = match &orig.conceptual_type {
+            ConceptualRustType::Rust(ident) => match args.get(ident) {
+                Some(resolved_type) => {
+                    let mut resolved = (*resolved_type).clone();
+                    resolved.encodings.extend(orig.encodings.iter().cloned());
+                    return resolved;
+                }
+                None => return orig.clone(),
+            },
+            ConceptualRustType::Array(ty) => ConceptualRustType::Array(resolve_boxed(ty)),
+            ConceptualRustType::Optional(ty) => ConceptualRustType::Optional(resolve_boxed(ty)),
+            ConceptualRustType::Result(ok, err) => {
+                ConceptualRustType::Result(resolve_boxed(ok), resolve_boxed(err))
+            }
+            ConceptualRustType::Map(k, v) => {
+                ConceptualRustType::Map(resolve_boxed(k), resolve_boxed(v))
             }
+            _ => return orig.clone(),
+        };
+        RustType {
+            conceptual_type,
+            ..orig.clone()
         }
-        orig.clone()
     }
 }
 
@@ -2992,6 +4719,29 @@ fn enum_variant_constant(variant: &EnumVariant) -> Option<FixedValue> {
     None
 }
 
+/// The text of every variant if they're all text constants e.g. "mainnet" / "testnet"
+pub fn enum_variants_text(variants: &[EnumVariant]) -> Option<Vec<String>> {
+    variants
+        .iter()
+        .map(|ev| match enum_variant_constant(ev) {
+            Some(FixedValue::Text(text)) => Some(text),
+            _ => None,
+        })
+        .collect()
+}
+
+/// The value of every variant if they're all integer constants e.g. 0 / 2 / -1
+pub fn enum_variants_ints(variants: &[EnumVariant]) -> Option<Vec<i128>> {
+    variants
+        .iter()
+        .map(|ev| match enum_variant_constant(ev) {
+            Some(FixedValue::Uint(u)) => Some(u as i128),
+            Some(FixedValue::Nint(i)) => Some(i as i128),
+            _ => None,
+        })
+        .collect()
+}
+
 pub fn enum_variants_have_same_encoding_var(variants: &[EnumVariant]) -> bool {
     variants
         .iter()
@@ -3008,8 +4758,8 @@ pub fn enum_variants_have_same_encoding_var(variants: &[EnumVariant]) -> bool {
                     (Some(FixedValue::Text(_)), Some(FixedValue::Text(_))) => acc,
                     // these don't have any encoding vars
                     (
-                        Some(FixedValue::Bool(_) | FixedValue::Null),
-                        Some(FixedValue::Bool(_) | FixedValue::Null),
+                        Some(FixedValue::Bool(_) | FixedValue::Null | FixedValue::SimpleValue(_)),
+                        Some(FixedValue::Bool(_) | FixedValue::Null | FixedValue::SimpleValue(_)),
                     ) => acc,
                     _ => None,
                 }
@@ -3038,6 +4788,7 @@ pub fn _enum_variants_common_constant_type(variants: &[EnumVariant]) -> Option<F
                 (Some(FixedValue::Bool(_)), Some(FixedValue::Bool(_))) => acc,
                 (Some(FixedValue::Float(_)), Some(FixedValue::Float(_))) => acc,
                 (Some(FixedValue::Null), Some(FixedValue::Null)) => acc,
+                (Some(FixedValue::SimpleValue(_)), Some(FixedValue::SimpleValue(_))) => acc,
                 (Some(FixedValue::Text(_)), Some(FixedValue::Text(_))) => acc,
                 _ => None,
             }
@@ -8,12 +8,9 @@ pub(crate) mod rust_reserved;
 pub(crate) mod utils;
 
 use clap::Parser;
-use cli::Cli;
-use comment_ast::RuleMetadata;
+use cli::{CborCrate, Cli, Visibility};
 use generation::GenerationScope;
-use intermediate::{CDDLIdent, IntermediateTypes, PlainGroupInfo, RustIdent};
 use once_cell::sync::Lazy;
-use parsing::{parse_rule, rule_ident, rule_is_scope_marker};
 
 pub static CLI_ARGS: Lazy<Cli> = Lazy::new(Cli::parse);
 
@@ -36,7 +33,133 @@ fn cddl_paths(
     Ok(())
 }
 
+fn file_paths(
+    output: &mut Vec<std::path::PathBuf>,
+    root: &std::path::Path,
+    dir: &std::path::Path,
+) -> std::io::Result<()> {
+    for dir_entry in std::fs::read_dir(dir)? {
+        let path = dir_entry?.path();
+        if path.is_dir() {
+            file_paths(output, root, &path)?;
+        } else {
+            output.push(pathdiff::diff_paths(&path, root).unwrap());
+        }
+    }
+    Ok(())
+}
+
+/// Prints a minimal diff of two files: the lines between their common prefix and suffix.
+fn print_diff(path: &std::path::Path, expected: &[u8], generated: &[u8]) {
+    println!("--- {} (existing)", path.display());
+    println!("+++ {} (generated)", path.display());
+    let (expected, generated) = match (
+        std::str::from_utf8(expected),
+        std::str::from_utf8(generated),
+    ) {
+        (Ok(expected), Ok(generated)) => (expected, generated),
+        _ => {
+            println!("binary files differ");
+            return;
+        }
+    };
+    let old_lines = expected.lines().collect::<Vec<_>>();
+    let new_lines = generated.lines().collect::<Vec<_>>();
+    let prefix = old_lines
+        .iter()
+        .zip(new_lines.iter())
+        .take_while(|(a, b)| a == b)
+        .count();
+    let suffix = old_lines[prefix..]
+        .iter()
+        .rev()
+        .zip(new_lines[prefix..].iter().rev())
+        .take_while(|(a, b)| a == b)
+        .count();
+    println!(
+        "@@ -{},{} +{},{} @@",
+        prefix + 1,
+        old_lines.len() - prefix - suffix,
+        prefix + 1,
+        new_lines.len() - prefix - suffix
+    );
+    for line in &old_lines[prefix..old_lines.len() - suffix] {
+        println!("-{line}");
+    }
+    for line in &new_lines[prefix..new_lines.len() - suffix] {
+        println!("+{line}");
+    }
+}
+
+/// Compares the freshly generated directory against the existing output directory.
+/// Returns whether they are identical, printing any differences found.
+fn compare_output(
+    existing: &std::path::Path,
+    generated: &std::path::Path,
+) -> std::io::Result<bool> {
+    let mut existing_files = Vec::new();
+    if existing.is_dir() {
+        file_paths(&mut existing_files, existing, existing)?;
+    }
+    let mut generated_files = Vec::new();
+    file_paths(&mut generated_files, generated, generated)?;
+    existing_files.sort();
+    generated_files.sort();
+    let mut identical = true;
+    for path in &generated_files {
+        if !existing_files.contains(path) {
+            println!("missing file: {}", path.display());
+            identical = false;
+            continue;
+        }
+        let expected = std::fs::read(existing.join(path))?;
+        let actual = std::fs::read(generated.join(path))?;
+        if expected != actual {
+            print_diff(path, &expected, &actual);
+            identical = false;
+        }
+    }
+    for path in existing_files
+        .iter()
+        .filter(|path| !generated_files.contains(path))
+    {
+        println!("extra file: {}", path.display());
+        identical = false;
+    }
+    Ok(identical)
+}
+
 fn main() -> Result<(), Box<dyn std::error::Error>> {
+    if CLI_ARGS.fixed_size_byte_arrays && CLI_ARGS.wasm {
+        return Err("--fixed-size-byte-arrays is not supported with --wasm=true".into());
+    }
+    if CLI_ARGS.cbor_crate == CborCrate::Minicbor && CLI_ARGS.preserve_encodings {
+        return Err("--cbor-crate=minicbor is not supported with --preserve-encodings".into());
+    }
+    // the CBOR serde impls would conflict with the derived JSON ones
+    if CLI_ARGS.cbor_crate == CborCrate::Ciborium && CLI_ARGS.json_serde_derives {
+        return Err("--cbor-crate=ciborium is not supported with --json-serde-derives".into());
+    }
+    // the wasm crate is a separate crate wrapping the rust types so they must be pub
+    if CLI_ARGS.visibility != Visibility::Pub && CLI_ARGS.wasm {
+        return Err("--visibility other than pub requires --wasm=false".into());
+    }
+    if CLI_ARGS.no_std {
+        if CLI_ARGS.cbor_crate != CborCrate::Minicbor {
+            return Err(
+                "--no-std requires --cbor-crate=minicbor as cbor_event depends on std".into(),
+            );
+        }
+        if CLI_ARGS.wasm {
+            return Err("--no-std is not supported with --wasm".into());
+        }
+        if CLI_ARGS.json_serde_derives {
+            return Err("--no-std is not supported with --json-serde-derives".into());
+        }
+        if CLI_ARGS.cbor_value_conversions {
+            return Err("--no-std is not supported with --cbor-value-conversions".into());
+        }
+    }
     // Pre-processing files for multi-file support
     let input_files = if CLI_ARGS.input.is_dir() {
         let mut cddl_paths_buf = Vec::new();
@@ -96,67 +219,42 @@ fn main() -> Result<(), Box<dyn std::error::Error>> {
         .collect::<Result<String, _>>()?;
     let export_raw_bytes_encoding_trait = input_files_content.contains(parsing::RAW_BYTES_MARKER);
     // we also need to mark the extern marker to a placeholder struct that won't get codegened
-    input_files_content.push_str(&format!("{} = [0]", parsing::EXTERN_MARKER));
     // and a raw bytes one too
-    input_files_content.push_str(&format!("{} = [1]", parsing::RAW_BYTES_MARKER));
+    parsing::append_marker_rules(&mut input_files_content);
 
-    // Plain group / scope marking
-    let cddl = cddl::parser::cddl_from_str(&input_files_content, true)?;
-    //panic!("cddl: {:#?}", cddl);
-    let pv = cddl::ast::parent::ParentVisitor::new(&cddl).unwrap();
-    let mut types = IntermediateTypes::new();
-    // mark scope and filter scope markers
-    let mut scope = ROOT_SCOPE.clone();
-    let cddl_rules = cddl
-        .rules
-        .iter()
-        .filter(|cddl_rule| {
-            // We inserted string constants with specific prefixes earlier to mark scope
-            if let Some(new_scope) = rule_is_scope_marker(cddl_rule) {
-                println!("Switching from scope '{scope}' to '{new_scope}'");
-                scope = new_scope;
-                false
-            } else {
-                let ident = rule_ident(cddl_rule);
-                types.mark_scope(ident, scope.clone());
-                true
-            }
-        })
-        .collect::<Vec<_>>();
-    // We need to know beforehand which are plain groups so we can serialize them properly
-    // e.g. x = (3, 4), y = [1, x, 2] should be [1, 3, 4, 2] instead of [1, [3, 4], 2]
-    for cddl_rule in cddl_rules.iter() {
-        if let cddl::ast::Rule::Group { rule, .. } = cddl_rule {
-            // Freely defined group - no need to generate anything outside of group module
-            match &rule.entry {
-                cddl::ast::GroupEntry::InlineGroup {
-                    group,
-                    comments_after_group,
-                    ..
-                } => {
-                    assert_eq!(group.group_choices.len(), 1);
-                    let rule_metadata = RuleMetadata::from(comments_after_group.as_ref());
-                    types.mark_plain_group(
-                        RustIdent::new(CDDLIdent::new(rule.name.to_string())),
-                        PlainGroupInfo::new(Some(group.clone()), rule_metadata),
-                    );
-                }
-                x => panic!("Group rule with non-inline group? {:?}", x),
-            }
-        }
+    let types = parsing::parse_cddl_str(&input_files_content, &CLI_ARGS)?;
+    if CLI_ARGS.no_std {
+        generation::check_no_std_support(&types)?;
     }
 
-    // Creating intermediate form from the CDDL
-    for cddl_rule in dep_graph::topological_rule_order(&cddl_rules) {
-        println!("\n\n------------------------------------------\n- Handling rule: {}:{}\n------------------------------------", scope, cddl_rule.name());
-        parse_rule(&mut types, &pv, cddl_rule, &CLI_ARGS);
+    if let Some(dot_file) = &CLI_ARGS.type_graph_dot {
+        std::fs::write(dot_file, dep_graph::type_graph_dot(&types))?;
     }
-    types.finalize(&pv, &CLI_ARGS);
 
     // Generating code from intermediate form
     println!("\n-----------------------------------------\n- Generating code...\n------------------------------------");
     let mut gen_scope = GenerationScope::new();
     gen_scope.generate(&types, &CLI_ARGS);
+    if CLI_ARGS.check {
+        let mut check_args = CLI_ARGS.clone();
+        check_args.output =
+            std::env::temp_dir().join(format!("cddl-codegen-check-{}", std::process::id()));
+        if check_args.output.exists() {
+            std::fs::remove_dir_all(&check_args.output)?;
+        }
+        gen_scope.export(&types, export_raw_bytes_encoding_trait, &check_args)?;
+        let identical = compare_output(&CLI_ARGS.output, &check_args.output);
+        std::fs::remove_dir_all(&check_args.output)?;
+        if !identical? {
+            println!(
+                "\nGenerated code differs from {}",
+                CLI_ARGS.output.display()
+            );
+            std::process::exit(1);
+        }
+        println!("\nGenerated code matches {}", CLI_ARGS.output.display());
+        return Ok(());
+    }
     gen_scope.export(&types, export_raw_bytes_encoding_trait, &CLI_ARGS)?;
     types.print_info();
 
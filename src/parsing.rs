@@ -1,26 +1,35 @@
 use crate::cli::Cli;
 use cddl::ast::parent::ParentVisitor;
 use cddl::{ast::*, token};
-use std::collections::BTreeMap;
+use std::collections::{BTreeMap, BTreeSet};
 
 use crate::comment_ast::{merge_metadata, metadata_from_comments, RuleMetadata};
+use crate::dep_graph;
 use crate::intermediate::{
-    AliasInfo, CBOREncodingOperation, CDDLIdent, ConceptualRustType, EnumVariant, FixedValue,
-    GenericDef, GenericInstance, IntermediateTypes, ModuleScope, PlainGroupInfo, Primitive,
-    Representation, RustField, RustIdent, RustRecord, RustStruct, RustStructType, RustType,
-    VariantIdent,
+    enum_variants_ints, enum_variants_text, AliasDepthExceeded, AliasIdent, AliasInfo,
+    ArityMismatchError, CBOREncodingOperation, CDDLIdent, ConceptualRustType, EnumVariant,
+    EnumVariantData, FixedValue, FloatBound, GenericDef, GenericInstance, IntermediateTypes,
+    ModuleScope, Occurrence, PlainGroupInfo, Primitive, RegexFlavor, RegexPattern, Representation,
+    RustField, RustIdent, RustRecord, RustStruct, RustStructType, RustType, UnresolvedRef,
+    VariantIdent, ROOT_SCOPE,
 };
 use crate::utils::{
     append_number_if_duplicate, convert_to_camel_case, convert_to_snake_case,
-    is_identifier_user_defined,
+    is_identifier_user_defined, is_valid_rust_ident,
 };
 
 #[derive(Clone, Debug)]
 #[allow(clippy::upper_case_acronyms)]
 enum ControlOperator {
     Range((Option<i128>, Option<i128>)),
-    CBOR(RustType),
+    /// Range on a float e.g. float .lt 1.5 or 0.5...1.5
+    FloatRange((Option<FloatBound>, Option<FloatBound>)),
+    /// None for .cbor any
+    CBOR(Option<RustType>),
     Default(FixedValue),
+    Regex(RegexPattern),
+    /// .cat of text constants, already concatenated
+    Cat(String),
 }
 
 pub const SCOPE_MARKER: &str = "_CDDL_CODEGEN_SCOPE_MARKER_";
@@ -28,6 +37,390 @@ pub const EXTERN_DEPS_DIR: &str = "_CDDL_CODEGEN_EXTERN_DEPS_DIR_";
 pub const EXTERN_MARKER: &str = "_CDDL_CODEGEN_EXTERN_TYPE_";
 pub const RAW_BYTES_MARKER: &str = "_CDDL_CODEGEN_RAW_BYTES_TYPE_";
 
+/// A syntax error reported by the cddl parser
+#[derive(Debug)]
+pub struct SyntaxError {
+    /// 0 if the cddl parser gave no position for the error
+    pub line: usize,
+    pub column: usize,
+    pub message: String,
+}
+
+impl From<&cddl::parser::Error> for SyntaxError {
+    fn from(error: &cddl::parser::Error) -> Self {
+        let (line, column) = match error {
+            cddl::parser::Error::PARSER { position, .. } => (position.line, position.column),
+            cddl::parser::Error::LEXER(lexer_error) => {
+                (lexer_error.position.line, lexer_error.position.column)
+            }
+            _ => (0, 0),
+        };
+        Self {
+            line,
+            column,
+            message: error.to_string(),
+        }
+    }
+}
+
+/// Where in the input a construct starts. Both are 1-based.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct SourceLocation {
+    pub line: usize,
+    pub column: usize,
+}
+
+/// An error about a specific construct in the spec
+#[derive(Debug)]
+pub struct ErrorDetails {
+    pub message: String,
+    /// Set by parse_cddl_str() from the offending construct's span.
+    /// Lines are counted in the whole input so for multiple files they're after concatenation.
+    pub location: Option<SourceLocation>,
+    span: Option<Span>,
+}
+
+impl ErrorDetails {
+    pub(crate) fn new(message: String) -> Self {
+        Self {
+            message,
+            location: None,
+            span: None,
+        }
+    }
+
+    /// Points the error at the construct with this span
+    pub(crate) fn at(mut self, span: Span) -> Self {
+        self.span = Some(span);
+        self
+    }
+
+    fn resolve_location(&mut self, input: &str) {
+        // the span's start is a byte offset into the input
+        if let Some(before) = self.span.and_then(|(start, _, _)| input.get(..start)) {
+            let line_start = before.rfind('\n').map(|i| i + 1).unwrap_or(0);
+            self.location = Some(SourceLocation {
+                line: before.matches('\n').count() + 1,
+                column: before[line_start..].chars().count() + 1,
+            });
+        }
+    }
+}
+
+#[derive(Debug)]
+pub enum CddlError {
+    /// The input was not valid CDDL
+    Syntax(Vec<SyntaxError>),
+    AliasDepthExceeded(AliasDepthExceeded),
+    ArityMismatch(ArityMismatchError),
+    /// References to types that were never defined, found after parsing everything
+    UnresolvedReferences(Vec<UnresolvedRef>),
+    /// Valid CDDL that code can't be generated for (yet)
+    Unsupported(ErrorDetails),
+    /// A control operator (e.g. .size) that isn't supported or is used on an unsupported type
+    UnsupportedControlOperator(ErrorDetails),
+    /// Generic parameters/arguments used somewhere they aren't supported
+    UnsupportedGenerics(ErrorDetails),
+    /// A comment DSL annotation (e.g. @result) used somewhere it isn't allowed
+    InvalidAnnotation(ErrorDetails),
+    /// A literal that can't be used where it is e.g. a decimal as a range bound
+    InvalidLiteral(ErrorDetails),
+    /// An inlined group without any name to generate a type for it with
+    AnonymousGroup(ErrorDetails),
+    /// A field without a derivable name under --strict-field-names
+    UnnamedField(ErrorDetails),
+}
+
+impl std::fmt::Display for CddlError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        // same line:column: prefix as the syntax errors
+        if let Some(location) = self.details().and_then(|details| details.location) {
+            write!(f, "{}:{}: ", location.line, location.column)?;
+        }
+        match self {
+            Self::Syntax(errors) => {
+                for (i, error) in errors.iter().enumerate() {
+                    if i > 0 {
+                        writeln!(f)?;
+                    }
+                    write!(f, "{}:{}: {}", error.line, error.column, error.message)?;
+                }
+                Ok(())
+            }
+            Self::AliasDepthExceeded(e) => e.fmt(f),
+            Self::ArityMismatch(e) => e.fmt(f),
+            Self::UnresolvedReferences(unresolved) => {
+                for (i, unresolved_ref) in unresolved.iter().enumerate() {
+                    if i > 0 {
+                        writeln!(f)?;
+                    }
+                    write!(f, "{unresolved_ref}")?;
+                }
+                Ok(())
+            }
+            Self::Unsupported(details) => write!(f, "unsupported: {}", details.message),
+            Self::UnsupportedControlOperator(details) => {
+                write!(f, "unsupported control operator: {}", details.message)
+            }
+            Self::UnsupportedGenerics(details) => {
+                write!(f, "unsupported generics: {}", details.message)
+            }
+            Self::InvalidAnnotation(details) => {
+                write!(f, "invalid annotation: {}", details.message)
+            }
+            Self::InvalidLiteral(details) => write!(f, "invalid literal: {}", details.message),
+            Self::AnonymousGroup(details) => write!(f, "anonymous group: {}", details.message),
+            Self::UnnamedField(details) => write!(f, "unnamed field: {}", details.message),
+        }
+    }
+}
+
+impl CddlError {
+    pub fn details(&self) -> Option<&ErrorDetails> {
+        match self {
+            Self::Syntax(_)
+            | Self::AliasDepthExceeded(_)
+            | Self::ArityMismatch(_)
+            | Self::UnresolvedReferences(_) => None,
+            Self::Unsupported(details)
+            | Self::UnsupportedControlOperator(details)
+            | Self::UnsupportedGenerics(details)
+            | Self::InvalidAnnotation(details)
+            | Self::InvalidLiteral(details)
+            | Self::AnonymousGroup(details)
+            | Self::UnnamedField(details) => Some(details),
+        }
+    }
+
+    /// Valid CDDL that we can't generate code for, as opposed to a mistake in the input
+    pub fn is_unsupported(&self) -> bool {
+        matches!(
+            self,
+            Self::Unsupported(_)
+                | Self::UnsupportedControlOperator(_)
+                | Self::UnsupportedGenerics(_)
+        )
+    }
+
+    fn details_mut(&mut self) -> Option<&mut ErrorDetails> {
+        match self {
+            Self::Syntax(_)
+            | Self::AliasDepthExceeded(_)
+            | Self::ArityMismatch(_)
+            | Self::UnresolvedReferences(_) => None,
+            Self::Unsupported(details)
+            | Self::UnsupportedControlOperator(details)
+            | Self::UnsupportedGenerics(details)
+            | Self::InvalidAnnotation(details)
+            | Self::InvalidLiteral(details)
+            | Self::AnonymousGroup(details)
+            | Self::UnnamedField(details) => Some(details),
+        }
+    }
+
+    /// Points the error at the construct with this span unless it already
+    /// points at a more specific one inside of it.
+    fn or_at(mut self, span: Span) -> Self {
+        if let Some(details) = self.details_mut() {
+            details.span.get_or_insert(span);
+        }
+        self
+    }
+
+    /// Locates the error in input, falling back to the rule it happened in
+    fn locate(mut self, input: &str, rule: &Rule) -> Self {
+        self = self.or_at(rule_span(rule));
+        if let Some(details) = self.details_mut() {
+            details.resolve_location(input);
+        }
+        self
+    }
+}
+
+fn rule_span(rule: &Rule) -> Span {
+    match rule {
+        Rule::Type { span, .. } | Rule::Group { span, .. } => *span,
+    }
+}
+
+// Regular (not @annotation) comment lines directly above a rule e.g. `; a foo\nfoo = [uint]`.
+// These are taken from the input as the parser attaches them to whatever came before.
+fn leading_doc_comment(input: &str, rule: &Rule) -> Option<String> {
+    let before = &input[..rule_span(rule).0];
+    let line_start = before.rfind('\n').map(|i| i + 1).unwrap_or(0);
+    if !before[line_start..].trim().is_empty() {
+        return None;
+    }
+    let mut lines = before[..line_start]
+        .lines()
+        .rev()
+        .map_while(|line| line.trim().strip_prefix(';'))
+        .map(|comment| comment.trim_start_matches(';').trim())
+        .filter(|comment| !comment.starts_with('@'))
+        .collect::<Vec<_>>();
+    lines.reverse();
+    let doc_comment = lines.join("\n");
+    (!doc_comment.trim().is_empty()).then(|| doc_comment.trim().to_owned())
+}
+
+impl std::error::Error for CddlError {}
+
+impl From<AliasDepthExceeded> for CddlError {
+    fn from(e: AliasDepthExceeded) -> Self {
+        Self::AliasDepthExceeded(e)
+    }
+}
+
+impl From<ArityMismatchError> for CddlError {
+    fn from(e: ArityMismatchError) -> Self {
+        Self::ArityMismatch(e)
+    }
+}
+
+/// Defines the marker types (e.g. _CDDL_CODEGEN_EXTERN_TYPE_) so that specs
+/// using them still pass the cddl parser's check for undefined rules.
+pub fn append_marker_rules(input: &mut String) {
+    input.push_str(&format!("\n{EXTERN_MARKER} = [0]"));
+    input.push_str(&format!("\n{RAW_BYTES_MARKER} = [1]"));
+}
+
+fn parse_cddl_ast(input: &str) -> Result<CDDL<'_>, CddlError> {
+    // as in cddl_from_str() the lexer is a temporary that must outlive the parser
+    match cddl::parser::Parser::new(input, Box::new(cddl::lexer::lexer_from_str(input).iter())) {
+        Ok(mut parser) => match parser.parse_cddl() {
+            Ok(cddl) => Ok(cddl),
+            // the individual errors are collected in the parser in this case
+            Err(cddl::parser::Error::INCREMENTAL) => Err(CddlError::Syntax(
+                parser.errors.iter().map(SyntaxError::from).collect(),
+            )),
+            Err(e) => Err(CddlError::Syntax(vec![SyntaxError::from(&e)])),
+        },
+        Err(e) => Err(CddlError::Syntax(vec![SyntaxError::from(&e)])),
+    }
+}
+
+/// Parses a CDDL spec into the intermediate form that code is generated from.
+/// Rules go into the root scope unless the input contains scope markers (see main.rs)
+/// and the marker types need to be defined with append_marker_rules() if used.
+pub fn parse_cddl_str<'a>(input: &'a str, cli: &Cli) -> Result<IntermediateTypes<'a>, CddlError> {
+    let cddl = parse_cddl_ast(input)?;
+    let pv = ParentVisitor::new(&cddl).unwrap();
+    let mut types = IntermediateTypes::new();
+    // mark scope and filter scope markers
+    let mut scope = ROOT_SCOPE.clone();
+    let mut cddl_rules = Vec::new();
+    for cddl_rule in cddl.rules.iter() {
+        // We inserted string constants with specific prefixes earlier to mark scope
+        if let Some(new_scope) = rule_is_scope_marker(cddl_rule) {
+            println!("Switching from scope '{scope}' to '{new_scope}'");
+            scope = new_scope;
+        } else {
+            let ident = rule_ident(cddl_rule).map_err(|e| e.locate(input, cddl_rule))?;
+            if let Some(message) = non_ascii_ident_error(
+                &format!("rule name \"{}\"", cddl_rule.name()),
+                &ident.to_string(),
+            ) {
+                return Err(
+                    CddlError::Unsupported(ErrorDetails::new(message)).locate(input, cddl_rule)
+                );
+            }
+            types.mark_scope(ident, scope.clone());
+            cddl_rules.push(cddl_rule);
+        }
+    }
+    // We need to know beforehand which are plain groups so we can serialize them properly
+    // e.g. x = (3, 4), y = [1, x, 2] should be [1, 3, 4, 2] instead of [1, [3, 4], 2]
+    // `foo //= (...)` extensions can come before `foo = (...)` so they're marked once all groups are
+    let mut group_extensions = Vec::new();
+    for cddl_rule in cddl_rules.iter() {
+        if let Rule::Group { rule, .. } = cddl_rule {
+            // Freely defined group - no need to generate anything outside of group module
+            match &rule.entry {
+                GroupEntry::InlineGroup {
+                    group,
+                    comments_after_group,
+                    ..
+                } => {
+                    if group.group_choices.len() != 1 {
+                        return Err(CddlError::Unsupported(
+                            ErrorDetails::new(format!(
+                                "{}: group choices in plain groups",
+                                rule.name
+                            ))
+                            .at(group.span),
+                        )
+                        .locate(input, cddl_rule));
+                    }
+                    let ident = RustIdent::new(CDDLIdent::new(rule.name.to_string()));
+                    if rule.is_group_choice_alternate {
+                        group_extensions.push((cddl_rule, ident, group));
+                    } else {
+                        let rule_metadata = RuleMetadata::from(comments_after_group.as_ref());
+                        types.mark_plain_group(
+                            ident,
+                            PlainGroupInfo::new(Some(group.clone()), rule_metadata),
+                        );
+                    }
+                }
+                // rule_ident() already checked this
+                _ => unreachable!(),
+            }
+        }
+    }
+    for (cddl_rule, ident, group) in group_extensions {
+        // appends its fields to the `foo` group in the order the extensions appear
+        types
+            .mark_plain_group_extension(&ident, group.clone())
+            .map_err(|e| e.locate(input, cddl_rule))?;
+    }
+
+    // Likewise `$foo /= ...` socket extensions can come before or after any other definition
+    for cddl_rule in cddl_rules.iter() {
+        if let Rule::Type { rule, .. } = cddl_rule {
+            if rule.name.socket.is_some() {
+                types.mark_type_socket(
+                    RustIdent::new(CDDLIdent::new(rule.name.to_string())),
+                    &rule.value.type_choices,
+                    rule.is_type_choice_alternate,
+                );
+            }
+        }
+    }
+
+    // Creating intermediate form from the CDDL
+    for cddl_rule in dep_graph::topological_rule_order(&cddl_rules) {
+        println!("\n\n------------------------------------------\n- Handling rule: {}:{}\n------------------------------------", scope, cddl_rule.name());
+        match parse_rule(&mut types, &pv, cddl_rule, cli) {
+            Ok(()) => {
+                if let Some(doc_comment) = leading_doc_comment(input, cddl_rule) {
+                    let ident = rule_ident(cddl_rule).map_err(|e| e.locate(input, cddl_rule))?;
+                    types.set_doc_comment(&ident, doc_comment);
+                }
+            }
+            Err(e) if cli.warn_unsupported && e.is_unsupported() => match cddl_rule {
+                Rule::Type { rule, .. } if rule.generic_params.is_none() => {
+                    eprintln!(
+                        "Warning: {}\n{} will be generated as RawCbor instead",
+                        e.locate(input, cddl_rule),
+                        rule.name
+                    );
+                    types.register_raw_cbor_placeholder(RustIdent::new(CDDLIdent::new(
+                        rule.name.to_string(),
+                    )));
+                }
+                _ => return Err(e.locate(input, cddl_rule)),
+            },
+            Err(e) => return Err(e.locate(input, cddl_rule)),
+        }
+    }
+    types.finalize(&pv, cli)?;
+    let unresolved = types.validate_completeness();
+    if !unresolved.is_empty() {
+        return Err(CddlError::UnresolvedReferences(unresolved));
+    }
+    Ok(types)
+}
+
 /// Some means it is a scope marker, containing the scope
 pub fn rule_is_scope_marker(cddl_rule: &cddl::ast::Rule) -> Option<ModuleScope> {
     match cddl_rule {
@@ -60,7 +453,7 @@ pub fn parse_rule(
     parent_visitor: &ParentVisitor,
     cddl_rule: &cddl::ast::Rule,
     cli: &Cli,
-) {
+) -> Result<(), CddlError> {
     match cddl_rule {
         cddl::ast::Rule::Type { rule, .. } => {
             let rust_ident = RustIdent::new(CDDLIdent::new(rule.name.to_string()));
@@ -71,19 +464,34 @@ pub fn parse_rule(
                 // ignore - this was inserted by us so that cddl's parsing succeeds
                 // see comments in main.rs
             } else {
-                // (1) is_type_choice_alternate ignored since shelley.cddl doesn't need it
-                //     It's used, but used for no reason as it is the initial definition
-                //     (which is also valid cddl), but it would be fine as = instead of /=
+                // (1) is_type_choice_alternate is only supported for $foo sockets. On regular
+                //     rules only the initial definition is used.
                 // (2) ignores control operators - only used in shelley spec to limit string length for application metadata
 
+                // dep_graph only gives us one rule per name so sockets take the choices of all of them
+                let socket_choices = types
+                    .type_socket(&rust_ident)
+                    .filter(|_| rule.name.socket.is_some())
+                    .map(|socket| {
+                        if !socket.is_extended() {
+                            eprintln!(
+                                "Warning: socket {} is never extended via /=. Sockets are meant for extensible types so consider a regular rule instead",
+                                rule.name
+                            );
+                        }
+                        socket.choices().to_vec()
+                    });
+                let type_choices = socket_choices
+                    .as_deref()
+                    .unwrap_or(&rule.value.type_choices);
                 let generic_params = rule.generic_params.as_ref().map(|gp| {
                     gp.params
                         .iter()
                         .map(|id| RustIdent::new(CDDLIdent::new(id.param.to_string())))
                         .collect::<Vec<_>>()
                 });
-                if rule.value.type_choices.len() == 1 {
-                    let choice = &rule.value.type_choices.first().unwrap();
+                if type_choices.len() == 1 {
+                    let choice = &type_choices.first().unwrap();
                     parse_type(
                         types,
                         parent_visitor,
@@ -92,44 +500,51 @@ pub fn parse_rule(
                         None,
                         generic_params,
                         cli,
-                    );
+                    )?;
                 } else {
                     parse_type_choices(
                         types,
                         parent_visitor,
                         &rust_ident,
-                        &rule.value.type_choices,
+                        type_choices,
                         None,
                         generic_params,
                         cli,
-                    );
+                    )?;
                 }
             }
         }
         cddl::ast::Rule::Group { rule, .. } => {
-            assert_eq!(
-                rule.generic_params, None,
-                "{}: Generics not supported on plain groups",
-                rule.name
-            );
-            // Freely defined group - no need to generate anything outside of group module
-            // already handled in main.rs
-            match &rule.entry {
-                cddl::ast::GroupEntry::InlineGroup { .. } => (),
-                x => panic!("Group rule with non-inline group? {:?}", x),
+            if let Some(generic_params) = &rule.generic_params {
+                return Err(CddlError::UnsupportedGenerics(
+                    ErrorDetails::new(format!("{}: generics on plain groups", rule.name))
+                        .at(generic_params.span),
+                ));
             }
+            // Freely defined group - no need to generate anything outside of group module
+            // already handled in parse_cddl_str() (including //= extensions)
+            rule_ident(cddl_rule)?;
         }
     }
+    Ok(())
 }
 
-pub fn rule_ident(cddl_rule: &cddl::ast::Rule) -> RustIdent {
+pub fn rule_ident(cddl_rule: &cddl::ast::Rule) -> Result<RustIdent, CddlError> {
     match cddl_rule {
-        cddl::ast::Rule::Type { rule, .. } => RustIdent::new(CDDLIdent::new(rule.name.to_string())),
+        cddl::ast::Rule::Type { rule, .. } => {
+            Ok(RustIdent::new(CDDLIdent::new(rule.name.to_string())))
+        }
         cddl::ast::Rule::Group { rule, .. } => match &rule.entry {
             cddl::ast::GroupEntry::InlineGroup { .. } => {
-                RustIdent::new(CDDLIdent::new(rule.name.to_string()))
+                Ok(RustIdent::new(CDDLIdent::new(rule.name.to_string())))
             }
-            x => panic!("Group rule with non-inline group? {:?}", x),
+            x => Err(CddlError::Unsupported(
+                ErrorDetails::new(format!(
+                    "{}: group rule with non-inline group {:?}",
+                    rule.name, x
+                ))
+                .at(group_entry_span(x)),
+            )),
         },
     }
 }
@@ -139,10 +554,22 @@ fn parse_type_choices(
     parent_visitor: &ParentVisitor,
     name: &RustIdent,
     type_choices: &[TypeChoice],
-    tag: Option<usize>,
+    tag: Option<u64>,
     generic_params: Option<Vec<RustIdent>>,
     cli: &Cli,
-) {
+) -> Result<(), CddlError> {
+    let rule_metadata = merge_metadata(
+        &RuleMetadata::from(
+            type_choices
+                .last()
+                .and_then(|tc| tc.comments_after_type.as_ref()),
+        ),
+        &RuleMetadata::from(
+            type_choices
+                .last()
+                .and_then(|tc| tc.type1.comments_after_type.as_ref()),
+        ),
+    );
     let optional_inner_type = if type_choices.len() == 2 {
         let a = &type_choices[0].type1;
         let b = &type_choices[1].type1;
@@ -160,9 +587,11 @@ fn parse_type_choices(
         if generic_params.is_some() {
             // the current generic support relies on having a RustStruct to swap out the types with
             // but that won't happen with T / null types since we generate an alias instead
-            todo!("support foo<T> = T / null");
+            return Err(CddlError::UnsupportedGenerics(
+                ErrorDetails::new(format!("{}: foo<T> = T / null", name)).at(inner_type2.span),
+            ));
         }
-        let inner_rust_type = rust_type_from_type1(types, parent_visitor, inner_type2, cli);
+        let inner_rust_type = rust_type_from_type1(types, parent_visitor, inner_type2, cli)?;
         let final_type = match tag {
             Some(tag) => {
                 RustType::new(ConceptualRustType::Optional(Box::new(inner_rust_type))).tag(tag)
@@ -174,76 +603,274 @@ fn parse_type_choices(
             name.clone(),
             AliasInfo::new_from_metadata(final_type, rule_metadata),
         );
-    } else {
-        let rule_metadata = merge_metadata(
-            &RuleMetadata::from(
-                type_choices
-                    .last()
-                    .and_then(|tc| tc.comments_after_type.as_ref()),
-            ),
-            &RuleMetadata::from(
-                type_choices
-                    .last()
-                    .and_then(|tc| tc.type1.comments_after_type.as_ref()),
-            ),
+    } else if let Some((ok_name, err_name)) = &rule_metadata.result {
+        if generic_params.is_some() {
+            return Err(CddlError::UnsupportedGenerics(ErrorDetails::new(format!(
+                "{}: generics with @result",
+                name
+            ))));
+        }
+        if cli.wasm {
+            return Err(CddlError::InvalidAnnotation(ErrorDetails::new(format!(
+                "{}: @result is not supported with --wasm as wasm_bindgen can't expose rust Results",
+                name
+            ))));
+        }
+        if cli.preserve_encodings {
+            return Err(CddlError::InvalidAnnotation(ErrorDetails::new(format!(
+                "{}: @result is not supported with --preserve-encodings",
+                name
+            ))));
+        }
+        if type_choices.len() != 2 {
+            return Err(CddlError::InvalidAnnotation(ErrorDetails::new(format!(
+                "{}: @result can only be used on type choices with exactly 2 choices",
+                name
+            ))));
+        }
+        if ok_name == err_name {
+            return Err(CddlError::InvalidAnnotation(ErrorDetails::new(format!(
+                "{}: @result ok and err must differ",
+                name
+            ))));
+        }
+        // whitespace is stripped to compare generic instances e.g. error<text> regardless of formatting
+        let strip_whitespace = |s: &str| s.split_whitespace().collect::<String>();
+        let mut result_choice_type = |type_name: &str| {
+            let choice = type_choices
+                .iter()
+                .find(|tc| {
+                    strip_whitespace(&tc.type1.type2.to_string()) == strip_whitespace(type_name)
+                })
+                .ok_or_else(|| {
+                    CddlError::InvalidAnnotation(ErrorDetails::new(format!(
+                        "{}: @result type '{}' is not one of the type choices",
+                        name, type_name
+                    )))
+                })?;
+            rust_type_from_type1(types, parent_visitor, &choice.type1, cli).map(Box::new)
+        };
+        let ok_type = result_choice_type(ok_name)?;
+        let err_type = result_choice_type(err_name)?;
+        let final_type = RustType::new(ConceptualRustType::Result(ok_type, err_type)).tag_if(tag);
+        types.register_type_alias(
+            name.clone(),
+            AliasInfo::new_from_metadata(final_type, rule_metadata),
         );
+    } else {
         if rule_metadata.used_as_key {
             types.mark_used_as_key(name.clone());
         }
-        let variants = create_variants_from_type_choices(types, parent_visitor, type_choices, cli);
+        let mut variant_names_used = BTreeMap::new();
+        let mut variants = create_variants_from_type_choices(
+            types,
+            parent_visitor,
+            type_choices,
+            &mut variant_names_used,
+            cli,
+        )?;
+        if rule_metadata.open {
+            if !matches!(enum_variants_ints(&variants), Some(ints) if ints.iter().all(|i| *i >= 0))
+            {
+                return Err(CddlError::InvalidAnnotation(ErrorDetails::new(format!(
+                    "{}: @open requires every type choice to be a uint constant e.g. 0 / 1 / 5",
+                    name
+                ))));
+            }
+            if rule_metadata.repr.is_some() {
+                return Err(CddlError::InvalidAnnotation(ErrorDetails::new(format!(
+                    "{}: @repr can't be used with @open as the catch-all variant has no discriminant",
+                    name
+                ))));
+            }
+            // the Unknown variant holding the value makes this a regular enum instead of a C-style one
+            variants.push(EnumVariant::new(
+                VariantIdent::new_custom(append_number_if_duplicate(
+                    &mut variant_names_used,
+                    "Unknown".to_owned(),
+                )),
+                ConceptualRustType::Primitive(Primitive::U64).into(),
+                false,
+                Some("Any value not listed in the CDDL".to_owned()),
+            ));
+        }
         let rust_struct =
             RustStruct::new_type_choice(name.clone(), tag, Some(&rule_metadata), variants, cli);
+        if let Some(repr) = &rule_metadata.repr {
+            check_enum_repr(name, repr, rust_struct.variant())?;
+        }
+        if rule_metadata.string_enum
+            && !matches!(
+                rust_struct.variant(),
+                RustStructType::CStyleEnum { variants } if enum_variants_text(variants).is_some()
+            )
+        {
+            return Err(CddlError::InvalidAnnotation(ErrorDetails::new(format!(
+                "{}: @string_enum requires every type choice to be a text literal e.g. \"a\" / \"b\" (and no tags with --preserve-encodings)",
+                name
+            ))));
+        }
         match generic_params {
             Some(params) => types.register_generic_def(GenericDef::new(params, rust_struct)),
-            None => types.register_rust_struct(parent_visitor, rust_struct, cli),
+            None => types.register_rust_struct(parent_visitor, rust_struct, cli)?,
         };
     }
+    Ok(())
 }
 
-fn ident_to_primitive(ident: &CDDLIdent) -> Option<Primitive> {
-    // TODO: what about aliases that resolve to these? is it even possible to know this at this stage?
-    match ident.to_string().as_str() {
-        "tstr" | "text" => Some(Primitive::Str),
-        "bstr" | "bytes" => Some(Primitive::Bytes),
-        "int" => Some(Primitive::I64),
-        "uint" => Some(Primitive::U64),
-        "nint" => Some(Primitive::N64),
-        "float16" | "float32" => Some(Primitive::F32),
-        "float64" => Some(Primitive::F64),
-        _other => None,
+// @repr is only for C-style enums of integer constants that all fit in the repr type
+fn check_enum_repr(
+    name: &RustIdent,
+    repr: &str,
+    rust_struct_type: &RustStructType,
+) -> Result<(), CddlError> {
+    let (min, max) = match repr {
+        "u8" => (u8::MIN as i128, u8::MAX as i128),
+        "u16" => (u16::MIN as i128, u16::MAX as i128),
+        "u32" => (u32::MIN as i128, u32::MAX as i128),
+        "u64" => (u64::MIN as i128, u64::MAX as i128),
+        "i8" => (i8::MIN as i128, i8::MAX as i128),
+        "i16" => (i16::MIN as i128, i16::MAX as i128),
+        "i32" => (i32::MIN as i128, i32::MAX as i128),
+        "i64" => (i64::MIN as i128, i64::MAX as i128),
+        _ => {
+            return Err(CddlError::InvalidAnnotation(ErrorDetails::new(format!(
+                "{}: @repr {} is not one of u8, u16, u32, u64, i8, i16, i32 or i64",
+                name, repr
+            ))))
+        }
+    };
+    let ints = match rust_struct_type {
+        RustStructType::CStyleEnum { variants } => enum_variants_ints(variants),
+        _ => None,
+    }
+    .ok_or_else(|| {
+        CddlError::InvalidAnnotation(ErrorDetails::new(format!(
+            "{}: @repr requires every type choice to be an integer constant e.g. 0 / 1 / 5 (and no tags with --preserve-encodings)",
+            name
+        )))
+    })?;
+    match ints.iter().find(|i| **i < min || **i > max) {
+        Some(out_of_range) => Err(CddlError::InvalidAnnotation(ErrorDetails::new(format!(
+            "{}: {} does not fit in @repr {}",
+            name, out_of_range, repr
+        )))),
+        None => Ok(()),
     }
 }
 
-fn type2_to_number_literal(type2: &Type2) -> i128 {
+fn type2_to_number_literal(type2: &Type2) -> Result<i128, CddlError> {
     match type2 {
-        Type2::UintValue { value, .. } => *value as i128,
-        Type2::IntValue { value, .. } => *value as i128,
+        Type2::UintValue { value, .. } => Ok(*value as i128),
+        Type2::IntValue { value, .. } => Ok(*value as i128),
         Type2::FloatValue { value, .. } => {
             // FloatToInt trait still experimental so just directly check
             let as_int = *value as i128;
-            assert_eq!(
-                as_int as f64, *value,
-                "decimal not supported. Issue: https://github.com/dcSpark/cddl-codegen/issues/178"
-            );
-            as_int
+            if as_int as f64 != *value {
+                return Err(CddlError::InvalidLiteral(ErrorDetails::new(format!(
+                    "decimal {} not supported. Issue: https://github.com/dcSpark/cddl-codegen/issues/178",
+                    value
+                ))));
+            }
+            Ok(as_int)
         }
-        _ => panic!(
+        _ => Err(CddlError::InvalidLiteral(ErrorDetails::new(format!(
             "Value specified: {:?} must be a number literal to be used here",
             type2
-        ),
+        )))),
     }
 }
 
-fn type2_to_fixed_value(type2: &Type2) -> FixedValue {
+/// Like type2_to_number_literal() but for float bounds which needn't be whole numbers
+fn type2_to_float_literal(type2: &Type2) -> Result<f64, CddlError> {
     match type2 {
-        Type2::UintValue { value, .. } => FixedValue::Uint(*value),
-        Type2::IntValue { value, .. } => FixedValue::Nint(*value),
-        Type2::FloatValue { value, .. } => FixedValue::Float(*value),
-        Type2::TextValue { value, .. } => FixedValue::Text(value.to_string()),
-        _ => panic!(
+        Type2::UintValue { value, .. } => Ok(*value as f64),
+        Type2::IntValue { value, .. } => Ok(*value as f64),
+        Type2::FloatValue { value, .. } => Ok(*value),
+        _ => Err(CddlError::InvalidLiteral(ErrorDetails::new(format!(
+            "Value specified: {:?} must be a number literal to be used here",
+            type2
+        )))),
+    }
+}
+
+fn type2_to_fixed_value(type2: &Type2) -> Result<FixedValue, CddlError> {
+    match type2 {
+        Type2::UintValue { value, .. } => Ok(FixedValue::Uint(*value)),
+        Type2::IntValue { value, .. } => Ok(FixedValue::Nint(*value)),
+        Type2::FloatValue { value, .. } => Ok(FixedValue::Float(*value)),
+        Type2::TextValue { value, .. } => Ok(FixedValue::Text(value.to_string())),
+        Type2::Typename {
+            ident,
+            generic_args: None,
+            ..
+        } if ident.ident == "true" || ident.ident == "false" => {
+            Ok(FixedValue::Bool(ident.ident == "true"))
+        }
+        _ => Err(CddlError::InvalidLiteral(ErrorDetails::new(format!(
             "Type2: {:?} does not correspond to a supported FixedValue",
             type2
-        ),
+        )))),
+    }
+}
+
+/// Value of a text constant operand to .cat i.e. a text literal, a rule defined
+/// as one (e.g. prefix = "v") or a parenthesized .cat of those
+fn const_text(types: &IntermediateTypes, type2: &Type2) -> Result<String, CddlError> {
+    let not_constant = || {
+        CddlError::UnsupportedControlOperator(ErrorDetails::new(format!(
+            ".cat operand {} is not a text constant (a text literal or a rule defined as one)",
+            type2
+        )))
+    };
+    match type2 {
+        Type2::TextValue { value, .. } => Ok(value.to_string()),
+        Type2::Typename {
+            ident,
+            generic_args: None,
+            ..
+        } => {
+            let alias_ident = AliasIdent::Rust(RustIdent::new(CDDLIdent::new(ident.to_string())));
+            match types.type_aliases().get(&alias_ident) {
+                Some(alias) if alias.base_type.encodings.is_empty() => {
+                    match alias.base_type.conceptual_type.resolve_alias_shallow() {
+                        ConceptualRustType::Fixed(FixedValue::Text(value)) => Ok(value.clone()),
+                        _ => Err(not_constant()),
+                    }
+                }
+                _ => Err(not_constant()),
+            }
+        }
+        Type2::ParenthesizedType { pt, .. } if pt.type_choices.len() == 1 => {
+            let type1 = &pt.type_choices[0].type1;
+            let value = const_text(types, &type1.type2)?;
+            match &type1.operator {
+                None => Ok(value),
+                Some(Operator {
+                    operator:
+                        RangeCtlOp::CtlOp {
+                            ctrl: token::ControlOperator::CAT,
+                            ..
+                        },
+                    type2,
+                    ..
+                }) => Ok(value + &const_text(types, type2)?),
+                Some(_) => Err(not_constant()),
+            }
+        }
+        _ => Err(not_constant()),
+    }
+}
+
+/// Integer value of a range bound literal in a control operator e.g. 5 in `.size (1..5)`
+fn range_bound(type2: &Type2, operator: &Operator) -> Result<i128, CddlError> {
+    match type2 {
+        Type2::UintValue { value, .. } => Ok(*value as i128),
+        Type2::IntValue { value, .. } => Ok(*value as i128),
+        Type2::FloatValue { value, .. } => Ok(*value as i128),
+        _ => Err(CddlError::UnsupportedControlOperator(ErrorDetails::new(
+            format!("unsupported type in range control operator: {:?}", operator),
+        ))),
     }
 }
 
@@ -253,72 +880,171 @@ fn parse_control_operator(
     type2: &Type2,
     operator: &Operator,
     cli: &Cli,
-) -> ControlOperator {
+) -> Result<ControlOperator, CddlError> {
+    let span = match operator.operator {
+        RangeCtlOp::RangeOp { span, .. } | RangeCtlOp::CtlOp { span, .. } => span,
+    };
+    parse_control_operator_impl(types, parent_visitor, type2, operator, cli)
+        .map_err(|e| e.or_at(span))
+}
+
+fn parse_control_operator_impl(
+    types: &mut IntermediateTypes,
+    parent_visitor: &ParentVisitor,
+    type2: &Type2,
+    operator: &Operator,
+    cli: &Cli,
+) -> Result<ControlOperator, CddlError> {
+    // .le/.lt on uint can't go below 0 and neither can the length that they bound
+    // for text/bytes (like .size does) e.g. tstr .le 64 is at most 64 long
     let lower_bound = match type2 {
-        Type2::Typename { ident, .. } if ident.to_string() == "uint" => Some(0),
+        Type2::Typename { ident, .. }
+            if matches!(
+                CDDLIdent::new(ident.to_string()).to_rust_primitive(),
+                Some(Primitive::U64 | Primitive::Str | Primitive::Bytes)
+            ) =>
+        {
+            Some(0)
+        }
         _ => None,
     };
+    let is_float = match type2 {
+        Type2::Typename { ident, .. } => matches!(
+            CDDLIdent::new(ident.to_string()).to_rust_primitive(),
+            Some(Primitive::F16 | Primitive::F32 | Primitive::F64)
+        ),
+        _ => false,
+    };
     //todo: read up on other range control operators in CDDL RFC
     // (rangeop / ctlop) S type2
-    match operator.operator {
+    let op = match operator.operator {
+        // e.g. 0.5...1.5 where a...b excludes b
+        RangeCtlOp::RangeOp { is_inclusive, .. } if matches!(type2, Type2::FloatValue { .. }) => {
+            let end = type2_to_float_literal(&operator.type2)?;
+            ControlOperator::FloatRange((
+                Some(FloatBound::inclusive(type2_to_float_literal(type2)?)),
+                Some(if is_inclusive {
+                    FloatBound::inclusive(end)
+                } else {
+                    FloatBound::exclusive(end)
+                }),
+            ))
+        }
         RangeCtlOp::RangeOp { is_inclusive, .. } => {
             let range_start = match type2 {
                 Type2::UintValue { value, .. } => *value as i128,
                 Type2::IntValue { value, .. } => *value as i128,
                 Type2::FloatValue { value, .. } => *value as i128,
-                _ => panic!("Number expected as range start. Found {:?}", type2),
-            };
-            let range_end = match operator.type2 {
-                Type2::UintValue { value, .. } => value as i128,
-                Type2::IntValue { value, .. } => value as i128,
-                Type2::FloatValue { value, .. } => value as i128,
-                _ => unimplemented!("unsupported type in range control operator: {:?}", operator),
+                _ => {
+                    return Err(CddlError::InvalidLiteral(ErrorDetails::new(format!(
+                        "Number expected as range start. Found {:?}",
+                        type2
+                    ))))
+                }
             };
+            let range_end = range_bound(&operator.type2, operator)?;
             ControlOperator::Range((
                 Some(range_start),
                 Some(if is_inclusive {
                     range_end
                 } else {
-                    range_end + 1
+                    range_end - 1
                 }),
             ))
         }
         RangeCtlOp::CtlOp { ctrl, .. } => match ctrl {
             token::ControlOperator::CBORSEQ
             | token::ControlOperator::WITHIN
-            | token::ControlOperator::AND => todo!("control operator {} not supported", ctrl),
+            | token::ControlOperator::AND => {
+                return Err(CddlError::UnsupportedControlOperator(ErrorDetails::new(
+                    format!("control operator {} not supported", ctrl),
+                )))
+            }
             token::ControlOperator::DEFAULT => {
-                ControlOperator::Default(type2_to_fixed_value(&operator.type2))
+                ControlOperator::Default(type2_to_fixed_value(&operator.type2)?)
+            }
+            token::ControlOperator::CBOR => match &operator.type2 {
+                Type2::Typename {
+                    ident,
+                    generic_args: None,
+                    ..
+                } if ident.ident == "any" => ControlOperator::CBOR(None),
+                type2 => ControlOperator::CBOR(Some(rust_type_from_type2(
+                    types,
+                    parent_visitor,
+                    type2,
+                    cli,
+                )?)),
+            },
+            token::ControlOperator::REGEXP | token::ControlOperator::PCRE => {
+                let (flavor, engine) = match ctrl {
+                    token::ControlOperator::REGEXP => (RegexFlavor::Xsd, cli.regexp_engine),
+                    _ => (RegexFlavor::Pcre, cli.pcre_engine),
+                };
+                let pattern = match &operator.type2 {
+                    Type2::TextValue { value, .. } => value.to_string(),
+                    _ => {
+                        return Err(CddlError::UnsupportedControlOperator(ErrorDetails::new(
+                            format!("{} expects a text pattern: {:?}", ctrl, operator),
+                        )))
+                    }
+                };
+                ControlOperator::Regex(
+                    RegexPattern::new(pattern, flavor, engine)
+                        .map_err(|e| CddlError::UnsupportedControlOperator(ErrorDetails::new(e)))?,
+                )
+            }
+            token::ControlOperator::CAT => ControlOperator::Cat(
+                const_text(types, type2)? + &const_text(types, &operator.type2)?,
+            ),
+            // float bounds are kept as-is since e.g. .lt 1.5 can't be turned into .le of anything
+            token::ControlOperator::EQ
+            | token::ControlOperator::LE
+            | token::ControlOperator::LT
+            | token::ControlOperator::GE
+            | token::ControlOperator::GT
+                if is_float =>
+            {
+                let value = type2_to_float_literal(&operator.type2)?;
+                ControlOperator::FloatRange(match ctrl {
+                    token::ControlOperator::LE => (None, Some(FloatBound::inclusive(value))),
+                    token::ControlOperator::LT => (None, Some(FloatBound::exclusive(value))),
+                    token::ControlOperator::GE => (Some(FloatBound::inclusive(value)), None),
+                    token::ControlOperator::GT => (Some(FloatBound::exclusive(value)), None),
+                    _ => (
+                        Some(FloatBound::inclusive(value)),
+                        Some(FloatBound::inclusive(value)),
+                    ),
+                })
+            }
+            token::ControlOperator::NE if is_float => {
+                return Err(CddlError::UnsupportedControlOperator(ErrorDetails::new(
+                    format!(".ne is not supported on floats: {:?}", operator),
+                )))
             }
-            token::ControlOperator::CBOR => ControlOperator::CBOR(rust_type_from_type2(
-                types,
-                parent_visitor,
-                &operator.type2,
-                cli,
-            )),
             token::ControlOperator::EQ => ControlOperator::Range((
-                Some(type2_to_number_literal(&operator.type2)),
-                Some(type2_to_number_literal(&operator.type2)),
+                Some(type2_to_number_literal(&operator.type2)?),
+                Some(type2_to_number_literal(&operator.type2)?),
             )),
             // TODO: this would be MUCH nicer (for error displaying, etc) to handle this in its own dedicated way
             //       which might be necessary once we support other control operators anyway
             token::ControlOperator::NE => ControlOperator::Range((
-                Some(type2_to_number_literal(&operator.type2) + 1),
-                Some(type2_to_number_literal(&operator.type2) - 1),
+                Some(type2_to_number_literal(&operator.type2)? + 1),
+                Some(type2_to_number_literal(&operator.type2)? - 1),
             )),
             token::ControlOperator::LE => ControlOperator::Range((
                 lower_bound,
-                Some(type2_to_number_literal(&operator.type2)),
+                Some(type2_to_number_literal(&operator.type2)?),
             )),
             token::ControlOperator::LT => ControlOperator::Range((
                 lower_bound,
-                Some(type2_to_number_literal(&operator.type2) - 1),
+                Some(type2_to_number_literal(&operator.type2)? - 1),
             )),
             token::ControlOperator::GE => {
-                ControlOperator::Range((Some(type2_to_number_literal(&operator.type2)), None))
+                ControlOperator::Range((Some(type2_to_number_literal(&operator.type2)?), None))
             }
             token::ControlOperator::GT => {
-                ControlOperator::Range((Some(type2_to_number_literal(&operator.type2) + 1), None))
+                ControlOperator::Range((Some(type2_to_number_literal(&operator.type2)? + 1), None))
             }
             token::ControlOperator::SIZE => {
                 let base_range = match &operator.type2 {
@@ -331,77 +1057,97 @@ fn parse_control_operator(
                     Type2::FloatValue { value, .. } => {
                         ControlOperator::Range((None, Some(*value as i128)))
                     }
-                    Type2::ParenthesizedType { pt, .. } => {
-                        assert_eq!(pt.type_choices.len(), 1);
+                    Type2::ParenthesizedType { pt, .. } if pt.type_choices.len() == 1 => {
                         let inner_type = &pt.type_choices.first().unwrap().type1;
-                        let min = match inner_type.type2 {
-                            Type2::UintValue { value, .. } => Some(value as i128),
-                            Type2::IntValue { value, .. } => Some(value as i128),
-                            Type2::FloatValue { value, .. } => Some(value as i128),
-                            _ => unimplemented!(
-                                "unsupported type in range control operator: {:?}",
-                                operator
-                            ),
-                        };
+                        let min = Some(range_bound(&inner_type.type2, operator)?);
                         match &inner_type.operator {
                             // if there was only one value instead of a range, we take that value to be the max
                             // ex: uint .size (1)
                             None => ControlOperator::Range((None, min)),
                             Some(op) => match op.operator {
                                 RangeCtlOp::RangeOp { is_inclusive, .. } => {
-                                    let value = match op.type2 {
-                                        Type2::UintValue { value, .. } => value as i128,
-                                        Type2::IntValue { value, .. } => value as i128,
-                                        Type2::FloatValue { value, .. } => value as i128,
-                                        _ => unimplemented!(
-                                            "unsupported type in range control operator: {:?}",
-                                            operator
-                                        ),
-                                    };
-                                    let max = Some(if is_inclusive { value } else { value + 1 });
+                                    let value = range_bound(&op.type2, operator)?;
+                                    // a...b excludes b
+                                    let max = Some(if is_inclusive { value } else { value - 1 });
                                     ControlOperator::Range((min, max))
                                 }
-                                RangeCtlOp::CtlOp { .. } => panic!(""),
+                                RangeCtlOp::CtlOp { .. } => {
+                                    return Err(CddlError::UnsupportedControlOperator(
+                                        ErrorDetails::new(format!(
+                                            "control operator inside of .size: {:?}",
+                                            operator
+                                        )),
+                                    ))
+                                }
                             },
                         }
                     }
                     _ => {
-                        unimplemented!("unsupported type in range control operator: {:?}", operator)
+                        return Err(CddlError::UnsupportedControlOperator(ErrorDetails::new(
+                            format!("unsupported type in range control operator: {:?}", operator),
+                        )))
                     }
                 };
                 match type2 {
-                    Type2::Typename { ident, .. } if ident.to_string() == "uint" => {
+                    Type2::Typename { ident, .. }
+                        if CDDLIdent::new(ident.to_string()).to_rust_primitive()
+                            == Some(Primitive::U64) =>
+                    {
                         // .size 3 means 24 bits
                         match &base_range {
                             ControlOperator::Range((Some(l), Some(h))) => ControlOperator::Range((
                                 Some(i128::pow(2, 8 * *l as u32)),
                                 Some(i128::pow(2, 8 * *h as u32) - 1),
                             )),
+                            // uint .size 16's upper bound doesn't fit in an i128 so we store it wrapped
+                            // which range_to_primitive() recognizes as u128::MIN..=u128::MAX
+                            ControlOperator::Range((None, Some(16))) => ControlOperator::Range((
+                                Some(u128::MIN as i128),
+                                Some(u128::MAX as i128),
+                            )),
                             ControlOperator::Range((None, Some(h))) => ControlOperator::Range((
                                 Some(0),
                                 Some(i128::pow(2, 8 * *h as u32) - 1),
                             )),
-                            _ => panic!(
-                                "unexpected partial range in size control operator: {:?}",
-                                operator
-                            ),
+                            _ => {
+                                return Err(CddlError::UnsupportedControlOperator(
+                                    ErrorDetails::new(format!(
+                                        "unexpected partial range in size control operator: {:?}",
+                                        operator
+                                    )),
+                                ))
+                            }
                         }
                     }
-                    Type2::Typename { ident, .. } if ident.to_string() == "int" => {
+                    Type2::Typename { ident, .. }
+                        if CDDLIdent::new(ident.to_string()).to_rust_primitive()
+                            == Some(Primitive::I64) =>
+                    {
                         match &base_range {
                             // this is complex to support since it requires two disjoint ranges of possible values
-                            ControlOperator::Range((Some(_), Some(_))) => panic!(
-                                ".size range unsupported for signed int type: {:?}",
-                                operator
-                            ),
+                            ControlOperator::Range((Some(_), Some(_))) => {
+                                return Err(CddlError::UnsupportedControlOperator(
+                                    ErrorDetails::new(format!(
+                                        ".size range unsupported for signed int type: {:?}",
+                                        operator
+                                    )),
+                                ))
+                            }
+                            ControlOperator::Range((None, Some(16))) => {
+                                ControlOperator::Range((Some(i128::MIN), Some(i128::MAX)))
+                            }
                             ControlOperator::Range((None, Some(h))) => ControlOperator::Range((
                                 Some(-i128::pow(2, ((8 * *h) - 1) as u32)),
                                 Some(i128::pow(2, ((8 * *h) - 1) as u32) - 1),
                             )),
-                            _ => panic!(
-                                "unexpected partial range in size control operator: {:?}",
-                                operator
-                            ),
+                            _ => {
+                                return Err(CddlError::UnsupportedControlOperator(
+                                    ErrorDetails::new(format!(
+                                        "unexpected partial range in size control operator: {:?}",
+                                        operator
+                                    )),
+                                ))
+                            }
                         }
                     }
                     _ => {
@@ -415,11 +1161,82 @@ fn parse_control_operator(
                     }
                 }
             }
-            _ => panic!(
-                "Unknown (not seen in RFC-8610) range control operator: {}",
-                ctrl
-            ),
+            _ => {
+                return Err(CddlError::UnsupportedControlOperator(ErrorDetails::new(
+                    format!(
+                        "Unknown (not seen in RFC-8610) range control operator: {}",
+                        ctrl
+                    ),
+                )))
+            }
         },
+    };
+    Ok(op)
+}
+
+/// Registers a newtype around a primitive that checks the given range e.g. foo = uint .le 1000
+#[allow(clippy::too_many_arguments)]
+fn register_range_wrapper(
+    types: &mut IntermediateTypes,
+    parent_visitor: &ParentVisitor,
+    type_name: &RustIdent,
+    outer_tag: Option<u64>,
+    rule_metadata: &RuleMetadata,
+    mut ranged_type: RustType,
+    min_max: (Option<i128>, Option<i128>),
+    cli: &Cli,
+) -> Result<(), CddlError> {
+    // without bounds since passed in other param
+    ranged_type.config.bounds = None;
+    // the full u128/i128 range is the type itself (and u128's doesn't fit in min_max anyway)
+    let min_max = match ranged_type.conceptual_type {
+        ConceptualRustType::Primitive(Primitive::U128 | Primitive::I128) => None,
+        _ => Some(min_max),
+    };
+    types.register_rust_struct(
+        parent_visitor,
+        RustStruct::new_wrapper(
+            type_name.clone(),
+            outer_tag,
+            Some(rule_metadata),
+            ranged_type,
+            min_max,
+        ),
+        cli,
+    )
+}
+
+/// @newtype would otherwise be silently ignored on rules that can't be wrapped
+fn check_no_newtype(
+    type_name: &RustIdent,
+    rule_metadata: &RuleMetadata,
+    what: &str,
+    span: Span,
+) -> Result<(), CddlError> {
+    if rule_metadata.newtype.is_some() {
+        return Err(CddlError::InvalidAnnotation(
+            ErrorDetails::new(format!("{}: @newtype on {}", type_name, what)).at(span),
+        ));
+    }
+    Ok(())
+}
+
+/// bstr .size N as a [u8; N] (with --fixed-size-byte-arrays) instead of a length-checked Vec<u8>
+/// and tstr .size 1 as a char (with --tstr-size-1-as-char) instead of a length-checked String
+fn fixed_size_primitive(
+    low: Option<i128>,
+    high: Option<i128>,
+    primitive: Primitive,
+    cli: &Cli,
+) -> Option<RustType> {
+    match (primitive, low, high) {
+        (Primitive::Bytes, Some(l), Some(h)) if cli.fixed_size_byte_arrays && l == h => {
+            Some(ConceptualRustType::Primitive(Primitive::ByteArray(l as usize)).into())
+        }
+        (Primitive::Str, Some(1), Some(1)) if cli.tstr_size_1_as_char => {
+            Some(ConceptualRustType::Primitive(Primitive::Char).into())
+        }
+        _ => None,
     }
 }
 
@@ -449,12 +1266,50 @@ fn range_to_primitive(low: Option<i128>, high: Option<i128>, primitive: Primitiv
         (Some(l), Some(h)) if l == i64::MIN as i128 && h == i64::MAX as i128 => {
             ConceptualRustType::Primitive(Primitive::I64).into()
         }
+        (Some(l), Some(h)) if l == u128::MIN as i128 && h == u128::MAX as i128 => {
+            ConceptualRustType::Primitive(Primitive::U128).into()
+        }
+        (Some(l), Some(h)) if l == i128::MIN && h == i128::MAX => {
+            ConceptualRustType::Primitive(Primitive::I128).into()
+        }
         (Some(l), Some(h)) if l == f32::MIN as i128 && h == f32::MAX as i128 => {
             ConceptualRustType::Primitive(Primitive::F32).into()
         }
         (Some(l), Some(h)) if l == f64::MIN as i128 && h == f64::MAX as i128 => {
             ConceptualRustType::Primitive(Primitive::F64).into()
         }
+        // one-sided bounds that are already the limits of the type itself e.g. uint .le 18446744073709551615
+        (l, h)
+            if primitive == Primitive::U64
+                && l.unwrap_or(0) == 0
+                && h.map_or(true, |h| h == u64::MAX as i128) =>
+        {
+            ConceptualRustType::Primitive(Primitive::U64).into()
+        }
+        (l, h)
+            if primitive == Primitive::I64
+                && l.map_or(true, |l| l == i64::MIN as i128)
+                && h.map_or(true, |h| h == i64::MAX as i128) =>
+        {
+            ConceptualRustType::Primitive(Primitive::I64).into()
+        }
+        // uint .size N for N other than 1/2/4/8 e.g. uint .size 3 is a u32 checked to be < 2^24
+        (l, Some(h))
+            if primitive == Primitive::U64
+                && l.unwrap_or(0) >= 0
+                && h <= u32::MAX as i128
+                && (h + 1).count_ones() == 1
+                && (h + 1).trailing_zeros() % 8 == 0 =>
+        {
+            let backing = if h <= u8::MAX as i128 {
+                Primitive::U8
+            } else if h <= u16::MAX as i128 {
+                Primitive::U16
+            } else {
+                Primitive::U32
+            };
+            RustType::from(ConceptualRustType::Primitive(backing)).with_bounds((l, Some(h)))
+        }
         // TODO: use minimal primitive or check here? e.g. uint .le 8 -> U8 instead of U64
         bounds => RustType::from(ConceptualRustType::Primitive(primitive)).with_bounds(bounds),
     }
@@ -465,10 +1320,10 @@ fn parse_type(
     parent_visitor: &ParentVisitor,
     type_name: &RustIdent,
     type_choice: &TypeChoice,
-    outer_tag: Option<usize>,
+    outer_tag: Option<u64>,
     generic_params: Option<Vec<RustIdent>>,
     cli: &Cli,
-) {
+) -> Result<(), CddlError> {
     let type1 = &type_choice.type1;
     let rule_metadata = merge_metadata(
         &RuleMetadata::from(type1.comments_after_type.as_ref()),
@@ -477,6 +1332,32 @@ fn parse_type(
     if rule_metadata.used_as_key {
         types.mark_used_as_key(type_name.clone());
     }
+    if let Some(Operator {
+        operator:
+            RangeCtlOp::CtlOp {
+                ctrl: token::ControlOperator::WITHIN,
+                ..
+            },
+        type2: narrowed,
+        ..
+    }) = &type1.operator
+    {
+        if generic_params.is_some() {
+            return Err(CddlError::UnsupportedGenerics(
+                ErrorDetails::new(format!("{}: generics with .within", type_name)).at(type1.span),
+            ));
+        }
+        return parse_within(
+            types,
+            parent_visitor,
+            type_name,
+            type1,
+            narrowed,
+            outer_tag,
+            cli,
+        )
+        .map_err(|e| e.or_at(type1.span));
+    }
     match &type1.type2 {
         Type2::Typename {
             ident,
@@ -488,13 +1369,13 @@ fn parse_type(
                     parent_visitor,
                     RustStruct::new_extern(type_name.clone()),
                     cli,
-                );
+                )?;
             } else if ident.ident == RAW_BYTES_MARKER {
                 types.register_rust_struct(
                     parent_visitor,
                     RustStruct::new_raw_bytes(type_name.clone()),
                     cli,
-                );
+                )?;
             } else {
                 // Note: this handles bool constants too, since we apply the type aliases and they resolve
                 // and there's no Type2::BooleanValue
@@ -502,27 +1383,55 @@ fn parse_type(
                 let control = type1
                     .operator
                     .as_ref()
-                    .map(|op| parse_control_operator(types, parent_visitor, &type1.type2, op, cli));
+                    .map(|op| parse_control_operator(types, parent_visitor, &type1.type2, op, cli))
+                    .transpose()?;
                 match control {
                     Some(control) => {
-                        assert!(
-                            generic_params.is_none(),
-                            "Generics combined with range specifiers not supported"
-                        );
+                        if generic_params.is_some() {
+                            return Err(CddlError::UnsupportedGenerics(
+                                ErrorDetails::new(format!(
+                                    "{}: generics combined with range specifiers",
+                                    type_name
+                                ))
+                                .at(type1.span),
+                            ));
+                        }
+                        if matches!(
+                            control,
+                            ControlOperator::CBOR(_)
+                                | ControlOperator::Cat(_)
+                                | ControlOperator::Default(_)
+                        ) {
+                            check_no_newtype(
+                                type_name,
+                                &rule_metadata,
+                                ".cbor, .cat or .default",
+                                type1.span,
+                            )?;
+                        }
                         match control {
                             ControlOperator::Range(min_max) => {
                                 // when declared top-level we make a new type as the default behavior like before
-                                let mut ranged_type = range_to_primitive(
-                                    min_max.0,
-                                    min_max.1,
-                                    ident_to_primitive(&cddl_ident).unwrap(),
-                                );
-                                if ranged_type.config.bounds.is_some()
-                                    || rule_metadata.newtype.is_some()
+                                let primitive =
+                                    cddl_ident.to_rust_primitive().ok_or_else(|| {
+                                        CddlError::UnsupportedControlOperator(
+                                            ErrorDetails::new(format!(
+                                                "{}: range/size control operators are only supported on builtin types, not {}",
+                                                type_name, cddl_ident
+                                            ))
+                                            .at(type1.span),
+                                        )
+                                    })?;
+                                let ranged_type =
+                                    fixed_size_primitive(min_max.0, min_max.1, primitive, cli)
+                                        .unwrap_or_else(|| {
+                                            range_to_primitive(min_max.0, min_max.1, primitive)
+                                        });
+                                if let ConceptualRustType::Primitive(
+                                    Primitive::ByteArray(_) | Primitive::Char,
+                                ) = ranged_type.conceptual_type
                                 {
-                                    // without bounds since passed in other param
-                                    ranged_type.config.bounds = None;
-                                    // has non-rust-primitive matching bounds
+                                    // the length is part of the type so the wrapper has nothing to check
                                     types.register_rust_struct(
                                         parent_visitor,
                                         RustStruct::new_wrapper(
@@ -530,10 +1439,24 @@ fn parse_type(
                                             outer_tag,
                                             Some(&rule_metadata),
                                             ranged_type,
-                                            Some(min_max),
+                                            None,
                                         ),
                                         cli,
-                                    );
+                                    )?;
+                                } else if ranged_type.config.bounds.is_some()
+                                    || rule_metadata.newtype.is_some()
+                                {
+                                    // has non-rust-primitive matching bounds
+                                    register_range_wrapper(
+                                        types,
+                                        parent_visitor,
+                                        type_name,
+                                        outer_tag,
+                                        &rule_metadata,
+                                        ranged_type,
+                                        min_max,
+                                        cli,
+                                    )?;
                                 } else {
                                     // matches to known rust type e.g. u32, i16, etc so just make an alias
                                     types.register_type_alias(
@@ -545,21 +1468,94 @@ fn parse_type(
                                     );
                                 }
                             }
-                            ControlOperator::CBOR(ty) => match ident_to_primitive(&cddl_ident) {
+                            ControlOperator::FloatRange(bounds) => {
+                                // only parsed on float primitives so there's always a primitive
+                                let primitive = cddl_ident.to_rust_primitive().unwrap();
+                                types.register_rust_struct(
+                                    parent_visitor,
+                                    RustStruct::new_wrapper(
+                                        type_name.clone(),
+                                        outer_tag,
+                                        Some(&rule_metadata),
+                                        RustType::from(ConceptualRustType::Primitive(primitive))
+                                            .with_float_bounds(bounds),
+                                        None,
+                                    ),
+                                    cli,
+                                )?;
+                            }
+                            ControlOperator::CBOR(ty) => match cddl_ident.to_rust_primitive() {
                                 Some(Primitive::Bytes) => {
                                     types.register_type_alias(
                                         type_name.clone(),
                                         AliasInfo::new_from_metadata(
-                                            ty.as_bytes().tag_if(outer_tag),
+                                            cbor_in_bytes(ty).tag_if(outer_tag),
+                                            rule_metadata,
+                                        ),
+                                    );
+                                }
+                                Some(Primitive::Str) if cli.allow_cbor_on_text => {
+                                    let ty = cbor_any_unsupported_on_text(ty, type1.span)?;
+                                    types.register_type_alias(
+                                        type_name.clone(),
+                                        AliasInfo::new_from_metadata(
+                                            ty.as_base64_text().tag_if(outer_tag),
                                             rule_metadata,
                                         ),
                                     );
                                 }
-                                _ => panic!(".cbor is only allowed on bytes as per CDDL spec"),
+                                _ => {
+                                    return Err(CddlError::UnsupportedControlOperator(
+                                        ErrorDetails::new(format!(
+                                            "{}: .cbor is only allowed on bytes as per CDDL spec (see --allow-cbor-on-text)",
+                                            type_name
+                                        ))
+                                        .at(type1.span),
+                                    ))
+                                }
                             },
+                            ControlOperator::Regex(regex) => {
+                                match cddl_ident.to_rust_primitive() {
+                                    Some(Primitive::Str) => {
+                                        types.register_rust_struct(
+                                            parent_visitor,
+                                            RustStruct::new_regex_wrapper(
+                                                type_name.clone(),
+                                                outer_tag,
+                                                Some(&rule_metadata),
+                                                ConceptualRustType::Primitive(Primitive::Str)
+                                                    .into(),
+                                                regex,
+                                            ),
+                                            cli,
+                                        )?;
+                                    }
+                                    _ => {
+                                        return Err(CddlError::UnsupportedControlOperator(
+                                            ErrorDetails::new(format!(
+                                                "{}: regexes are only supported on text",
+                                                type_name
+                                            ))
+                                            .at(type1.span),
+                                        ))
+                                    }
+                                }
+                            }
+                            ControlOperator::Cat(value) => {
+                                types.register_type_alias(
+                                    type_name.clone(),
+                                    AliasInfo::new_from_metadata(
+                                        RustType::new(ConceptualRustType::Fixed(FixedValue::Text(
+                                            value,
+                                        )))
+                                        .tag_if(outer_tag),
+                                        rule_metadata,
+                                    ),
+                                );
+                            }
                             ControlOperator::Default(default_value) => {
                                 let default_type =
-                                    rust_type_from_type2(types, parent_visitor, &type1.type2, cli)
+                                    rust_type_from_type2(types, parent_visitor, &type1.type2, cli)?
                                         .default(default_value)
                                         .tag_if(outer_tag);
                                 types.register_type_alias(
@@ -576,16 +1572,42 @@ fn parse_type(
                             concrete_type.conceptual_type = *ty;
                         };
                         match &generic_params {
+                            Some(params)
+                                if generic_args.is_none()
+                                    && params.contains(&RustIdent::new(cddl_ident.clone())) =>
+                            {
+                                // the param itself e.g. tagged<T> = #6.24(T) which is a wrapper around
+                                // whatever T gets resolved to, with the tag around that
+                                types.register_generic_def(GenericDef::new(
+                                    params.clone(),
+                                    RustStruct::new_wrapper(
+                                        type_name.clone(),
+                                        outer_tag,
+                                        Some(&rule_metadata),
+                                        types.new_type(&cddl_ident, cli),
+                                        None,
+                                    ),
+                                ));
+                            }
                             Some(_params) => {
                                 // this should be the only situation where you need this as otherwise the params would be unbound
-                                todo!("generics on defined types e.g. foo<T, U> = [T, U], bar<V> = foo<V, uint>");
                                 // TODO: maybe you could do this by resolving it here then storing the resolved one as GenericDef
+                                return Err(CddlError::UnsupportedGenerics(ErrorDetails::new(format!(
+                                    "{}: generics on defined types e.g. foo<T, U> = [T, U], bar<V> = foo<V, uint>",
+                                    type_name
+                                )).at(type1.span)));
                             }
                             None => {
                                 match generic_args {
                                     Some(arg) => {
                                         // This is for named generic instances such as:
                                         // foo = bar<text>
+                                        check_no_newtype(
+                                            type_name,
+                                            &rule_metadata,
+                                            "generic instances",
+                                            type1.span,
+                                        )?;
                                         let generic_args = arg
                                             .args
                                             .iter()
@@ -597,15 +1619,32 @@ fn parse_type(
                                                     cli,
                                                 )
                                             })
-                                            .collect();
-                                        types.register_generic_instance(GenericInstance::new(
-                                            type_name.clone(),
-                                            RustIdent::new(cddl_ident.clone()),
-                                            generic_args,
-                                        ))
+                                            .collect::<Result<_, _>>()?;
+                                        let instance_ident = types.register_generic_instance(
+                                            GenericInstance::new(
+                                                type_name.clone(),
+                                                RustIdent::new(cddl_ident.clone()),
+                                                generic_args,
+                                            ),
+                                        )?;
+                                        if instance_ident != *type_name {
+                                            // same instantiation as one used before
+                                            types.register_type_alias(
+                                                type_name.clone(),
+                                                AliasInfo::new_from_metadata(
+                                                    ConceptualRustType::Rust(instance_ident).into(),
+                                                    rule_metadata,
+                                                ),
+                                            );
+                                        }
                                     }
                                     None => {
-                                        if rule_metadata.newtype.is_some() {
+                                        // #6.N(any) is an opaque envelope around content we don't interpret.
+                                        // It's always its own type as an alias to RawCbor would lose the tag
+                                        // whenever it's (de)serialized on its own instead of as a field.
+                                        let tagged_any =
+                                            outer_tag.is_some() && ident.ident == "any";
+                                        if rule_metadata.newtype.is_some() || tagged_any {
                                             types.register_rust_struct(
                                                 parent_visitor,
                                                 RustStruct::new_wrapper(
@@ -616,7 +1655,7 @@ fn parse_type(
                                                     None,
                                                 ),
                                                 cli,
-                                            );
+                                            )?;
                                         } else {
                                             types.register_type_alias(
                                                 type_name.clone(),
@@ -645,7 +1684,7 @@ fn parse_type(
                 generic_params,
                 &rule_metadata,
                 cli,
-            );
+            )?;
         }
         Type2::Array { group, .. } => {
             // TODO: We could potentially generate an array-wrapper type around this
@@ -660,13 +1699,22 @@ fn parse_type(
                 generic_params,
                 &rule_metadata,
                 cli,
-            );
+            )?;
         }
         Type2::TaggedData { tag, t, .. } => {
             if outer_tag.is_some() {
-                panic!("doubly nested tags are not supported");
+                return Err(CddlError::Unsupported(
+                    ErrorDetails::new(format!("{}: doubly nested tags", type_name)).at(type1.span),
+                ));
             }
-            let tag_unwrap = tag.expect("not sure what empty tag here would mean - unsupported");
+            // the cddl AST stores tags as usize so widen them here as they can be up to u64::MAX
+            let tag = tag.map(|t| t as u64);
+            let tag_unwrap = tag.ok_or_else(|| {
+                CddlError::Unsupported(
+                    ErrorDetails::new(format!("{}: tagged data without a tag", type_name))
+                        .at(type1.span),
+                )
+            })?;
             match t.type_choices.len() {
                 1 => {
                     let inner_type = &t.type_choices.first().unwrap();
@@ -678,7 +1726,7 @@ fn parse_type(
                         Some(tag_unwrap),
                         generic_params,
                         cli,
-                    );
+                    )?;
                 }
                 _ => {
                     parse_type_choices(
@@ -686,13 +1734,42 @@ fn parse_type(
                         parent_visitor,
                         type_name,
                         &t.type_choices,
-                        *tag,
+                        tag,
                         generic_params,
                         cli,
-                    );
+                    )?;
                 }
             };
         }
+        // e.g. foo = (bytes .cbor bar) .size (0..64) where the operator applies to the whole type
+        Type2::ParenthesizedType { .. } if type1.operator.is_some() => {
+            let rust_type = rust_type_from_type1(types, parent_visitor, type1, cli)?;
+            types.register_type_alias(
+                type_name.clone(),
+                AliasInfo::new_from_metadata(rust_type.tag_if(outer_tag), rule_metadata),
+            );
+        }
+        // e.g. foo = (a / b) is the same as foo = a / b
+        Type2::ParenthesizedType { pt, .. } => match pt.type_choices.len() {
+            1 => parse_type(
+                types,
+                parent_visitor,
+                type_name,
+                pt.type_choices.first().unwrap(),
+                outer_tag,
+                generic_params,
+                cli,
+            )?,
+            _ => parse_type_choices(
+                types,
+                parent_visitor,
+                type_name,
+                &pt.type_choices,
+                outer_tag,
+                generic_params,
+                cli,
+            )?,
+        },
         // Note: bool constants are handled via Type2::Typename
         Type2::IntValue { value, .. } => {
             let fallback_type = ConceptualRustType::Fixed(FixedValue::Nint(*value));
@@ -700,13 +1777,31 @@ fn parse_type(
             let control = type1
                 .operator
                 .as_ref()
-                .map(|op| parse_control_operator(types, parent_visitor, &type1.type2, op, cli));
+                .map(|op| parse_control_operator(types, parent_visitor, &type1.type2, op, cli))
+                .transpose()?;
             // We end up here with ranges like foo = 0..5 which is why we're not just reporting a fixed value
             let base_type = match control {
                 Some(ControlOperator::Range(min_max)) => {
-                    range_to_primitive(min_max.0, min_max.1, Primitive::I64)
+                    let ranged_type = range_to_primitive(min_max.0, min_max.1, Primitive::I64);
+                    if rule_metadata.newtype.is_some() {
+                        register_range_wrapper(
+                            types,
+                            parent_visitor,
+                            type_name,
+                            outer_tag,
+                            &rule_metadata,
+                            ranged_type,
+                            min_max,
+                            cli,
+                        )?;
+                        return Ok(());
+                    }
+                    ranged_type
+                }
+                _ => {
+                    check_no_newtype(type_name, &rule_metadata, "constants", type1.span)?;
+                    fallback_type.into()
                 }
-                _ => fallback_type.into(),
             };
             types.register_type_alias(
                 type_name.clone(),
@@ -719,13 +1814,31 @@ fn parse_type(
             let control = type1
                 .operator
                 .as_ref()
-                .map(|op| parse_control_operator(types, parent_visitor, &type1.type2, op, cli));
+                .map(|op| parse_control_operator(types, parent_visitor, &type1.type2, op, cli))
+                .transpose()?;
             // We end up here with ranges like foo = 0..5 which is why we're not just reporting a fixed value
             let base_type = match control {
                 Some(ControlOperator::Range(min_max)) => {
-                    range_to_primitive(min_max.0, min_max.1, Primitive::U64)
+                    let ranged_type = range_to_primitive(min_max.0, min_max.1, Primitive::U64);
+                    if rule_metadata.newtype.is_some() {
+                        register_range_wrapper(
+                            types,
+                            parent_visitor,
+                            type_name,
+                            outer_tag,
+                            &rule_metadata,
+                            ranged_type,
+                            min_max,
+                            cli,
+                        )?;
+                        return Ok(());
+                    }
+                    ranged_type
+                }
+                _ => {
+                    check_no_newtype(type_name, &rule_metadata, "constants", type1.span)?;
+                    fallback_type.into()
                 }
-                _ => fallback_type.into(),
             };
             types.register_type_alias(
                 type_name.clone(),
@@ -733,13 +1846,30 @@ fn parse_type(
             );
         }
         Type2::TextValue { value, .. } => {
+            check_no_newtype(type_name, &rule_metadata, "constants", type1.span)?;
+            let value = match type1
+                .operator
+                .as_ref()
+                .map(|op| parse_control_operator(types, parent_visitor, &type1.type2, op, cli))
+                .transpose()?
+            {
+                Some(ControlOperator::Cat(value)) => value,
+                None => value.to_string(),
+                Some(_) => {
+                    return Err(CddlError::UnsupportedControlOperator(
+                        ErrorDetails::new(format!(
+                            "{}: only .cat is supported on text literals",
+                            type_name
+                        ))
+                        .at(type1.span),
+                    ))
+                }
+            };
             types.register_type_alias(
                 type_name.clone(),
                 AliasInfo::new_from_metadata(
-                    RustType::new(ConceptualRustType::Fixed(FixedValue::Text(
-                        value.to_string(),
-                    )))
-                    .tag_if(outer_tag),
+                    RustType::new(ConceptualRustType::Fixed(FixedValue::Text(value)))
+                        .tag_if(outer_tag),
                     rule_metadata,
                 ),
             );
@@ -750,23 +1880,239 @@ fn parse_type(
             let control = type1
                 .operator
                 .as_ref()
-                .map(|op| parse_control_operator(types, parent_visitor, &type1.type2, op, cli));
+                .map(|op| parse_control_operator(types, parent_visitor, &type1.type2, op, cli))
+                .transpose()?;
             // We end up here with ranges like foo = 0..5 which is why we're not just reporting a fixed value
             let base_type = match control {
-                Some(ControlOperator::Range(min_max)) => {
-                    range_to_primitive(min_max.0, min_max.1, Primitive::F64)
+                Some(ControlOperator::FloatRange(bounds)) => {
+                    let ranged_type = RustType::from(ConceptualRustType::Primitive(Primitive::F64))
+                        .with_float_bounds(bounds);
+                    if rule_metadata.newtype.is_some() {
+                        types.register_rust_struct(
+                            parent_visitor,
+                            RustStruct::new_wrapper(
+                                type_name.clone(),
+                                outer_tag,
+                                Some(&rule_metadata),
+                                ranged_type,
+                                None,
+                            ),
+                            cli,
+                        )?;
+                        return Ok(());
+                    }
+                    ranged_type
+                }
+                _ => {
+                    check_no_newtype(type_name, &rule_metadata, "constants", type1.span)?;
+                    fallback_type.into()
                 }
-                _ => fallback_type.into(),
             };
             types.register_type_alias(
                 type_name.clone(),
                 AliasInfo::new_from_metadata(base_type.tag_if(outer_tag), rule_metadata),
             );
         }
+        Type2::DataMajorType {
+            mt: 7, constraint, ..
+        } => {
+            check_no_newtype(type_name, &rule_metadata, "constants", type1.span)?;
+            types.register_type_alias(
+                type_name.clone(),
+                AliasInfo::new_from_metadata(
+                    major_type_7(*constraint, &type1.type2)
+                        .map_err(|e| e.or_at(type1.span))?
+                        .tag_if(outer_tag),
+                    rule_metadata,
+                ),
+            );
+        }
+        Type2::ChoiceFromInlineGroup { group, .. } => {
+            parse_choice_from_group(
+                types,
+                parent_visitor,
+                type_name,
+                std::slice::from_ref(group),
+                outer_tag,
+                &rule_metadata,
+                cli,
+            )?;
+        }
+        Type2::ChoiceFromGroup {
+            ident,
+            generic_args,
+            span,
+            ..
+        } => {
+            if generic_args.is_some() {
+                return Err(CddlError::UnsupportedGenerics(
+                    ErrorDetails::new(format!("{}: generic args on &{}", type_name, ident))
+                        .at(*span),
+                ));
+            }
+            let groups = types
+                .plain_group_definition(&RustIdent::new(CDDLIdent::new(ident.to_string())))
+                .ok_or_else(|| {
+                    CddlError::Unsupported(
+                        ErrorDetails::new(format!(
+                            "{}: &{} must refer to a group defined in the .cddl e.g. {} = (a: \"a\", b: \"b\")",
+                            type_name, ident, ident
+                        ))
+                        .at(*span),
+                    )
+                })?;
+            parse_choice_from_group(
+                types,
+                parent_visitor,
+                type_name,
+                &groups,
+                outer_tag,
+                &rule_metadata,
+                cli,
+            )?;
+        }
         x => {
-            panic!("\nignored typename {} -> {:?}\n", type_name, x);
+            return Err(CddlError::Unsupported(
+                ErrorDetails::new(format!("ignored typename {} -> {:?}", type_name, x))
+                    .at(type1.span),
+            ));
         }
     }
+    Ok(())
+}
+
+/// &group / &(...) is a choice between the values of the group's entries.
+/// We only support constants here which generate an enum whose variants are named after the entries
+/// e.g. &(english: "en", french: "fr") generates English / French
+/// `foo .within (a / b)` narrows foo down to just a / b which become an enum of their own, so
+/// any other value of foo is rejected when deserializing. If foo is a type choice each of them
+/// must be one of its choices, otherwise they must be encoded as the same CBOR type(s) as foo.
+fn parse_within(
+    types: &mut IntermediateTypes,
+    parent_visitor: &ParentVisitor,
+    type_name: &RustIdent,
+    type1: &Type1,
+    narrowed: &Type2,
+    tag: Option<u64>,
+    cli: &Cli,
+) -> Result<(), CddlError> {
+    let choices = match narrowed {
+        Type2::ParenthesizedType { pt, .. } => &pt.type_choices,
+        _ => {
+            return Err(CddlError::UnsupportedControlOperator(ErrorDetails::new(
+                format!(
+                    "{}: .within expects a parenthesized type choice e.g. foo .within (a / b)",
+                    type_name
+                ),
+            )))
+        }
+    };
+    let parent = rust_type_from_type2(types, parent_visitor, &type1.type2, cli)?;
+    parse_type_choices(types, parent_visitor, type_name, choices, tag, None, cli)?;
+    let variants = match types.rust_struct(type_name).map(RustStruct::variant) {
+        Some(RustStructType::TypeChoice { variants } | RustStructType::CStyleEnum { variants }) => {
+            variants.clone()
+        }
+        // e.g. T / null is an Option alias
+        _ => {
+            return Err(CddlError::UnsupportedControlOperator(ErrorDetails::new(
+                format!("{}: .within narrowing to {}", type_name, narrowed),
+            )))
+        }
+    };
+    let parent_choices = match parent.conceptual_type.resolve_alias_shallow() {
+        ConceptualRustType::Rust(ident) => {
+            match types.rust_struct(ident).map(RustStruct::variant) {
+                Some(
+                    RustStructType::TypeChoice { variants }
+                    | RustStructType::CStyleEnum { variants },
+                ) => Some(
+                    variants
+                        .iter()
+                        .filter_map(|variant| match &variant.data {
+                            EnumVariantData::RustType(ty) => Some(ty.clone()),
+                            EnumVariantData::Inlined(_) => None,
+                        })
+                        .collect::<Vec<_>>(),
+                ),
+                _ => None,
+            }
+        }
+        _ => None,
+    };
+    let parent_cbor_types = parent.cbor_types(types);
+    for variant in variants.iter() {
+        let is_within = match (&variant.data, &parent_choices) {
+            (EnumVariantData::RustType(ty), Some(parent_choices)) => parent_choices.contains(ty),
+            (EnumVariantData::RustType(ty), None) => ty
+                .cbor_types(types)
+                .iter()
+                .all(|cbor_type| parent_cbor_types.contains(cbor_type)),
+            (EnumVariantData::Inlined(_), _) => false,
+        };
+        if !is_within {
+            return Err(CddlError::UnsupportedControlOperator(ErrorDetails::new(
+                format!(
+                    "{}: choice {} is not within {}",
+                    type_name, variant.name, type1.type2
+                ),
+            )));
+        }
+    }
+    Ok(())
+}
+
+fn parse_choice_from_group(
+    types: &mut IntermediateTypes,
+    parent_visitor: &ParentVisitor,
+    name: &RustIdent,
+    groups: &[Group],
+    tag: Option<u64>,
+    rule_metadata: &RuleMetadata,
+    cli: &Cli,
+) -> Result<(), CddlError> {
+    let mut variant_names_used = BTreeMap::<String, u32>::new();
+    let mut variants = Vec::new();
+    for group in groups {
+        if group.group_choices.len() != 1 {
+            return Err(CddlError::Unsupported(
+                ErrorDetails::new(format!("{}: group choices inside of &(...)", name))
+                    .at(group.span),
+            ));
+        }
+        for (entry, optional_comma) in group.group_choices[0].group_entries.iter() {
+            let rust_type = group_entry_to_type(types, parent_visitor, entry, cli)?;
+            if !rust_type.is_fixed_value() {
+                return Err(CddlError::Unsupported(
+                    ErrorDetails::new(format!(
+                        "{}: &(...) entries must be constants e.g. &(a: \"a\", b: 1)",
+                        name
+                    ))
+                    .at(group_entry_span(entry)),
+                ));
+            }
+            let entry_metadata = group_entry_rule_metadata(entry, optional_comma)?;
+            let base_name = match &entry_metadata.name {
+                Some(name) => name_from_annotation(name, convert_to_camel_case)?,
+                None => match group_entry_to_raw_field_name(entry)? {
+                    Some(field_name) => convert_to_camel_case(&field_name),
+                    None => variant_name_from_type(&rust_type)
+                        .map_err(|e| e.or_at(group_entry_span(entry)))?,
+                },
+            };
+            let variant_name = append_number_if_duplicate(&mut variant_names_used, base_name);
+            variants.push(EnumVariant::new(
+                VariantIdent::new_custom(variant_name),
+                rust_type,
+                false,
+                entry_metadata.comment.clone(),
+            ));
+        }
+    }
+    let rust_struct =
+        RustStruct::new_type_choice(name.clone(), tag, Some(rule_metadata), variants, cli);
+    types.register_rust_struct(parent_visitor, rust_struct, cli)?;
+    Ok(())
 }
 
 // TODO: Also generates individual choices if required, ie for a / [foo] / c would generate Foos
@@ -774,27 +2120,27 @@ pub fn create_variants_from_type_choices(
     types: &mut IntermediateTypes,
     parent_visitor: &ParentVisitor,
     type_choices: &[TypeChoice],
+    variant_names_used: &mut BTreeMap<String, u32>,
     cli: &Cli,
-) -> Vec<EnumVariant> {
-    let mut variant_names_used = BTreeMap::<String, u32>::new();
+) -> Result<Vec<EnumVariant>, CddlError> {
     type_choices
         .iter()
-        .map(|choice| {
-            let rust_type = rust_type_from_type1(types, parent_visitor, &choice.type1, cli);
+        .map(|choice| -> Result<EnumVariant, CddlError> {
+            let rust_type = rust_type_from_type1(types, parent_visitor, &choice.type1, cli)?;
             let rule_metadata = RuleMetadata::from(choice.type1.comments_after_type.as_ref());
             let base_name = match &rule_metadata {
                 RuleMetadata {
                     name: Some(name), ..
-                } => convert_to_camel_case(name),
-                _ => rust_type.for_variant().to_string(),
+                } => name_from_annotation(name, convert_to_camel_case)?,
+                _ => variant_name_from_type(&rust_type).map_err(|e| e.or_at(choice.type1.span))?,
             };
-            let variant_name = append_number_if_duplicate(&mut variant_names_used, base_name);
-            EnumVariant::new(
+            let variant_name = append_number_if_duplicate(variant_names_used, base_name);
+            Ok(EnumVariant::new(
                 VariantIdent::new_custom(variant_name),
                 rust_type,
                 false,
                 rule_metadata.comment.clone(),
-            )
+            ))
         })
         .collect()
 }
@@ -822,21 +2168,23 @@ fn parse_group_type<'a>(
     group_choice: &'a GroupChoice<'a>,
     rep: Representation,
     cli: &Cli,
-) -> GroupParsingType {
+) -> Result<GroupParsingType, CddlError> {
     match rep {
         Representation::Array => {
             if group_choice.group_entries.len() == 1 {
                 let (entry, _has_comma) = &group_choice.group_entries[0];
                 let (elem_type, occur) = match entry {
                     GroupEntry::ValueMemberKey { ge, .. } => (
-                        rust_type(types, parent_visitor, &ge.entry_type, cli),
+                        rust_type(types, parent_visitor, &ge.entry_type, cli)?,
                         &ge.occur,
                     ),
                     GroupEntry::TypeGroupname { ge, .. } => (
                         types.new_type(&CDDLIdent::new(ge.name.to_string()), cli),
                         &ge.occur,
                     ),
-                    _ => panic!("UNSUPPORTED_ARRAY_ELEMENT<{:?}>", entry),
+                    GroupEntry::InlineGroup { group, .. } => {
+                        return Err(inline_group_unsupported(group))
+                    }
                 };
                 let bounds = occur.as_ref().map(|o| match o.occur {
                     Occur::ZeroOrMore { .. } => (None, None),
@@ -849,7 +2197,7 @@ fn parse_group_type<'a>(
                 });
                 match bounds {
                     // no bounds
-                    Some((None, None)) => return GroupParsingType::HomogenousArray(elem_type),
+                    Some((None, None)) => return Ok(GroupParsingType::HomogenousArray(elem_type)),
                     None | Some((Some(1), Some(1))) => {
                         // if the only element is a basic group we don't need to create a new group but can just
                         // change how it is (de)serialized
@@ -857,13 +2205,17 @@ fn parse_group_type<'a>(
                             elem_type.conceptual_type.resolve_alias_shallow()
                         {
                             if types.is_plain_group(elem_ident) {
-                                return GroupParsingType::WrappedBasicGroup(elem_type.not_basic());
+                                return Ok(GroupParsingType::WrappedBasicGroup(
+                                    elem_type.not_basic(),
+                                ));
                             }
                         }
                         // fall-through generic case. this is a general 1-element struct that needs creating
                     }
                     Some(bounds) => {
-                        return GroupParsingType::HomogenousArray(elem_type.with_bounds(bounds))
+                        return Ok(GroupParsingType::HomogenousArray(
+                            elem_type.with_bounds(bounds),
+                        ))
                     }
                 }
             }
@@ -877,32 +2229,65 @@ fn parse_group_type<'a>(
             // cbor but I would hope that the cddl specs we are using follow this.
             if group_choice.group_entries.len() == 1 {
                 match group_choice.group_entries.first() {
-                    Some((GroupEntry::ValueMemberKey { ge, .. }, _)) => {
+                    Some((GroupEntry::ValueMemberKey { ge, span, .. }, _)) => {
                         match &ge.member_key {
-                            Some(MemberKey::Type1 { t1, .. }) => {
+                            Some(member_key @ MemberKey::Type1 { t1, .. })
+                                if member_key_literal(member_key).is_none() =>
+                            {
                                 // TODO: Do we need to handle cuts for what we're doing?
                                 // Does the range control operator matter?
-                                let key_type = rust_type_from_type1(types, parent_visitor, t1, cli);
+                                let key_type =
+                                    rust_type_from_type1(types, parent_visitor, t1, cli)?;
                                 let value_type =
-                                    rust_type(types, parent_visitor, &ge.entry_type, cli);
-                                return GroupParsingType::HomogenousMap(key_type, value_type);
+                                    rust_type(types, parent_visitor, &ge.entry_type, cli)?;
+                                return Ok(GroupParsingType::HomogenousMap(key_type, value_type));
                             }
-                            Some(MemberKey::Value { .. }) => {
-                                // has a fixed value - this is just a 1-element struct
+                            Some(MemberKey::Value { .. } | MemberKey::Type1 { .. }) => {
+                                // has a fixed value e.g. { 0 => uint } - this is just a 1-element struct
+                            }
+                            _ => {
+                                return Err(CddlError::Unsupported(
+                                    ErrorDetails::new(format!("table map key (1): {:?}", ge))
+                                        .at(*span),
+                                ))
                             }
-                            _ => panic!("unsupported table map key (1): {:?}", ge),
                         }
                     }
-                    _ => panic!(
-                        "unsupported table map key (2): {:?}",
-                        group_choice.group_entries.first().unwrap()
-                    ),
+                    _ => {
+                        return Err(CddlError::Unsupported(
+                            ErrorDetails::new(format!(
+                                "table map key (2): {:?}",
+                                group_choice.group_entries.first().unwrap()
+                            ))
+                            .at(group_entry_span(
+                                &group_choice.group_entries.first().unwrap().0,
+                            )),
+                        ))
+                    }
                 }
             }
         }
     }
     // must be a heterogenous struct or 1-element fixed struct
-    GroupParsingType::Heterogenous
+    Ok(GroupParsingType::Heterogenous)
+}
+
+fn group_entry_span(entry: &GroupEntry) -> Span {
+    match entry {
+        GroupEntry::ValueMemberKey { span, .. }
+        | GroupEntry::TypeGroupname { span, .. }
+        | GroupEntry::InlineGroup { span, .. } => *span,
+    }
+}
+
+fn inline_group_unsupported(group: &Group) -> CddlError {
+    CddlError::Unsupported(
+        ErrorDetails::new(format!(
+            "inline group entries (define a new struct for this!): {}",
+            group
+        ))
+        .at(group.span),
+    )
 }
 
 // would use rust_type_from_type1 but that requires IntermediateTypes which we shouldn't
@@ -961,13 +2346,60 @@ fn type_to_field_name(t: &Type) -> Option<String> {
     }
 }
 
-fn _field_name_from_comments(comments: &Option<Comments<'_>>) -> Option<String> {
-    comments
-        .as_ref()?
-        .0
-        .iter()
-        .find(|c| c.trim().starts_with("field:"))
-        .map(|c| c.trim()[6..].trim().to_owned())
+fn _field_name_from_comments(comments: &Option<Comments<'_>>) -> Option<String> {
+    comments
+        .as_ref()?
+        .0
+        .iter()
+        .find(|c| c.trim().starts_with("field:"))
+        .map(|c| c.trim()[6..].trim().to_owned())
+}
+
+/// A @name converted to snake_case / CamelCase like any other generated name.
+/// Errors if that still isn't a valid rust identifier e.g. due to punctuation or non-latin letters.
+fn name_from_annotation(name: &str, convert: fn(&str) -> String) -> Result<String, CddlError> {
+    let converted = convert(name);
+    if is_valid_rust_ident(&converted) {
+        Ok(converted)
+    } else if let Some(message) = non_ascii_ident_error(&format!("@name \"{name}\""), &converted) {
+        Err(CddlError::InvalidAnnotation(ErrorDetails::new(message)))
+    } else {
+        Err(CddlError::InvalidAnnotation(ErrorDetails::new(format!(
+            "@name \"{}\" can't be used as a rust identifier (\"{}\"). Names can only contain letters, digits, spaces, _ and -",
+            name, converted
+        ))))
+    }
+}
+
+/// Generated names are ASCII-only. The case conversion already transliterated latin letters
+/// so this describes any other characters left in `converted`, which was generated from `what`.
+fn non_ascii_ident_error(what: &str, converted: &str) -> Option<String> {
+    let non_ascii = converted
+        .chars()
+        .filter(|c| !c.is_ascii())
+        .map(|c| format!("'{c}'"))
+        .collect::<Vec<_>>();
+    (!non_ascii.is_empty()).then(|| {
+        format!(
+            "{} contains characters with no ASCII equivalent ({}). Only latin letters with diacritics (e.g. é -> e) are transliterated, please use an ASCII name instead",
+            what,
+            non_ascii.join(", ")
+        )
+    })
+}
+
+/// Variant name generated from a type choice without a @name e.g. Foo for "foo" or U64 for uint
+fn variant_name_from_type(rust_type: &RustType) -> Result<String, CddlError> {
+    if let ConceptualRustType::Fixed(FixedValue::Text(text)) = &rust_type.conceptual_type {
+        // for_variant() silently drops anything that isn't ASCII
+        if let Some(message) = non_ascii_ident_error(
+            &format!("text value \"{text}\""),
+            &convert_to_camel_case(text),
+        ) {
+            return Err(CddlError::InvalidLiteral(ErrorDetails::new(message)));
+        }
+    }
+    Ok(rust_type.for_variant().to_string())
 }
 
 fn combine_comments<'a>(a: &'a Option<Comments>, b: &'a Option<Comments>) -> Option<Vec<&'a str>> {
@@ -989,8 +2421,23 @@ fn group_entry_to_field_name(
     index: usize,
     already_generated: &mut BTreeMap<String, u32>,
     optional_comma: &OptionalComma,
-) -> String {
+    cli: &Cli,
+) -> Result<String, CddlError> {
     //println!("group_entry_to_field_name() = {:#?}", entry);
+    // index_0 / key_1 etc when there is nothing better to name the field after
+    let positional_name = |prefix: &str, suffix: &dyn std::fmt::Display| {
+        if cli.strict_field_names {
+            Err(CddlError::UnnamedField(
+                ErrorDetails::new(format!(
+                    "{} has no name to derive one from. Give it one using the @name notation e.g. `; @name foo`",
+                    entry
+                ))
+                .at(group_entry_span(entry)),
+            ))
+        } else {
+            Ok(format!("{prefix}_{suffix}"))
+        }
+    };
     let field_name = convert_to_snake_case(&match entry {
         GroupEntry::ValueMemberKey {
             trailing_comments,
@@ -998,38 +2445,51 @@ fn group_entry_to_field_name(
             ..
         } => match ge.member_key.as_ref() {
             Some(member_key) => match member_key {
-                MemberKey::Value { value, .. } => {
+                MemberKey::Value { .. } | MemberKey::Type1 { .. } => {
                     let combined_comments =
                         combine_comments(trailing_comments, &optional_comma.trailing_comments);
                     match metadata_from_comments(&combined_comments.unwrap_or_default()) {
                         RuleMetadata {
                             name: Some(name), ..
-                        } => name,
-                        _ => format!("key_{value}"),
+                        } => name_from_annotation(&name, convert_to_snake_case)?,
+                        _ => match (member_key, member_key_literal(member_key)) {
+                            // 1: foo and 1 => foo are both key_1
+                            (_, Some(FixedValue::Uint(value))) => {
+                                positional_name(&cli.key_field_prefix, &value)?
+                            }
+                            (MemberKey::Value { value, .. }, _) => {
+                                positional_name(&cli.key_field_prefix, value)?
+                            }
+                            _ => {
+                                return Err(CddlError::Unsupported(ErrorDetails::new(format!(
+                                    "Type1 member key in multi-field map: {:?}",
+                                    entry
+                                )).at(group_entry_span(entry))))
+                            }
+                        },
                     }
                 }
                 MemberKey::Bareword { ident, .. } => ident.to_string(),
-                MemberKey::Type1 { t1, .. } => match t1.type2 {
-                    Type2::UintValue { value, .. } => format!("key_{value}"),
-                    _ => panic!(
-                        "Encountered Type1 member key in multi-field map - not supported: {:?}",
-                        entry
-                    ),
-                },
                 MemberKey::NonMemberKey { .. } => {
-                    panic!("Please open a github issue with repro steps")
+                    return Err(CddlError::Unsupported(ErrorDetails::new(format!(
+                        "non-member key in field: {:?}. Please open a github issue with repro steps",
+                        entry
+                    )).at(group_entry_span(entry))))
                 }
             },
-            None => type_to_field_name(&ge.entry_type).unwrap_or_else(|| {
-                let combined_comments =
-                    combine_comments(trailing_comments, &optional_comma.trailing_comments);
-                match metadata_from_comments(&combined_comments.unwrap_or_default()) {
-                    RuleMetadata {
-                        name: Some(name), ..
-                    } => name,
-                    _ => format!("index_{index}"),
+            None => match type_to_field_name(&ge.entry_type) {
+                Some(field_name) => field_name,
+                None => {
+                    let combined_comments =
+                        combine_comments(trailing_comments, &optional_comma.trailing_comments);
+                    match metadata_from_comments(&combined_comments.unwrap_or_default()) {
+                        RuleMetadata {
+                            name: Some(name), ..
+                        } => name_from_annotation(&name, convert_to_snake_case)?,
+                        _ => positional_name(&cli.index_field_prefix, &index)?,
+                    }
                 }
-            }),
+            },
         },
         GroupEntry::TypeGroupname {
             trailing_comments,
@@ -1042,44 +2502,49 @@ fn group_entry_to_field_name(
                 match metadata_from_comments(&combined_comments.unwrap_or_default()) {
                     RuleMetadata {
                         name: Some(name), ..
-                    } => name,
-                    _ => format!("index_{index}"),
+                    } => name_from_annotation(&name, convert_to_snake_case)?,
+                    _ => positional_name(&cli.index_field_prefix, &index)?,
                 }
             }
             false => name.to_string(),
         },
-        GroupEntry::InlineGroup { group, .. } => panic!(
-            "not implemented (define a new struct for this!) = {}\n\n {:?}",
-            group, group
-        ),
+        GroupEntry::InlineGroup { group, .. } => return Err(inline_group_unsupported(group)),
     });
-    append_number_if_duplicate(already_generated, field_name)
+    // e.g. from a text key "名前": uint
+    if let Some(message) =
+        non_ascii_ident_error(&format!("field name \"{field_name}\""), &field_name)
+    {
+        return Err(CddlError::InvalidLiteral(
+            ErrorDetails::new(message).at(group_entry_span(entry)),
+        ));
+    }
+    Ok(append_number_if_duplicate(already_generated, field_name))
 }
 
 // Only returns Some(String) if there was an explicit field name provided, otherwise None.
 // If you need to try and make one using the type/etc, then try group_entry_to_field_name()
 // Also does not do any CamelCase or snake_case formatting.
-fn group_entry_to_raw_field_name(entry: &GroupEntry) -> Option<String> {
+fn group_entry_to_raw_field_name(entry: &GroupEntry) -> Result<Option<String>, CddlError> {
     match entry {
         GroupEntry::ValueMemberKey { ge, .. } => match ge.member_key.as_ref() {
-            Some(MemberKey::Bareword { ident, .. }) => Some(ident.to_string()),
-            _ => None,
+            Some(MemberKey::Bareword { ident, .. }) => Ok(Some(ident.to_string())),
+            _ => Ok(None),
         },
         GroupEntry::TypeGroupname {
             ge: TypeGroupnameEntry { name, .. },
             ..
         } => match !is_identifier_user_defined(&name.to_string()) {
-            true => None,
-            false => Some(name.to_string()),
+            true => Ok(None),
+            false => Ok(Some(name.to_string())),
         },
-        GroupEntry::InlineGroup { group, .. } => panic!(
-            "not implemented (define a new struct for this!) = {}\n\n {:?}",
-            group, group
-        ),
+        GroupEntry::InlineGroup { group, .. } => Err(inline_group_unsupported(group)),
     }
 }
 
-fn group_entry_rule_metadata(entry: &GroupEntry, optional_comma: &OptionalComma) -> RuleMetadata {
+fn group_entry_rule_metadata(
+    entry: &GroupEntry,
+    optional_comma: &OptionalComma,
+) -> Result<RuleMetadata, CddlError> {
     let entry_trailing_comments = match entry {
         GroupEntry::ValueMemberKey {
             trailing_comments, ..
@@ -1087,14 +2552,31 @@ fn group_entry_rule_metadata(entry: &GroupEntry, optional_comma: &OptionalComma)
         GroupEntry::TypeGroupname {
             trailing_comments, ..
         } => trailing_comments,
-        GroupEntry::InlineGroup { group, .. } => panic!(
-            "not implemented (define a new struct for this!) = {}\n\n {:?}",
-            group, group
-        ),
+        GroupEntry::InlineGroup { group, .. } => return Err(inline_group_unsupported(group)),
     };
     let combined_comments =
         combine_comments(entry_trailing_comments, &optional_comma.trailing_comments);
-    metadata_from_comments(&combined_comments.unwrap_or_default())
+    Ok(metadata_from_comments(
+        &combined_comments.unwrap_or_default(),
+    ))
+}
+
+/// bytes .cbor T. For .cbor any there is no rust type for the inner item
+/// so the bytes are kept as-is, which also preserves them exactly.
+fn cbor_in_bytes(ty: Option<RustType>) -> RustType {
+    match ty {
+        Some(ty) => ty.as_bytes(),
+        None => ConceptualRustType::Primitive(Primitive::Bytes).into(),
+    }
+}
+
+fn cbor_any_unsupported_on_text(ty: Option<RustType>, span: Span) -> Result<RustType, CddlError> {
+    ty.ok_or_else(|| {
+        CddlError::UnsupportedControlOperator(
+            ErrorDetails::new("text .cbor any is not supported (only bytes .cbor any)".to_owned())
+                .at(span),
+        )
+    })
 }
 
 fn rust_type_from_type1(
@@ -1102,25 +2584,40 @@ fn rust_type_from_type1(
     parent_visitor: &ParentVisitor,
     type1: &Type1,
     cli: &Cli,
-) -> RustType {
+) -> Result<RustType, CddlError> {
     let control = type1
         .operator
         .as_ref()
-        .map(|op| parse_control_operator(types, parent_visitor, &type1.type2, op, cli));
-    let base_type = rust_type_from_type2(types, parent_visitor, &type1.type2, cli);
+        .map(|op| parse_control_operator(types, parent_visitor, &type1.type2, op, cli))
+        .transpose()?;
+    let base_type = rust_type_from_type2(types, parent_visitor, &type1.type2, cli)
+        .map_err(|e| e.or_at(type1.span))?;
     // println!("type1: {:#?}", type1);
-    match control {
-        Some(ControlOperator::CBOR(ty)) => {
-            assert!(matches!(
-                base_type.conceptual_type.resolve_alias_shallow(),
-                ConceptualRustType::Primitive(Primitive::Bytes)
-            ));
-            ty.as_bytes()
-        }
+    let rust_type = match control {
+        Some(ControlOperator::CBOR(ty)) => match base_type.conceptual_type.resolve_alias_shallow() {
+            // e.g. (bytes .size (0..64)) .cbor foo
+            ConceptualRustType::Primitive(Primitive::Bytes) => match base_type.config.bounds {
+                Some(bounds) => cbor_in_bytes(ty).with_bounds(bounds),
+                None => cbor_in_bytes(ty),
+            },
+            ConceptualRustType::Primitive(Primitive::Str) if cli.allow_cbor_on_text => {
+                cbor_any_unsupported_on_text(ty, type1.span)?.as_base64_text()
+            }
+            _ => {
+                return Err(CddlError::UnsupportedControlOperator(
+                    ErrorDetails::new(format!(
+                        ".cbor is only allowed on bytes as per CDDL spec (see --allow-cbor-on-text): {}",
+                        type1
+                    ))
+                    .at(type1.span),
+                ))
+            }
+        },
         Some(ControlOperator::Range((low, high))) => match &type1.type2 {
             Type2::Typename { ident, .. } => {
-                match ident_to_primitive(&CDDLIdent::new(ident.to_string())) {
-                    Some(p) => range_to_primitive(low, high, p),
+                match CDDLIdent::new(ident.to_string()).to_rust_primitive() {
+                    Some(p) => fixed_size_primitive(low, high, p, cli)
+                        .unwrap_or_else(|| range_to_primitive(low, high, p)),
                     None => base_type.with_bounds((low, high)),
                 }
             }
@@ -1130,9 +2627,82 @@ fn rust_type_from_type1(
             Type2::UintValue { .. } => range_to_primitive(low, high, Primitive::U64),
             _ => base_type.with_bounds((low, high)),
         },
+        Some(ControlOperator::FloatRange(bounds)) => match &type1.type2 {
+            // e.g. x: 0.5..1.5 where the base value is a constant like with integer ranges
+            Type2::FloatValue { .. } => RustType::from(ConceptualRustType::Primitive(Primitive::F64))
+                .with_float_bounds(bounds),
+            _ => base_type.with_float_bounds(bounds),
+        },
         Some(ControlOperator::Default(default_value)) => base_type.default(default_value),
+        Some(ControlOperator::Cat(value)) => {
+            ConceptualRustType::Fixed(FixedValue::Text(value)).into()
+        }
+        Some(ControlOperator::Regex(_)) => {
+            // there'd be nowhere to report a mismatch when setting the field
+            return Err(CddlError::UnsupportedControlOperator(
+                ErrorDetails::new(format!(
+                    "regexes are only supported on rules e.g. foo = text .regexp \"...\": {}",
+                    type1
+                ))
+                .at(type1.span),
+            ));
+        }
         None => base_type,
+    };
+    Ok(rust_type)
+}
+
+// Name for an argument of an anonymous generic instance. Arguments which are themselves
+// generic instances are spelled out e.g. bar<uint> becomes BarOfU64 so that foo<bar<uint>>
+// (FooBarOfU64) doesn't end up with the same name as foo<bar, uint> (FooBarU64).
+fn generic_arg_name(types: &IntermediateTypes, arg: &RustType) -> String {
+    if let ConceptualRustType::Rust(ident) = &arg.conceptual_type {
+        if let Some(instance) = types.get_generic_instance(ident) {
+            let args_name = instance
+                .generic_args()
+                .iter()
+                .map(|arg| generic_arg_name(types, arg))
+                .collect::<Vec<_>>()
+                .join("And");
+            return format!("{}Of{}", instance.generic_ident(), args_name);
+        }
+    }
+    arg.for_variant().to_string()
+}
+
+// Identifier for anonymous generic instances e.g. bar_string_bool for bar<text, bool>.
+// The name is only reused for the identical instantiation and otherwise a number is appended,
+// which is also the case if a rule already has that name e.g. foo_bar_of_u64 for foo<bar<uint>>.
+fn anonymous_generic_instance_ident(
+    types: &IntermediateTypes,
+    cddl_ident: &CDDLIdent,
+    generic_ident: &RustIdent,
+    generic_args: &[RustType],
+) -> CDDLIdent {
+    let args_name = generic_args
+        .iter()
+        .map(|arg| generic_arg_name(types, arg))
+        .collect::<Vec<String>>()
+        .join("_");
+    let base_name = format!("{cddl_ident}_{args_name}");
+    let mut instance_cddl_ident = CDDLIdent::new(base_name.clone());
+    let mut n = 2;
+    loop {
+        let instance_ident = RustIdent::new(instance_cddl_ident.clone());
+        match types.get_generic_instance(&instance_ident) {
+            Some(existing)
+                if existing.generic_ident() == generic_ident
+                    && existing.generic_args() == generic_args =>
+            {
+                break
+            }
+            None if !types.is_rule(&instance_ident) => break,
+            _ => (),
+        }
+        instance_cddl_ident = CDDLIdent::new(format!("{base_name}_{n}"));
+        n += 1;
     }
+    instance_cddl_ident
 }
 
 fn rust_type_from_type2(
@@ -1140,9 +2710,9 @@ fn rust_type_from_type2(
     parent_visitor: &ParentVisitor,
     type2: &Type2,
     cli: &Cli,
-) -> RustType {
-    // TODO: socket plugs (used in hash type)
-    match &type2 {
+) -> Result<RustType, CddlError> {
+    // TODO: group socket plugs ($$foo)
+    let rust_type = match &type2 {
         Type2::UintValue { value, .. } => {
             ConceptualRustType::Fixed(FixedValue::Uint(*value)).into()
         }
@@ -1156,9 +2726,19 @@ fn rust_type_from_type2(
         Type2::Typename {
             ident,
             generic_args,
-            ..
+            span,
         } => {
             let cddl_ident = CDDLIdent::new(ident.ident);
+            if ident.socket.is_some()
+                && types
+                    .type_socket(&RustIdent::new(cddl_ident.clone()))
+                    .is_none()
+            {
+                // this would be an empty type choice which we'd need `any` support for
+                return Err(CddlError::Unsupported(
+                    ErrorDetails::new(format!("{}: socket that is never defined", ident)).at(*span),
+                ));
+            }
             match generic_args {
                 Some(args) => {
                     // This is for anonymous instances (i.e. members) such as:
@@ -1169,21 +2749,20 @@ fn rust_type_from_type2(
                         .args
                         .iter()
                         .map(|a| rust_type_from_type1(types, parent_visitor, &a.arg, cli))
-                        .collect::<Vec<_>>();
-                    let args_name = generic_args
-                        .iter()
-                        .map(|t| t.for_variant().to_string())
-                        .collect::<Vec<String>>()
-                        .join("_");
-                    let instance_cddl_ident = CDDLIdent::new(format!("{cddl_ident}_{args_name}"));
-                    let instance_ident = RustIdent::new(instance_cddl_ident.clone());
-                    let generic_ident = RustIdent::new(cddl_ident);
-                    types.register_generic_instance(GenericInstance::new(
-                        instance_ident,
+                        .collect::<Result<Vec<_>, _>>()?;
+                    let generic_ident = RustIdent::new(cddl_ident.clone());
+                    let instance_cddl_ident = anonymous_generic_instance_ident(
+                        types,
+                        &cddl_ident,
+                        &generic_ident,
+                        &generic_args,
+                    );
+                    let instance_ident = types.register_generic_instance(GenericInstance::new(
+                        RustIdent::new(instance_cddl_ident),
                         generic_ident,
                         generic_args,
-                    ));
-                    types.new_type(&instance_cddl_ident, cli)
+                    ))?;
+                    ConceptualRustType::Rust(instance_ident).into()
                 }
                 None => types.new_type(&cddl_ident, cli),
             }
@@ -1199,7 +2778,7 @@ fn rust_type_from_type2(
                         group_choice,
                         Representation::Array,
                         cli,
-                    ) {
+                    )? {
                         GroupParsingType::HomogenousArray(element_type) => {
                             if let ConceptualRustType::Rust(element_ident) =
                                 &element_type.conceptual_type
@@ -1209,7 +2788,7 @@ fn rust_type_from_type2(
                                     element_ident,
                                     Representation::Array,
                                     cli,
-                                );
+                                )?;
                             }
                             ConceptualRustType::Array(Box::new(element_type)).into()
                         }
@@ -1220,8 +2799,12 @@ fn rust_type_from_type2(
                                     .as_ref(),
                             );
                             let name = match rule_metadata.name.as_ref() {
-                                Some(name) => name,
-                                None => panic!("Anonymous groups not allowed. Either create an explicit rule (foo = [0, bytes]) or give it a name using the @name notation. Group: {:#?}", group)
+                                Some(name) => {
+                                    // RustIdent::new() does the conversion itself
+                                    name_from_annotation(name, convert_to_camel_case)?;
+                                    name
+                                }
+                                None => return Err(CddlError::AnonymousGroup(ErrorDetails::new(format!("Either create an explicit rule (foo = [0, bytes]) or give it a name using the @name notation. Group: {}", group)).at(group.span)))
                             };
                             let cddl_ident = CDDLIdent::new(name);
                             let rust_ident = RustIdent::new(cddl_ident.clone());
@@ -1235,7 +2818,7 @@ fn rust_type_from_type2(
                                 None,
                                 &rule_metadata,
                                 cli,
-                            );
+                            )?;
                             // we aren't returning an array, but rather a struct where the fields are ordered
                             types.new_type(&cddl_ident, cli)
                         }
@@ -1243,7 +2826,12 @@ fn rust_type_from_type2(
                     }
                 }
                 // array of elements with choices: enums?
-                _ => unimplemented!("group choices in array type not supported"),
+                _ => {
+                    return Err(CddlError::Unsupported(
+                        ErrorDetails::new(format!("group choices in array type: {}", group))
+                            .at(group.span),
+                    ))
+                }
             }
         }
         Type2::Map { group, .. } => {
@@ -1256,7 +2844,7 @@ fn rust_type_from_type2(
                         group_choice,
                         Representation::Map,
                         cli,
-                    ) {
+                    )? {
                         // Table map - homogenous key/value types
                         GroupParsingType::HomogenousMap(key_type, value_type) => {
                             // Generate a MapTToV for a { t => v } table-type map as we are an anonymous type
@@ -1267,25 +2855,88 @@ fn rust_type_from_type2(
                             //types.register_rust_struct(RustStruct::new_table(table_type_ident, None, key_type.clone(), value_type.clone()));
                             ConceptualRustType::Map(Box::new(key_type), Box::new(value_type)).into()
                         }
-                        _ => unimplemented!("TODO: non-table types as types: {:?}", group),
+                        _ => {
+                            let rule_metadata = RuleMetadata::from(
+                                get_comment_after(parent_visitor, &CDDLType::from(type2), None)
+                                    .as_ref(),
+                            );
+                            let name = match rule_metadata.name.as_ref() {
+                                Some(name) => {
+                                    // RustIdent::new() does the conversion itself
+                                    name_from_annotation(name, convert_to_camel_case)?;
+                                    name.clone()
+                                }
+                                None => anonymous_map_name(parent_visitor, &CDDLType::from(type2))
+                                    .ok_or_else(|| CddlError::AnonymousGroup(ErrorDetails::new(format!("Either create an explicit rule (foo = {{ a: uint }}) or give it a name using the @name notation. Group: {}", group)).at(group.span)))?,
+                            };
+                            let cddl_ident = CDDLIdent::new(name);
+                            let rust_ident = RustIdent::new(cddl_ident.clone());
+                            parse_group(
+                                types,
+                                parent_visitor,
+                                group,
+                                &rust_ident,
+                                Representation::Map,
+                                None,
+                                None,
+                                &rule_metadata,
+                                cli,
+                            )?;
+                            types.new_type(&cddl_ident, cli)
+                        }
                     }
                 }
-                _ => unimplemented!(
-                    "group choices in inlined map types not allowed: {:?}",
-                    group
-                ),
+                _ => {
+                    return Err(CddlError::Unsupported(
+                        ErrorDetails::new(format!("group choices in inlined map types: {}", group))
+                            .at(group.span),
+                    ))
+                }
             }
         }
         // unsure if we need to handle the None case - when does this happen?
         Type2::TaggedData { tag, t, .. } => {
-            let tag_unwrap = tag.expect("tagged data without tag not supported");
-            rust_type(types, parent_visitor, t, cli).tag(tag_unwrap)
+            let tag_unwrap = tag.ok_or_else(|| {
+                CddlError::Unsupported(ErrorDetails::new(format!(
+                    "tagged data without tag: {}",
+                    type2
+                )))
+            })?;
+            rust_type(types, parent_visitor, t, cli)?.tag(tag_unwrap as u64)
         }
-        Type2::ParenthesizedType { pt, .. } => rust_type(types, parent_visitor, pt, cli),
+        Type2::ParenthesizedType { pt, .. } => rust_type(types, parent_visitor, pt, cli)?,
+        Type2::DataMajorType {
+            mt: 7, constraint, ..
+        } => major_type_7(*constraint, type2)?,
         _ => {
-            panic!("Ignoring Type2: {:?}", type2);
+            return Err(CddlError::Unsupported(ErrorDetails::new(format!(
+                "Ignoring Type2: {:?}",
+                type2
+            ))))
         }
-    }
+    };
+    Ok(rust_type)
+}
+
+// #7.N where N is the additional information of major type 7 (RFC 8610 section 3.6)
+// i.e. a simple value for N < 24 (or a one-byte simple value N >= 32) or a float for 25-27
+fn major_type_7(constraint: Option<u64>, type2: &Type2) -> Result<RustType, CddlError> {
+    let rust_type = match constraint {
+        Some(20) => ConceptualRustType::Fixed(FixedValue::Bool(false)),
+        Some(21) => ConceptualRustType::Fixed(FixedValue::Bool(true)),
+        Some(22) => ConceptualRustType::Fixed(FixedValue::Null),
+        Some(25) => ConceptualRustType::Primitive(Primitive::F16),
+        Some(26) => ConceptualRustType::Primitive(Primitive::F32),
+        Some(27) => ConceptualRustType::Primitive(Primitive::F64),
+        Some(n @ (0..=19 | 32..=255)) => ConceptualRustType::Fixed(FixedValue::SimpleValue(n as u8)),
+        _ => {
+            return Err(CddlError::Unsupported(ErrorDetails::new(format!(
+                "{}: only #7.N for simple values 0-19/32-255, false/true/null (20-22) and floats (25-27) are supported",
+                type2
+            ))))
+        }
+    };
+    Ok(rust_type.into())
 }
 
 fn rust_type(
@@ -1293,7 +2944,7 @@ fn rust_type(
     parent_visitor: &ParentVisitor,
     t: &Type,
     cli: &Cli,
-) -> RustType {
+) -> Result<RustType, CddlError> {
     if t.type_choices.len() == 1 {
         rust_type_from_type1(
             types,
@@ -1310,26 +2961,31 @@ fn rust_type(
             let a = &t.type_choices[0].type1;
             let b = &t.type_choices[1].type1;
             if type2_is_null(&a.type2) {
-                return ConceptualRustType::Optional(Box::new(rust_type_from_type1(
+                return Ok(ConceptualRustType::Optional(Box::new(rust_type_from_type1(
                     types,
                     parent_visitor,
                     b,
                     cli,
-                )))
-                .into();
+                )?))
+                .into());
             }
             if type2_is_null(&b.type2) {
-                return ConceptualRustType::Optional(Box::new(rust_type_from_type1(
+                return Ok(ConceptualRustType::Optional(Box::new(rust_type_from_type1(
                     types,
                     parent_visitor,
                     a,
                     cli,
-                )))
-                .into();
+                )?))
+                .into());
             }
         }
-        let variants =
-            create_variants_from_type_choices(types, parent_visitor, &t.type_choices, cli);
+        let variants = create_variants_from_type_choices(
+            types,
+            parent_visitor,
+            &t.type_choices,
+            &mut BTreeMap::new(),
+            cli,
+        )?;
         let mut combined_name = String::new();
         // one caveat: nested types can leave ambiguous names and cause problems like
         // (a / b) / c and a / (b / c) would both be AOrBOrC
@@ -1345,21 +3001,27 @@ fn rust_type(
             parent_visitor,
             RustStruct::new_type_choice(combined_ident, None, Some(&rule_metadata), variants, cli),
             cli,
-        );
-        types.new_type(&CDDLIdent::new(combined_name), cli)
+        )?;
+        Ok(types.new_type(&CDDLIdent::new(combined_name), cli))
     }
 }
 
-fn group_entry_optional(entry: &GroupEntry) -> bool {
+/// The occurrence indicator of a group entry e.g. Occurrence::OneOrMore for + T
+pub fn group_entry_to_occurrence(entry: &GroupEntry) -> Occurrence {
     let occur = match entry {
         GroupEntry::ValueMemberKey { ge, .. } => &ge.occur,
         GroupEntry::TypeGroupname { ge, .. } => &ge.occur,
-        GroupEntry::InlineGroup { .. } => panic!("inline group entries are not implemented"),
+        GroupEntry::InlineGroup { occur, .. } => occur,
     };
-    occur
-        .as_ref()
-        .map(|o| matches!(o.occur, Occur::Optional { .. }))
-        .unwrap_or(false)
+    match occur.as_ref().map(|o| &o.occur) {
+        None => Occurrence::Required,
+        Some(Occur::Optional { .. }) => Occurrence::Optional,
+        Some(Occur::ZeroOrMore { .. }) => Occurrence::ZeroOrMore,
+        Some(Occur::OneOrMore { .. }) => Occurrence::OneOrMore,
+        Some(Occur::Exact { lower, upper, .. }) => {
+            Occurrence::Bounded(lower.unwrap_or(0), upper.unwrap_or(usize::MAX))
+        }
+    }
 }
 
 fn group_entry_to_type(
@@ -1367,86 +3029,215 @@ fn group_entry_to_type(
     parent_visitor: &ParentVisitor,
     entry: &GroupEntry,
     cli: &Cli,
-) -> RustType {
+) -> Result<RustType, CddlError> {
     match entry {
         GroupEntry::ValueMemberKey { ge, .. } => {
             rust_type(types, parent_visitor, &ge.entry_type, cli)
         }
-        GroupEntry::TypeGroupname { ge, .. } => {
+        GroupEntry::TypeGroupname { ge, span, .. } => {
             if ge.generic_args.is_some() {
                 // I am not sure how we end up with this kind of generic args since definitional ones
                 // and member ones are created elsewhere. I thought that if you had a field like
                 // foo: bar<uint> it would be here but it turns out it's in the ValueMemberKey
                 // variant instead.
-                todo!("If you run into this please create a github issue and include the .cddl that caused it");
+                return Err(CddlError::UnsupportedGenerics(ErrorDetails::new(format!(
+                    "generic args on group entry {}. If you run into this please create a github issue and include the .cddl that caused it",
+                    ge.name
+                )).at(*span)));
             }
             let cddl_ident = CDDLIdent::new(ge.name.to_string());
-            types.new_type(&cddl_ident, cli)
+            Ok(types.new_type(&cddl_ident, cli))
         }
-        GroupEntry::InlineGroup { .. } => panic!("inline group entries are not implemented"),
+        GroupEntry::InlineGroup { group, .. } => Err(inline_group_unsupported(group)),
     }
 }
 
-fn group_entry_to_key(entry: &GroupEntry) -> Option<FixedValue> {
-    match entry {
-        GroupEntry::ValueMemberKey { ge, .. } => match ge.member_key.as_ref()? {
-            MemberKey::Value { value, .. } => match value {
-                cddl::token::Value::UINT(x) => Some(FixedValue::Uint(*x)),
-                cddl::token::Value::INT(x) => Some(FixedValue::Nint(*x)),
-                cddl::token::Value::TEXT(x) => Some(FixedValue::Text(x.to_string())),
-                cddl::token::Value::FLOAT(x) => Some(FixedValue::Float(*x)),
-                _ => panic!("unsupported map identifier(1): {:?}", value),
-            },
-            MemberKey::Bareword { ident, .. } => Some(FixedValue::Text(ident.to_string())),
-            MemberKey::Type1 { t1, .. } => match &t1.type2 {
-                Type2::UintValue { value, .. } => Some(FixedValue::Uint(*value)),
-                Type2::IntValue { value, .. } => Some(FixedValue::Nint(*value)),
-                Type2::TextValue { value, .. } => Some(FixedValue::Text(value.to_string())),
-                Type2::FloatValue { value, .. } => Some(FixedValue::Float(*value)),
-                _ => panic!("unsupported map identifier(2): {:?}", entry),
-            },
-            MemberKey::NonMemberKey { .. } => panic!("Please open a github issue with repro steps"),
+/// Literal value of a map key written either as a value (e.g. `1: uint`) or as
+/// a type (e.g. `1 => uint`), which are the same key for literals
+fn member_key_literal(member_key: &MemberKey) -> Option<FixedValue> {
+    match member_key {
+        MemberKey::Value { value, .. } => match value {
+            cddl::token::Value::UINT(x) => Some(FixedValue::Uint(*x)),
+            cddl::token::Value::INT(x) => Some(FixedValue::Nint(*x)),
+            cddl::token::Value::TEXT(x) => Some(FixedValue::Text(x.to_string())),
+            cddl::token::Value::FLOAT(x) => Some(FixedValue::Float(*x)),
+            _ => None,
+        },
+        // e.g. 0..5 => uint is a range of keys, not the key 0
+        MemberKey::Type1 { t1, .. } if t1.operator.is_none() => match &t1.type2 {
+            Type2::UintValue { value, .. } => Some(FixedValue::Uint(*value)),
+            Type2::IntValue { value, .. } => Some(FixedValue::Nint(*value)),
+            Type2::TextValue { value, .. } => Some(FixedValue::Text(value.to_string())),
+            Type2::FloatValue { value, .. } => Some(FixedValue::Float(*value)),
+            _ => None,
         },
         _ => None,
     }
 }
 
-fn parse_record_from_group_choice(
+fn group_entry_to_key(entry: &GroupEntry) -> Result<Option<FixedValue>, CddlError> {
+    let key = match entry {
+        GroupEntry::ValueMemberKey { ge, .. } => match ge.member_key.as_ref() {
+            Some(MemberKey::Bareword { ident, .. }) => FixedValue::Text(ident.to_string()),
+            Some(MemberKey::NonMemberKey { .. }) => {
+                return Err(CddlError::Unsupported(
+                    ErrorDetails::new(format!(
+                        "non-member key in map: {:?}. Please open a github issue with repro steps",
+                        entry
+                    ))
+                    .at(group_entry_span(entry)),
+                ))
+            }
+            Some(member_key) => member_key_literal(member_key).ok_or_else(|| {
+                CddlError::Unsupported(
+                    ErrorDetails::new(format!("map identifier: {:?}", member_key))
+                        .at(group_entry_span(entry)),
+                )
+            })?,
+            None => return Ok(None),
+        },
+        _ => return Ok(None),
+    };
+    Ok(Some(key))
+}
+
+pub fn parse_record_from_group_choice(
     types: &mut IntermediateTypes,
     rep: Representation,
     parent_visitor: &ParentVisitor,
     group_choice: &GroupChoice,
     cli: &Cli,
-) -> RustRecord {
+) -> Result<RustRecord, CddlError> {
     let mut generated_fields = BTreeMap::<String, u32>::new();
     let fields = group_choice
         .group_entries
         .iter()
         .enumerate()
-        .map(|(index, (group_entry, optional_comma))| {
+        .map(|(index, (group_entry, optional_comma))| -> Result<RustField, CddlError> {
             let field_name = group_entry_to_field_name(
                 group_entry,
                 index,
                 &mut generated_fields,
                 optional_comma,
-            );
-            let rule_metadata = group_entry_rule_metadata(group_entry, optional_comma);
+                cli,
+            )?;
+            let rule_metadata = group_entry_rule_metadata(group_entry, optional_comma)?;
             // does not exist for fixed values importantly
-            let field_type = group_entry_to_type(types, parent_visitor, group_entry, cli);
+            let mut field_type = group_entry_to_type(types, parent_visitor, group_entry, cli)?;
+            if let Some(path) = &rule_metadata.rust_type {
+                let is_path = path.split("::").all(|segment| {
+                    segment.starts_with(|c: char| c.is_ascii_alphabetic() || c == '_')
+                        && segment.chars().all(|c| c.is_ascii_alphanumeric() || c == '_')
+                });
+                if !is_path || field_type.is_fixed_value() {
+                    return Err(CddlError::InvalidAnnotation(
+                        ErrorDetails::new(format!(
+                            "{}: @rust_type {} must be a type path and can't be used on constant fields",
+                            field_name, path
+                        ))
+                        .at(group_entry_span(group_entry)),
+                    ));
+                }
+                field_type = types.rust_type_override(path);
+            }
             if let ConceptualRustType::Rust(ident) = &field_type.conceptual_type {
-                types.set_rep_if_plain_group(parent_visitor, ident, rep, cli);
+                types.set_rep_if_plain_group(parent_visitor, ident, rep, cli)?;
             }
-            let optional_field = group_entry_optional(group_entry);
-            let key = match rep {
-                Representation::Map => {
-                    Some(group_entry_to_key(group_entry).expect("map fields need keys"))
+            let occurrence = group_entry_to_occurrence(group_entry);
+            if let (Representation::Map, Some((Some(lower), _))) =
+                (rep, occurrence.repeated_bounds())
+            {
+                if lower > 1 {
+                    return Err(CddlError::Unsupported(ErrorDetails::new(format!(
+                        "map field {} must occur at least {} times but map keys are unique",
+                        field_name, lower
+                    )).at(group_entry_span(group_entry))));
+                }
+            }
+            let optional_field = occurrence.is_optional_in(rep);
+            // [header: uint, * item] - the repeated tail is stored as a Vec
+            let repeated = match rep {
+                Representation::Array => occurrence.repeated_bounds(),
+                Representation::Map => None,
+            };
+            let tail = repeated.is_some();
+            if let Some(occurrences) = repeated {
+                if index + 1 != group_choice.group_entries.len() {
+                    return Err(CddlError::Unsupported(ErrorDetails::new(format!(
+                        "repeated entry {} in an array that isn't the last entry",
+                        field_name
+                    )).at(group_entry_span(group_entry))));
+                }
+                if cli.preserve_encodings {
+                    return Err(CddlError::Unsupported(ErrorDetails::new(format!(
+                        "repeated entry {} after other array entries with --preserve-encodings",
+                        field_name
+                    )).at(group_entry_span(group_entry))));
                 }
+                field_type = RustType::from(ConceptualRustType::Array(Box::new(field_type)));
+                // e.g. + T needs at least one element
+                if occurrences != (None, None) {
+                    field_type = field_type.with_bounds(occurrences);
+                }
+                field_type.config.non_empty = occurrence == Occurrence::OneOrMore;
+            }
+            let key = match rep {
+                Representation::Map => Some(group_entry_to_key(group_entry)?.ok_or_else(|| {
+                    CddlError::Unsupported(
+                        ErrorDetails::new(format!("map field {} without a key", field_name))
+                            .at(group_entry_span(group_entry)),
+                    )
+                })?),
                 Representation::Array => None,
             };
-            RustField::new(field_name, field_type, optional_field, key, rule_metadata)
+            // ? key: T / null is Option<Option<T>> keeping an explicit null distinct from the key
+            // being absent. @collapse_null reads both as None instead.
+            let nullable_map_field = rep == Representation::Map
+                && optional_field
+                && matches!(field_type.conceptual_type, ConceptualRustType::Optional(_));
+            for (annotation, used) in [
+                ("@explicit_null", rule_metadata.explicit_null),
+                ("@collapse_null", rule_metadata.collapse_null),
+            ] {
+                if used && !nullable_map_field {
+                    return Err(CddlError::InvalidAnnotation(ErrorDetails::new(format!(
+                        "{} on field {} is only supported on optional map fields of the form ? key: T / null",
+                        annotation, field_name
+                    )).at(group_entry_span(group_entry))));
+                }
+            }
+            if rule_metadata.explicit_null && rule_metadata.collapse_null {
+                return Err(CddlError::InvalidAnnotation(ErrorDetails::new(format!(
+                    "field {} can't use both @explicit_null and @collapse_null",
+                    field_name
+                )).at(group_entry_span(group_entry))));
+            }
+            if rule_metadata.hex
+                && (optional_field
+                    || !matches!(
+                        field_type.resolve_alias_shallow(),
+                        ConceptualRustType::Primitive(Primitive::Bytes | Primitive::ByteArray(_))
+                    ))
+            {
+                return Err(CddlError::InvalidAnnotation(ErrorDetails::new(format!(
+                    "@hex on field {} is only supported on non-optional bytes fields",
+                    field_name
+                )).at(group_entry_span(group_entry))));
+            }
+            let collapse_null = rule_metadata.collapse_null;
+            if collapse_null {
+                if let ConceptualRustType::Optional(inner) = field_type.conceptual_type {
+                    field_type = *inner;
+                }
+            }
+            let mut field =
+                RustField::new(field_name, field_type, occurrence, key, rule_metadata);
+            field.collapse_null = collapse_null;
+            field.tail = tail;
+            Ok(field)
         })
-        .collect();
-    RustRecord { rep, fields }
+        .collect::<Result<_, _>>()?;
+    Ok(RustRecord { rep, fields })
 }
 
 #[allow(clippy::too_many_arguments)]
@@ -1456,11 +3247,11 @@ fn parse_group_choice(
     group_choice: &GroupChoice,
     name: &RustIdent,
     rep: Representation,
-    tag: Option<usize>,
+    tag: Option<u64>,
     generic_params: Option<Vec<RustIdent>>,
     parent_rule_metadata: Option<&RuleMetadata>,
     cli: &Cli,
-) {
+) -> Result<(), CddlError> {
     let rule_metadata = RuleMetadata::from(
         get_comment_after(parent_visitor, &CDDLType::from(group_choice), None).as_ref(),
     );
@@ -1469,7 +3260,7 @@ fn parse_group_choice(
     } else {
         rule_metadata
     };
-    let rust_struct = match parse_group_type(types, parent_visitor, group_choice, rep, cli) {
+    let rust_struct = match parse_group_type(types, parent_visitor, group_choice, rep, cli)? {
         GroupParsingType::HomogenousArray(element_type) => {
             if rule_metadata.newtype.is_some() {
                 // generate newtype over array
@@ -1507,21 +3298,117 @@ fn parse_group_choice(
             }
         }
         GroupParsingType::Heterogenous | GroupParsingType::WrappedBasicGroup(_) => {
-            assert!(
-                rule_metadata.newtype.is_none(),
-                "Can only use @newtype on primtives + heterogenious arrays/maps"
-            );
             // Heterogenous map or array with defined key/value pairs in the cddl like a struct
             let record =
-                parse_record_from_group_choice(types, rep, parent_visitor, group_choice, cli);
+                parse_record_from_group_choice(types, rep, parent_visitor, group_choice, cli)?;
+            if rule_metadata.all_fields_optional {
+                if generic_params.is_some() {
+                    return Err(CddlError::UnsupportedGenerics(
+                        ErrorDetails::new(format!(
+                            "{}: @all_fields_optional on generic types",
+                            name
+                        ))
+                        .at(group_choice.span),
+                    ));
+                }
+                register_all_fields_optional_record(
+                    types,
+                    parent_visitor,
+                    name,
+                    tag,
+                    &record,
+                    cli,
+                )?;
+            }
+            if rule_metadata.newtype.is_some() {
+                if generic_params.is_some() {
+                    return Err(CddlError::UnsupportedGenerics(
+                        ErrorDetails::new(format!(
+                            "{}: @newtype on generic map/array structs",
+                            name
+                        ))
+                        .at(group_choice.span),
+                    ));
+                }
+                // the struct itself goes under {name}Inner with the newtype around it, which
+                // also gets the tag since that's what is written first
+                let inner_ident = RustIdent::new(CDDLIdent::new(format!("{name}Inner")));
+                types.mark_scope(inner_ident.clone(), types.scope(name).clone());
+                types.register_rust_struct(
+                    parent_visitor,
+                    RustStruct::new_record(inner_ident.clone(), None, None, record),
+                    cli,
+                )?;
+                types.register_rust_struct(
+                    parent_visitor,
+                    RustStruct::new_wrapper(
+                        name.clone(),
+                        tag,
+                        Some(&rule_metadata),
+                        ConceptualRustType::Rust(inner_ident).into(),
+                        None,
+                    ),
+                    cli,
+                )?;
+                return Ok(());
+            }
             // We need to store this in IntermediateTypes so we can refer from one struct to another.
             RustStruct::new_record(name.clone(), tag, Some(&rule_metadata), record)
         }
     };
     match generic_params {
         Some(params) => types.register_generic_def(GenericDef::new(params, rust_struct)),
-        None => types.register_rust_struct(parent_visitor, rust_struct, cli),
+        None => types.register_rust_struct(parent_visitor, rust_struct, cli)?,
     };
+    Ok(())
+}
+
+// Creates {name}Update alongside the record with every (non-fixed) field optional
+// so that it can be used for partial updates where only the present keys are serialized.
+fn register_all_fields_optional_record(
+    types: &mut IntermediateTypes,
+    parent_visitor: &ParentVisitor,
+    name: &RustIdent,
+    tag: Option<u64>,
+    record: &RustRecord,
+    cli: &Cli,
+) -> Result<(), CddlError> {
+    if record.rep != Representation::Map {
+        return Err(CddlError::InvalidAnnotation(ErrorDetails::new(format!(
+            "{}: @all_fields_optional is only supported on map-encoded structs as array fields can't be omitted",
+            name
+        ))));
+    }
+    let fields = record
+        .fields
+        .iter()
+        .map(|field| {
+            let mut update_field = field.clone();
+            // fixed values have no member to be optional and are always written
+            if !field.rust_type.is_fixed_value() {
+                update_field.optional = true;
+                // a missing field means no update, not the default value
+                update_field.rust_type.config.default = None;
+            }
+            update_field
+        })
+        .collect();
+    let update_ident = RustIdent::new(CDDLIdent::new(format!("{name}Update")));
+    types.mark_scope(update_ident.clone(), types.scope(name).clone());
+    types.register_rust_struct(
+        parent_visitor,
+        RustStruct::new_record(
+            update_ident,
+            tag,
+            None,
+            RustRecord {
+                rep: Representation::Map,
+                fields,
+            },
+        ),
+        cli,
+    )?;
+    Ok(())
 }
 
 #[allow(clippy::too_many_arguments)]
@@ -1531,11 +3418,11 @@ pub fn parse_group(
     group: &Group,
     name: &RustIdent,
     rep: Representation,
-    tag: Option<usize>,
+    tag: Option<u64>,
     generic_params: Option<Vec<RustIdent>>,
     parent_rule_metadata: &RuleMetadata,
     cli: &Cli,
-) {
+) -> Result<(), CddlError> {
     if group.group_choices.len() == 1 {
         // Handle simple (no choices) group.
         parse_group_choice(
@@ -1548,130 +3435,204 @@ pub fn parse_group(
             generic_params,
             Some(parent_rule_metadata),
             cli,
-        );
+        )
     } else {
         if generic_params.is_some() {
-            todo!("{}: generic group choices not supported", name);
+            return Err(CddlError::UnsupportedGenerics(
+                ErrorDetails::new(format!("{}: generic group choices", name)).at(group.span),
+            ));
+        }
+        if parent_rule_metadata.newtype.is_some() {
+            return Err(CddlError::InvalidAnnotation(
+                ErrorDetails::new(format!("{}: @newtype on group choices", name)).at(group.span),
+            ));
         }
-        assert!(parent_rule_metadata.newtype.is_none());
         // Generate Enum object that is not exposed to wasm, since wasm can't expose
         // fully featured rust enums via wasm_bindgen
 
         // TODO: We don't support generating SerializeEmbeddedGroup for group choices which is necessary for plain groups
         // It would not be as trivial to add as we do the outer group's array/map tag writing inside the variant match
         // to avoid having to always generate SerializeEmbeddedGroup when not necessary.
-        assert!(!types.is_plain_group(name));
+        if types.is_plain_group(name) {
+            return Err(CddlError::Unsupported(
+                ErrorDetails::new(format!("{}: group choices in plain groups", name))
+                    .at(group.span),
+            ));
+        }
 
         // Handle group with choices by generating an enum then generating a group for every choice
+        let choices_metadata = group
+            .group_choices
+            .iter()
+            .map(|group_choice| RuleMetadata::from(group_choice.comments_before_grpchoice.as_ref()))
+            .collect::<Vec<_>>();
+        // @name'd variants take priority so generated names for the rest must avoid them
+        let mut explicit_variant_names = BTreeSet::new();
+        for (group_choice, variant_name) in group
+            .group_choices
+            .iter()
+            .zip(choices_metadata.iter())
+            .filter_map(|(group_choice, rmd)| Some((group_choice, rmd.name.as_ref()?)))
+        {
+            if !explicit_variant_names.insert(convert_to_camel_case(variant_name)) {
+                return Err(CddlError::InvalidAnnotation(
+                    ErrorDetails::new(format!(
+                        "{}: @name {} used on multiple group choices",
+                        name, variant_name
+                    ))
+                    .at(group_choice.span),
+                ));
+            }
+        }
         let mut variants_names_used = BTreeMap::<String, u32>::new();
+        // tracked by the final variant name since e.g. a field foo_1 and the 2nd choice of foo both become Foo1
+        let mut unused_variant_name = |base_name: String| loop {
+            let variant_name = append_number_if_duplicate(
+                &mut variants_names_used,
+                convert_to_camel_case(&base_name),
+            );
+            if !explicit_variant_names.contains(&convert_to_camel_case(&variant_name)) {
+                break variant_name;
+            }
+        };
         let variants: Vec<EnumVariant> = group
             .group_choices
             .iter()
+            .zip(choices_metadata)
             .enumerate()
-            .map(|(i, group_choice)| {
-                let rule_metadata =
-                    RuleMetadata::from(group_choice.comments_before_grpchoice.as_ref());
-                // If we're a 1-element we should just wrap that type in the variant rather than
-                // define a new struct just for each variant.
-                // TODO: handle map-based enums? It would require being able to extract the key logic
-                // We might end up doing this anyway to support table-maps in choices though.
-                if group_choice.group_entries.len() == 1 {
-                    let group_entry = &group_choice.group_entries.first().unwrap().0;
-                    let ty = group_entry_to_type(types, parent_visitor, group_entry, cli);
-                    let serialize_as_embedded =
-                        if let ConceptualRustType::Rust(ident) = &ty.conceptual_type {
-                            // we might need to generate it if not used elsewhere
-                            types.set_rep_if_plain_group(parent_visitor, ident, rep, cli);
-                            // manual match in case we expand operaitons later
-                            types.is_plain_group(ident)
-                                && !ty.encodings.iter().any(|enc| match enc {
-                                    CBOREncodingOperation::Tagged(_) => true,
-                                    CBOREncodingOperation::CBORBytes => true,
-                                })
-                        } else {
-                            false
-                        };
-                    let ident_name = rule_metadata.name.unwrap_or_else(|| {
-                        match group_entry_to_raw_field_name(group_entry) {
-                            Some(name) => name,
-                            None => append_number_if_duplicate(
-                                &mut variants_names_used,
-                                ty.for_variant().to_string(),
-                            ),
-                        }
-                    });
-                    let variant_ident =
-                        VariantIdent::new_custom(convert_to_camel_case(&ident_name));
-                    EnumVariant::new(
-                        variant_ident,
-                        ty,
-                        serialize_as_embedded,
-                        rule_metadata.comment.clone(),
-                    )
-                    // None => {
-                    //     // TODO: Weird case, group choice with only one fixed-value field.
-                    //     // What should we do here? In the future we could make this a
-                    //     // non-value-taking enum then handle this in the serialization code.
-                    //     // However, for now we just default to default behavior:
-                    //     let variant_name = format!("{}{}", name, i);
-                    //     // TODO: Should we generate these within their own namespace?
-                    //     codegen_group_choice(global, group_choice, &variant_name, rep, None);
-                    //     EnumVariant::new(variant_name.clone(), RustType::Rust(variant_name), true)
-                    // },
-                } else {
-                    let ident_name = rule_metadata.name.unwrap_or_else(|| format!("{name}{i}"));
-                    // General case, GroupN type identifiers and generate group choice since it's inlined here
-                    let variant_name = RustIdent::new(CDDLIdent::new(ident_name));
-                    types.mark_plain_group(
-                        variant_name.clone(),
-                        PlainGroupInfo::new(None, RuleMetadata::default()),
-                    );
-                    parse_group_choice(
-                        types,
-                        parent_visitor,
-                        group_choice,
-                        &variant_name,
-                        rep,
-                        None,
-                        generic_params.clone(),
-                        None,
-                        cli,
-                    );
-                    let name = VariantIdent::new_rust(variant_name.clone());
-                    let variant_ident = ConceptualRustType::Rust(variant_name.clone());
-                    if EnumVariant::can_embed_fields(types, &variant_ident) {
-                        let embedded_record =
-                            match types.remove_rust_struct(&variant_name).unwrap().variant {
-                                RustStructType::Record(record) => record,
-                                _ => unreachable!(),
+            .map(
+                |(i, (group_choice, rule_metadata))| -> Result<EnumVariant, CddlError> {
+                    // If we're a 1-element we should just wrap that type in the variant rather than
+                    // define a new struct just for each variant.
+                    // TODO: handle map-based enums? It would require being able to extract the key logic
+                    // We might end up doing this anyway to support table-maps in choices though.
+                    if group_choice.group_entries.len() == 1 {
+                        let group_entry = &group_choice.group_entries.first().unwrap().0;
+                        let ty = group_entry_to_type(types, parent_visitor, group_entry, cli)?;
+                        let serialize_as_embedded =
+                            if let ConceptualRustType::Rust(ident) = &ty.conceptual_type {
+                                // we might need to generate it if not used elsewhere
+                                types.set_rep_if_plain_group(parent_visitor, ident, rep, cli)?;
+                                // manual match in case we expand operaitons later
+                                types.is_plain_group(ident)
+                                    && !ty.encodings.iter().any(|enc| match enc {
+                                        CBOREncodingOperation::Tagged(_) => true,
+                                        CBOREncodingOperation::CBORBytes(_) => true,
+                                        CBOREncodingOperation::CBORText => true,
+                                    })
+                            } else {
+                                false
                             };
-                        EnumVariant::new_embedded(
-                            name,
-                            embedded_record,
+                        let ident_name = match rule_metadata.name {
+                            Some(name) => name_from_annotation(&name, convert_to_camel_case)?,
+                            None => unused_variant_name(
+                                match group_entry_to_raw_field_name(group_entry)? {
+                                    Some(field_name) => field_name,
+                                    None => variant_name_from_type(&ty)
+                                        .map_err(|e| e.or_at(group_entry_span(group_entry)))?,
+                                },
+                            ),
+                        };
+                        let variant_ident =
+                            VariantIdent::new_custom(convert_to_camel_case(&ident_name));
+                        Ok(EnumVariant::new(
+                            variant_ident,
+                            ty,
+                            serialize_as_embedded,
                             rule_metadata.comment.clone(),
-                        )
+                        ))
+                        // None => {
+                        //     // TODO: Weird case, group choice with only one fixed-value field.
+                        //     // What should we do here? In the future we could make this a
+                        //     // non-value-taking enum then handle this in the serialization code.
+                        //     // However, for now we just default to default behavior:
+                        //     let variant_name = format!("{}{}", name, i);
+                        //     // TODO: Should we generate these within their own namespace?
+                        //     codegen_group_choice(global, group_choice, &variant_name, rep, None);
+                        //     EnumVariant::new(variant_name.clone(), RustType::Rust(variant_name), true)
+                        // },
                     } else {
-                        EnumVariant::new(
-                            name,
-                            variant_ident.into(),
-                            true,
-                            rule_metadata.comment.clone(),
-                        )
+                        let ident_name = rule_metadata
+                            .name
+                            .unwrap_or_else(|| unused_variant_name(format!("{name}{i}")));
+                        // General case, GroupN type identifiers and generate group choice since it's inlined here
+                        let variant_name = RustIdent::new(CDDLIdent::new(ident_name));
+                        types.mark_plain_group(
+                            variant_name.clone(),
+                            PlainGroupInfo::new(None, RuleMetadata::default()),
+                        );
+                        parse_group_choice(
+                            types,
+                            parent_visitor,
+                            group_choice,
+                            &variant_name,
+                            rep,
+                            None,
+                            generic_params.clone(),
+                            None,
+                            cli,
+                        )?;
+                        let name = VariantIdent::new_rust(variant_name.clone());
+                        let variant_ident = ConceptualRustType::Rust(variant_name.clone());
+                        if EnumVariant::can_embed_fields(types, &variant_ident) {
+                            let embedded_record =
+                                match types.remove_rust_struct(&variant_name).unwrap().variant {
+                                    RustStructType::Record(record) => record,
+                                    _ => unreachable!(),
+                                };
+                            Ok(EnumVariant::new_embedded(
+                                name,
+                                embedded_record,
+                                rule_metadata.comment.clone(),
+                            ))
+                        } else {
+                            Ok(EnumVariant::new(
+                                name,
+                                variant_ident.into(),
+                                true,
+                                rule_metadata.comment.clone(),
+                            ))
+                        }
                     }
-                }
-            })
-            .collect();
+                },
+            )
+            .collect::<Result<_, _>>()?;
         let rule_metadata = merge_metadata(
             &RuleMetadata::from(
                 get_comment_after(parent_visitor, &CDDLType::from(group), None).as_ref(),
             ),
             parent_rule_metadata,
         );
+        if rule_metadata.tag_field.is_some() && rep != Representation::Map {
+            return Err(CddlError::InvalidAnnotation(
+                ErrorDetails::new(format!(
+                    "{}: @tag_field is only supported on map group choices",
+                    name
+                ))
+                .at(group.span),
+            ));
+        }
+        if let Some(tag_field) = &rule_metadata.tag_field {
+            for variant in variants.iter() {
+                let message = match variant.tag_field_value(types, tag_field) {
+                    Ok(FixedValue::Float(_)) => {
+                        "float discriminators not supported for @tag_field".to_owned()
+                    }
+                    Ok(_) => continue,
+                    Err(message) => message,
+                };
+                return Err(CddlError::InvalidAnnotation(
+                    ErrorDetails::new(format!("{}::{}: {}", name, variant.name, message))
+                        .at(group.span),
+                ));
+            }
+        }
         types.register_rust_struct(
             parent_visitor,
             RustStruct::new_group_choice(name.clone(), tag, Some(&rule_metadata), variants, rep),
             cli,
-        );
+        )?;
+        Ok(())
     }
 }
 
@@ -1715,6 +3676,64 @@ fn get_comments_if_type_parent<'a>(
 ///
 /// Note: we do NOT merge comments when the type is coincidentally the last node inside its parent structure
 ///    For example, the last CDDLType::GroupChoice inside a CDDLType::Group will not return its parent's comment
+// Default name for a struct-like map defined inline as a field's type e.g. the
+// { x: uint } in foo = { a: uint, b: { x: uint } } is foo_field1_map (FooField1Map).
+// For maps nested in those it's relative to the enclosing one's name e.g. foo_field1_map_field0_map
+fn anonymous_map_name<'a>(
+    parent_visitor: &'a ParentVisitor<'a, 'a>,
+    cddl_type: &CDDLType<'a, 'a>,
+) -> Option<String> {
+    let (field, mut ancestor) = enclosing_group_entry(parent_visitor, cddl_type)?;
+    let field_index = loop {
+        if let CDDLType::GroupChoice(group_choice) = ancestor {
+            break group_choice
+                .group_entries
+                .iter()
+                .position(|(entry, _)| std::ptr::eq(entry, field))?;
+        }
+        ancestor = ancestor.parent(parent_visitor)?;
+    };
+    // the struct the field is in is either a rule or an inlined map itself
+    let parent_name = loop {
+        match ancestor {
+            CDDLType::Rule(Rule::Type { rule, .. }) => break rule.name.to_string(),
+            CDDLType::Rule(Rule::Group { rule, .. }) => break rule.name.to_string(),
+            CDDLType::Type2(Type2::Map { .. } | Type2::Array { .. })
+                if enclosing_group_entry(parent_visitor, ancestor).is_some() =>
+            {
+                let rule_metadata =
+                    RuleMetadata::from(get_comment_after(parent_visitor, ancestor, None).as_ref());
+                break match (rule_metadata.name, ancestor) {
+                    (Some(name), _) => name,
+                    (None, CDDLType::Type2(Type2::Map { .. })) => {
+                        anonymous_map_name(parent_visitor, ancestor)?
+                    }
+                    // inlined arrays must be @name'd
+                    (None, _) => return None,
+                };
+            }
+            _ => (),
+        }
+        ancestor = ancestor.parent(parent_visitor)?;
+    };
+    Some(format!("{parent_name}_field{field_index}_map"))
+}
+
+// The group entry (field) that this is (part of) the type of, if it isn't directly a rule's
+fn enclosing_group_entry<'a>(
+    parent_visitor: &'a ParentVisitor<'a, 'a>,
+    cddl_type: &CDDLType<'a, 'a>,
+) -> Option<(&'a GroupEntry<'a>, &'a CDDLType<'a, 'a>)> {
+    let mut ancestor = cddl_type.parent(parent_visitor)?;
+    loop {
+        match ancestor {
+            CDDLType::GroupEntry(group_entry) => return Some((*group_entry, ancestor)),
+            CDDLType::Rule(_) => return None,
+            _ => ancestor = ancestor.parent(parent_visitor)?,
+        }
+    }
+}
+
 fn get_comment_after<'a>(
     parent_visitor: &'a ParentVisitor<'a, 'a>,
     cddl_type: &CDDLType<'a, 'a>,
@@ -49,24 +49,30 @@ fn run_test(
         .append(true)
         .open(test_path.join(format!("{export_path}/rust/src/lib.rs")))
         .unwrap();
+    // no_std crates have neither a serialization module nor cbor_event for deser_test
+    let no_std = options.contains(&"--no-std=true");
     // some external files/tests pasted in might need this
-    lib_rs
-        .write_all("\nuse serialization::*;\n".as_bytes())
-        .unwrap();
+    if !no_std {
+        lib_rs
+            .write_all("\nuse serialization::*;\n".as_bytes())
+            .unwrap();
+    }
     // copy external files in too (if needed) too
     for external_rust_file_path in external_rust_file_paths {
         let extern_rs = std::fs::read_to_string(external_rust_file_path).unwrap();
         lib_rs.write_all("\n\n".as_bytes()).unwrap();
         lib_rs.write_all(extern_rs.as_bytes()).unwrap();
     }
-    let deser_test_rs = std::fs::read_to_string(
-        std::path::PathBuf::from_str("tests")
-            .unwrap()
-            .join("deser_test"),
-    )
-    .unwrap();
-    lib_rs.write_all("\n\n".as_bytes()).unwrap();
-    lib_rs.write_all(deser_test_rs.as_bytes()).unwrap();
+    if !no_std {
+        let deser_test_rs = std::fs::read_to_string(
+            std::path::PathBuf::from_str("tests")
+                .unwrap()
+                .join("deser_test"),
+        )
+        .unwrap();
+        lib_rs.write_all("\n\n".as_bytes()).unwrap();
+        lib_rs.write_all(deser_test_rs.as_bytes()).unwrap();
+    }
     let test_rs = std::fs::read_to_string(test_path.join("tests.rs")).unwrap();
     lib_rs.write_all("\n\n".as_bytes()).unwrap();
     lib_rs.write_all(test_rs.as_bytes()).unwrap();
@@ -135,6 +141,15 @@ fn run_test(
         }
     }
     if wasm_test_dir.exists() {
+        // copy wasm tests into generated wasm code
+        let mut wasm_lib_rs = std::fs::OpenOptions::new()
+            .append(true)
+            .open(test_path.join(format!("{export_path}/wasm/src/lib.rs")))
+            .unwrap();
+        let test_wasm_rs = std::fs::read_to_string(&wasm_test_dir).unwrap();
+        wasm_lib_rs.write_all("\n\n".as_bytes()).unwrap();
+        wasm_lib_rs.write_all(test_wasm_rs.as_bytes()).unwrap();
+        std::mem::drop(wasm_lib_rs);
         println!("   ------ testing (wasm) ------");
         let cargo_test_wasm = std::process::Command::new("cargo")
             .arg("test")
@@ -184,6 +199,54 @@ fn run_test(
     }
 }
 
+/// parse_cddl_str() with the default CLI args plus any extra ones e.g. "--wasm=false"
+fn parse<'a>(
+    cddl: &'a str,
+    extra_args: &[&str],
+) -> Result<crate::intermediate::IntermediateTypes<'a>, crate::parsing::CddlError> {
+    use clap::Parser;
+    let cli = crate::cli::Cli::parse_from(
+        ["cddl-codegen", "--input=.", "--output=."]
+            .iter()
+            .chain(extra_args),
+    );
+    crate::parsing::parse_cddl_str(cddl, &cli)
+}
+
+fn ident(name: &str) -> crate::intermediate::RustIdent {
+    crate::intermediate::RustIdent::new(crate::intermediate::CDDLIdent::new(name))
+}
+
+#[test]
+fn item_order() {
+    // the same rules in another order must generate exactly the same code so that
+    // reordering a spec doesn't show up in diffs of the generated code
+    let test_path = std::path::PathBuf::from("tests").join("item-order");
+    let generate = |input: &str, check: bool| {
+        let output = std::process::Command::new("cargo")
+            .arg("run")
+            .arg("--")
+            .arg(format!(
+                "--input={}",
+                test_path.join(input).to_str().unwrap()
+            ))
+            .arg(format!(
+                "--output={}",
+                test_path.join("export").to_str().unwrap()
+            ))
+            .arg(format!("--check={check}"))
+            .output()
+            .unwrap();
+        if !output.status.success() {
+            eprintln!("{}", String::from_utf8(output.stdout).unwrap());
+            eprintln!("{}", String::from_utf8(output.stderr).unwrap());
+        }
+        output.status.success()
+    };
+    assert!(generate("input.cddl", false));
+    assert!(generate("reordered.cddl", true));
+}
+
 #[test]
 fn core_with_wasm() {
     use std::str::FromStr;
@@ -227,6 +290,1782 @@ fn core_no_wasm() {
     );
 }
 
+#[test]
+fn wasm() {
+    run_test(
+        "wasm",
+        &["--wasm=true", "--to-from-bytes-methods=true"],
+        None,
+        &[],
+        &[],
+        false,
+        &[],
+    );
+}
+
+#[test]
+fn result() {
+    run_test("result", &["--wasm=false"], None, &[], &[], false, &[]);
+}
+
+#[test]
+fn alias_depth_exceeded() {
+    let input = std::path::PathBuf::from("tests")
+        .join("alias-depth")
+        .join("input.cddl");
+    let cargo_run_result = std::process::Command::new("cargo")
+        .arg("run")
+        .arg("--")
+        .arg(format!("--input={}", input.to_str().unwrap()))
+        .arg("--output=tests/alias-depth/export")
+        .arg("--max-alias-depth=8")
+        .output()
+        .unwrap();
+    // cyclic aliases must be reported as an error instead of hanging or overflowing the stack
+    assert!(!cargo_run_result.status.success());
+    let stderr = String::from_utf8(cargo_run_result.stderr).unwrap();
+    assert!(stderr.contains("alias chain exceeded max depth of 8"));
+}
+
+#[test]
+fn parse_cddl_str() {
+    use crate::intermediate::{AliasIdent, RustStructType};
+    use crate::parsing::CddlError;
+    let types = parse(
+        "point = [x: uint, y: uint]\nshape = point / text\nid = uint\n",
+        &[],
+    )
+    .unwrap();
+    assert!(matches!(
+        types.rust_struct(&ident("point")).unwrap().variant(),
+        RustStructType::Record(record) if record.fields.len() == 2
+    ));
+    assert!(matches!(
+        types.rust_struct(&ident("shape")).unwrap().variant(),
+        RustStructType::TypeChoice { variants, .. } if variants.len() == 2
+    ));
+    assert!(types
+        .type_aliases()
+        .contains_key(&AliasIdent::Rust(ident("id"))));
+    let err = parse("point = [x: uint,\ny: ]\n", &[]).unwrap_err();
+    match err {
+        // positions are 1-based so 0 would mean the position was lost
+        CddlError::Syntax(errors) => {
+            assert!(!errors.is_empty());
+            assert!(errors.iter().all(|e| e.line > 0 && e.column > 0));
+        }
+        e => panic!("expected a syntax error, got {e}"),
+    }
+}
+
+#[test]
+fn parse_cddl_str_type_sockets() {
+    use crate::intermediate::{AliasIdent, RustStructType};
+    // extensions both before and after the socket is used
+    let types = parse(
+        "$choice /= uint\nfoo = [choice: $choice, single: $single]\n$choice /= text\n$single /= bytes\n",
+        &[],
+    )
+    .unwrap();
+    assert!(matches!(
+        types.rust_struct(&ident("choice")).unwrap().variant(),
+        RustStructType::TypeChoice { variants, .. } if variants.len() == 2
+    ));
+    assert!(types
+        .type_aliases()
+        .contains_key(&AliasIdent::Rust(ident("single"))));
+    assert!(parse("foo = [choice: $choice]\n", &[]).is_err());
+}
+
+#[test]
+fn validate_completeness() {
+    use crate::intermediate::UnresolvedRef;
+    let mut types = parse(
+        "foo = [bar: bar, bars: [* bar], baz: baz]\nbar = [uint, text]\nbaz = bar\n",
+        &[],
+    )
+    .unwrap();
+    assert!(types.validate_completeness().is_empty());
+    types.remove_rust_struct(&ident("bar"));
+    assert_eq!(
+        types.validate_completeness(),
+        vec![
+            UnresolvedRef {
+                referencing_type: ident("foo"),
+                unresolved_ident: ident("bar"),
+            },
+            UnresolvedRef {
+                referencing_type: ident("baz"),
+                unresolved_ident: ident("bar"),
+            },
+        ]
+    );
+}
+
+#[test]
+fn parse_cddl_str_full_range_bounds() {
+    use crate::intermediate::{AliasIdent, ConceptualRustType, Primitive};
+    let types = parse(
+        "a = uint .le 18446744073709551615\nb = int .ge -9223372036854775808\nc = int .le 9223372036854775807\nd = uint .le 18446744073709551614\n",
+        &[],
+    )
+    .unwrap();
+    let alias_type = |name: &str| {
+        types
+            .type_aliases()
+            .get(&AliasIdent::Rust(ident(name)))
+            .map(|info| info.base_type.clone())
+    };
+    let a = alias_type("a").unwrap();
+    assert_eq!(
+        a.conceptual_type,
+        ConceptualRustType::Primitive(Primitive::U64)
+    );
+    assert!(a.config.bounds.is_none());
+    for name in ["b", "c"] {
+        let t = alias_type(name).unwrap();
+        assert_eq!(
+            t.conceptual_type,
+            ConceptualRustType::Primitive(Primitive::I64)
+        );
+        assert!(t.config.bounds.is_none());
+    }
+    // an actual bound still needs a checked wrapper
+    assert!(alias_type("d").is_none());
+    assert!(types.rust_struct(&ident("d")).is_some());
+}
+
+#[test]
+fn parse_cddl_str_uint_odd_byte_sizes() {
+    use crate::intermediate::{ConceptualRustType, Primitive, RustStructType};
+    let types = parse("u24 = uint .size 3\nu48 = uint .size 6\n", &[]).unwrap();
+    for (name, backing, max) in [
+        ("u24", Primitive::U32, (1i128 << 24) - 1),
+        ("u48", Primitive::U64, (1i128 << 48) - 1),
+    ] {
+        match types.rust_struct(&ident(name)).unwrap().variant() {
+            RustStructType::Wrapper {
+                wrapped, min_max, ..
+            } => {
+                assert_eq!(
+                    wrapped.conceptual_type,
+                    ConceptualRustType::Primitive(backing)
+                );
+                assert_eq!(*min_max, Some((Some(0), Some(max))));
+            }
+            other => panic!("expected a wrapper: {other:?}"),
+        }
+    }
+}
+
+#[test]
+fn parse_cddl_str_128_bit_ints() {
+    use crate::intermediate::{AliasIdent, ConceptualRustType, Primitive, RustStructType};
+    let types = parse(
+        "u = uint .size 16\ni = int .size 16\nwrapped = uint .size 16 ; @newtype\n",
+        &[],
+    )
+    .unwrap();
+    for (name, primitive) in [("u", Primitive::U128), ("i", Primitive::I128)] {
+        let t = types
+            .type_aliases()
+            .get(&AliasIdent::Rust(ident(name)))
+            .map(|info| info.base_type.clone())
+            .unwrap();
+        assert_eq!(t.conceptual_type, ConceptualRustType::Primitive(primitive));
+        assert!(t.config.bounds.is_none());
+    }
+    // the full range is the type itself so there's nothing left to check
+    match types.rust_struct(&ident("wrapped")).unwrap().variant() {
+        RustStructType::Wrapper {
+            wrapped, min_max, ..
+        } => {
+            assert_eq!(
+                wrapped.conceptual_type,
+                ConceptualRustType::Primitive(Primitive::U128)
+            );
+            assert_eq!(*min_max, None);
+        }
+        other => panic!("expected a wrapper: {other:?}"),
+    }
+}
+
+#[test]
+fn parse_cddl_str_newtype() {
+    use crate::intermediate::{AliasIdent, RustStructType};
+    use crate::parsing::CddlError;
+    let types = parse(
+        "my-id = uint ; @newtype
+plain-id = uint
+small = uint .le 255 ; @newtype
+range = 0..1000 ; @newtype
+other = plain-id ; @newtype
+",
+        &[],
+    )
+    .unwrap();
+    for name in ["my-id", "small", "range", "other"] {
+        assert!(
+            matches!(
+                types.rust_struct(&ident(name)).map(|rs| rs.variant()),
+                Some(RustStructType::Wrapper { .. })
+            ),
+            "{name} should be a newtype"
+        );
+        assert!(!types
+            .type_aliases()
+            .contains_key(&AliasIdent::Rust(ident(name))));
+    }
+    assert!(types
+        .type_aliases()
+        .contains_key(&AliasIdent::Rust(ident("plain-id"))));
+    for input in [
+        "five = 5 ; @newtype
+",
+        "hello = \"hello\" ; @newtype
+",
+        "foo = bytes .cbor uint ; @newtype
+",
+    ] {
+        assert!(matches!(
+            parse(input, &[]),
+            Err(CddlError::InvalidAnnotation(_))
+        ));
+    }
+}
+
+#[test]
+fn parse_cddl_str_table_occurrences() {
+    use crate::intermediate::{ConceptualRustType, Primitive, RustStructType};
+    let types = parse(
+        "optional_table = { ? text => uint }\n\
+         one_or_more_table = { + text => uint }\n\
+         holder = [scores: { ? text => uint }]\n",
+        &[],
+    )
+    .unwrap();
+    let text = ConceptualRustType::Primitive(Primitive::Str);
+    let uint = ConceptualRustType::Primitive(Primitive::U64);
+    // a single entry with a type key is a table no matter its occurrence, not a 1-field struct
+    for name in ["optional_table", "one_or_more_table"] {
+        match types.rust_struct(&ident(name)).unwrap().variant() {
+            RustStructType::Table { domain, range } => {
+                assert_eq!(domain.conceptual_type, text);
+                assert_eq!(range.conceptual_type, uint);
+            }
+            other => panic!("{name} should be a table: {other:?}"),
+        }
+    }
+    match types.rust_struct(&ident("holder")).unwrap().variant() {
+        RustStructType::Record(record) => {
+            assert_eq!(
+                record.fields[0]
+                    .rust_type
+                    .conceptual_type
+                    .resolve_alias_shallow(),
+                &ConceptualRustType::Map(Box::new(text.into()), Box::new(uint.into()))
+            );
+        }
+        other => panic!("expected a record: {other:?}"),
+    }
+}
+
+#[test]
+fn parse_cddl_str_choice_from_group() {
+    use crate::intermediate::RustStructType;
+    let types = parse(
+        "colors = (red: \"r\", green: \"g\")\ncolors //= (blue: \"b\")\ncolor = &colors\nsize = &(small: 1, large: 2)\npalette = { * color => uint }\n",
+        &[],
+    )
+    .unwrap();
+    let variant_names = |name: &str| match types.rust_struct(&ident(name)).unwrap().variant() {
+        RustStructType::CStyleEnum { variants } => variants
+            .iter()
+            .map(|v| v.name.to_string())
+            .collect::<Vec<_>>(),
+        other => panic!("{name} should be a c-style enum, got {other:?}"),
+    };
+    assert_eq!(variant_names("color"), vec!["Red", "Green", "Blue"]);
+    assert_eq!(variant_names("size"), vec!["Small", "Large"]);
+    assert!(parse("keys = (a: uint)\nkey = &keys\n", &[]).is_err());
+}
+
+#[test]
+fn cddl_ident_builtins() {
+    use crate::intermediate::{CDDLIdent, Primitive};
+    for (name, primitive) in [
+        ("uint", Primitive::U64),
+        ("int", Primitive::I64),
+        ("nint", Primitive::N64),
+        ("tstr", Primitive::Str),
+        ("text", Primitive::Str),
+        ("bstr", Primitive::Bytes),
+        ("bytes", Primitive::Bytes),
+        ("bool", Primitive::Bool),
+        ("float", Primitive::F64),
+        ("float16", Primitive::F16),
+        ("float32", Primitive::F32),
+        ("float64", Primitive::F64),
+    ] {
+        let ident = CDDLIdent::new(name);
+        assert_eq!(ident.to_rust_primitive(), Some(primitive));
+        assert!(ident.is_builtin());
+    }
+    for constant in ["null", "nil", "true", "false"] {
+        assert!(CDDLIdent::new(constant).is_builtin());
+        assert_eq!(CDDLIdent::new(constant).to_rust_primitive(), None);
+    }
+    // the rest of the standard prelude is defined in CDDL itself
+    for not_builtin in ["biguint", "tdate", "foo"] {
+        assert!(!CDDLIdent::new(not_builtin).is_builtin());
+    }
+}
+
+#[test]
+fn parse_cddl_str_bool() {
+    use crate::intermediate::{
+        AliasIdent, CDDLIdent, ConceptualRustType, FixedValue, Primitive, RustStructType,
+    };
+    let types = parse(
+        "flags = [flag: bool, yes: true, no: false, ? enabled: bool .default true]\n",
+        &[],
+    )
+    .unwrap();
+    // bool/true/false are never aliases
+    for builtin in ["bool", "true", "false"] {
+        assert!(!types
+            .type_aliases()
+            .contains_key(&AliasIdent::new(CDDLIdent::new(builtin))));
+    }
+    match types.rust_struct(&ident("flags")).unwrap().variant() {
+        RustStructType::Record(record) => {
+            assert_eq!(
+                record.fields[0].rust_type.conceptual_type,
+                ConceptualRustType::Primitive(Primitive::Bool)
+            );
+            assert_eq!(
+                record.fields[1].rust_type.conceptual_type,
+                ConceptualRustType::Fixed(FixedValue::Bool(true))
+            );
+            assert_eq!(
+                record.fields[2].rust_type.conceptual_type,
+                ConceptualRustType::Fixed(FixedValue::Bool(false))
+            );
+            assert_eq!(
+                record.fields[3].rust_type.conceptual_type,
+                ConceptualRustType::Primitive(Primitive::Bool)
+            );
+            assert_eq!(
+                record.fields[3].rust_type.config.default,
+                Some(FixedValue::Bool(true))
+            );
+        }
+        other => panic!("expected a record: {other:?}"),
+    }
+}
+
+#[test]
+fn estimated_size() {
+    use crate::intermediate::RustStructType;
+    let types = parse(
+        "point = [x: uint, y: uint, ? label: text, kind: 0]\n\
+         choice = uint / point / [* point] / bool\n",
+        &[],
+    )
+    .unwrap();
+    match types.rust_struct(&ident("choice")).unwrap().variant() {
+        RustStructType::TypeChoice { variants } => {
+            let sizes = variants
+                .iter()
+                .map(|variant| variant.estimated_size(&types))
+                .collect::<Vec<_>>();
+            // the fixed value isn't stored and the Vec's contents are on the heap
+            assert_eq!(sizes, vec![8, 8 + 8 + 8 + 24, 24, 1]);
+        }
+        other => panic!("expected a type choice: {other:?}"),
+    }
+}
+
+#[test]
+fn parse_cddl_str_within() {
+    use crate::intermediate::RustStructType;
+    use crate::parsing::CddlError;
+    let types = parse(
+        "color = \"red\" / \"green\" / \"blue\"\n\
+         warm = color .within (\"red\" / \"green\")\n\
+         small = uint .within (1 / 2)\n",
+        &[],
+    )
+    .unwrap();
+    let variant_names = |name: &str| match types.rust_struct(&ident(name)).unwrap().variant() {
+        RustStructType::CStyleEnum { variants } => variants
+            .iter()
+            .map(|v| v.name.to_string())
+            .collect::<Vec<_>>(),
+        other => panic!("{name} should be a c-style enum, got {other:?}"),
+    };
+    assert_eq!(variant_names("warm"), vec!["Red", "Green"]);
+    assert_eq!(variant_names("small"), vec!["One", "Two"]);
+    // choices outside of the parent type
+    for invalid in [
+        "color = \"red\" / \"green\"\nother = color .within (\"red\" / \"purple\")\n",
+        "small = uint .within (1 / \"two\")\n",
+        "small = uint .within 1\n",
+    ] {
+        assert!(matches!(
+            parse(invalid, &[]),
+            Err(CddlError::UnsupportedControlOperator(_))
+        ));
+    }
+}
+
+#[test]
+fn parse_cddl_str_cbor_size() {
+    use crate::intermediate::{CBOREncodingOperation, RustStructType};
+    let types = parse(
+        "foo = [uint, text]\n\
+         bar = [\n\
+           a: (bytes .cbor foo) .size (0..64),\n\
+           b: (bytes .size (2..8)) .cbor uint,\n\
+           c: bytes .cbor foo,\n\
+         ]\n",
+        &[],
+    )
+    .unwrap();
+    match types.rust_struct(&ident("bar")).unwrap().variant() {
+        RustStructType::Record(record) => {
+            let encodings = record
+                .fields
+                .iter()
+                .map(|field| {
+                    assert!(field.rust_type.config.bounds.is_none());
+                    field.rust_type.encodings.clone()
+                })
+                .collect::<Vec<_>>();
+            assert_eq!(
+                encodings,
+                vec![
+                    vec![CBOREncodingOperation::CBORBytes(Some((None, Some(64))))],
+                    vec![CBOREncodingOperation::CBORBytes(Some((Some(2), Some(8))))],
+                    vec![CBOREncodingOperation::CBORBytes(None)],
+                ]
+            );
+        }
+        other => panic!("bar should be a record, got {other:?}"),
+    }
+}
+
+#[test]
+fn parse_cddl_str_literal_map_keys() {
+    use crate::intermediate::{FixedValue, RustStructType};
+    use crate::parsing::CddlError;
+    let types = parse(
+        "foo = { 0 => uint, 1: text, ? 2 => bytes, label: text }\n",
+        &[],
+    )
+    .unwrap();
+    match types.rust_struct(&ident("foo")).unwrap().variant() {
+        RustStructType::Record(record) => {
+            assert_eq!(
+                record
+                    .fields
+                    .iter()
+                    .map(|field| field.key.clone().unwrap())
+                    .collect::<Vec<_>>(),
+                vec![
+                    FixedValue::Uint(0),
+                    FixedValue::Uint(1),
+                    FixedValue::Uint(2),
+                    FixedValue::Text("label".to_owned()),
+                ]
+            );
+            assert_eq!(record.fields[0].name, "key_0");
+            assert_eq!(record.fields[1].name, "key_1");
+        }
+        other => panic!("foo should be a record, got {other:?}"),
+    }
+    // a range of keys isn't a single literal key
+    assert!(matches!(
+        parse("foo = { 0..5 => uint, label: text }\n", &[]),
+        Err(CddlError::Unsupported(_))
+    ));
+}
+
+#[test]
+fn parse_cddl_str_positional_field_names() {
+    use crate::intermediate::RustStructType;
+    use crate::parsing::CddlError;
+    let input = "foo = [uint, text]\nbar = { 0 => uint, label: text }\n";
+    let field_names = |types: &crate::intermediate::IntermediateTypes, name: &str| match types
+        .rust_struct(&ident(name))
+        .unwrap()
+        .variant()
+    {
+        RustStructType::Record(record) => record
+            .fields
+            .iter()
+            .map(|field| field.name.clone())
+            .collect::<Vec<_>>(),
+        other => panic!("{name} should be a record, got {other:?}"),
+    };
+    let types = parse(
+        input,
+        &["--index-field-prefix=field", "--key-field-prefix=unnamed"],
+    )
+    .unwrap();
+    assert_eq!(field_names(&types, "foo"), vec!["field_0", "field_1"]);
+    assert_eq!(field_names(&types, "bar"), vec!["unnamed_0", "label"]);
+    let strict: &[&str] = &["--strict-field-names=true"];
+    assert!(matches!(
+        parse("foo = [uint, text]\n", strict),
+        Err(CddlError::UnnamedField(_))
+    ));
+    assert!(matches!(
+        parse("bar = { 0 => uint, label: text }\n", strict),
+        Err(CddlError::UnnamedField(_))
+    ));
+    // anything with a name given or derived from its type is fine
+    let types = parse(
+        "baz = uint\nfoo = [baz, text, ; @name label\n]\nbar = {\n  0 => uint, ; @name id\n  label: text\n}\n",
+        strict,
+    )
+    .unwrap();
+    assert_eq!(field_names(&types, "foo"), vec!["baz", "label"]);
+    assert_eq!(field_names(&types, "bar"), vec!["id", "label"]);
+}
+
+#[test]
+fn parse_cddl_str_float_bounds() {
+    use crate::intermediate::{FloatBound, RustStructType};
+    let types = parse(
+        "foo = [\n\
+           a: float .lt 1.5,\n\
+           b: float32 .ge -2,\n\
+           c: 0.5...2.5,\n\
+           d: float64 .eq 3,\n\
+         ]\n",
+        &[],
+    )
+    .unwrap();
+    match types.rust_struct(&ident("foo")).unwrap().variant() {
+        RustStructType::Record(record) => {
+            assert!(record
+                .fields
+                .iter()
+                .all(|f| f.rust_type.config.bounds.is_none()));
+            assert_eq!(
+                record
+                    .fields
+                    .iter()
+                    .map(|f| f.rust_type.config.float_bounds)
+                    .collect::<Vec<_>>(),
+                vec![
+                    Some((None, Some(FloatBound::exclusive(1.5)))),
+                    Some((Some(FloatBound::inclusive(-2.0)), None)),
+                    Some((
+                        Some(FloatBound::inclusive(0.5)),
+                        Some(FloatBound::exclusive(2.5))
+                    )),
+                    Some((
+                        Some(FloatBound::inclusive(3.0)),
+                        Some(FloatBound::inclusive(3.0))
+                    )),
+                ]
+            );
+        }
+        other => panic!("foo should be a record, got {other:?}"),
+    }
+}
+
+#[test]
+fn parse_cddl_str_rust_type() {
+    use crate::intermediate::{ConceptualRustType, RustStructType};
+    use crate::parsing::CddlError;
+    let types = parse(
+        "payment = [\n  to: text,\n  amount: uint, ; @rust_type crate::domain::Amount\n]\n",
+        &[],
+    )
+    .unwrap();
+    match types.rust_struct(&ident("payment")).unwrap().variant() {
+        RustStructType::Record(record) => match &record.fields[1].rust_type.conceptual_type {
+            ConceptualRustType::Rust(ident) => {
+                assert_eq!(ident.to_string(), "crate::domain::Amount");
+                // provided by the user like extern types
+                assert!(matches!(
+                    types.rust_struct(ident).unwrap().variant(),
+                    RustStructType::Extern
+                ));
+            }
+            other => panic!("expected the forced type: {other:?}"),
+        },
+        other => panic!("expected a record: {other:?}"),
+    }
+    for invalid in [
+        "payment = [amount: uint, ; @rust_type crate::Amount<T>\n]\n",
+        "payment = [tag: 0, ; @rust_type crate::Tag\n]\n",
+    ] {
+        assert!(matches!(
+            parse(invalid, &[]),
+            Err(CddlError::InvalidAnnotation(_))
+        ));
+    }
+}
+
+#[test]
+fn parse_cddl_str_cat() {
+    use crate::intermediate::{AliasIdent, ConceptualRustType, FixedValue};
+    use crate::parsing::CddlError;
+    // suffix is defined after its use to check that constants are resolved first
+    let types = parse(
+        "x = \"v\" .cat \"1\"\ny = prefix .cat suffix\nz = \"a\" .cat (prefix .cat \"2\")\nprefix = \"v\"\nsuffix = \"1\"\n",
+        &[],
+    )
+    .unwrap();
+    let text_constant = |name: &str| match &types
+        .type_aliases()
+        .get(&AliasIdent::Rust(ident(name)))
+        .unwrap()
+        .base_type
+        .conceptual_type
+    {
+        ConceptualRustType::Fixed(FixedValue::Text(value)) => value.clone(),
+        other => panic!("{name} is not a text constant: {other:?}"),
+    };
+    assert_eq!(text_constant("x"), "v1");
+    assert_eq!(text_constant("y"), "v1");
+    assert_eq!(text_constant("z"), "av2");
+    assert!(matches!(
+        parse("x = \"v\" .cat text\n", &[]),
+        Err(CddlError::UnsupportedControlOperator(_))
+    ));
+}
+
+#[test]
+fn parse_cddl_str_annotated_names() {
+    use crate::intermediate::RustStructType;
+    use crate::parsing::CddlError;
+    let types = parse(
+        "foo = [\n  uint, ; @name \"first value\"\n  text, ; @name Second-Value\n]\n",
+        &[],
+    )
+    .unwrap();
+    match types.rust_struct(&ident("foo")).unwrap().variant() {
+        RustStructType::Record(record) => {
+            let names = record
+                .fields
+                .iter()
+                .map(|f| f.name.as_str())
+                .collect::<Vec<_>>();
+            assert_eq!(names, ["first_value", "second_value"]);
+        }
+        other => panic!("expected a record: {other:?}"),
+    }
+    let leading_digit = parse("foo = [\n  uint, ; @name 2nd\n]\n", &[]).unwrap();
+    match leading_digit.rust_struct(&ident("foo")).unwrap().variant() {
+        RustStructType::Record(record) => assert_eq!(record.fields[0].name, "n2nd"),
+        other => panic!("expected a record: {other:?}"),
+    }
+    assert!(matches!(
+        parse("foo = [\n  uint, ; @name a.b\n]\n", &[]),
+        Err(CddlError::InvalidAnnotation(_))
+    ));
+    let accented = parse("foo = [\n  uint, ; @name Größe\n]\n", &[]).unwrap();
+    match accented.rust_struct(&ident("foo")).unwrap().variant() {
+        RustStructType::Record(record) => assert_eq!(record.fields[0].name, "grosse"),
+        other => panic!("expected a record: {other:?}"),
+    }
+    match parse("foo = [\n  uint, ; @name 名前\n]\n", &[]) {
+        Err(CddlError::InvalidAnnotation(details)) => {
+            assert!(
+                details.message.contains("'名', '前'"),
+                "{}",
+                details.message
+            )
+        }
+        other => panic!("expected an invalid annotation error: {other:?}"),
+    }
+}
+
+#[test]
+fn parse_cddl_str_non_ascii_names() {
+    use crate::intermediate::RustStructType;
+    use crate::parsing::CddlError;
+    let types = parse("dir = \"nörd\" / \"süd\"\n", &[]).unwrap();
+    match types.rust_struct(&ident("dir")).unwrap().variant() {
+        RustStructType::CStyleEnum { variants } => assert_eq!(
+            variants
+                .iter()
+                .map(|v| v.name.to_string())
+                .collect::<Vec<_>>(),
+            ["Nord", "Sud"]
+        ),
+        other => panic!("expected a c-style enum: {other:?}"),
+    }
+    // these would otherwise be empty variant names
+    match parse("dir = \"北\" / \"南\"\n", &[]) {
+        Err(CddlError::InvalidLiteral(details)) => {
+            assert!(
+                details.message.contains("text value \"北\""),
+                "{}",
+                details.message
+            );
+            assert!(
+                details.message.contains("no ASCII equivalent ('北')"),
+                "{}",
+                details.message
+            );
+            assert!(details.location.is_some());
+        }
+        other => panic!("expected an invalid literal error: {other:?}"),
+    }
+    assert!(matches!(
+        parse("foo = { \"名前\": text, id: uint }\n", &[]),
+        Err(CddlError::InvalidLiteral(_))
+    ));
+}
+
+#[test]
+fn parse_cddl_str_hex_fields() {
+    use crate::parsing::CddlError;
+    assert!(parse("foo = [\n  key: bytes, ; @hex\n]\n", &[]).is_ok());
+    assert!(matches!(
+        parse("foo = [\n  count: uint, ; @hex\n]\n", &[]),
+        Err(CddlError::InvalidAnnotation(_))
+    ));
+    assert!(matches!(
+        parse("foo = {\n  ? 1: bytes, ; @hex\n}\n", &[]),
+        Err(CddlError::InvalidAnnotation(_))
+    ));
+}
+
+#[test]
+fn parse_cddl_str_field_occurrence() {
+    use crate::intermediate::{Occurrence, RustStructType};
+    let types = parse(
+        "required = [a: uint, b: uint]\n\
+         optional = [a: uint, ? b: uint]\n\
+         zero_or_more = [a: uint, * b: uint]\n\
+         one_or_more = [a: uint, + b: uint]\n\
+         bounded = [a: uint, 2*4 b: uint]\n\
+         upper_only = [a: uint, *3 b: uint]\n\
+         map_repeats = { 0 => uint, * 1 => uint, + 2 => uint }\n",
+        &[],
+    )
+    .unwrap();
+    let occurrences = |name: &str| match types.rust_struct(&ident(name)).unwrap().variant() {
+        RustStructType::Record(record) => record
+            .fields
+            .iter()
+            .map(|f| (f.occurrence, f.optional))
+            .collect::<Vec<_>>(),
+        other => panic!("expected a record: {other:?}"),
+    };
+    let required = (Occurrence::Required, false);
+    assert_eq!(occurrences("required"), [required, required]);
+    assert_eq!(
+        occurrences("optional"),
+        [required, (Occurrence::Optional, true)]
+    );
+    assert_eq!(
+        occurrences("zero_or_more"),
+        [required, (Occurrence::ZeroOrMore, false)]
+    );
+    assert_eq!(
+        occurrences("one_or_more"),
+        [required, (Occurrence::OneOrMore, false)]
+    );
+    // only + T tails are stored as a NonEmptyVec<T>
+    let non_empty = |name: &str| match types.rust_struct(&ident(name)).unwrap().variant() {
+        RustStructType::Record(record) => record.fields[1].rust_type.config.non_empty,
+        other => panic!("expected a record: {other:?}"),
+    };
+    assert!(non_empty("one_or_more"));
+    assert!(!non_empty("zero_or_more"));
+    assert!(!non_empty("bounded"));
+    assert_eq!(
+        occurrences("bounded"),
+        [required, (Occurrence::Bounded(2, 4), false)]
+    );
+    assert_eq!(
+        occurrences("upper_only"),
+        [required, (Occurrence::Bounded(0, 3), false)]
+    );
+    // map keys can't repeat so these are just optional / required fields
+    assert_eq!(
+        occurrences("map_repeats"),
+        [
+            required,
+            (Occurrence::ZeroOrMore, true),
+            (Occurrence::OneOrMore, false)
+        ]
+    );
+    assert!(matches!(
+        parse("foo = { 0 => uint, 2*3 1 => uint }\n", &[]),
+        Err(crate::parsing::CddlError::Unsupported(_))
+    ));
+    assert_eq!(
+        Occurrence::Bounded(0, 3).repeated_bounds(),
+        Some((None, Some(3)))
+    );
+    assert_eq!(
+        Occurrence::Bounded(2, usize::MAX).repeated_bounds(),
+        Some((Some(2), None))
+    );
+}
+
+#[test]
+fn convert_leading_digits() {
+    use crate::utils::{convert_to_camel_case, convert_to_snake_case};
+    assert_eq!(convert_to_camel_case("3des-cbc"), "N3desCbc");
+    assert_eq!(convert_to_snake_case("3des-cbc"), "n3des_cbc");
+    assert_eq!(convert_to_camel_case("0"), "N0");
+    assert_eq!(convert_to_snake_case("3DES"), "n3_des");
+    assert_eq!(convert_to_snake_case("0"), "n0");
+    assert_eq!(convert_to_camel_case("_1"), "N1");
+    assert_eq!(convert_to_camel_case("aes-128"), "Aes128");
+    assert_eq!(convert_to_snake_case("aes-128"), "aes_128");
+}
+
+#[test]
+fn convert_non_ascii() {
+    use crate::utils::{convert_to_camel_case, convert_to_snake_case, is_valid_rust_ident};
+    assert_eq!(convert_to_camel_case("café-crème"), "CafeCreme");
+    assert_eq!(convert_to_snake_case("ÉtatCivil"), "etat_civil");
+    assert_eq!(convert_to_snake_case("Œuvre"), "oeuvre");
+    assert_eq!(convert_to_camel_case("straße"), "Strasse");
+    assert!(is_valid_rust_ident(&convert_to_snake_case("Łódź")));
+    // no transliteration for these so they're rejected instead of becoming a non-ASCII ident
+    assert!(!is_valid_rust_ident(&convert_to_snake_case("名前")));
+    assert!(!is_valid_rust_ident(&convert_to_camel_case("Ωmega")));
+}
+
+#[test]
+fn append_number_if_duplicate_skips_used_names() {
+    use crate::utils::append_number_if_duplicate;
+    use std::collections::BTreeMap;
+    let mut used = BTreeMap::new();
+    assert_eq!(
+        append_number_if_duplicate(&mut used, "foo".to_owned()),
+        "foo"
+    );
+    assert_eq!(
+        append_number_if_duplicate(&mut used, "foo2".to_owned()),
+        "foo2"
+    );
+    assert_eq!(
+        append_number_if_duplicate(&mut used, "foo".to_owned()),
+        "foo3"
+    );
+    assert_eq!(
+        append_number_if_duplicate(&mut used, "foo".to_owned()),
+        "foo4"
+    );
+    assert_eq!(
+        append_number_if_duplicate(&mut used, "foo3".to_owned()),
+        "foo32"
+    );
+}
+
+#[test]
+fn parse_cddl_str_group_choice_variant_names_unique() {
+    use crate::intermediate::RustStructType;
+    use std::collections::BTreeSet;
+    // the 2nd choice would be Foo1 by default, as would the field foo_1 of the 1st
+    let types = parse("foo = [foo_1: uint // 1, text, text]\n", &[]).unwrap();
+    match types.rust_struct(&ident("foo")).unwrap().variant() {
+        RustStructType::GroupChoice { variants, .. } => {
+            let names = variants
+                .iter()
+                .map(|variant| variant.name.to_string())
+                .collect::<BTreeSet<_>>();
+            assert_eq!(names.len(), variants.len());
+        }
+        other => panic!("expected a group choice: {other:?}"),
+    }
+}
+
+#[test]
+fn parse_cddl_str_errors() {
+    use crate::parsing::CddlError;
+    let parse_err = |input: &str| parse(input, &[]).unwrap_err();
+    assert!(matches!(
+        parse_err("foo = text .cbor uint\n"),
+        CddlError::UnsupportedControlOperator(_)
+    ));
+    assert!(matches!(
+        parse_err("foo = uint .le 1.5\n"),
+        CddlError::InvalidLiteral(_)
+    ));
+    assert!(matches!(
+        parse_err("foo = [bar: [0, bytes]]\n"),
+        CddlError::AnonymousGroup(_)
+    ));
+    assert!(matches!(
+        parse_err("foo<T> = T / null\nbar = foo<uint>\n"),
+        CddlError::UnsupportedGenerics(_)
+    ));
+    assert!(matches!(
+        parse_err("foo = #6.1(#6.2(uint))\n"),
+        CddlError::Unsupported(_)
+    ));
+    // --wasm is on by default and can't expose rust Results
+    assert!(matches!(
+        parse_err("failure = [code: uint]\nres = uint / failure ; @result ok=uint err=failure\n"),
+        CddlError::InvalidAnnotation(_)
+    ));
+    assert!(matches!(
+        parse_err("network = \"mainnet\" / 1 ; @string_enum\n"),
+        CddlError::InvalidAnnotation(_)
+    ));
+    for tag_field_error in [
+        // not a map
+        "foo = [\n  type: \"a\", x: uint //\n  type: \"b\", y: text\n] ; @tag_field type\n",
+        // missing and optional discriminator
+        "foo = {\n  type: \"a\", x: uint //\n  y: text, z: uint\n} ; @tag_field type\n",
+        "foo = {\n  type: \"a\", x: uint //\n  ? type: \"b\", y: text\n} ; @tag_field type\n",
+        "foo = {\n  type: 1.5, x: uint //\n  type: 2.5, y: text\n} ; @tag_field type\n",
+    ] {
+        assert!(matches!(
+            parse_err(tag_field_error),
+            CddlError::InvalidAnnotation(_)
+        ));
+    }
+    assert!(matches!(
+        parse_err("foo = {\n  1: uint / null, ; @collapse_null\n}\n"),
+        CddlError::InvalidAnnotation(_)
+    ));
+    assert!(matches!(
+        parse_err("foo = {\n  ? 1: uint / null, ; @explicit_null @collapse_null\n}\n"),
+        CddlError::InvalidAnnotation(_)
+    ));
+}
+
+#[test]
+fn plain_group_extension_errors() {
+    use crate::parsing::CddlError;
+    let unsupported_message = |input: &str| match parse(input, &[]) {
+        Err(CddlError::Unsupported(details)) => {
+            assert!(details.location.is_some(), "{}", details.message);
+            details.message
+        }
+        other => panic!("expected an unsupported error: {other:?}"),
+    };
+    assert!(
+        unsupported_message("g //= (a: uint)\nfoo = [id: uint, g]\n")
+            .contains("without a plain group definition")
+    );
+    assert!(
+        unsupported_message("g = (a: uint)\ng //= (a: text)\nfoo = [id: uint, g]\n")
+            .contains("field 'a' added via //= is already defined")
+    );
+    assert!(
+        unsupported_message("g = (* uint)\ng //= (a: text)\nfoo = [id: uint, g]\n")
+            .contains("only groups with non-homogeneous fields can be extended")
+    );
+}
+
+#[test]
+fn plain_group_extension_before_base() {
+    use crate::intermediate::RustStructType;
+    let types = parse(
+        "g //= (b: text)\nfoo = [id: uint, g]\ng = (a: uint)\ng //= (c: bytes)\n",
+        &[],
+    )
+    .unwrap();
+    // the base group's fields come first, then the extensions in the order they appear
+    match types.rust_struct(&ident("g")).unwrap().variant() {
+        RustStructType::Record(record) => assert_eq!(
+            record
+                .fields
+                .iter()
+                .map(|field| field.name.as_str())
+                .collect::<Vec<_>>(),
+            vec!["a", "b", "c"]
+        ),
+        other => panic!("expected a record: {other:?}"),
+    }
+}
+
+#[test]
+fn parse_cddl_str_warn_unsupported() {
+    use crate::intermediate::{AliasIdent, ConceptualRustType};
+    use crate::parsing::CddlError;
+    let warn: &[&str] = &["--warn-unsupported=true"];
+    let types = parse("foo = #6.1(#6.2(uint))\nbar = [foo]\n", warn).unwrap();
+    let foo = types
+        .type_aliases()
+        .get(&AliasIdent::Rust(ident("foo")))
+        .unwrap();
+    assert!(
+        matches!(&foo.base_type.conceptual_type, ConceptualRustType::Rust(raw) if *raw == ident("raw_cbor"))
+    );
+    assert!(types.rust_struct(&ident("bar")).is_some());
+    // only things we can't generate code for are skipped, not mistakes in the input
+    assert!(matches!(
+        parse("network = \"mainnet\" / 1 ; @string_enum\n", warn),
+        Err(CddlError::InvalidAnnotation(_))
+    ));
+}
+
+#[test]
+fn parse_cddl_str_nested_generic_instances() {
+    use crate::intermediate::{ConceptualRustType, RustIdent, RustStructType};
+    let types = parse(
+        "foo<T> = [a: T]\nbar<T> = [b: T]\npair<A, B> = [a: A, b: B]\n\
+         uses = [nested: foo<bar<uint>>, flat: pair<bar<uint>, uint>, deeper: pair<bar<pair<uint, uint>>, uint>]\n",
+        &[],
+    )
+    .unwrap();
+    let field_type = |rust_struct: &RustIdent, index: usize| match types
+        .rust_struct(rust_struct)
+        .unwrap()
+        .variant()
+    {
+        RustStructType::Record(record) => record.fields[index].rust_type.conceptual_type.clone(),
+        _ => panic!("{rust_struct} should be a record"),
+    };
+    // inner instances are concrete types of their own that the outer ones refer to
+    assert!(types.rust_struct(&ident("bar_U64")).is_some());
+    assert_eq!(
+        field_type(&ident("foo_BarOfU64"), 0),
+        ConceptualRustType::Rust(ident("bar_U64"))
+    );
+    assert_eq!(
+        field_type(&ident("pair_BarOfU64_U64"), 0),
+        ConceptualRustType::Rust(ident("bar_U64"))
+    );
+    assert!(types
+        .rust_struct(&ident("pair_BarOfPairOfU64AndU64_U64"))
+        .is_some());
+    // not to be confused with e.g. foo<bar, uint> which would be FooBarU64
+    assert!(types.rust_struct(&ident("foo_Bar_U64")).is_none());
+}
+
+#[test]
+fn parse_cddl_str_generic_instance_name_taken_by_rule() {
+    use crate::intermediate::{ConceptualRustType, RustStructType};
+    // foo<bar<uint>> would be named FooBarOfU64 the same as the foo_bar_of_u64 rule
+    let types = parse(
+        "uses = [nested: foo<bar<uint>>, deeper: foo<foo<bar<uint>>>]\n\
+         foo<T> = [a: T]\nbar<T> = [b: T]\n\
+         foo_bar_of_u64 = [x: text]\n",
+        &[],
+    )
+    .unwrap();
+    let fields = |rust_struct: &str| match types.rust_struct(&ident(rust_struct)).unwrap().variant()
+    {
+        RustStructType::Record(record) => record
+            .fields
+            .iter()
+            .map(|field| field.rust_type.conceptual_type.clone())
+            .collect::<Vec<_>>(),
+        other => panic!("{rust_struct} should be a record: {other:?}"),
+    };
+    // the rule keeps its name and the instance gets a number appended instead
+    match types
+        .rust_struct(&ident("foo_bar_of_u64"))
+        .unwrap()
+        .variant()
+    {
+        RustStructType::Record(record) => assert_eq!(record.fields[0].name, "x"),
+        other => panic!("expected a record: {other:?}"),
+    }
+    assert_eq!(
+        fields("uses"),
+        vec![
+            ConceptualRustType::Rust(ident("foo_BarOfU64_2")),
+            ConceptualRustType::Rust(ident("foo_FooOfBarOfU64")),
+        ]
+    );
+    // two levels of nesting refer down to the renamed instance
+    assert_eq!(
+        fields("foo_FooOfBarOfU64"),
+        vec![ConceptualRustType::Rust(ident("foo_BarOfU64_2"))]
+    );
+    assert_eq!(
+        fields("foo_BarOfU64_2"),
+        vec![ConceptualRustType::Rust(ident("bar_U64"))]
+    );
+}
+
+#[test]
+fn parse_cddl_str_visit_rust_structs() {
+    use crate::intermediate::RustStructKind;
+    use std::collections::BTreeMap;
+    let types = parse(
+        "point = [x: uint, y: uint]\n\
+         area = { width: uint, height: uint }\n\
+         labels = { * text => uint }\n\
+         points = [* point]\n\
+         value = uint / text\n\
+         shape = [radius: uint // side: text]\n\
+         hash = bytes .size 32\n\
+         color = 0 / 1 / 2\n",
+        &[],
+    )
+    .unwrap();
+    let mut kinds = BTreeMap::new();
+    let mut point_fields = Vec::new();
+    types.visit_rust_structs(&mut |rust_struct| {
+        *kinds.entry(rust_struct.kind()).or_insert(0) += 1;
+        if rust_struct.ident().to_string() == "Point" {
+            point_fields = rust_struct
+                .field_types()
+                .into_iter()
+                .map(|(name, ty)| (name, ty.conceptual_type.clone()))
+                .collect();
+        }
+    });
+    assert_eq!(
+        kinds,
+        BTreeMap::from([
+            (RustStructKind::Record, 2),
+            (RustStructKind::Table, 1),
+            (RustStructKind::Array, 1),
+            (RustStructKind::TypeChoice, 1),
+            (RustStructKind::GroupChoice, 1),
+            (RustStructKind::Wrapper, 1),
+            (RustStructKind::CStyleEnum, 1),
+        ])
+    );
+    let uint =
+        crate::intermediate::ConceptualRustType::Primitive(crate::intermediate::Primitive::U64);
+    assert_eq!(
+        point_fields,
+        vec![("x".to_owned(), uint.clone()), ("y".to_owned(), uint)]
+    );
+}
+
+#[test]
+fn type_graph_dot() {
+    let types = parse(
+        "point = [x: uint, y: uint]\n\
+         circle = [center: point, radius: uint]\n\
+         shape = point / circle\n\
+         labeled<T> = [label: text, value: T]\n\
+         points = [* point]\n\
+         labeled_points = labeled<points>\n",
+        &[],
+    )
+    .unwrap();
+    let dot = crate::dep_graph::type_graph_dot(&types);
+    assert!(dot.starts_with("digraph types {\n"));
+    assert!(dot.ends_with("}\n"));
+    for line in [
+        r#""Point" [shape=box, label="Point\n(Record)"];"#,
+        r#""Shape" [shape=box, label="Shape\n(TypeChoice)"];"#,
+        r#""Labeled" [shape=box, style=dashed, label="Labeled\n(generic)"];"#,
+        // field reference
+        r#""Circle" -> "Point" [label="center"];"#,
+        // enum variants
+        r#""Shape" -> "Point" [label="Point", style=dashed];"#,
+        r#""Shape" -> "Circle" [label="Circle", style=dashed];"#,
+        r#""Points" -> "Point" [label="element"];"#,
+        r#""LabeledPoints" -> "Points" [label="value"];"#,
+        r#""LabeledPoints" -> "Labeled" [label="instance of", style=dotted];"#,
+    ] {
+        assert!(dot.contains(line), "missing {line} in:\n{dot}");
+    }
+    // primitives aren't nodes
+    assert!(!dot.contains("-> \"U64\""));
+}
+
+#[test]
+fn parse_cddl_str_identical_generic_instances_shared() {
+    use crate::intermediate::{AliasIdent, ConceptualRustType, RustStructType};
+    let types = parse(
+        "bar<T> = [x: T]\n\
+         foo = bar<uint>\n\
+         uses_uint = [a: bar<uint>, b: foo]\n\
+         holder = [a: bar<text>]\n\
+         text_bar = bar<text>\n",
+        &[],
+    )
+    .unwrap();
+    // one struct each for bar<uint> and bar<text>, under whichever name came first
+    assert_eq!(types.list_generic_instances().count(), 2);
+    assert!(types.rust_struct(&ident("foo")).is_some());
+    assert!(types.rust_struct(&ident("bar_U64")).is_none());
+    match types.rust_struct(&ident("uses_uint")).unwrap().variant() {
+        RustStructType::Record(record) => assert_eq!(
+            record.fields[0].rust_type.conceptual_type,
+            ConceptualRustType::Rust(ident("foo"))
+        ),
+        other => panic!("expected a record: {other:?}"),
+    }
+    // rules are handled in dependency then alphabetical order so holder's bar<text> came first
+    assert!(types.rust_struct(&ident("text_bar")).is_none());
+    assert_eq!(
+        types
+            .type_aliases()
+            .get(&AliasIdent::Rust(ident("text_bar")))
+            .unwrap()
+            .base_type
+            .conceptual_type,
+        ConceptualRustType::Rust(ident("bar_Text"))
+    );
+}
+
+#[test]
+fn parse_cddl_str_fixed_size_byte_arrays() {
+    use crate::cli::Cli;
+    use crate::intermediate::{ConceptualRustType, Primitive, RustStructType};
+    use clap::Parser;
+    let cli = Cli::parse_from([
+        "cddl-codegen",
+        "--input=.",
+        "--output=.",
+        "--wasm=false",
+        "--fixed-size-byte-arrays=true",
+    ]);
+    // for_rust_member() below needs the CLI too so this one can't use parse()
+    let types = crate::parsing::parse_cddl_str(
+        "hash32 = bstr .size 32\n\
+         label = bstr .size (1..8)\n",
+        &cli,
+    )
+    .unwrap();
+    match types.rust_struct(&ident("hash32")).unwrap().variant() {
+        RustStructType::Wrapper {
+            wrapped, min_max, ..
+        } => {
+            assert_eq!(
+                wrapped.conceptual_type,
+                ConceptualRustType::Primitive(Primitive::ByteArray(32))
+            );
+            assert_eq!(
+                wrapped.conceptual_type.for_rust_member(&types, false, &cli),
+                "[u8; 32]"
+            );
+            assert_eq!(*min_max, None);
+        }
+        other => panic!("expected a wrapper: {other:?}"),
+    }
+    match types.rust_struct(&ident("label")).unwrap().variant() {
+        RustStructType::Wrapper {
+            wrapped, min_max, ..
+        } => {
+            assert_eq!(
+                wrapped.conceptual_type,
+                ConceptualRustType::Primitive(Primitive::Bytes)
+            );
+            assert_eq!(*min_max, Some((Some(1), Some(8))));
+        }
+        other => panic!("expected a wrapper: {other:?}"),
+    }
+}
+
+#[test]
+fn parse_cddl_str_tstr_size_range() {
+    use crate::intermediate::{ConceptualRustType, Primitive, RustStructType};
+    let types = parse(
+        "name = tstr .size (4..64)\n\
+         exclusive_name = tstr .size (4...64)\n\
+         exact_name = tstr .size 8\n",
+        &[],
+    )
+    .unwrap();
+    for (name, bounds) in [
+        ("name", (Some(4), Some(64))),
+        ("exclusive_name", (Some(4), Some(63))),
+        ("exact_name", (Some(8), Some(8))),
+    ] {
+        match types.rust_struct(&ident(name)).unwrap().variant() {
+            RustStructType::Wrapper {
+                wrapped, min_max, ..
+            } => {
+                assert_eq!(
+                    wrapped.conceptual_type,
+                    ConceptualRustType::Primitive(Primitive::Str)
+                );
+                assert_eq!(*min_max, Some(bounds), "{name}");
+            }
+            other => panic!("expected a wrapper: {other:?}"),
+        }
+    }
+}
+
+#[test]
+fn parse_cddl_str_generic_params_under_encodings() {
+    use crate::intermediate::{
+        CBOREncodingOperation, ConceptualRustType, Primitive, RustStructType,
+    };
+    let types = parse(
+        "tagged<T> = #6.24(T)\n\
+         holder<T> = [tagged: #6.30(T), embedded: bytes .cbor T, all: [* T]]\n\
+         tagged_uint = tagged<uint>\n\
+         holder_text = holder<text>\n",
+        &[],
+    )
+    .unwrap();
+    let tagged_uint = types.rust_struct(&ident("tagged_uint")).unwrap();
+    assert_eq!(tagged_uint.tag(), Some(24));
+    match tagged_uint.variant() {
+        RustStructType::Wrapper { wrapped, .. } => {
+            assert_eq!(
+                wrapped.conceptual_type,
+                ConceptualRustType::Primitive(Primitive::U64)
+            );
+        }
+        other => panic!("expected a wrapper: {other:?}"),
+    }
+    let text = ConceptualRustType::Primitive(Primitive::Str);
+    match types.rust_struct(&ident("holder_text")).unwrap().variant() {
+        RustStructType::Record(record) => {
+            assert_eq!(record.fields[0].rust_type.conceptual_type, text);
+            assert_eq!(
+                record.fields[0].rust_type.encodings,
+                vec![CBOREncodingOperation::Tagged(30)]
+            );
+            assert_eq!(record.fields[1].rust_type.conceptual_type, text);
+            assert_eq!(
+                record.fields[1].rust_type.encodings,
+                vec![CBOREncodingOperation::CBORBytes(None)]
+            );
+            assert_eq!(
+                record.fields[2].rust_type.conceptual_type,
+                ConceptualRustType::Array(Box::new(text.clone().into()))
+            );
+        }
+        other => panic!("expected a record: {other:?}"),
+    }
+}
+
+#[test]
+fn parse_cddl_str_large_tags() {
+    use crate::intermediate::{CBOREncodingOperation, RustStructType};
+    let types = parse(
+        "huge_tagged = #6.18446744073709551615(uint)\n\
+         holder = [big: #6.4294967296(text)]\n",
+        &[],
+    )
+    .unwrap();
+    assert_eq!(
+        types.rust_struct(&ident("huge_tagged")).unwrap().tag(),
+        Some(u64::MAX)
+    );
+    match types.rust_struct(&ident("holder")).unwrap().variant() {
+        RustStructType::Record(record) => {
+            assert_eq!(
+                record.fields[0].rust_type.encodings,
+                vec![CBOREncodingOperation::Tagged(u32::MAX as u64 + 1)]
+            );
+        }
+        other => panic!("expected a record: {other:?}"),
+    }
+}
+
+#[test]
+fn parse_cddl_str_generic_arity() {
+    use crate::parsing::CddlError;
+    let arity_err = |input: &str| match parse(input, &[]) {
+        Err(CddlError::ArityMismatch(e)) => (e.def_name.to_string(), e.expected, e.actual),
+        other => panic!("expected an arity mismatch: {other:?}"),
+    };
+    // named instance
+    assert_eq!(
+        arity_err("foo<T> = [a: T]\nbar = foo<uint, text>\n"),
+        ("Foo".to_owned(), 1, 2)
+    );
+    // anonymous instance
+    assert_eq!(
+        arity_err("foo<T, U> = [a: T, b: U]\nbar = [x: foo<uint>]\n"),
+        ("Foo".to_owned(), 2, 1)
+    );
+    // instance before the definition
+    assert_eq!(
+        arity_err("bar = [x: foo<uint, text>]\nfoo<T> = [a: T]\n"),
+        ("Foo".to_owned(), 1, 2)
+    );
+    assert!(parse(
+        "foo<T, U> = [a: T, b: U]\nbar = [x: foo<uint, text>]\n",
+        &[]
+    )
+    .is_ok());
+}
+
+#[test]
+fn parse_cddl_str_inline_map_names() {
+    use crate::intermediate::{ConceptualRustType, RustStructType};
+    let types = parse(
+        "outer = [id: uint, meta: { ? note: text, nested: { x: int } }]\n",
+        &[],
+    )
+    .unwrap();
+    let field_type = |rust_struct: &str, index: usize| match types
+        .rust_struct(&ident(rust_struct))
+        .unwrap()
+        .variant()
+    {
+        RustStructType::Record(record) => record.fields[index].rust_type.conceptual_type.clone(),
+        other => panic!("expected a record: {other:?}"),
+    };
+    assert_eq!(
+        field_type("outer", 1),
+        ConceptualRustType::Rust(ident("outer_field1_map"))
+    );
+    assert_eq!(
+        field_type("outer_field1_map", 1),
+        ConceptualRustType::Rust(ident("outer_field1_map_field1_map"))
+    );
+}
+
+#[test]
+fn parse_cddl_str_leading_doc_comments() {
+    let types = parse(
+        "; @used_as_key\n\
+         ; A foo\n\
+         ; over two lines\n\
+         foo = [uint]\n\
+         \n\
+         bar = [text]\n\
+         \n\
+         ; A baz\n\
+         \n\
+         baz = [bytes]\n",
+        &[],
+    )
+    .unwrap();
+    let foo = types.rust_struct(&ident("foo")).unwrap();
+    assert_eq!(foo.doc_comment(), Some("A foo\nover two lines"));
+    assert_eq!(
+        foo.documented_config().doc.as_deref(),
+        Some("A foo\nover two lines")
+    );
+    assert_eq!(
+        types.rust_struct(&ident("bar")).unwrap().doc_comment(),
+        None
+    );
+    // not directly above the rule
+    assert_eq!(
+        types.rust_struct(&ident("baz")).unwrap().doc_comment(),
+        None
+    );
+}
+
+#[test]
+fn parse_cddl_str_regex_flavors() {
+    use crate::parsing::CddlError;
+    let fancy: &[&str] = &["--pcre-engine=fancy-regex"];
+    // character class subtraction is XML Schema only. In PCRE it's a class followed by a literal ]
+    assert!(matches!(
+        parse("foo = text .regexp \"[a-z-[aeiou]]\"\n", &[]),
+        Err(CddlError::UnsupportedControlOperator(_))
+    ));
+    assert!(parse("foo = text .pcre \"[a-z-[aeiou]]\"\n", &[]).is_ok());
+    // lookahead is PCRE only and needs fancy-regex
+    assert!(matches!(
+        parse("foo = text .regexp \"(?=a)a\"\n", fancy),
+        Err(CddlError::UnsupportedControlOperator(_))
+    ));
+    assert!(matches!(
+        parse("foo = text .pcre \"(?=a)a\"\n", &[]),
+        Err(CddlError::UnsupportedControlOperator(_))
+    ));
+    assert!(parse("foo = text .pcre \"(?=a)a\"\n", fancy).is_ok());
+    // neither engine supports recursion
+    assert!(parse("foo = text .pcre \"\\\\((?R)?\\\\)\"\n", fancy).is_err());
+}
+
+#[test]
+fn parse_cddl_str_error_location() {
+    use crate::parsing::{CddlError, SourceLocation};
+    // points at the offending map entry, not the rule
+    let err = parse("foo = uint\nbar = {\n  a: uint,\n}\n", &[]).unwrap_err();
+    assert!(matches!(err, CddlError::Unsupported(_)));
+    assert_eq!(
+        err.details().unwrap().location,
+        Some(SourceLocation { line: 3, column: 3 })
+    );
+    assert!(err.to_string().starts_with("3:3: unsupported: "));
+    // falls back to the rule when there's no more specific construct
+    let err = parse(
+        "foo = uint\n\nbar = [x: uint] ; @all_fields_optional\n",
+        &[],
+    )
+    .unwrap_err();
+    assert!(matches!(err, CddlError::InvalidAnnotation(_)));
+    assert_eq!(
+        err.details().unwrap().location,
+        Some(SourceLocation { line: 3, column: 1 })
+    );
+}
+
+#[test]
+fn tstr_size_bytes() {
+    run_test("tstr-size-bytes", &[], None, &[], &[], false, &[]);
+}
+
+#[test]
+fn regex() {
+    run_test(
+        "regex",
+        &["--pcre-engine=fancy-regex"],
+        None,
+        &[],
+        &[],
+        false,
+        &[],
+    );
+}
+
+#[test]
+fn sort_fields() {
+    run_test(
+        "sort-fields",
+        &["--sort-fields=true"],
+        None,
+        &[],
+        &[],
+        false,
+        &[],
+    );
+}
+
+#[test]
+fn shortest_floats() {
+    run_test(
+        "shortest-floats",
+        &["--shortest-floats=true"],
+        None,
+        &[],
+        &[],
+        false,
+        &[],
+    );
+}
+
+#[test]
+fn warn_unsupported() {
+    run_test(
+        "warn-unsupported",
+        &["--warn-unsupported=true"],
+        None,
+        &[],
+        &[],
+        false,
+        &[],
+    );
+}
+
+#[test]
+fn cbor_value() {
+    run_test(
+        "cbor-value",
+        &["--cbor-value-conversions=true"],
+        None,
+        &[],
+        &[],
+        false,
+        &[],
+    );
+}
+
+#[test]
+fn ciborium() {
+    run_test(
+        "ciborium",
+        &["--wasm=false", "--cbor-crate=ciborium"],
+        None,
+        &[],
+        &[],
+        false,
+        &[],
+    );
+}
+
+#[test]
+fn cbor_on_text() {
+    run_test(
+        "cbor-on-text",
+        &["--allow-cbor-on-text=true"],
+        None,
+        &[],
+        &[],
+        false,
+        &[],
+    );
+}
+
+#[test]
+fn self_describe() {
+    run_test(
+        "self_describe",
+        &["--self-describe-tag=true"],
+        None,
+        &[],
+        &[],
+        false,
+        &[],
+    );
+    run_test(
+        "self_describe",
+        &["--self-describe-tag=true", "--preserve-encodings=true"],
+        Some("preserve"),
+        &[],
+        &[],
+        false,
+        &[],
+    );
+}
+
+#[test]
+fn minicbor() {
+    run_test(
+        "minicbor",
+        &[
+            "--wasm=false",
+            "--cbor-crate=minicbor",
+            "--tstr-size-1-as-char=true",
+        ],
+        None,
+        &[],
+        &[],
+        false,
+        &[],
+    );
+}
+
+#[test]
+fn no_std() {
+    run_test(
+        "no-std",
+        &["--wasm=false", "--cbor-crate=minicbor", "--no-std=true"],
+        None,
+        &[],
+        &[],
+        false,
+        &[],
+    );
+}
+
+#[test]
+fn no_std_unsupported_types() {
+    use crate::generation::check_no_std_support;
+    let no_std = ["--wasm=false", "--cbor-crate=minicbor", "--no-std=true"];
+    let types = parse("point = { 0: uint, \"label\": text }\n", &no_std).unwrap();
+    assert!(check_no_std_support(&types).is_ok());
+    // type choices only have minicbor impls that delegate to cbor_event
+    let types = parse("shape = uint / text\n", &no_std).unwrap();
+    assert!(check_no_std_support(&types)
+        .unwrap_err()
+        .starts_with("Shape:"));
+    // NonEmptyVec::new() returns a DeserializeError
+    let types = parse("batch = [kind: uint, + items: uint]\n", &no_std).unwrap();
+    assert!(check_no_std_support(&types)
+        .unwrap_err()
+        .starts_with("Batch.items:"));
+}
+
+#[test]
+fn visibility() {
+    run_test(
+        "visibility",
+        &["--wasm=false", "--visibility=pub(crate)"],
+        None,
+        &[],
+        &[],
+        false,
+        &[],
+    );
+}
+
+#[test]
+fn tstr_size_codepoints() {
+    run_test(
+        "tstr-size-codepoints",
+        &["--tstr-size-codepoints=true"],
+        None,
+        &[],
+        &[],
+        false,
+        &[],
+    );
+}
+
+#[test]
+fn tstr_size_char() {
+    run_test(
+        "tstr-size-char",
+        &["--tstr-size-1-as-char=true"],
+        None,
+        &[],
+        &[],
+        false,
+        &[],
+    );
+    run_test(
+        "tstr-size-char",
+        &["--tstr-size-1-as-char=true", "--preserve-encodings=true"],
+        Some("preserve"),
+        &[],
+        &[],
+        false,
+        &[],
+    );
+}
+
+#[test]
+fn fixed_size_byte_arrays() {
+    run_test(
+        "fixed-size-bytes",
+        &["--wasm=false", "--fixed-size-byte-arrays=true"],
+        None,
+        &[],
+        &[],
+        false,
+        &[],
+    );
+    run_test(
+        "fixed-size-bytes",
+        &[
+            "--wasm=false",
+            "--fixed-size-byte-arrays=true",
+            "--preserve-encodings=true",
+        ],
+        Some("preserve"),
+        &[],
+        &[],
+        false,
+        &[],
+    );
+}
+
+#[test]
+fn constants_module() {
+    run_test(
+        "constants",
+        &["--constants-module=true"],
+        None,
+        &[],
+        &[],
+        false,
+        &[],
+    );
+}
+
+#[test]
+fn default_int_width() {
+    run_test(
+        "default-int-width",
+        &["--default-int-width=32"],
+        None,
+        &[],
+        &[],
+        false,
+        &[],
+    );
+    run_test(
+        "default-int-width",
+        &["--default-int-width=32", "--preserve-encodings=true"],
+        Some("preserve"),
+        &[],
+        &[],
+        false,
+        &[],
+    );
+}
+
 #[test]
 fn comment_dsl() {
     run_test(
@@ -1,5 +1,7 @@
 use std::collections::BTreeMap;
 
+use crate::intermediate::CDDLIdent;
+
 pub fn cbor_type_code_str(cbor_type: cbor_event::Type) -> &'static str {
     match cbor_type {
         cbor_event::Type::UnsignedInteger => "cbor_event::Type::UnsignedInteger",
@@ -15,11 +17,13 @@ pub fn cbor_type_code_str(cbor_type: cbor_event::Type) -> &'static str {
 
 pub fn convert_to_snake_case(ident: &str) -> String {
     let mut snake_case = String::new();
+    let ident = transliterate(ident);
     let mut in_uppercase_run = false;
     let mut iter = ident.chars().peekable();
     while let Some(c) = iter.next() {
         match c {
-            '-' => {
+            // spaces can come from @name
+            '-' | ' ' => {
                 snake_case.push('_');
             }
             '$' | '@' => {
@@ -52,15 +56,15 @@ pub fn convert_to_snake_case(ident: &str) -> String {
             }
         }
     }
-    snake_case
+    sanitize_leading_digit(&snake_case, SNAKE_CASE_LEADING_DIGIT_PREFIX)
 }
 
 pub fn convert_to_camel_case(ident: &str) -> String {
     let mut camel_case = String::new();
     let mut uppercase = true;
-    for c in ident.chars() {
+    for c in transliterate(ident).chars() {
         match c {
-            '_' | '-' => {
+            '_' | '-' | ' ' => {
                 uppercase = true;
             }
             '$' | '@' => {
@@ -76,21 +80,115 @@ pub fn convert_to_camel_case(ident: &str) -> String {
             }
         }
     }
-    camel_case
+    sanitize_leading_digit(&camel_case, CAMEL_CASE_LEADING_DIGIT_PREFIX)
+}
+
+/// Replaces latin letters with diacritics and ligatures with their closest ASCII letters
+/// e.g. "Größe" -> "Grosse". Anything else that isn't ASCII (e.g. CJK) is left as-is for is_valid_rust_ident() to reject.
+fn transliterate(ident: &str) -> String {
+    let mut ascii = String::with_capacity(ident.len());
+    for c in ident.chars() {
+        match transliterate_char(c) {
+            Some(replacement) => ascii.push_str(replacement),
+            None => ascii.push(c),
+        }
+    }
+    ascii
+}
+
+#[rustfmt::skip]
+fn transliterate_char(c: char) -> Option<&'static str> {
+    // Latin-1 Supplement and Latin Extended-A letters
+    let replacement = match c {
+        'À'..='Å' | 'Ā' | 'Ă' | 'Ą' => "A",
+        'à'..='å' | 'ā' | 'ă' | 'ą' => "a",
+        'Æ' => "Ae",
+        'æ' => "ae",
+        'Ç' | 'Ć' | 'Ĉ' | 'Ċ' | 'Č' => "C",
+        'ç' | 'ć' | 'ĉ' | 'ċ' | 'č' => "c",
+        'Ð' | 'Ď' | 'Đ' => "D",
+        'ð' | 'ď' | 'đ' => "d",
+        'È'..='Ë' | 'Ē' | 'Ĕ' | 'Ė' | 'Ę' | 'Ě' => "E",
+        'è'..='ë' | 'ē' | 'ĕ' | 'ė' | 'ę' | 'ě' => "e",
+        'Ĝ' | 'Ğ' | 'Ġ' | 'Ģ' => "G",
+        'ĝ' | 'ğ' | 'ġ' | 'ģ' => "g",
+        'Ĥ' | 'Ħ' => "H",
+        'ĥ' | 'ħ' => "h",
+        'Ì'..='Ï' | 'Ĩ' | 'Ī' | 'Ĭ' | 'Į' | 'İ' => "I",
+        'ì'..='ï' | 'ĩ' | 'ī' | 'ĭ' | 'į' | 'ı' => "i",
+        'Ĳ' => "Ij",
+        'ĳ' => "ij",
+        'Ĵ' => "J",
+        'ĵ' => "j",
+        'Ķ' => "K",
+        'ķ' | 'ĸ' => "k",
+        'Ĺ' | 'Ļ' | 'Ľ' | 'Ŀ' | 'Ł' => "L",
+        'ĺ' | 'ļ' | 'ľ' | 'ŀ' | 'ł' => "l",
+        'Ñ' | 'Ń' | 'Ņ' | 'Ň' | 'Ŋ' => "N",
+        'ñ' | 'ń' | 'ņ' | 'ň' | 'ŉ' | 'ŋ' => "n",
+        'Ò'..='Ö' | 'Ø' | 'Ō' | 'Ŏ' | 'Ő' => "O",
+        'ò'..='ö' | 'ø' | 'ō' | 'ŏ' | 'ő' => "o",
+        'Œ' => "Oe",
+        'œ' => "oe",
+        'Ŕ' | 'Ŗ' | 'Ř' => "R",
+        'ŕ' | 'ŗ' | 'ř' => "r",
+        'Ś' | 'Ŝ' | 'Ş' | 'Š' => "S",
+        'ś' | 'ŝ' | 'ş' | 'š' | 'ſ' => "s",
+        'ß' => "ss",
+        'Ţ' | 'Ť' | 'Ŧ' => "T",
+        'ţ' | 'ť' | 'ŧ' => "t",
+        'Þ' => "Th",
+        'þ' => "th",
+        'Ù'..='Ü' | 'Ũ' | 'Ū' | 'Ŭ' | 'Ů' | 'Ű' | 'Ų' => "U",
+        'ù'..='ü' | 'ũ' | 'ū' | 'ŭ' | 'ů' | 'ű' | 'ų' => "u",
+        'Ŵ' => "W",
+        'ŵ' => "w",
+        'Ý' | 'Ÿ' | 'Ŷ' => "Y",
+        'ý' | 'ÿ' | 'ŷ' => "y",
+        'Ź' | 'Ż' | 'Ž' => "Z",
+        'ź' | 'ż' | 'ž' => "z",
+        _ => return None,
+    };
+    Some(replacement)
+}
+
+/// Prepended to CamelCase identifiers that would otherwise start with a digit e.g. "1" -> N1
+pub const CAMEL_CASE_LEADING_DIGIT_PREFIX: &str = "N";
+
+/// Prepended to snake_case identifiers that would otherwise start with a digit e.g. "1" -> n1
+pub const SNAKE_CASE_LEADING_DIGIT_PREFIX: &str = "n";
+
+/// Rust identifiers can't start with a digit, which text values like "0" or "3des" would otherwise end up as
+pub fn sanitize_leading_digit(ident: &str, prefix: &str) -> String {
+    match ident.chars().next() {
+        Some(c) if c.is_ascii_digit() => format!("{prefix}{ident}"),
+        _ => ident.to_owned(),
+    }
+}
+
+/// Whether an already case-converted name can be used as-is as a rust identifier.
+/// Only ASCII is accepted even though rust allows more, as the wasm bindings export these to JS
+/// and non-ASCII idents trip rustc's confusable/mixed script lints.
+pub fn is_valid_rust_ident(ident: &str) -> bool {
+    let mut chars = ident.chars();
+    match chars.next() {
+        Some(first) => {
+            (first.is_ascii_alphabetic() || (first == '_' && ident.len() > 1))
+                && chars.all(|c| c.is_ascii_alphanumeric() || c == '_')
+        }
+        None => false,
+    }
 }
 
 pub fn cddl_prelude(name: &str) -> Option<&str> {
+    // custom implemented types like uint, bool, etc
+    // are handled in the alias system and shouldn't reach here
+    if CDDLIdent::new(name).is_builtin() {
+        unreachable!("{} should be handled by the alias system instead", name);
+    }
     match name {
-        // custom implemented types like uint, bool, etc
-        // are handled in the alias system and shouldn't reach here
-        "uint" | "nint" | "int" | "bool" | "tstr" | "text" |
-        "bstr" | "bytes" | "null" | "nil" | "true"  | "false" |
-        "float16" | // #7.25
-        "float32" | // #7.26
-        "float64" | // #7.27
         "float16-32" | // float16 / float32
-        "float32-64" | // float32 / float64
-        "float" => unreachable!("{} should be handled by the alias system instead", name),
+        "float32-64" => unreachable!("{} should be handled by the alias system instead", name), // float32 / float64
         "tdate" => Some("#6.0(tstr)"),
         "time" => Some("#6.1(number)"),
         "number" => Some("int / float"),
@@ -107,8 +205,11 @@ pub fn cddl_prelude(name: &str) -> Option<&str> {
         "b64legacy" => Some("#6.34(tstr)"),
         "regexp" => Some("#6.35(tstr)"),
         "mime-message" => Some("#6.36(tstr)"),
-        // TODO: we don't support any (yet) - could we use message-signing's code?
-        "any" | // #
+        // not part of RFC 8610's prelude but defined in the IANA CDDL registry.
+        // base64url alphabet (RFC 4648 section 5) with optional padding
+        "base64url" => Some("tstr .regexp \"([A-Za-z0-9_-]{4})*([A-Za-z0-9_-]{2}(==)?|[A-Za-z0-9_-]{3}=?)?\""),
+        // any itself is RawCbor (see IntermediateTypes::new_type) but not these tagged forms of it (yet)
+        "any" => unreachable!("{} should be handled as RawCbor instead", name),
         "cbor-any" | // #6.55799(any)
         "eb64url" | // #6.21(any)
         "eb64legacy" | // #6.22(any)
@@ -154,6 +255,7 @@ pub fn is_identifier_reserved(name: &str) -> bool {
         "uri"        |
         "b64url"     |
         "b64legacy"  |
+        "base64url"  |
         "regexp"     |
         "mime-messag e" |
         "cbor-any"   |
@@ -192,7 +294,16 @@ pub fn append_number_if_duplicate(used_names: &mut BTreeMap<String, u32>, name:
     let entry = used_names.entry(name.clone()).or_default();
     *entry += 1;
     if *entry > 1 {
-        format!("{}{}", name, *entry)
+        // the numbered name could already be in use too e.g. foo, foo2, foo
+        let mut count = *entry;
+        let mut numbered = format!("{name}{count}");
+        while used_names.contains_key(&numbered) {
+            count += 1;
+            numbered = format!("{name}{count}");
+        }
+        used_names.insert(name, count);
+        used_names.insert(numbered.clone(), 1);
+        numbered
     } else {
         name
     }
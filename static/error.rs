@@ -40,6 +40,22 @@ pub enum DeserializeFailure {
         min: Option<isize>,
         max: Option<isize>,
     },
+    /// Float outside of its bounds. Each bound is (value, inclusive)
+    FloatRangeCheck{
+        found: f64,
+        min: Option<(f64, bool)>,
+        max: Option<(f64, bool)>,
+    },
+    /// Text didn't match a .regexp / .pcre pattern
+    RegexMismatch{
+        found: String,
+        pattern: String,
+    },
+    /// Expected the #7.N simple value `expected`
+    SimpleValueMismatch{
+        found: cbor_event::Special,
+        expected: u8,
+    },
     TagMismatch{
         found: u64,
         expected: u64,
@@ -107,11 +123,22 @@ impl DeserializeError {
                 Ok(())
             },
             DeserializeFailure::RangeCheck{ found, min, max } => match (min, max) {
-                (Some(min), Some(max)) => write!(f, "{} not in range {} - {}", found, min, max),
-                (Some(min), None) => write!(f, "{} not at least {}", found, min),
-                (None, Some(max)) => write!(f, "{} not at most {}", found, max),
+                (Some(min), Some(max)) => write!(f, "expected {}..={}, got {}", min, max, found),
+                (Some(min), None) => write!(f, "expected at least {}, got {}", min, found),
+                (None, Some(max)) => write!(f, "expected at most {}, got {}", max, found),
                 (None, None) => write!(f, "invalid range (no min nor max specified)"),
             },
+            DeserializeFailure::FloatRangeCheck{ found, min, max } => {
+                let min = min.map(|(min, inclusive)| format!("{} {}", if inclusive { ">=" } else { ">" }, min));
+                let max = max.map(|(max, inclusive)| format!("{} {}", if inclusive { "<=" } else { "<" }, max));
+                match (min, max) {
+                    (Some(min), Some(max)) => write!(f, "expected {} and {}, got {}", min, max, found),
+                    (Some(bound), None) | (None, Some(bound)) => write!(f, "expected {}, got {}", bound, found),
+                    (None, None) => write!(f, "invalid range (no min nor max specified)"),
+                }
+            },
+            DeserializeFailure::RegexMismatch{ found, pattern } => write!(f, "\"{}\" does not match pattern \"{}\"", found, pattern),
+            DeserializeFailure::SimpleValueMismatch{ found, expected } => write!(f, "Expected simple value {}, found {:?}", expected, found),
             DeserializeFailure::TagMismatch{ found, expected } => write!(f, "Expected tag {}, found {}", expected, found),
             DeserializeFailure::UnknownKey(key) => write!(f, "Found unexpected key {}", key),
             DeserializeFailure::UnexpectedKeyType(ty) => write!(f, "Found unexpected key of CBOR type {:?}", ty),
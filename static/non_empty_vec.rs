@@ -0,0 +1,56 @@
+use crate::error::{DeserializeError, DeserializeFailure};
+use std::convert::TryFrom;
+
+/// Vec<T> with at least one element, used for + T array entries
+#[derive(Clone, Debug, Hash, Ord, Eq, PartialEq, PartialOrd)]
+pub struct NonEmptyVec<T>(Vec<T>);
+
+impl<T> NonEmptyVec<T> {
+    pub fn new(elems: Vec<T>) -> Result<Self, DeserializeError> {
+        if elems.is_empty() {
+            return Err(DeserializeFailure::RangeCheck {
+                found: 0,
+                min: Some(1),
+                max: None,
+            }
+            .into());
+        }
+        Ok(Self(elems))
+    }
+
+    pub fn push(&mut self, elem: T) {
+        self.0.push(elem);
+    }
+
+    pub fn into_inner(self) -> Vec<T> {
+        self.0
+    }
+}
+
+impl<T> std::ops::Deref for NonEmptyVec<T> {
+    type Target = [T];
+
+    fn deref(&self) -> &Self::Target {
+        &self.0
+    }
+}
+
+impl<T> std::ops::DerefMut for NonEmptyVec<T> {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        &mut self.0
+    }
+}
+
+impl<T> TryFrom<Vec<T>> for NonEmptyVec<T> {
+    type Error = DeserializeError;
+
+    fn try_from(elems: Vec<T>) -> Result<Self, Self::Error> {
+        Self::new(elems)
+    }
+}
+
+impl<T> From<NonEmptyVec<T>> for Vec<T> {
+    fn from(elems: NonEmptyVec<T>) -> Self {
+        elems.0
+    }
+}
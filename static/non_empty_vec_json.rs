@@ -0,0 +1,16 @@
+impl<T> serde::Serialize for NonEmptyVec<T> where
+    T: serde::Serialize {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where S: serde::Serializer {
+        self.0.serialize(serializer)
+    }
+}
+
+impl<'de, T> serde::de::Deserialize<'de> for NonEmptyVec<T> where
+    T: serde::Deserialize<'de> {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error> where
+    D: serde::de::Deserializer<'de> {
+        let elems = <Vec<T> as serde::de::Deserialize>::deserialize(deserializer)?;
+        Self::new(elems).map_err(|_e| serde::de::Error::invalid_length(0, &"at least one element"))
+    }
+}
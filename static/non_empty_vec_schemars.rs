@@ -0,0 +1,10 @@
+impl<T> schemars::JsonSchema for NonEmptyVec<T> where
+    T: schemars::JsonSchema {
+    fn schema_name() -> String { format!("NonEmptyVec<{}>", T::schema_name()) }
+    fn json_schema(gen: &mut schemars::gen::SchemaGenerator) -> schemars::schema::Schema {
+        let mut schema = Vec::<T>::json_schema(gen).into_object();
+        schema.array().min_items = Some(1);
+        schema.into()
+    }
+    fn is_referenceable() -> bool { false }
+}
@@ -15,3 +15,153 @@ impl<T: cbor_event::de::Deserialize> Deserialize for T {
         T::deserialize(raw).map_err(DeserializeError::from)
     }
 }
+
+// Preferred serialization of floats (RFC 8949 section 4.2.2):
+// the shortest of half/single/double precision that represents the value exactly
+pub trait SerializeShortestFloat {
+    fn write_float_shortest(&mut self, f: f64) -> cbor_event::Result<&mut Self>;
+}
+
+impl<W: Write + Sized> SerializeShortestFloat for Serializer<W> {
+    fn write_float_shortest(&mut self, f: f64) -> cbor_event::Result<&mut Self> {
+        if let Some(half) = f16_bits_exact(f) {
+            let [hi, lo] = half.to_be_bytes();
+            self.write_raw_bytes(&[0xf9, hi, lo])
+        } else if (f as f32) as f64 == f {
+            let [b0, b1, b2, b3] = (f as f32).to_bits().to_be_bytes();
+            self.write_raw_bytes(&[0xfa, b0, b1, b2, b3])
+        } else {
+            self.write_special(cbor_event::Special::Float(f))
+        }
+    }
+}
+
+// cbor_event only reads single/double precision floats so half precision ones are read here
+pub trait DeserializeShortestFloat {
+    fn float_any_width(&mut self) -> Result<f64, DeserializeError>;
+}
+
+impl<R: BufRead + Seek> DeserializeShortestFloat for Deserializer<R> {
+    fn float_any_width(&mut self) -> Result<f64, DeserializeError> {
+        let half = self.as_mut_ref().fill_buf().ok().and_then(|buf| match buf {
+            [0xf9, hi, lo, ..] => Some(u16::from_be_bytes([*hi, *lo])),
+            _ => None,
+        });
+        match half {
+            Some(bits) => {
+                self.as_mut_ref().consume(3);
+                Ok(f16_bits_to_f64(bits))
+            }
+            None => <f64 as cbor_event::de::Deserialize>::deserialize(self).map_err(Into::into),
+        }
+    }
+}
+
+fn f16_bits_to_f64(bits: u16) -> f64 {
+    let sign = if bits & 0x8000 != 0 { -1.0 } else { 1.0 };
+    let exp = (bits >> 10) & 0x1f;
+    let mantissa = (bits & 0x3ff) as f64;
+    sign * match exp {
+        0 => mantissa * 2f64.powi(-24),
+        0x1f if mantissa == 0.0 => f64::INFINITY,
+        0x1f => f64::NAN,
+        _ => (1.0 + mantissa / 1024.0) * 2f64.powi(exp as i32 - 15),
+    }
+}
+
+fn f16_bits_exact(f: f64) -> Option<u16> {
+    if f.is_nan() {
+        return Some(0x7e00);
+    }
+    let single = f as f32;
+    if single as f64 != f {
+        return None;
+    }
+    let bits = single.to_bits();
+    let sign = ((bits >> 16) & 0x8000) as u16;
+    let exp = ((bits >> 23) & 0xff) as i32;
+    let mantissa = bits & 0x7f_ffff;
+    if exp == 0xff {
+        // infinity (NaN was handled above)
+        return Some(sign | 0x7c00);
+    }
+    if exp == 0 {
+        // zero, or a single precision subnormal which is far too small for half precision
+        return if mantissa == 0 { Some(sign) } else { None };
+    }
+    let unbiased = exp - 127;
+    if (-14..=15).contains(&unbiased) {
+        // normal half: only 10 of the 23 mantissa bits fit
+        if mantissa & 0x1fff == 0 {
+            Some(sign | (((unbiased + 15) as u16) << 10) | (mantissa >> 13) as u16)
+        } else {
+            None
+        }
+    } else if (-24..-14).contains(&unbiased) {
+        // subnormal half i.e. a multiple of 2^-24
+        let shift = -(unbiased + 1);
+        let full = mantissa | 0x80_0000;
+        if full & ((1 << shift) - 1) == 0 {
+            Some(sign | (full >> shift) as u16)
+        } else {
+            None
+        }
+    } else {
+        None
+    }
+}
+
+// u128/i128 (uint .size 16 / int .size 16) are bignums (RFC 8949 section 3.4.3)
+// but always with exactly 16 big-endian content bytes instead of the shortest form
+pub trait SerializeBignum128 {
+    fn write_u128_bignum(&mut self, x: u128) -> cbor_event::Result<&mut Self>;
+
+    fn write_i128_bignum(&mut self, x: i128) -> cbor_event::Result<&mut Self>;
+}
+
+impl<W: Write + Sized> SerializeBignum128 for Serializer<W> {
+    fn write_u128_bignum(&mut self, x: u128) -> cbor_event::Result<&mut Self> {
+        self.write_tag(2)?.write_bytes(x.to_be_bytes())
+    }
+
+    fn write_i128_bignum(&mut self, x: i128) -> cbor_event::Result<&mut Self> {
+        if x >= 0 {
+            self.write_u128_bignum(x as u128)
+        } else {
+            // negative bignums hold -1 - n
+            self.write_tag(3)?.write_bytes(((-1 - x) as u128).to_be_bytes())
+        }
+    }
+}
+
+pub trait DeserializeBignum128 {
+    fn u128_bignum(&mut self) -> Result<u128, DeserializeError>;
+
+    fn i128_bignum(&mut self) -> Result<i128, DeserializeError>;
+}
+
+impl<R: BufRead + Seek> DeserializeBignum128 for Deserializer<R> {
+    fn u128_bignum(&mut self) -> Result<u128, DeserializeError> {
+        match self.tag()? {
+            2 => bignum_128_content(self),
+            tag => Err(DeserializeFailure::TagMismatch { found: tag, expected: 2 }.into()),
+        }
+    }
+
+    fn i128_bignum(&mut self) -> Result<i128, DeserializeError> {
+        let tag = self.tag()?;
+        if tag != 2 && tag != 3 {
+            return Err(DeserializeFailure::TagMismatch { found: tag, expected: 2 }.into());
+        }
+        let n = i128::try_from(bignum_128_content(self)?)
+            .map_err(|e| DeserializeFailure::InvalidStructure(Box::new(e)))?;
+        Ok(if tag == 2 { n } else { -1 - n })
+    }
+}
+
+fn bignum_128_content<R: BufRead + Seek>(raw: &mut Deserializer<R>) -> Result<u128, DeserializeError> {
+    let bytes = raw.bytes()?;
+    <[u8; 16]>::try_from(bytes)
+        .map(u128::from_be_bytes)
+        .map_err(|bytes| DeserializeFailure::RangeCheck { found: bytes.len() as isize, min: Some(16), max: Some(16) }.into())
+}
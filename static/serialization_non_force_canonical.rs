@@ -6,6 +6,7 @@ pub trait SerializeEmbeddedGroup {
 }
 
 pub trait ToCBORBytes {
+    #[must_use]
     fn to_cbor_bytes(&self) -> Vec<u8>;
 }
 
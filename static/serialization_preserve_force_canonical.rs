@@ -54,12 +54,14 @@ impl StringEncoding {
 }
 
 pub trait Serialize {
+    #[must_use]
     fn to_canonical_cbor_bytes(&self) -> Vec<u8> {
         let mut buf = Serializer::new_vec();
         self.serialize(&mut buf, true).unwrap();
         buf.finalize()
     }
 
+    #[must_use]
     fn to_cbor_bytes(&self) -> Vec<u8> {
         let mut buf = Serializer::new_vec();
         self.serialize(&mut buf, false).unwrap();
@@ -0,0 +1,10 @@
+
+// self-described CBOR (RFC 8949 section 3.4.6)
+pub const SELF_DESCRIBE_CBOR_TAG: u64 = 55799;
+
+// the tag's preferred (shortest) encoding, which is what encoders put at the start of a document
+const SELF_DESCRIBE_CBOR_MAGIC: [u8; 3] = [0xd9, 0xd9, 0xf7];
+
+fn strip_self_describe_tag(data: &[u8]) -> &[u8] {
+    data.strip_prefix(&SELF_DESCRIBE_CBOR_MAGIC[..]).unwrap_or(data)
+}
@@ -0,0 +1,45 @@
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serialization::Deserialize;
+
+    #[test]
+    fn base64_encoded() {
+        let point = Point::new(1, 2);
+        // [1, 2] = 0x820102 -> "ggEC"
+        assert_eq!(point.to_cbor_bytes(), vec![0x82, 0x01, 0x02]);
+        let shape = Shape::new(String::from("dot"), point);
+        let bytes = shape.to_cbor_bytes();
+        let expected = vec![
+            map_def(2),
+                cbor_string("name"),
+                cbor_string("dot"),
+                cbor_string("center"),
+                cbor_string("ggEC"),
+        ].into_iter().flatten().collect::<Vec<u8>>();
+        assert_eq!(bytes, expected);
+        assert_eq!(Shape::from_cbor_bytes(&bytes).unwrap().to_cbor_bytes(), bytes);
+    }
+
+    #[test]
+    fn optional_field() {
+        let mut shape = Shape::new(String::from("circle"), Point::new(0, 0));
+        shape.size = Some(300);
+        let bytes = shape.to_cbor_bytes();
+        let deser = Shape::from_cbor_bytes(&bytes).unwrap();
+        assert_eq!(deser.size, Some(300));
+        assert_eq!(deser.to_cbor_bytes(), bytes);
+    }
+
+    #[test]
+    fn invalid_base64() {
+        let bytes = vec![
+            map_def(2),
+                cbor_string("name"),
+                cbor_string("dot"),
+                cbor_string("center"),
+                cbor_string("not base64!"),
+        ].into_iter().flatten().collect::<Vec<u8>>();
+        assert!(Shape::from_cbor_bytes(&bytes).is_err());
+    }
+}
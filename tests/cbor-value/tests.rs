@@ -0,0 +1,34 @@
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ciborium::value::Value;
+
+    #[test]
+    fn record_to_value() {
+        let mut shape = Shape::new(String::from("dot"), Point::new(1, 2));
+        shape.key_1 = Some(5);
+        let value = Value::from(shape.clone());
+        let expected = Value::Map(vec![
+            (Value::Integer(1.into()), Value::Integer(5.into())),
+            (Value::Text(String::from("name")), Value::Text(String::from("dot"))),
+            (
+                Value::Text(String::from("center")),
+                Value::Array(vec![Value::Integer(1.into()), Value::Integer(2.into())]),
+            ),
+        ]);
+        assert_eq!(value, expected);
+        assert_eq!(Shape::try_from(value).unwrap().to_cbor_bytes(), shape.to_cbor_bytes());
+    }
+
+    #[test]
+    fn value_to_record() {
+        let point = Point::try_from(Value::Array(vec![
+            Value::Integer(3.into()),
+            Value::Integer(4.into()),
+        ]))
+        .unwrap();
+        assert_eq!(point.x, 3);
+        assert_eq!(point.y, 4);
+        assert!(Point::try_from(Value::Text(String::from("not a point"))).is_err());
+    }
+}
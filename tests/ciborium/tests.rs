@@ -0,0 +1,61 @@
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ciborium::value::Value;
+
+    fn round_trip<T>(orig: &T) -> T
+    where
+        T: ToCBORBytes + serde::Serialize + for<'de> serde::Deserialize<'de>,
+    {
+        let mut bytes = Vec::new();
+        ciborium::ser::into_writer(orig, &mut bytes).unwrap();
+        // same encoding as our own serialization
+        assert_eq!(bytes, orig.to_cbor_bytes());
+        ciborium::de::from_reader(bytes.as_slice()).unwrap()
+    }
+
+    #[test]
+    fn map_record() {
+        let shape = Shape::new(String::from("dot"), Point::new(1, 2));
+        assert_eq!(round_trip(&shape).to_cbor_bytes(), shape.to_cbor_bytes());
+        assert_eq!(
+            Value::from(shape),
+            Value::Map(vec![
+                (Value::Text(String::from("name")), Value::Text(String::from("dot"))),
+                (
+                    Value::Text(String::from("center")),
+                    Value::Array(vec![Value::Integer(1.into()), Value::Integer(2.into())]),
+                ),
+            ])
+        );
+    }
+
+    #[test]
+    fn tagged() {
+        let tagged = TaggedPoint::new(3, 4);
+        assert_eq!(round_trip(&tagged).to_cbor_bytes(), tagged.to_cbor_bytes());
+        // tags can be read with ciborium's own types too
+        let mut bytes = Vec::new();
+        ciborium::ser::into_writer(&tagged, &mut bytes).unwrap();
+        let required: ciborium::tag::Required<(u64, u64), 1000> =
+            ciborium::de::from_reader(bytes.as_slice()).unwrap();
+        assert_eq!(required.0, (3, 4));
+        let wrong_tag = Value::Tag(
+            1001,
+            Box::new(Value::Array(vec![Value::Integer(3.into()), Value::Integer(4.into())])),
+        );
+        assert!(TaggedPoint::try_from(wrong_tag).is_err());
+    }
+
+    #[test]
+    fn choices() {
+        for coordinate in [
+            Coordinate::Point(Point::new(5, 6)),
+            Coordinate::Text(String::from("origin")),
+        ] {
+            assert_eq!(round_trip(&coordinate).to_cbor_bytes(), coordinate.to_cbor_bytes());
+        }
+        assert_eq!(Value::from(Direction::South), Value::Text(String::from("south")));
+        assert!(matches!(round_trip(&Direction::North), Direction::North));
+    }
+}
@@ -65,4 +65,12 @@ mod tests {
         let pm = ProtocolMagic::new(5);
         assert_eq!(pm.get(), 5);
     }
+
+    #[test]
+    fn feature_gated() {
+        let lib_rs = include_str!("lib.rs");
+        let gated = lib_rs.find("pub struct FeatureGated {").unwrap();
+        let item_start = lib_rs[..gated].rfind("\n\n").unwrap();
+        assert!(lib_rs[item_start..gated].contains("#[cfg(feature = \"experimental\")]"));
+    }
 }
@@ -0,0 +1,35 @@
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serialization::Deserialize;
+
+    #[test]
+    fn rule_constants() {
+        assert_eq!(constants::PROTOCOL_VERSION, 3u64);
+        assert_eq!(constants::NETWORK_NAME, "mainnet");
+    }
+
+    #[test]
+    fn map_key_constants() {
+        assert_eq!(constants::HEADER_SLOT_KEY, 1u64);
+        assert_eq!(constants::HEADER_MEMO_KEY, "memo");
+        assert_eq!(constants::HEADER_KIND_KEY, "kind");
+        assert_eq!(constants::HEADER_KIND, "transfer");
+    }
+
+    #[test]
+    fn fixed_field_constants() {
+        assert_eq!(constants::VERSIONED_VERSION, 3u64);
+        assert_eq!(constants::VERSIONED_RATIO, 0.5f64);
+        // the constants agree with what is actually encoded
+        let versioned = Versioned::new(vec![0xFF]);
+        let expected = vec![
+            arr_def(3),
+                cbor_int(constants::VERSIONED_VERSION as i128, cbor_event::Sz::Inline),
+                cbor_float(constants::VERSIONED_RATIO),
+                cbor_bytes_sz(vec![0xFF], cbor_event::StringLenSz::Len(cbor_event::Sz::Inline)),
+        ].into_iter().flatten().collect::<Vec<u8>>();
+        assert_eq!(versioned.to_cbor_bytes(), expected);
+        deser_test(&versioned);
+    }
+}
@@ -36,6 +36,24 @@ mod tests {
         deser_test(&Foo2::new(143546, None));
     }
 
+    #[test]
+    fn large_tag() {
+        let large_tag = LargeTag::new(5);
+        let expected = vec![
+            arr_def(1),
+            cbor_tag_sz(18446744073709551614, cbor_event::Sz::Eight),
+            cbor_int(5, cbor_event::Sz::Inline),
+        ].into_iter().flatten().clone().collect::<Vec<u8>>();
+        assert_eq!(large_tag.to_cbor_bytes(), expected);
+        deser_test(&large_tag);
+        let wrong_tag = vec![
+            arr_def(1),
+            cbor_tag_sz(18446744073709551615, cbor_event::Sz::Eight),
+            cbor_int(5, cbor_event::Sz::Inline),
+        ].into_iter().flatten().clone().collect::<Vec<u8>>();
+        assert!(LargeTag::from_cbor_bytes(&wrong_tag).is_err());
+    }
+
     #[test]
     fn bar() {
         let mut bar = Bar::new(Foo::new(436, String::from("jfkdf"), vec![6, 4]), None, 3.3);
@@ -45,6 +63,44 @@ mod tests {
 
     }
 
+    #[test]
+    fn bar_shuffled_keys() {
+        let bar = Bar::new(Foo::new(436, String::from("jfkdf"), vec![6, 4]), Some(10), 3.5);
+        let foo_entry = vec![
+            cbor_string("foo"),
+            cbor_tag_sz(1337, cbor_event::Sz::Two),
+                arr_def(3),
+                    cbor_int(436, cbor_event::Sz::Two),
+                    cbor_string("jfkdf"),
+                    cbor_bytes_sz(vec![6, 4], cbor_event::StringLenSz::Len(cbor_event::Sz::Inline)),
+        ].into_iter().flatten().clone().collect::<Vec<u8>>();
+        let shuffled = vec![
+            map_def(4),
+                cbor_string("float"),
+                cbor_float(3.5),
+                cbor_int(1, cbor_event::Sz::Inline),
+                cbor_int(10, cbor_event::Sz::Inline),
+                cbor_string("five"),
+                cbor_int(5, cbor_event::Sz::Inline),
+                foo_entry.clone(),
+        ].into_iter().flatten().clone().collect::<Vec<u8>>();
+        let from_bytes = Bar::from_cbor_bytes(&shuffled).unwrap();
+        assert_eq!(from_bytes.one, Some(10));
+        assert_eq!(from_bytes.foo.index_0, 436);
+        // re-serialized in CDDL order
+        assert_eq!(from_bytes.to_cbor_bytes(), bar.to_cbor_bytes());
+        // still errors when a mandatory key is missing no matter where the others are
+        let missing_five = vec![
+            map_def(3),
+                cbor_int(1, cbor_event::Sz::Inline),
+                cbor_int(10, cbor_event::Sz::Inline),
+                cbor_string("float"),
+                cbor_float(3.5),
+                foo_entry,
+        ].into_iter().flatten().clone().collect::<Vec<u8>>();
+        assert!(Bar::from_cbor_bytes(&missing_five).is_err());
+    }
+
     #[test]
     fn plain() {
         deser_test(&Plain::new(7576, String::from("wiorurri34h").into()));
@@ -109,7 +165,7 @@ mod tests {
 
     #[test]
     fn type_choice_0() {
-        deser_test(&TypeChoice::I0);
+        deser_test(&TypeChoice::Zero);
     }
 
     #[test]
@@ -139,7 +195,7 @@ mod tests {
 
     #[test]
     fn enums() {
-        let enums = Enums::new(CEnum::I3, TypeChoice::U64(53435364));
+        let enums = Enums::new(CEnum::Three, TypeChoice::U64(53435364));
     }
 
     #[test]
@@ -163,6 +219,169 @@ mod tests {
         deser_test(&CborInCbor::new(foo.clone(), 9, foo))
     }
 
+    #[test]
+    fn cbor_any() {
+        // indefinite array containing a map and a tag, kept exactly as-is
+        let inner = vec![
+            vec![ARR_INDEF],
+                map_def(1),
+                    cbor_int(1, cbor_event::Sz::Inline),
+                    cbor_string("one"),
+                cbor_tag(30),
+                    arr_def(0),
+            vec![BREAK],
+        ].into_iter().flatten().clone().collect::<Vec<u8>>();
+        let cbor_any = CborAny::new(inner.clone(), Foo::new(0, String::new(), vec![]).to_cbor_bytes());
+        assert_eq!(cbor_any.any_bytes, inner);
+        deser_test(&cbor_any);
+    }
+
+    #[test]
+    fn bounded_cbor() {
+        use cbor_event::{Sz, StringLenSz};
+        let small = BoundedCbor::new(Foo::new(0, String::new(), vec![]), 1000);
+        deser_test(&small);
+        let encode = |foo: &Foo, x: u64| {
+            vec![
+                arr_def(2),
+                    cbor_bytes_sz(foo.to_cbor_bytes(), StringLenSz::Len(Sz::One)),
+                    cbor_bytes_sz(cbor_int(x as i128, Sz::Inline), StringLenSz::Len(Sz::Inline)),
+            ].into_iter().flatten().clone().collect::<Vec<u8>>()
+        };
+        assert!(BoundedCbor::from_cbor_bytes(&encode(&small.small_foo, 10)).is_ok());
+        // encoded foo is over 16 bytes even though foo itself has no bounds
+        let big_foo = Foo::new(0, "more than sixteen bytes".to_owned(), vec![]);
+        assert!(BoundedCbor::from_cbor_bytes(&encode(&big_foo, 10)).is_err());
+        // an empty byte string would be caught by the .size (1..4) even before decoding it
+        let empty_uint = vec![
+            arr_def(2),
+                cbor_bytes_sz(small.small_foo.to_cbor_bytes(), StringLenSz::Len(Sz::Inline)),
+                cbor_bytes_sz(vec![], StringLenSz::Len(Sz::Inline)),
+        ].into_iter().flatten().clone().collect::<Vec<u8>>();
+        assert!(BoundedCbor::from_cbor_bytes(&empty_uint).is_err());
+        // u64::MAX takes 9 bytes to encode
+        let big_uint = BoundedCbor::new(small.small_foo.clone(), u64::MAX);
+        assert!(BoundedCbor::from_cbor_bytes(&big_uint.to_cbor_bytes()).is_err());
+    }
+
+    #[test]
+    fn plain_any() {
+        use cbor_event::Sz;
+        // tags that aren't in the CDDL at all, including one nested in another
+        let value = vec![
+            cbor_tag_sz(1234567, Sz::Four),
+                arr_def(2),
+                    cbor_tag_sz(1001, Sz::Two),
+                        cbor_string("nested"),
+                    cbor_tag_sz(u64::MAX, Sz::Eight),
+                        cbor_int(1, Sz::Inline),
+        ].into_iter().flatten().clone().collect::<Vec<u8>>();
+        let bytes = vec![
+            arr_def(2),
+                cbor_int(3, Sz::Inline),
+                value.clone(),
+        ].into_iter().flatten().clone().collect::<Vec<u8>>();
+        let plain_any = PlainAny::from_cbor_bytes(&bytes).unwrap();
+        assert_eq!(plain_any.id, 3);
+        assert_eq!(plain_any.value.0, value);
+        assert_eq!(plain_any.to_cbor_bytes(), bytes);
+        deser_test(&plain_any);
+    }
+
+    #[test]
+    fn tagged_any() {
+        use cbor_event::Sz;
+        // non-canonical encodings inside are kept as they aren't decoded into anything
+        let payload = vec![
+            map_def(1),
+                cbor_int(1, Sz::Two),
+                vec![ARR_INDEF],
+                    cbor_tag_sz(1001, Sz::Eight),
+                        cbor_str_sz("opaque", cbor_event::StringLenSz::Len(Sz::Four)),
+                vec![BREAK],
+        ].into_iter().flatten().clone().collect::<Vec<u8>>();
+        let bytes = vec![
+            cbor_tag_sz(100, Sz::One),
+            payload.clone(),
+        ].into_iter().flatten().clone().collect::<Vec<u8>>();
+        let extension = Extension::from_cbor_bytes(&bytes).unwrap();
+        assert_eq!(extension.0.0, payload);
+        assert_eq!(extension.to_cbor_bytes(), bytes);
+        assert_eq!(Extension::new(RawCbor(payload.clone())).to_cbor_bytes(), bytes);
+        deser_test(&extension);
+        let wrong_tag = vec![
+            cbor_tag_sz(101, Sz::One),
+            payload.clone(),
+        ].into_iter().flatten().clone().collect::<Vec<u8>>();
+        assert!(Extension::from_cbor_bytes(&wrong_tag).is_err());
+        let extensions_bytes = vec![
+            arr_def(2),
+                bytes.clone(),
+                cbor_tag_sz(100, Sz::One),
+                cbor_int(-5, Sz::Inline),
+        ].into_iter().flatten().clone().collect::<Vec<u8>>();
+        let extensions = Extensions::from_cbor_bytes(&extensions_bytes).unwrap();
+        assert_eq!(extensions.first.0.0, payload);
+        assert_eq!(extensions.second.0.0, cbor_int(-5, Sz::Inline));
+        assert_eq!(extensions.to_cbor_bytes(), extensions_bytes);
+    }
+
+    #[test]
+    fn length_bounds() {
+        assert!(ShortName::new(String::new()).is_ok());
+        assert!(ShortName::new(String::from("abcd")).is_ok());
+        assert!(ShortName::new(String::from("abcde")).is_err());
+        let bytes = |at_most_4: &str, under_4: Vec<u8>, at_least_2: &str, over_2: Vec<u8>| vec![
+            arr_def(4),
+                cbor_string(at_most_4),
+                cbor_bytes_sz(under_4, cbor_event::StringLenSz::Len(cbor_event::Sz::Inline)),
+                cbor_string(at_least_2),
+                cbor_bytes_sz(over_2, cbor_event::StringLenSz::Len(cbor_event::Sz::Inline)),
+        ].into_iter().flatten().clone().collect::<Vec<u8>>();
+        let ok = bytes("abcd", vec![1, 2, 3], "ab", vec![1, 2, 3]);
+        assert_eq!(LengthBounds::from_cbor_bytes(&ok).unwrap().to_cbor_bytes(), ok);
+        assert!(LengthBounds::from_cbor_bytes(&bytes("", vec![], "abcdefgh", vec![0; 10])).is_ok());
+        assert!(LengthBounds::from_cbor_bytes(&bytes("abcde", vec![1, 2, 3], "ab", vec![1, 2, 3])).is_err());
+        assert!(LengthBounds::from_cbor_bytes(&bytes("abcd", vec![1, 2, 3, 4], "ab", vec![1, 2, 3])).is_err());
+        assert!(LengthBounds::from_cbor_bytes(&bytes("abcd", vec![1, 2, 3], "a", vec![1, 2, 3])).is_err());
+        assert!(LengthBounds::from_cbor_bytes(&bytes("abcd", vec![1, 2, 3], "ab", vec![1, 2])).is_err());
+    }
+
+    #[test]
+    fn discriminated() {
+        let amount = Discriminated::new_discriminated0(5);
+        assert_eq!(amount.discriminant(), 0);
+        assert_eq!(amount.to_cbor_bytes(), vec![arr_def(2), cbor_int(0, cbor_event::Sz::Inline), cbor_int(5, cbor_event::Sz::Inline)].into_iter().flatten().clone().collect::<Vec<u8>>());
+        deser_test(&amount);
+        let label = Discriminated::new_discriminated1("five".to_owned());
+        assert_eq!(label.discriminant(), 1);
+        assert_eq!(label.to_cbor_bytes(), vec![arr_def(2), cbor_int(1, cbor_event::Sz::Inline), cbor_string("five")].into_iter().flatten().clone().collect::<Vec<u8>>());
+        deser_test(&label);
+    }
+
+    #[test]
+    fn unnamed_discriminated() {
+        let uint = UnnamedDiscriminated::new_unnamed_discriminated0(5);
+        assert_eq!(uint.discriminant(), 0);
+        assert_eq!(uint.to_cbor_bytes(), vec![arr_def(2), cbor_int(0, cbor_event::Sz::Inline), cbor_int(5, cbor_event::Sz::Inline)].into_iter().flatten().clone().collect::<Vec<u8>>());
+        deser_test(&uint);
+        let text = UnnamedDiscriminated::new_unnamed_discriminated1("five".to_owned());
+        assert_eq!(text.discriminant(), 1);
+        assert_eq!(text.to_cbor_bytes(), vec![arr_def(2), cbor_int(1, cbor_event::Sz::Inline), cbor_string("five")].into_iter().flatten().clone().collect::<Vec<u8>>());
+        deser_test(&text);
+        // the leading integer picks the variant so a mismatched one can't be read
+        let mismatched = vec![arr_def(2), cbor_int(1, cbor_event::Sz::Inline), cbor_int(5, cbor_event::Sz::Inline)].into_iter().flatten().clone().collect::<Vec<u8>>();
+        assert!(UnnamedDiscriminated::from_cbor_bytes(&mismatched).is_err());
+    }
+
+    #[test]
+    fn inline_map() {
+        let mut inner = InlineMapField1Map::new(1);
+        deser_test(&InlineMap::new(0, inner.clone()));
+        inner.y = Some("y".to_owned());
+        deser_test(&InlineMap::new(0, inner));
+    }
+
     #[test]
     fn test_prelude_numbers() {
         assert_eq!(0u8, U8::from(0u8));
@@ -171,6 +390,26 @@ mod tests {
         assert_eq!(0i8, I8::from(0i8));
         assert_eq!(0u64, U64::from(0u64));
         assert_eq!(0i64, I64::from(0i64));
+        assert_eq!(0u128, U128::from(0u128));
+        assert_eq!(0i128, I128::from(0i128));
+    }
+
+    #[test]
+    fn odd_byte_sizes() {
+        use cbor_event::Sz;
+        // backed by u32 as there is no 24-bit type
+        let max: u32 = (1 << 24) - 1;
+        assert!(U24::new(max).is_ok());
+        assert!(U24::new(max + 1).is_err());
+        deser_test(&U24::new(max).unwrap());
+        assert!(U24::from_cbor_bytes(&cbor_int(max as i128 + 1, Sz::Four)).is_err());
+        // out of range for the u32 too, which must not be truncated into range
+        assert!(U24::from_cbor_bytes(&cbor_int(u32::MAX as i128 + 2, Sz::Eight)).is_err());
+        let max: u64 = (1 << 48) - 1;
+        assert!(U48::new(max).is_ok());
+        assert!(U48::new(max + 1).is_err());
+        deser_test(&U48::new(max).unwrap());
+        assert!(U48::from_cbor_bytes(&cbor_int(max as i128 + 1, Sz::Eight)).is_err());
     }
 
     #[test]
@@ -181,6 +420,43 @@ mod tests {
         deser_test(&max);
     }
 
+    #[test]
+    fn big_ints() {
+        deser_test(&BigInts::new(u128::MIN, i128::MIN));
+        deser_test(&BigInts::new(u128::MAX, i128::MAX));
+        // always #6.2/#6.3 bignums with exactly 16 bytes regardless of the value
+        let one_and_minus_one = vec![
+            arr_def(2),
+                cbor_tag(2),
+                    vec![0x50],
+                    1u128.to_be_bytes().to_vec(),
+                cbor_tag(3),
+                    vec![0x50],
+                    0u128.to_be_bytes().to_vec(),
+        ].into_iter().flatten().clone().collect::<Vec<u8>>();
+        assert_eq!(BigInts::new(1, -1).to_cbor_bytes(), one_and_minus_one);
+        // shortest form bignums aren't accepted
+        let shortest = vec![
+            arr_def(2),
+                cbor_tag(2),
+                    vec![0x41, 0x01],
+                cbor_tag(3),
+                    vec![0x40],
+        ].into_iter().flatten().clone().collect::<Vec<u8>>();
+        assert!(BigInts::from_cbor_bytes(&shortest).is_err());
+        // -1 - (i128::MAX + 1) is out of range for an i128
+        let too_negative = vec![
+            arr_def(2),
+                cbor_tag(2),
+                    vec![0x50],
+                    0u128.to_be_bytes().to_vec(),
+                cbor_tag(3),
+                    vec![0x50],
+                    (i128::MAX as u128 + 1).to_be_bytes().to_vec(),
+        ].into_iter().flatten().clone().collect::<Vec<u8>>();
+        assert!(BigInts::from_cbor_bytes(&too_negative).is_err());
+    }
+
     #[test]
     fn defaults() {
         let mut md = MapWithDefaults::new();
@@ -191,6 +467,49 @@ mod tests {
         deser_test(&md);
     }
 
+    #[test]
+    fn array_defaults() {
+        let mut ad = ArrayWithDefaults::new(5);
+        assert_eq!(ad.label, "none");
+        assert_eq!(ad.retries, 3);
+        // trailing elements equal to their defaults are left out
+        assert_eq!(ad.to_cbor_bytes(), vec![arr_def(1), cbor_int(5, cbor_event::Sz::Inline)].into_iter().flatten().collect::<Vec<u8>>());
+        deser_test(&ad);
+        ad.retries = 0;
+        deser_test(&ad);
+        ad.label = "some".into();
+        deser_test(&ad);
+        // missing trailing elements deserialize to their defaults
+        let only_label = vec![
+            arr_def(2),
+                cbor_int(5, cbor_event::Sz::Inline),
+                cbor_string("x"),
+        ].into_iter().flatten().collect::<Vec<u8>>();
+        let from_bytes = ArrayWithDefaults::from_cbor_bytes(&only_label).unwrap();
+        assert_eq!(from_bytes.label, "x");
+        assert_eq!(from_bytes.retries, 3);
+        let only_retries = vec![
+            arr_def(2),
+                cbor_int(5, cbor_event::Sz::Inline),
+                cbor_int(7, cbor_event::Sz::Inline),
+        ].into_iter().flatten().collect::<Vec<u8>>();
+        let from_bytes = ArrayWithDefaults::from_cbor_bytes(&only_retries).unwrap();
+        assert_eq!(from_bytes.label, "none");
+        assert_eq!(from_bytes.retries, 7);
+    }
+
+    #[test]
+    fn default_impl() {
+        let default = AllDefaultable::default();
+        assert_eq!(default.optional, None);
+        assert_eq!(default.defaulted, "default");
+        assert_eq!(default.to_cbor_bytes(), AllDefaultable::new().to_cbor_bytes());
+        deser_test(&default);
+        let default = MapWithDefaults::default();
+        assert_eq!(default.key_1, 1337);
+        deser_test(&default);
+    }
+
     #[test]
     fn no_alias() {
         use std::str::FromStr;
@@ -230,6 +549,68 @@ mod tests {
         ));
     }
 
+    #[test]
+    fn nested_generics() {
+        use cbor_event::Sz;
+        let labeled_u64 = LabeledU64::new(String::from("a"), 5);
+        let nested_generics = NestedGenerics::new(
+            LabeledLabeledOfU64::new(String::from("b"), labeled_u64.clone()),
+            LabeledLabeledOfLabeledOfU64::new(
+                String::from("c"),
+                LabeledLabeledOfU64::new(String::from("d"), labeled_u64),
+            ),
+        );
+        let labeled_u64_bytes = vec![
+            arr_def(2),
+                cbor_string("a"),
+                cbor_int(5, Sz::Inline),
+        ].into_iter().flatten().clone().collect::<Vec<u8>>();
+        let bytes = vec![
+            arr_def(2),
+                arr_def(2),
+                    cbor_string("b"),
+                    labeled_u64_bytes.clone(),
+                arr_def(2),
+                    cbor_string("c"),
+                    arr_def(2),
+                        cbor_string("d"),
+                        labeled_u64_bytes,
+        ].into_iter().flatten().clone().collect::<Vec<u8>>();
+        assert_eq!(nested_generics.to_cbor_bytes(), bytes);
+        deser_test(&nested_generics);
+    }
+
+    #[test]
+    fn within() {
+        use cbor_event::Sz;
+        deser_test(&PublicNetwork::Mainnet);
+        deser_test(&PublicNetwork::Preview);
+        // a network but not one of the narrowed ones
+        assert!(Network::from_cbor_bytes(&cbor_string("testnet")).is_ok());
+        assert!(PublicNetwork::from_cbor_bytes(&cbor_string("testnet")).is_err());
+        for flag in [FlagBit::One, FlagBit::Two, FlagBit::Four] {
+            deser_test(&flag);
+        }
+        assert!(FlagBit::from_cbor_bytes(&cbor_int(3, Sz::Inline)).is_err());
+    }
+
+    #[test]
+    fn forced_rust_type() {
+        use cbor_event::Sz;
+        let forced = ForcedRustType::new(5, ExternalFoo::new(1, String::from("foo"), vec![2]));
+        // ExternalFoo's own (de)serialization is used for the field
+        let bytes = vec![
+            arr_def(2),
+                cbor_int(5, Sz::Inline),
+                arr_def(3),
+                    cbor_int(1, Sz::Inline),
+                    cbor_string("foo"),
+                    vec![0x41, 0x02],
+        ].into_iter().flatten().clone().collect::<Vec<u8>>();
+        assert_eq!(forced.to_cbor_bytes(), bytes);
+        deser_test(&forced);
+    }
+
     #[test]
     fn top_level_arrays() {
         // this part of the test just tests that the resulting code compiles
@@ -451,6 +832,13 @@ mod tests {
         assert!(make_bounds(OOB::Lower, OOB::Upper, OOB::Lower, OOB::Upper, OOB::Above, OOB::Upper).is_err());
         // b oob
         assert!(make_bounds(OOB::Lower, OOB::Upper, OOB::Lower, OOB::Upper, OOB::Upper, OOB::Above).is_err());
+        // errors should report both the bounds and the offending value
+        let w_err = make_bounds(OOB::Above, OOB::Upper, OOB::Lower, OOB::Upper, OOB::Upper, OOB::Upper).unwrap_err();
+        assert!(w_err.to_string().contains("expected -1000..=1000, got 1001"));
+        let x_err = make_bounds(OOB::Lower, OOB::Above, OOB::Lower, OOB::Upper, OOB::Upper, OOB::Upper).unwrap_err();
+        assert!(x_err.to_string().contains("expected 0..=7, got 8"));
+        let hash_err = Hash::new(vec![0; 9]).unwrap_err();
+        assert!(hash_err.to_string().contains("expected 0..=8, got 9"));
 
         // type and group choices share the same deserialization code so we only check the API
         assert!(BoundsTypeChoice::new_bytes(vec![0; 64]).is_ok());
@@ -560,6 +948,89 @@ mod tests {
         deser_test(&from_bytes);
     }
 
+    #[test]
+    fn bytes_hex_display() {
+        let hash = Hash::new(vec![0xDE, 0xAD, 0x0B]).unwrap();
+        assert_eq!(hash.to_string(), "dead0b");
+        assert_eq!(format!("{:x}", hash), "dead0b");
+        assert_eq!(format!("{:X}", hash), "DEAD0B");
+        assert_eq!(Hash::new(vec![]).unwrap().to_string(), "");
+        let lib_rs_with_tests = std::fs::read_to_string(std::path::Path::new("src").join("lib.rs")).unwrap();
+        let lib_rs = &lib_rs_with_tests[..lib_rs_with_tests.find("#[cfg(test)]").unwrap()];
+        assert!(lib_rs.contains("impl std::fmt::Display for Hash32 {"));
+        // @no_hex_display
+        assert!(!lib_rs.contains("impl std::fmt::Display for OpaqueBytes {"));
+        assert!(!lib_rs.contains("impl std::fmt::LowerHex for OpaqueBytes {"));
+    }
+
+    #[test]
+    fn fixed_length_bytes_table() {
+        let key = Hash32::new(vec![0xAA; 32]).unwrap();
+        assert!(Hash32::new(vec![0xAA; 31]).is_err());
+        let mut sigs = std::collections::BTreeMap::new();
+        sigs.insert(key.clone(), Sig64::new(vec![0xBB; 64]).unwrap());
+        deser_test(&SignatureTable::new(sigs));
+        // this is just here to make sure this compiles (i.e. Hash is derived)
+        let mut set_key: std::collections::HashSet<Hash32> = std::collections::HashSet::new();
+        set_key.insert(key);
+        // inline .size is checked when deserializing keys and values
+        let mut inline_sigs = std::collections::BTreeMap::new();
+        inline_sigs.insert(vec![0xAA; 32], vec![0xBB; 64]);
+        deser_test(&InlineSignatureTable::new(inline_sigs.clone()));
+        inline_sigs.insert(vec![0xCC; 31], vec![0xBB; 64]);
+        let bytes = InlineSignatureTable::new(inline_sigs).to_cbor_bytes();
+        assert!(InlineSignatureTable::from_cbor_bytes(&bytes).is_err());
+    }
+
+    #[test]
+    fn wrapper_iter() {
+        use cbor_event::Sz;
+        let bytes = vec![
+            map_sz(2, Sz::Inline),
+                cbor_int(5, Sz::Inline),
+                    cbor_int(4, Sz::Inline),
+                cbor_int(3, Sz::Inline),
+                    cbor_int(2, Sz::Inline),
+        ].into_iter().flatten().clone().collect::<Vec<u8>>();
+        let table = WrapperTable::from_cbor_bytes(&bytes).unwrap();
+        // ordered by the BTreeMap's keys, not the encoding order
+        assert_eq!(table.keys().copied().collect::<Vec<_>>(), vec![3, 5]);
+        assert_eq!(table.values().copied().collect::<Vec<_>>(), vec![2, 4]);
+        let mut pairs = vec![];
+        for (k, v) in &table {
+            pairs.push((*k, *v));
+        }
+        assert_eq!(pairs, vec![(3, 2), (5, 4)]);
+        assert_eq!(table.into_iter().collect::<Vec<_>>(), vec![(3, 2), (5, 4)]);
+        let list = WrapperList::new(vec![1, 2, 3]);
+        assert_eq!((&list).into_iter().sum::<u64>(), 6);
+        assert_eq!(list.into_iter().rev().collect::<Vec<_>>(), vec![3, 2, 1]);
+    }
+
+    #[test]
+    fn wrapper_record() {
+        use cbor_event::Sz;
+        let mut inner = WrapperRecordInner::new(7);
+        inner.name = Some("seven".to_owned());
+        let mut wrapper = WrapperRecord::new(inner.clone());
+        // same encoding as the struct itself
+        assert_eq!(wrapper.to_cbor_bytes(), inner.to_cbor_bytes());
+        deser_test(&wrapper);
+        // fields are accessible through Deref/DerefMut
+        assert_eq!(wrapper.id, 7);
+        wrapper.id = 8;
+        assert_eq!(wrapper.name.as_deref(), Some("seven"));
+        let bytes = vec![
+            map_sz(1, Sz::Inline),
+                cbor_string("id"),
+                cbor_int(8, Sz::Inline),
+        ].into_iter().flatten().clone().collect::<Vec<u8>>();
+        let from_bytes = WrapperRecord::from_cbor_bytes(&bytes).unwrap();
+        assert_eq!(from_bytes.id, 8);
+        assert_eq!(from_bytes.name, None);
+        assert_eq!(WrapperRecordInner::from(from_bytes).to_cbor_bytes(), bytes);
+    }
+
     #[test]
     fn wrapper_list() {
         use cbor_event::Sz;
@@ -581,6 +1052,455 @@ mod tests {
         assert_eq!(128, x.custom_getter());
     }
 
+    #[test]
+    fn wrapper_deref() {
+        let mut list = WrapperList::new(vec![1, 2, 3]);
+        assert_eq!(list.len(), 3);
+        list.push(4);
+        assert_eq!(*list, vec![1, 2, 3, 4]);
+        let mut x = WrapperInt::new(128);
+        *x += 1;
+        assert_eq!(x.custom_getter(), 129);
+        // constrained wrappers are read-only through Deref
+        let hash = Hash::new(vec![0xBA, 0xAD]).unwrap();
+        assert_eq!(hash.len(), 2);
+        assert_eq!(hash[0], 0xBA);
+    }
+
+    #[test]
+    fn try_from_bytes() {
+        let foo = Foo::new(436, String::from("jfkdsjfd"), vec![1, 1, 1]);
+        let bytes = foo.to_cbor_bytes();
+        let from_slice = Foo::try_from(&bytes[..]).unwrap();
+        assert_eq!(from_slice.to_cbor_bytes(), bytes);
+        let truncated: Result<Foo, DeserializeError> = Foo::try_from(&bytes[..bytes.len() - 1]);
+        assert!(truncated.is_err());
+        let hash_bytes = Hash::new(vec![0xBA, 0xAD]).unwrap().to_cbor_bytes();
+        assert_eq!(Hash::try_from(hash_bytes.as_slice()).unwrap().to_cbor_bytes(), hash_bytes);
+        let type_choice_bytes = TypeChoice::new_text(String::from("text")).to_cbor_bytes();
+        assert_eq!(TypeChoice::try_from(type_choice_bytes.as_slice()).unwrap().to_cbor_bytes(), type_choice_bytes);
+    }
+
+    #[test]
+    fn tag_field_choice() {
+        let a = TagFieldChoice::new_tag_field_a(5, String::from("five"));
+        deser_test(&a);
+        let b = TagFieldChoice::new_tag_field_b(vec![0xBE, 0xEF], 2);
+        deser_test(&b);
+        // the discriminator does not need to be the first key
+        let b_reordered = vec![
+            map_def(3),
+                cbor_string("z"),
+                cbor_bytes_sz(vec![0xBE, 0xEF], cbor_event::StringLenSz::Len(cbor_event::Sz::Inline)),
+                cbor_string("type"),
+                cbor_string("b"),
+                cbor_string("w"),
+                cbor_int(2, cbor_event::Sz::Inline),
+        ].into_iter().flatten().collect::<Vec<u8>>();
+        assert!(matches!(TagFieldChoice::from_cbor_bytes(&b_reordered).unwrap(), TagFieldChoice::TagFieldB(_)));
+        let unknown_discriminator = vec![
+            map_def(3),
+                cbor_string("type"),
+                cbor_string("c"),
+                cbor_string("x"),
+                cbor_int(5, cbor_event::Sz::Inline),
+                cbor_string("y"),
+                cbor_string("five"),
+        ].into_iter().flatten().collect::<Vec<u8>>();
+        assert!(TagFieldChoice::from_cbor_bytes(&unknown_discriminator).is_err());
+    }
+
+    #[test]
+    fn group_extension() {
+        let group = ExtendedGroup::new(5, String::from("five"), vec![0xCA, 0xFE]);
+        assert_eq!(group.ext_c, vec![0xCA, 0xFE]);
+        let user = ExtendedGroupUser::new(0, group);
+        deser_test(&user);
+        // the extension's fields are embedded right after the base group's fields
+        let expected = vec![
+            arr_def(4),
+                cbor_int(0, cbor_event::Sz::Inline),
+                cbor_int(5, cbor_event::Sz::Inline),
+                cbor_string("five"),
+                cbor_bytes_sz(vec![0xCA, 0xFE], cbor_event::StringLenSz::Len(cbor_event::Sz::Inline)),
+        ].into_iter().flatten().collect::<Vec<u8>>();
+        assert_eq!(user.to_cbor_bytes(), expected);
+    }
+
+    #[test]
+    fn nullable_fields() {
+        // absent
+        let absent_bytes = map_def(0);
+        let absent = NullableFields::from_cbor_bytes(&absent_bytes).unwrap();
+        assert_eq!(absent.collapsed, None);
+        assert_eq!(absent.three_state, None);
+        assert_eq!(absent.unannotated, None);
+        assert_eq!(absent.to_cbor_bytes(), absent_bytes);
+        // present but explicitly null
+        let null_bytes = vec![
+            map_def(1),
+                cbor_int(2, cbor_event::Sz::Inline),
+                vec![0xf6],
+        ].into_iter().flatten().collect::<Vec<u8>>();
+        let null = NullableFields::from_cbor_bytes(&null_bytes).unwrap();
+        assert_eq!(null.three_state, Some(None));
+        assert_eq!(null.to_cbor_bytes(), null_bytes);
+        // present with a value
+        let mut value = NullableFields::new();
+        value.three_state = Some(Some(String::from("value")));
+        deser_test(&value);
+        let value_bytes = vec![
+            map_def(1),
+                cbor_int(2, cbor_event::Sz::Inline),
+                cbor_string("value"),
+        ].into_iter().flatten().collect::<Vec<u8>>();
+        assert_eq!(value.to_cbor_bytes(), value_bytes);
+        // three-state is the default without any annotation
+        let unannotated_null_bytes = vec![
+            map_def(1),
+                cbor_int(3, cbor_event::Sz::Inline),
+                vec![0xf6],
+        ].into_iter().flatten().collect::<Vec<u8>>();
+        let unannotated_null = NullableFields::from_cbor_bytes(&unannotated_null_bytes).unwrap();
+        assert_eq!(unannotated_null.unannotated, Some(None));
+        assert_eq!(unannotated_null.to_cbor_bytes(), unannotated_null_bytes);
+        // with @collapse_null a null is read the same as if the key were absent
+        let collapsed_null_bytes = vec![
+            map_def(1),
+                cbor_int(1, cbor_event::Sz::Inline),
+                vec![0xf6],
+        ].into_iter().flatten().collect::<Vec<u8>>();
+        let collapsed_null = NullableFields::from_cbor_bytes(&collapsed_null_bytes).unwrap();
+        assert_eq!(collapsed_null.collapsed, None);
+        assert_eq!(collapsed_null.to_cbor_bytes(), absent_bytes);
+        let mut collapsed_value = NullableFields::new();
+        collapsed_value.collapsed = Some(9);
+        deser_test(&collapsed_value);
+    }
+
+    #[test]
+    fn cbor_in_bytes_keys() {
+        let mut entries = BTreeMap::new();
+        entries.insert(CborKey::new(5, String::from("five")), 5);
+        entries.insert(CborKey::new(0, String::from("zero")), 0);
+        let keyed = CborKeyed::new(entries);
+        deser_test(&keyed);
+        // keys are the CBOR of the key struct wrapped in a byte string
+        let key_bytes = vec![
+            arr_def(2),
+                cbor_int(0, cbor_event::Sz::Inline),
+                cbor_string("zero"),
+        ].into_iter().flatten().collect::<Vec<u8>>();
+        let single = vec![
+            arr_def(1),
+                map_def(1),
+                    cbor_bytes_sz(key_bytes, cbor_event::StringLenSz::Len(cbor_event::Sz::Inline)),
+                    cbor_int(0, cbor_event::Sz::Inline),
+        ].into_iter().flatten().collect::<Vec<u8>>();
+        let from_bytes = CborKeyed::from_cbor_bytes(&single).unwrap();
+        assert_eq!(from_bytes.entries.get(&CborKey::new(0, String::from("zero"))), Some(&0));
+        assert_eq!(from_bytes.to_cbor_bytes(), single);
+    }
+
+    #[test]
+    fn all_fields_optional() {
+        let full = Patchable::new(7, String::from("full"));
+        assert_eq!(full.retries, 3);
+        deser_test(&full);
+        let mut update = PatchableUpdate::new();
+        assert!(update.id.is_none());
+        assert!(update.retries.is_none());
+        update.name = Some(String::from("renamed"));
+        deser_test(&update);
+        // only the fields being updated are serialized
+        let expected = vec![
+            map_def(1),
+                cbor_string("name"),
+                cbor_string("renamed"),
+        ].into_iter().flatten().collect::<Vec<u8>>();
+        assert_eq!(update.to_cbor_bytes(), expected);
+        update.retries = Some(3);
+        let from_bytes = PatchableUpdate::from_cbor_bytes(&update.to_cbor_bytes()).unwrap();
+        assert_eq!(from_bytes.retries, Some(3));
+        assert!(PatchableUpdate::from_cbor_bytes(&map_def(0)).unwrap().name.is_none());
+    }
+
+    #[test]
+    fn group_choice_variant_names() {
+        let named_first = MixedNamedChoices::new_named_first(5);
+        assert!(matches!(named_first, MixedNamedChoices::NamedFirst { .. }));
+        deser_test(&named_first);
+        let unnamed_second = MixedNamedChoices::new_mixed_named_choices1(String::from("second"));
+        assert!(matches!(unnamed_second, MixedNamedChoices::MixedNamedChoices1 { .. }));
+        deser_test(&unnamed_second);
+        let named_third = MixedNamedChoices::new_named_third(vec![0xBE, 0xEF]);
+        assert!(matches!(named_third, MixedNamedChoices::NamedThird { .. }));
+        deser_test(&named_third);
+        let unnamed_fourth = MixedNamedChoices::new_mixed_named_choices3(4, String::from("fourth"));
+        assert!(matches!(unnamed_fourth, MixedNamedChoices::MixedNamedChoices3 { .. }));
+        deser_test(&unnamed_fourth);
+        let clashing = ClashingChoiceNames::new_clashing_choice_names02(0);
+        assert!(matches!(clashing, ClashingChoiceNames::ClashingChoiceNames02 { .. }));
+        deser_test(&clashing);
+        let named = ClashingChoiceNames::new_clashing_choice_names0(String::from("named"));
+        assert!(matches!(named, ClashingChoiceNames::ClashingChoiceNames0 { .. }));
+        deser_test(&named);
+    }
+
+    #[test]
+    fn recursive_types() {
+        let list = LinkedList::new(
+            1,
+            Some(Box::new(LinkedList::new(2, Some(Box::new(LinkedList::new(3, None)))))),
+        );
+        deser_test(&list);
+        let expected = vec![
+            arr_def(2),
+                cbor_int(1, cbor_event::Sz::Inline),
+                arr_def(2),
+                    cbor_int(2, cbor_event::Sz::Inline),
+                    arr_def(2),
+                        cbor_int(3, cbor_event::Sz::Inline),
+                        vec![0xf6],
+        ].into_iter().flatten().collect::<Vec<u8>>();
+        assert_eq!(list.to_cbor_bytes(), expected);
+        let tree = Tree::new(0, vec![
+            Tree::new(1, vec![]),
+            Tree::new(2, vec![Tree::new(3, vec![])]),
+        ]);
+        deser_test(&tree);
+    }
+
+    #[test]
+    fn mutually_recursive_types() {
+        // Pong -> Ping is boxed, Ping -> Pong isn't
+        let ping = Ping::new(1, Some(Pong::new(2, Some(Box::new(Ping::new(3, None))))));
+        deser_test(&ping);
+        let pong = Pong::new(4, Some(Box::new(Ping::new(5, Some(Pong::new(6, None))))));
+        deser_test(&pong);
+        let expected = vec![
+            arr_def(2),
+                cbor_int(4, cbor_event::Sz::Inline),
+                arr_def(2),
+                    cbor_int(5, cbor_event::Sz::Inline),
+                    arr_def(2),
+                        cbor_int(6, cbor_event::Sz::Inline),
+                        vec![0xf6],
+        ].into_iter().flatten().collect::<Vec<u8>>();
+        assert_eq!(pong.to_cbor_bytes(), expected);
+    }
+
+    #[test]
+    fn prefixed_choice() {
+        let zero = PrefixedChoice::new_zero(5);
+        deser_test(&zero);
+        let one = PrefixedChoice::new_one(7, String::from("one"));
+        deser_test(&one);
+        let two = PrefixedChoice::new_two(vec![1, 2, 3]);
+        deser_test(&two);
+        // no choice starts with 3
+        let unknown = vec![
+            arr_def(2),
+                cbor_int(3, cbor_event::Sz::Inline),
+                cbor_int(5, cbor_event::Sz::Inline),
+        ].into_iter().flatten().collect::<Vec<u8>>();
+        assert!(PrefixedChoice::from_cbor_bytes(&unknown).is_err());
+        // the leading 1 picks the one choice even though the rest only matches zero's
+        let mismatched = vec![
+            arr_def(2),
+                cbor_int(1, cbor_event::Sz::Inline),
+                cbor_int(5, cbor_event::Sz::Inline),
+        ].into_iter().flatten().collect::<Vec<u8>>();
+        assert!(PrefixedChoice::from_cbor_bytes(&mismatched).is_err());
+    }
+
+    #[test]
+    fn parenthesized_types() {
+        deser_test(&Parenthesized::U64(5));
+        deser_test(&Parenthesized::Text(String::from("parenthesized")));
+        deser_test(&ParenthesizedFields::new(Some(5), vec![0xCA, 0xFE], String::from("tagged")));
+        deser_test(&ParenthesizedFields::new(None, vec![], String::new()));
+    }
+
+    #[test]
+    fn type_sockets() {
+        deser_test(&UsesSockets::new(Extensible::U64(5), vec![0xBE, 0xEF]));
+        deser_test(&UsesSockets::new(Extensible::Text("extended".into()), vec![]));
+    }
+
+    #[test]
+    fn hash_derives() {
+        use std::str::FromStr;
+        fn assert_hash<T: std::hash::Hash>() {}
+        assert_hash::<CEnum>();
+        assert_hash::<Hashable>();
+        assert_hash::<HashableInner>();
+        assert_hash::<HashForced>();
+        let lib_rs_with_tests = std::fs::read_to_string(std::path::PathBuf::from_str("src").unwrap().join("lib.rs")).unwrap();
+        let lib_rs = &lib_rs_with_tests[..lib_rs_with_tests.find("#[cfg(test)]").unwrap()];
+        let derives_of = |name: &str| {
+            let decl = lib_rs.find(&format!("pub struct {name} ")).unwrap();
+            &lib_rs[lib_rs[..decl].rfind("#[derive(").unwrap()..decl]
+        };
+        assert!(derives_of("Hashable").contains("Hash"));
+        assert!(!derives_of("HashFloat").contains("Hash"));
+        assert!(!derives_of("HashTable").contains("Hash"));
+    }
+
+    #[test]
+    fn eq_impls() {
+        fn implements_eq<T: Eq>() {}
+        implements_eq::<Foo>();
+        implements_eq::<Bar>();
+        implements_eq::<HashTable>();
+        implements_eq::<EqFloat>();
+        implements_eq::<EqForced>();
+        assert_eq!(Foo::new(1, "a".into(), vec![2]), Foo::new(1, "a".into(), vec![2]));
+        assert_ne!(Foo::new(1, "a".into(), vec![2]), Foo::new(1, "b".into(), vec![2]));
+        // floats are compared by their bits so NaN is equal to itself
+        let nan = EqFloat::new(f64::NAN, vec![0.5, -0.0], "nan".into());
+        assert_eq!(nan, nan.clone());
+        let mut positive_zero = nan.clone();
+        positive_zero.z[1] = 0.0;
+        assert_ne!(nan, positive_zero);
+        let mut with_y = nan.clone();
+        with_y.y = Some(1.5);
+        assert_ne!(nan, with_y);
+        assert_eq!(with_y, with_y.clone());
+    }
+
+    #[test]
+    fn unit_structs() {
+        assert_eq!(EmptyArray.to_cbor_bytes(), vec![0x80]);
+        assert_eq!(EmptyMap.to_cbor_bytes(), vec![0xa0]);
+        assert_eq!(FixedOnly.to_cbor_bytes(), vec![0x82, 0x00, 0x64, b'u', b'n', b'i', b't']);
+        deser_test(&EmptyArray);
+        deser_test(&EmptyMap);
+        deser_test(&FixedOnly::new());
+        assert!(EmptyArray::from_cbor_bytes(&[0x81, 0x00]).is_err());
+    }
+
+    #[test]
+    fn string_enum() {
+        assert_eq!(Network::Mainnet.as_str(), "mainnet");
+        assert_eq!(Network::Preview.to_string(), "preview");
+        assert_eq!(Network::try_from("testnet").unwrap(), Network::Testnet);
+        assert!(Network::try_from("Testnet").is_err());
+        for network in [Network::Mainnet, Network::Testnet, Network::Preview] {
+            assert_eq!(Network::try_from(network.as_str()).unwrap(), network);
+        }
+    }
+
+    #[test]
+    fn int_enums() {
+        assert_eq!(ClosedCommand::Uint24 as u8, 24);
+        assert_eq!(ClosedCommand::UINT24, 24);
+        assert_eq!(ClosedCommand::ZERO, 0);
+        assert_eq!(OpenCommand::FIVE, 5);
+        deser_test(&IntEnums::new(ClosedCommand::Two, OpenCommand::Five));
+        let unknown = IntEnums::new(ClosedCommand::Zero, OpenCommand::Unknown(7));
+        assert_eq!(unknown.to_cbor_bytes(), vec![arr_def(2), cbor_int(0, cbor_event::Sz::Inline), cbor_int(7, cbor_event::Sz::Inline)].into_iter().flatten().clone().collect::<Vec<u8>>());
+        deser_test(&unknown);
+        let closed_unknown = vec![arr_def(2), cbor_int(3, cbor_event::Sz::Inline), cbor_int(0, cbor_event::Sz::Inline)].into_iter().flatten().clone().collect::<Vec<u8>>();
+        assert!(IntEnums::from_cbor_bytes(&closed_unknown).is_err());
+    }
+
+    #[test]
+    fn array_tail() {
+        let with_entries = HeaderPlusList::new(5, vec![String::from("a"), String::from("b")]);
+        let bytes = vec![
+            arr_def(3),
+                vec![0x05],
+                cbor_string("a"),
+                cbor_string("b"),
+        ].into_iter().flatten().clone().collect::<Vec<u8>>();
+        assert_eq!(with_entries.to_cbor_bytes(), bytes);
+        deser_test(&with_entries);
+        let empty = HeaderPlusList::new(5, vec![]);
+        assert_eq!(empty.to_cbor_bytes(), vec![0x81, 0x05]);
+        deser_test(&empty);
+        let indefinite = vec![
+            vec![ARR_INDEF],
+                vec![0x05],
+                cbor_string("a"),
+            vec![BREAK],
+        ].into_iter().flatten().clone().collect::<Vec<u8>>();
+        let from_indefinite = HeaderPlusList::from_cbor_bytes(&indefinite).unwrap();
+        assert_eq!(from_indefinite.header, 5);
+        assert_eq!(from_indefinite.entries, vec![String::from("a")]);
+        // definite length that doesn't match the elements present
+        let too_short = vec![
+            arr_def(3),
+                vec![0x05],
+                cbor_string("a"),
+        ].into_iter().flatten().clone().collect::<Vec<u8>>();
+        assert!(HeaderPlusList::from_cbor_bytes(&too_short).is_err());
+    }
+
+    #[test]
+    fn array_tail_non_empty() {
+        use non_empty_vec::NonEmptyVec;
+        assert!(NonEmptyVec::<String>::new(vec![]).is_err());
+        assert!(NonEmptyTail::new(5, vec![]).is_err());
+        let one = NonEmptyTail::new(5, vec![String::from("a")]).unwrap();
+        let entries: &NonEmptyVec<String> = &one.entries;
+        assert_eq!(entries.len(), 1);
+        assert_eq!(entries.clone().into_inner(), vec![String::from("a")]);
+        assert_eq!(one.to_cbor_bytes(), vec![0x82, 0x05, 0x61, 0x61]);
+        deser_test(&one);
+        assert!(NonEmptyTail::from_cbor_bytes(&[0x81, 0x05]).is_err());
+        assert!(NonEmptyTail::from_cbor_bytes(&[ARR_INDEF, 0x05, BREAK]).is_err());
+    }
+
+    #[test]
+    fn optional_leading_element() {
+        let mut with_version = VersionedPayload::new(vec![0xCA, 0xFE]);
+        with_version.version = Some(2);
+        let bytes = vec![
+            arr_def(2),
+                vec![0x02],
+                vec![0x42, 0xCA, 0xFE],
+        ].into_iter().flatten().clone().collect::<Vec<u8>>();
+        assert_eq!(with_version.to_cbor_bytes(), bytes);
+        deser_test(&with_version);
+        let without_version = VersionedPayload::new(vec![0xCA, 0xFE]);
+        assert_eq!(without_version.to_cbor_bytes(), vec![0x81, 0x42, 0xCA, 0xFE]);
+        deser_test(&without_version);
+        let from_indefinite = VersionedPayload::from_cbor_bytes(&[ARR_INDEF, 0x42, 0xCA, 0xFE, BREAK]).unwrap();
+        assert_eq!(from_indefinite.version, None);
+        assert_eq!(from_indefinite.payload, vec![0xCA, 0xFE]);
+        // the version alone without the mandatory payload
+        assert!(VersionedPayload::from_cbor_bytes(&[0x81, 0x02]).is_err());
+    }
+
+    #[test]
+    fn choice_from_group_keys() {
+        let mut names = std::collections::BTreeMap::new();
+        names.insert(Language::French, String::from("Bonjour"));
+        names.insert(Language::English, String::from("Hello"));
+        let translations = Translations::new(Language::English, names);
+        let bytes = vec![
+            arr_def(2),
+                cbor_string("en"),
+                map_def(2),
+                    cbor_string("en"),
+                    cbor_string("Hello"),
+                    cbor_string("fr"),
+                    cbor_string("Bonjour"),
+        ].into_iter().flatten().clone().collect::<Vec<u8>>();
+        assert_eq!(translations.to_cbor_bytes(), bytes);
+        deser_test(&translations);
+        // keys outside of the group's constants
+        let unknown_key = vec![
+            arr_def(2),
+                cbor_string("en"),
+                map_def(1),
+                    cbor_string("de"),
+                    cbor_string("Hallo"),
+        ].into_iter().flatten().clone().collect::<Vec<u8>>();
+        assert!(Translations::from_cbor_bytes(&unknown_key).is_err());
+    }
+
     #[test]
     fn docs() {
         use std::str::FromStr;
@@ -595,4 +1515,74 @@ mod tests {
         assert!(lib_rs.contains("comments about second"));
         assert!(lib_rs.contains("type-level comment"));
     }
+
+    #[test]
+    fn simple_values() {
+        use cbor_event::Sz;
+        let terminated = SentinelTerminated::new(vec![1, 2]);
+        let bytes = terminated.to_cbor_bytes();
+        let expected_bytes = vec![
+            arr_def(2),
+                arr_def(2),
+                    cbor_int(1, Sz::Inline),
+                    cbor_int(2, Sz::Inline),
+                vec![0xf0],
+        ].into_iter().flatten().clone().collect::<Vec<u8>>();
+        assert_eq!(bytes, expected_bytes);
+        deser_test(&terminated);
+        // any other simple value is rejected
+        let mut wrong_marker = bytes.clone();
+        *wrong_marker.last_mut().unwrap() = 0xf1;
+        assert!(SentinelTerminated::from_cbor_bytes(&wrong_marker).is_err());
+        deser_test(&UintOrSentinel::Simple16);
+        deser_test(&UintOrSentinel::U64(16));
+    }
+
+    #[test]
+    fn mixed_keys() {
+        use cbor_event::Sz;
+        let mixed = MixedKeys::new(5, "one".to_owned(), "label".to_owned());
+        assert_eq!(mixed.key_2, None);
+        let expected = vec![
+            map_def(3),
+                cbor_int(0, Sz::Inline),
+                cbor_int(5, Sz::Inline),
+                cbor_int(1, Sz::Inline),
+                cbor_string("one"),
+                cbor_string("label"),
+                cbor_string("label"),
+        ].into_iter().flatten().clone().collect::<Vec<u8>>();
+        assert_eq!(mixed.to_cbor_bytes(), expected);
+        deser_test(&mixed);
+        let single = SingleLiteralKey::new("zero".to_owned());
+        let expected = vec![
+            map_def(1),
+                cbor_int(0, Sz::Inline),
+                cbor_string("zero"),
+        ].into_iter().flatten().clone().collect::<Vec<u8>>();
+        assert_eq!(single.to_cbor_bytes(), expected);
+        deser_test(&single);
+    }
+
+    #[test]
+    fn float_bounds() {
+        assert!(BelowLimit::new(1.25).is_ok());
+        // equal to the exclusive bound
+        assert!(BelowLimit::new(1.5).is_err());
+        assert!(BelowLimit::new(f64::NAN).is_err());
+        assert!(BelowLimit::from_cbor_bytes(&cbor_float(1.25)).is_ok());
+        assert!(BelowLimit::from_cbor_bytes(&cbor_float(1.5)).is_err());
+        let mut bounded = FloatBounds::new(0.5, 0.0).unwrap();
+        bounded.limit = Some(BelowLimit::new(-3.0).unwrap());
+        deser_test(&bounded);
+        assert!(FloatBounds::new(0.0, 0.5).is_err());
+        assert!(FloatBounds::new(0.5, 1.0).is_err());
+        assert!(FloatBounds::new(0.5, 0.999).is_ok());
+        let bytes = vec![
+            arr_def(2),
+                cbor_float(0.5),
+                cbor_float(1.0),
+        ].into_iter().flatten().clone().collect::<Vec<u8>>();
+        assert!(FloatBounds::from_cbor_bytes(&bytes).is_err());
+    }
 }
@@ -0,0 +1,32 @@
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serialization::Deserialize;
+
+    fn reading_bytes(count: i128, offset: i128) -> Vec<u8> {
+        vec![
+            arr_def(3),
+                cbor_int(count, cbor_event::Sz::Eight),
+                cbor_int(offset, cbor_event::Sz::Eight),
+                cbor_int(u64::MAX as i128, cbor_event::Sz::Eight),
+        ].into_iter().flatten().collect::<Vec<u8>>()
+    }
+
+    #[test]
+    fn narrowed_types() {
+        let count: Counter = u32::MAX;
+        let reading = Reading::new(count, i32::MIN, u64::MAX);
+        deser_test(&reading);
+        let max = Reading::from_cbor_bytes(&reading_bytes(u32::MAX as i128, i32::MIN as i128)).unwrap();
+        assert_eq!(max.count, u32::MAX);
+        assert_eq!(max.offset, i32::MIN);
+        assert_eq!(max.big, u64::MAX);
+    }
+
+    #[test]
+    fn overflow_is_an_error() {
+        assert!(Reading::from_cbor_bytes(&reading_bytes(u32::MAX as i128 + 1, 0)).is_err());
+        assert!(Reading::from_cbor_bytes(&reading_bytes(0, i32::MAX as i128 + 1)).is_err());
+        assert!(Reading::from_cbor_bytes(&reading_bytes(0, i32::MIN as i128 - 1)).is_err());
+    }
+}
@@ -0,0 +1,56 @@
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serialization::Deserialize;
+
+    fn signed_bytes(key: Vec<u8>) -> Vec<u8> {
+        use cbor_event::{Sz, StringLenSz};
+        vec![
+            arr_def(3),
+                cbor_bytes_sz(vec![0xAA; 32], StringLenSz::Len(Sz::One)),
+                cbor_bytes_sz(key, StringLenSz::Len(Sz::Inline)),
+                arr_def(1),
+                    cbor_bytes_sz(vec![0xBB; 64], StringLenSz::Len(Sz::One)),
+        ].into_iter().flatten().collect::<Vec<u8>>()
+    }
+
+    #[test]
+    fn wrapper() {
+        let hash = Hash32::new([0xAA; 32]);
+        let inner: [u8; 32] = hash.clone().into();
+        assert_eq!(inner, [0xAA; 32]);
+        deser_test(&hash);
+        deser_test(&Sig64::new([0xBB; 64]));
+        // hash32 is serialized as a bstr and not an array of uints
+        let bytes = hash.to_cbor_bytes();
+        assert_eq!(bytes[..2], [0x58, 32]);
+        assert!(Label::new(vec![0; 9]).is_err());
+    }
+
+    #[test]
+    fn field() {
+        let signed = Signed::from_cbor_bytes(&signed_bytes(vec![1, 2, 3, 4])).unwrap();
+        assert_eq!(signed.key, [1, 2, 3, 4]);
+        deser_test(&signed);
+    }
+
+    #[test]
+    fn wrong_length() {
+        assert!(Signed::from_cbor_bytes(&signed_bytes(vec![1, 2, 3])).is_err());
+        assert!(Signed::from_cbor_bytes(&signed_bytes(vec![1, 2, 3, 4, 5])).is_err());
+        let short_hash = cbor_bytes_sz(vec![0xAA; 31], cbor_event::StringLenSz::Len(cbor_event::Sz::One));
+        assert!(Hash32::from_cbor_bytes(&short_hash).is_err());
+    }
+
+    #[test]
+    fn large_enum_variant() {
+        deser_test(&KeyOrIndex::BigKey(BigKey::new([0xCC; 300])));
+        deser_test(&KeyOrIndex::U64(3));
+        let lib_rs_with_tests = std::fs::read_to_string(std::path::PathBuf::from("src").join("lib.rs")).unwrap();
+        let lib_rs = &lib_rs_with_tests[..lib_rs_with_tests.find("#[cfg(test)]").unwrap()];
+        let enum_start = lib_rs.find("pub enum KeyOrIndex").unwrap();
+        let attrs_start = lib_rs[..enum_start].rfind('}').unwrap();
+        assert!(lib_rs[attrs_start..enum_start].contains("#[allow(clippy::large_enum_variant)]"));
+        assert_eq!(lib_rs.matches("clippy::large_enum_variant").count(), 1);
+    }
+}
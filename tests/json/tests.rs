@@ -89,4 +89,25 @@ mod tests {
         assert_eq!(json_str, serde_json::to_string_pretty(&from_value).unwrap());
         assert_eq!(json_str, serde_json::to_string_pretty(&from_json).unwrap());
     }
+
+    #[test]
+    fn hex_field() {
+        let json_str = r#"{"key":"dead","raw":[1,2]}"#;
+        let from_value = SignedPayload::new(vec![0xDE, 0xAD], vec![1, 2]);
+        let from_json: SignedPayload = serde_json::from_str(json_str).unwrap();
+        assert_eq!(json_str, serde_json::to_string(&from_value).unwrap());
+        assert_eq!(json_str, serde_json::to_string(&from_json).unwrap());
+        assert!(serde_json::from_str::<SignedPayload>(r#"{"key":"xyz","raw":[]}"#).is_err());
+    }
+
+    #[test]
+    fn plain_group_flattened() {
+        // coords' fields are at the same level as label like in the CBOR array
+        let json_str = r#"{"label":"origin","x":0,"y":5}"#;
+        let from_value = LabelledPoint::new("origin".to_owned(), Coords::new(0, 5));
+        let from_json: LabelledPoint = serde_json::from_str(json_str).unwrap();
+        assert_eq!(json_str, serde_json::to_string(&from_value).unwrap());
+        assert_eq!(json_str, serde_json::to_string(&from_json).unwrap());
+        assert_eq!(from_json.to_cbor_bytes(), from_value.to_cbor_bytes());
+    }
 }
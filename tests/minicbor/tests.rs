@@ -0,0 +1,154 @@
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serialization::*;
+
+    fn minicbor_test<T>(x: T)
+    where
+        T: ToCBORBytes + Deserialize + minicbor::Encode<()> + for<'b> minicbor::Decode<'b, ()>,
+    {
+        let orig_bytes = x.to_cbor_bytes();
+        print_cbor_types("orig", &orig_bytes);
+        let minicbor_bytes = minicbor::to_vec(&x).unwrap();
+        print_cbor_types("minicbor", &minicbor_bytes);
+        assert_eq!(orig_bytes, minicbor_bytes);
+        let decoded: T = minicbor::decode(&orig_bytes).unwrap();
+        assert_eq!(decoded.to_cbor_bytes(), orig_bytes);
+    }
+
+    fn record() -> Record {
+        let mut record = Record::new(
+            5,
+            String::from("foo"),
+            vec![0xBA, 0xAD, 0xF0, 0x0D],
+            vec![Point::new(1, 2), Point::new(3, 4)],
+        );
+        record.points[1].label = Some(String::from("bar"));
+        record
+    }
+
+    #[test]
+    fn derived_map() {
+        let mut point = Point::new(7, 300);
+        minicbor_test(point.clone());
+        point.label = Some(String::from("baz"));
+        minicbor_test(point);
+    }
+
+    #[test]
+    fn derived_array() {
+        let mut record = record();
+        minicbor_test(record.clone());
+        record.note = Some(String::from("qux"));
+        minicbor_test(record);
+    }
+
+    #[test]
+    fn manual() {
+        minicbor_test(Labelled::new(String::from("foo"), 1024));
+        minicbor_test(Choice::U64(9));
+        minicbor_test(Choice::Text(String::from("text")));
+    }
+
+    #[test]
+    fn manual_char_and_float16() {
+        minicbor_test(Glyph::new('a', 1.5));
+    }
+
+    #[test]
+    fn manual_containing_derived() {
+        minicbor_test(Holder::new(
+            Labelled::new(String::from("foo"), 1024),
+            Choice::Text(String::from("choice")),
+            record(),
+        ));
+    }
+
+    #[test]
+    fn manual_map() {
+        let mut settings = Settings::new(String::from("foo"), vec![-3, 0, 500]);
+        minicbor_test(settings.clone());
+        settings.limit = Some(10);
+        settings.fallback = Some(None);
+        minicbor_test(settings.clone());
+        settings.fallback = Some(Some(String::from("bar")));
+        settings.values.clear();
+        minicbor_test(settings);
+    }
+
+    #[test]
+    fn manual_map_indefinite() {
+        let bytes = vec![
+            vec![MAP_INDEF],
+                cbor_string("values"),
+                vec![ARR_INDEF],
+                    cbor_int(-3, cbor_event::Sz::Inline),
+                vec![BREAK],
+                cbor_string("name"),
+                cbor_string("foo"),
+            vec![BREAK],
+        ].into_iter().flatten().collect::<Vec<u8>>();
+        let decoded: Settings = minicbor::decode(&bytes).unwrap();
+        assert_eq!(decoded.name, "foo");
+        assert_eq!(decoded.values, vec![-3]);
+        assert_eq!(decoded.limit, None);
+    }
+
+    #[test]
+    fn manual_map_rejects_invalid_keys() {
+        let settings = Settings::new(String::from("foo"), vec![]);
+        let mut unknown_key = settings.to_cbor_bytes();
+        unknown_key[0] += 1;
+        unknown_key.extend(cbor_string("extra"));
+        unknown_key.extend(cbor_int(1, cbor_event::Sz::Inline));
+        assert!(minicbor::decode::<Settings>(&unknown_key).is_err());
+        let missing_key = vec![
+            map_def(1),
+            cbor_string("name"),
+            cbor_string("foo"),
+        ].into_iter().flatten().collect::<Vec<u8>>();
+        assert!(minicbor::decode::<Settings>(&missing_key).is_err());
+        let duplicate_key = vec![
+            map_def(3),
+            cbor_string("name"),
+            cbor_string("foo"),
+            cbor_string("values"),
+            arr_def(0),
+            cbor_string("name"),
+            cbor_string("bar"),
+        ].into_iter().flatten().collect::<Vec<u8>>();
+        assert!(minicbor::decode::<Settings>(&duplicate_key).is_err());
+    }
+
+    #[test]
+    fn manual_array_tail() {
+        minicbor_test(Batch::new(1, vec![2]).unwrap());
+        minicbor_test(Batch::new(1, vec![2, 3, 4]).unwrap());
+        let indefinite = vec![
+            vec![ARR_INDEF],
+                cbor_int(1, cbor_event::Sz::Inline),
+                cbor_int(2, cbor_event::Sz::Inline),
+                cbor_int(3, cbor_event::Sz::Inline),
+            vec![BREAK],
+        ].into_iter().flatten().collect::<Vec<u8>>();
+        let decoded: Batch = minicbor::decode(&indefinite).unwrap();
+        assert_eq!(decoded.items.clone().into_inner(), vec![2, 3]);
+        assert!(minicbor::decode::<Batch>(&[0x81, 0x01]).is_err());
+        assert!(minicbor::decode::<Batch>(&[ARR_INDEF, 0x01, BREAK]).is_err());
+    }
+
+    #[test]
+    fn manual_decode_advances_decoder() {
+        let labelled = Labelled::new(String::from("foo"), 1024);
+        let bytes = vec![
+            arr_def(2),
+            labelled.to_cbor_bytes(),
+            cbor_int(5, cbor_event::Sz::Inline),
+        ].into_iter().flatten().collect::<Vec<u8>>();
+        let mut d = minicbor::Decoder::new(&bytes);
+        assert_eq!(d.array().unwrap(), Some(2));
+        let decoded: Labelled = d.decode().unwrap();
+        assert_eq!(decoded.to_cbor_bytes(), labelled.to_cbor_bytes());
+        assert_eq!(d.u64().unwrap(), 5);
+    }
+}
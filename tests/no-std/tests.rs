@@ -0,0 +1,49 @@
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use alloc::{string::String, vec, vec::Vec};
+
+    // re-encoding is compared as the generated types don't implement PartialEq
+    fn round_trip<T>(x: &T) -> Vec<u8>
+    where
+        T: minicbor::Encode<()> + for<'b> minicbor::Decode<'b, ()>,
+    {
+        let bytes = minicbor::to_vec(x).unwrap();
+        let decoded: T = minicbor::decode(&bytes).unwrap();
+        assert_eq!(minicbor::to_vec(&decoded).unwrap(), bytes);
+        bytes
+    }
+
+    #[test]
+    fn point() {
+        assert_eq!(round_trip(&Point::new(1, 2)), vec![0xa2, 0x00, 0x01, 0x01, 0x02]);
+        let mut labelled = Point::new(3, 4);
+        labelled.label = Some(String::from("no_std"));
+        round_trip(&labelled);
+    }
+
+    #[test]
+    fn shape() {
+        let mut shape = Shape::new(
+            String::from("triangle"),
+            true,
+            vec![0xBA, 0xAD],
+            vec![Point::new(0, 0), Point::new(1, 0), Point::new(0, 1)],
+        );
+        round_trip(&shape);
+        shape.origin = Some(Point::new(5, 5));
+        round_trip(&shape);
+    }
+
+    #[test]
+    fn style() {
+        let mut style = Style::new(String::from("red"), vec![]);
+        round_trip(&style);
+        style.width = Some(2);
+        style.fill = Some(None);
+        style.outline.push(Shape::new(String::from("line"), false, vec![], vec![Point::new(0, 0)]));
+        round_trip(&style);
+        style.fill = Some(Some(String::from("blue")));
+        round_trip(&style);
+    }
+}
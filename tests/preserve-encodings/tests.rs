@@ -876,6 +876,19 @@ mod tests {
                 }
             }
         }
+        // array elements explicitly encoded with their default value are kept
+        for def_enc in &def_encodings {
+            let irregular_bytes = vec![
+                vec![ARR_INDEF],
+                    cbor_int(5, *def_enc),
+                    cbor_int(3, *def_enc),
+                vec![BREAK],
+            ].into_iter().flatten().clone().collect::<Vec<u8>>();
+            let irregular = ArrayWithDefaults::from_cbor_bytes(&irregular_bytes).unwrap();
+            assert_eq!(irregular_bytes, irregular.to_cbor_bytes());
+            assert_eq!(irregular.label, "none");
+            assert_eq!(irregular.retries, 3);
+        }
     }
 
     #[test]
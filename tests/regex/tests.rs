@@ -0,0 +1,59 @@
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serialization::Deserialize;
+
+    fn uses_regexes_bytes(anchored: &str, unanchored: &str) -> Vec<u8> {
+        vec![
+            arr_def(2),
+                cbor_string(anchored),
+                cbor_string(unanchored),
+        ].into_iter().flatten().collect::<Vec<u8>>()
+    }
+
+    #[test]
+    fn regexp_matches_whole_string() {
+        assert!(Anchored::new(String::from("abc")).is_ok());
+        assert!(Anchored::new(String::from("abc1")).is_err());
+        assert!(Anchored::new(String::new()).is_err());
+        // ^ and $ are regular characters in XML Schema regexes
+        assert!(LiteralAnchors::new(String::from("^123$")).is_ok());
+        assert!(LiteralAnchors::new(String::from("123")).is_err());
+    }
+
+    #[test]
+    fn pcre_matches_anywhere() {
+        assert!(Unanchored::new(String::from("abc1")).is_ok());
+        assert!(Unanchored::new(String::from("abc")).is_err());
+        assert!(Lookahead::new(String::from("abc1")).is_ok());
+        assert!(Lookahead::new(String::from("abc")).is_err());
+    }
+
+    #[test]
+    fn deserialize() {
+        assert!(UsesRegexes::from_cbor_bytes(&uses_regexes_bytes("abc", "1")).is_ok());
+        assert!(UsesRegexes::from_cbor_bytes(&uses_regexes_bytes("ABC", "1")).is_err());
+        assert!(UsesRegexes::from_cbor_bytes(&uses_regexes_bytes("abc", "one")).is_err());
+    }
+
+    #[test]
+    fn base64url() {
+        assert!(PreludeBase64url::new(String::from("SGVsbG8")).is_ok());
+        assert!(PreludeBase64url::new(String::from("SGVsbG8=")).is_ok());
+        assert!(PreludeBase64url::new(String::from("-_-_")).is_ok());
+        assert!(PreludeBase64url::new(String::new()).is_ok());
+        // standard base64 alphabet
+        assert!(PreludeBase64url::new(String::from("a+b/")).is_err());
+        // no valid encoding has this length
+        assert!(PreludeBase64url::new(String::from("SGVsb")).is_err());
+        let token = Token::new(PreludeBase64url::new(String::from("SGVsbG8")).unwrap());
+        let bytes = token.to_cbor_bytes();
+        assert_eq!(Token::from_cbor_bytes(&bytes).unwrap().to_cbor_bytes(), bytes);
+        let invalid = vec![
+            map_def(1),
+                cbor_string("payload"),
+                cbor_string("a+b/"),
+        ].into_iter().flatten().collect::<Vec<u8>>();
+        assert!(Token::from_cbor_bytes(&invalid).is_err());
+    }
+}
@@ -0,0 +1,51 @@
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use cbor_event::de::Deserializer;
+    use serialization::Deserialize;
+
+    fn deser_test<T: Deserialize + ToCBORBytes>(orig: &T) {
+        print_cbor_types("orig", &orig.to_cbor_bytes());
+        let deser = T::deserialize(&mut Deserializer::from(std::io::Cursor::new(orig.to_cbor_bytes()))).unwrap();
+        print_cbor_types("deser", &deser.to_cbor_bytes());
+        assert_eq!(orig.to_cbor_bytes(), deser.to_cbor_bytes());
+    }
+
+    #[test]
+    fn result_ok() {
+        let lookup = Lookup::new(0, Ok(5), Ok(String::from("text")));
+        deser_test(&lookup);
+        let expected = vec![
+            arr_def(3),
+                cbor_int(0, cbor_event::Sz::Inline),
+                cbor_int(5, cbor_event::Sz::Inline),
+                cbor_string("text"),
+        ].into_iter().flatten().clone().collect::<Vec<u8>>();
+        assert_eq!(lookup.to_cbor_bytes(), expected);
+        let deser = Lookup::from_cbor_bytes(&expected).unwrap();
+        assert_eq!(deser.outcome.unwrap(), 5);
+        assert_eq!(deser.raw.unwrap(), "text");
+    }
+
+    #[test]
+    fn result_err() {
+        let lookup = Lookup::new(1, Err(Failure::new(404, String::from("not found"))), Err(vec![0xBA, 0xAD]));
+        deser_test(&lookup);
+        let deser = Lookup::from_cbor_bytes(&lookup.to_cbor_bytes()).unwrap();
+        let failure = deser.outcome.unwrap_err();
+        assert_eq!(failure.code, 404);
+        assert_eq!(failure.message, "not found");
+        assert_eq!(deser.raw.unwrap_err(), vec![0xBA, 0xAD]);
+    }
+
+    #[test]
+    fn result_neither() {
+        let neither = vec![
+            arr_def(3),
+                cbor_int(2, cbor_event::Sz::Inline),
+                cbor_string("neither uint nor failure"),
+                cbor_string("text"),
+        ].into_iter().flatten().clone().collect::<Vec<u8>>();
+        assert!(Lookup::from_cbor_bytes(&neither).is_err());
+    }
+}
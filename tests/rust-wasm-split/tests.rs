@@ -43,7 +43,7 @@ mod tests {
 
     #[test]
     fn type_choice_0() {
-        deser_test(&TypeChoice::I0);
+        deser_test(&TypeChoice::Zero);
     }
 
     #[test]
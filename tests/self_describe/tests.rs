@@ -0,0 +1,42 @@
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serialization::Deserialize;
+
+    const SELF_DESCRIBE_TAG: [u8; 3] = [0xd9, 0xd9, 0xf7];
+
+    fn foo_bytes() -> Vec<u8> {
+        vec![
+            arr_def(2),
+                vec![0x05],
+                cbor_string("five"),
+        ].into_iter().flatten().collect::<Vec<u8>>()
+    }
+
+    #[test]
+    fn serialize_with_tag() {
+        let foo = Foo::new(5, String::from("five"));
+        let bytes = foo.to_cbor_bytes();
+        assert_eq!(bytes, [SELF_DESCRIBE_TAG.to_vec(), foo_bytes()].concat());
+        assert_eq!(Foo::from_cbor_bytes(&bytes).unwrap().to_cbor_bytes(), bytes);
+    }
+
+    #[test]
+    fn only_tags_document_root() {
+        let bar = Bar::new(Foo::new(5, String::from("five")));
+        let bytes = bar.to_cbor_bytes();
+        assert!(bytes.starts_with(&SELF_DESCRIBE_TAG));
+        // the nested foo has no tag of its own
+        assert!(!bytes[SELF_DESCRIBE_TAG.len()..].windows(SELF_DESCRIBE_TAG.len()).any(|w| w == SELF_DESCRIBE_TAG));
+        assert_eq!(Bar::from_cbor_bytes(&bytes).unwrap().to_cbor_bytes(), bytes);
+    }
+
+    #[test]
+    fn deserialize_without_tag() {
+        let foo = Foo::from_cbor_bytes(&foo_bytes()).unwrap();
+        assert_eq!(foo.id, 5);
+        assert_eq!(foo.name, "five");
+        // serializing always adds the tag back
+        assert_eq!(foo.to_cbor_bytes(), [SELF_DESCRIBE_TAG.to_vec(), foo_bytes()].concat());
+    }
+}
@@ -0,0 +1,68 @@
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serialization::Deserialize;
+
+    fn round_trip(floats: &Floats) -> Vec<u8> {
+        let bytes = floats.to_cbor_bytes();
+        let deser = Floats::from_cbor_bytes(&bytes).unwrap();
+        assert_eq!(deser.to_cbor_bytes(), bytes);
+        bytes
+    }
+
+    #[test]
+    fn half_when_exact() {
+        let bytes = round_trip(&Floats::new(1.5, -0.0, 65504.0));
+        assert_eq!(bytes, vec![
+            0x83,
+                0xf9, 0x3e, 0x00,
+                0xf9, 0x80, 0x00,
+                0xf9, 0x7b, 0xff,
+        ]);
+        // smallest half precision subnormal (2^-24)
+        let bytes = round_trip(&Floats::new(5.960464477539063e-8, 0.0, 0.0));
+        assert_eq!(&bytes[1..4], &[0xf9, 0x00, 0x01]);
+        let bytes = round_trip(&Floats::new(f32::INFINITY, f32::NEG_INFINITY, f64::INFINITY));
+        assert_eq!(&bytes[1..], &[0xf9, 0x7c, 0x00, 0xf9, 0xfc, 0x00, 0xf9, 0x7c, 0x00]);
+        assert_eq!(&Floats::new(f32::NAN, 0.0, 0.0).to_cbor_bytes()[1..4], &[0xf9, 0x7e, 0x00]);
+    }
+
+    #[test]
+    fn single_when_exact() {
+        // too large for half precision
+        let bytes = round_trip(&Floats::new(100000.0, 0.0, 100000.0));
+        assert_eq!(&bytes[1..6], &[0xfa, 0x47, 0xc3, 0x50, 0x00]);
+        assert_eq!(&bytes[9..], &[0xfa, 0x47, 0xc3, 0x50, 0x00]);
+        // too precise for half precision
+        let bytes = round_trip(&Floats::new(0.1, 0.1, 0.0));
+        assert_eq!(&bytes[1..6], &[0xfa, 0x3d, 0xcc, 0xcc, 0xcd]);
+        assert_eq!(&bytes[6..11], &[0xfa, 0x3d, 0xcc, 0xcc, 0xcd]);
+    }
+
+    #[test]
+    fn double_otherwise() {
+        let bytes = round_trip(&Floats::new(0.0, 0.0, 0.1));
+        assert_eq!(&bytes[7..], &[0xfb, 0x3f, 0xb9, 0x99, 0x99, 0x99, 0x99, 0x99, 0x9a]);
+    }
+
+    #[test]
+    fn deserialize_any_width() {
+        let bytes = vec![
+            0x83,
+                0xfb, 0x3f, 0xf8, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
+                0xfa, 0x3f, 0xc0, 0x00, 0x00,
+                0xf9, 0x3e, 0x00,
+        ];
+        let floats = Floats::from_cbor_bytes(&bytes).unwrap();
+        assert_eq!(floats.half, 1.5);
+        assert_eq!(floats.single, 1.5);
+        assert_eq!(floats.double, 1.5);
+    }
+
+    #[test]
+    fn fixed_value() {
+        let fixed = FixedFloat::new();
+        assert_eq!(fixed.to_cbor_bytes(), vec![0x81, 0xf9, 0x3e, 0x00]);
+        assert!(FixedFloat::from_cbor_bytes(&fixed.to_cbor_bytes()).is_ok());
+    }
+}
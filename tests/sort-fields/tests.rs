@@ -0,0 +1,55 @@
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use cbor_event::Sz;
+    use serialization::Deserialize;
+    use std::str::FromStr;
+
+    fn round_trip<T: Deserialize + ToCBORBytes>(orig: &T) {
+        let bytes = orig.to_cbor_bytes();
+        assert_eq!(T::from_cbor_bytes(&bytes).unwrap().to_cbor_bytes(), bytes);
+    }
+
+    #[test]
+    fn map_fields_sorted() {
+        // new() takes the required fields by name too
+        let mut settings = Settings::new(true, String::from("dark"), 2);
+        settings.autosave = Some(false);
+        round_trip(&settings);
+        let lib_rs_with_tests = std::fs::read_to_string(std::path::PathBuf::from_str("src").unwrap().join("lib.rs")).unwrap();
+        let lib_rs = &lib_rs_with_tests[..lib_rs_with_tests.find("#[cfg(test)]").unwrap()];
+        let autosave = lib_rs.find("pub autosave: Option<bool>").unwrap();
+        let beta = lib_rs.find("pub beta: bool").unwrap();
+        let theme = lib_rs.find("pub theme: String").unwrap();
+        let zoom = lib_rs.find("pub zoom: u64").unwrap();
+        assert!(autosave < beta && beta < theme && theme < zoom);
+    }
+
+    #[test]
+    fn map_encoding_order_unchanged() {
+        let bytes = vec![
+            map_def(3),
+                cbor_string("zoom"),
+                cbor_int(2, Sz::Inline),
+                cbor_string("theme"),
+                cbor_string("dark"),
+                cbor_string("beta"),
+                vec![0xf5],
+        ].into_iter().flatten().clone().collect::<Vec<u8>>();
+        assert_eq!(Settings::new(true, String::from("dark"), 2).to_cbor_bytes(), bytes);
+    }
+
+    #[test]
+    fn array_fields_in_cddl_order() {
+        let position = Position::new(1, 2, 3);
+        assert_eq!((position.z, position.x, position.y), (1, 2, 3));
+        let bytes = vec![
+            arr_def(3),
+                cbor_int(1, Sz::Inline),
+                cbor_int(2, Sz::Inline),
+                cbor_int(3, Sz::Inline),
+        ].into_iter().flatten().clone().collect::<Vec<u8>>();
+        assert_eq!(position.to_cbor_bytes(), bytes);
+        round_trip(&position);
+    }
+}
@@ -0,0 +1,38 @@
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serialization::Deserialize;
+
+    fn person_bytes(grade: &str) -> Vec<u8> {
+        vec![
+            arr_def(2),
+                cbor_string("Ada"),
+                cbor_string(grade),
+        ].into_iter().flatten().collect::<Vec<u8>>()
+    }
+
+    #[test]
+    fn field() {
+        let person = Person::from_cbor_bytes(&person_bytes("A")).unwrap();
+        assert_eq!(person.grade, 'A');
+        assert_eq!(person.to_cbor_bytes(), person_bytes("A"));
+        // one character even though it's 2 bytes in UTF-8
+        let person = Person::from_cbor_bytes(&person_bytes("é")).unwrap();
+        assert_eq!(person.grade, 'é');
+        assert_eq!(person.to_cbor_bytes(), person_bytes("é"));
+        assert!(Person::from_cbor_bytes(&person_bytes("AB")).is_err());
+        assert!(Person::from_cbor_bytes(&person_bytes("")).is_err());
+    }
+
+    #[test]
+    fn wrapper() {
+        let initial = Initial::new('J');
+        let bytes = initial.to_cbor_bytes();
+        assert_eq!(bytes, cbor_string("J"));
+        assert_eq!(Initial::from_cbor_bytes(&bytes).unwrap().to_cbor_bytes(), bytes);
+        let mut person = Person::new(String::from("Ada"), 'A');
+        person.middle = Some(initial);
+        assert_eq!(Person::from_cbor_bytes(&person.to_cbor_bytes()).unwrap().to_cbor_bytes(), person.to_cbor_bytes());
+        assert!(ShortText::new(String::from("ab")).is_ok());
+    }
+}
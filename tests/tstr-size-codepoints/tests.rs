@@ -0,0 +1,30 @@
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serialization::Deserialize;
+
+    fn named_bytes(name: &str) -> Vec<u8> {
+        vec![
+            arr_def(1),
+                cbor_string(name),
+        ].into_iter().flatten().collect::<Vec<u8>>()
+    }
+
+    #[test]
+    fn ascii() {
+        assert!(ShortText::new(String::from("abcd")).is_ok());
+        assert!(ShortText::new(String::from("abcde")).is_err());
+        assert!(Named::from_cbor_bytes(&named_bytes("abcd")).is_ok());
+        assert!(Named::from_cbor_bytes(&named_bytes("abcde")).is_err());
+    }
+
+    #[test]
+    fn multi_byte() {
+        // 3 codepoints but 6 UTF-8 bytes
+        assert!(ShortText::new(String::from("ééé")).is_ok());
+        assert!(ShortText::new(String::from("éééé")).is_ok());
+        assert!(ShortText::new(String::from("ééééé")).is_err());
+        assert!(Named::from_cbor_bytes(&named_bytes("ééé")).is_ok());
+        assert!(Named::from_cbor_bytes(&named_bytes("ééééé")).is_err());
+    }
+}
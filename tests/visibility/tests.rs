@@ -0,0 +1,43 @@
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serialization::Deserialize;
+    use std::str::FromStr;
+
+    fn round_trip<T: Deserialize + ToCBORBytes>(orig: &T) {
+        let bytes = orig.to_cbor_bytes();
+        assert_eq!(T::from_cbor_bytes(&bytes).unwrap().to_cbor_bytes(), bytes);
+    }
+
+    fn generated_lib_rs() -> String {
+        let lib_rs_with_tests = std::fs::read_to_string(std::path::PathBuf::from_str("src").unwrap().join("lib.rs")).unwrap();
+        lib_rs_with_tests[..lib_rs_with_tests.find("#[cfg(test)]").unwrap()].to_owned()
+    }
+
+    #[test]
+    fn round_trips() {
+        let point = Point::new(Int::new_uint(3), Int::new_uint(4), Color::Two);
+        round_trip(&point);
+        round_trip(&Shape::new_point(point));
+        round_trip(&Shape::new_nonce(Nonce::new(5)));
+    }
+
+    #[test]
+    fn visibility_from_flag() {
+        let lib_rs = generated_lib_rs();
+        assert!(lib_rs.contains("pub(crate) struct Point {"));
+        assert!(lib_rs.contains("pub(crate) fn new("));
+        assert!(lib_rs.contains("pub(crate) enum Shape {"));
+        assert!(lib_rs.contains("pub(crate) fn new_point(point: Point) -> Self"));
+    }
+
+    #[test]
+    fn visibility_from_annotation() {
+        let lib_rs = generated_lib_rs();
+        assert!(lib_rs.contains("pub type Coord = Int;"));
+        assert!(lib_rs.contains("pub struct Nonce("));
+        assert!(lib_rs.contains("pub fn get(&self) -> u64"));
+        assert!(lib_rs.contains("\nenum Color {"));
+        assert!(!lib_rs.contains("pub enum Color"));
+    }
+}
@@ -0,0 +1,22 @@
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serialization::Deserialize;
+
+    #[test]
+    fn placeholder_round_trip() {
+        let seq_bytes = vec![
+            cbor_tag(24),
+                cbor_string("not checked"),
+        ].into_iter().flatten().clone().collect::<Vec<u8>>();
+        let bytes = vec![
+            arr_def(2),
+                cbor_int(7, cbor_event::Sz::Inline),
+                seq_bytes.clone(),
+        ].into_iter().flatten().clone().collect::<Vec<u8>>();
+        let wrapper = Wrapper::from_cbor_bytes(&bytes).unwrap();
+        assert_eq!(wrapper.id, 7);
+        assert_eq!(wrapper.seq.0, seq_bytes);
+        assert_eq!(wrapper.to_cbor_bytes(), bytes);
+    }
+}
@@ -0,0 +1,33 @@
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use cbor_event::de::Deserializer;
+    use serialization::Deserialize;
+
+    fn deser_test<T: Deserialize + ToCBORBytes>(orig: &T) {
+        print_cbor_types("orig", &orig.to_cbor_bytes());
+        let deser = T::deserialize(&mut Deserializer::from(std::io::Cursor::new(orig.to_cbor_bytes()))).unwrap();
+        print_cbor_types("deser", &deser.to_cbor_bytes());
+        assert_eq!(orig.to_cbor_bytes(), deser.to_cbor_bytes());
+    }
+
+    #[test]
+    fn foo() {
+        deser_test(&Foo::new(436, String::from("jfkdsjfd"), vec![1, 1, 1]));
+    }
+
+    #[test]
+    fn bar() {
+        let mut bar = Bar::new(5);
+        deser_test(&bar);
+        bar.b = Some(String::from("optional"));
+        deser_test(&bar);
+    }
+
+    #[test]
+    fn choice() {
+        deser_test(&Choice::new_foo(Foo::new(0, String::new(), vec![])));
+        deser_test(&Choice::new_bar(Bar::new(1)));
+        deser_test(&Choice::new_uint(2));
+    }
+}
@@ -0,0 +1,69 @@
+#[cfg(test)]
+mod wasm_tests {
+    use super::*;
+
+    // JsError/JsValue can't be constructed outside of wasm32 so we only test the
+    // non-failing paths here and avoid unwrap() (JsError has no Debug impl natively)
+
+    #[test]
+    fn foo_ctor_getters() {
+        let foo = Foo::new(436, String::from("jfkdsjfd"), vec![1, 2, 3]);
+        assert_eq!(foo.index_0(), 436);
+        assert_eq!(foo.index_1(), "jfkdsjfd");
+        assert_eq!(foo.index_2(), vec![1, 2, 3]);
+    }
+
+    #[test]
+    fn foo_bytes_roundtrip() {
+        let foo = Foo::new(436, String::from("jfkdsjfd"), vec![1, 2, 3]);
+        let bytes = foo.to_cbor_bytes();
+        let deser = Foo::from_cbor_bytes(&bytes).ok().unwrap();
+        assert_eq!(deser.to_cbor_bytes(), bytes);
+        // wasm -> rust conversion must not alter the encoding
+        let native: cddl_lib::Foo = deser.into();
+        assert_eq!(cddl_lib::serialization::ToCBORBytes::to_cbor_bytes(&native), bytes);
+    }
+
+    #[test]
+    fn bar_optional_field() {
+        let mut bar = Bar::new(5);
+        assert_eq!(bar.a(), 5);
+        assert_eq!(bar.b(), None);
+        bar.set_b(String::from("optional"));
+        assert_eq!(bar.b(), Some(String::from("optional")));
+        let bytes = bar.to_cbor_bytes();
+        let deser = Bar::from_cbor_bytes(&bytes).ok().unwrap();
+        assert_eq!(deser.b(), Some(String::from("optional")));
+    }
+
+    #[test]
+    fn choice_tagged_union() {
+        let foo = Foo::new(0, String::new(), vec![]);
+        let choice = Choice::new_foo(&foo);
+        assert!(matches!(choice.kind(), ChoiceKind::Foo));
+        assert_eq!(choice.as_foo().unwrap().to_cbor_bytes(), foo.to_cbor_bytes());
+        assert!(choice.as_uint().is_none());
+        let choice = Choice::new_uint(2);
+        assert!(matches!(choice.kind(), ChoiceKind::Uint));
+        assert_eq!(choice.as_uint(), Some(2));
+        let bytes = choice.to_cbor_bytes();
+        let deser = Choice::from_cbor_bytes(&bytes).ok().unwrap();
+        assert!(matches!(deser.kind(), ChoiceKind::Uint));
+    }
+
+    #[test]
+    fn collection_len() {
+        // [[[1, "", h'']], {"a": 1, "b": 2}]
+        let bytes = vec![0x82, 0x81, 0x83, 0x01, 0x60, 0x40, 0xa2, 0x61, 0x61, 0x01, 0x61, 0x62, 0x02];
+        let bag = Bag::from_cbor_bytes(&bytes).ok().unwrap();
+        assert_eq!(bag.foos().len(), 1);
+        assert!(!bag.foos().is_empty());
+        assert_eq!(bag.names().len(), 2);
+        assert!(!bag.names().is_empty());
+        // [[], {}]
+        let empty = Bag::from_cbor_bytes(&[0x82, 0x80, 0xa0]).ok().unwrap();
+        assert!(empty.foos().is_empty());
+        assert_eq!(empty.names().len(), 0);
+        assert!(empty.names().is_empty());
+    }
+}